@@ -0,0 +1,31 @@
+use async_graphql::InputObject;
+
+use crate::models::member::SchoolRole;
+use crate::utils::common_types::{Attachment, AudienceScope};
+
+#[derive(InputObject)]
+pub struct CreateAnnouncementInput {
+    pub school_id: String,
+    pub title: String,
+    pub body: String,
+    pub scope: AudienceScope,
+    /// Branch ID, grade level or class ID; required unless the scope is School
+    pub scope_id: Option<String>,
+    /// Only these roles (e.g., Parent) see it; leave empty for everyone
+    pub roles: Option<Vec<SchoolRole>>,
+    pub attachments: Option<Vec<Attachment>>,
+    pub pinned: Option<bool>,
+    /// Hide from readers after this time (RFC 3339)
+    pub expires_at: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateAnnouncementInput {
+    pub announcement_id: String,
+    pub title: Option<String>,
+    pub body: Option<String>,
+    /// Replaces all attachments
+    pub attachments: Option<Vec<Attachment>>,
+    pub pinned: Option<bool>,
+    pub expires_at: Option<String>,
+}
@@ -0,0 +1,287 @@
+use super::inputs::{CreateAnnouncementInput, UpdateAnnouncementInput};
+use super::queries::{check_author, check_visible, find_announcement, recipient_user_ids};
+use crate::graphql::library::queries::to_bson;
+use crate::graphql::message::queries::{clean_attachments, require_messaging};
+use crate::models::announcement::{Announcement, AnnouncementRead};
+use crate::models::member::Permission;
+use crate::models::notification::NotificationKind;
+use crate::models::school::SchoolFeature;
+use crate::utils::audience::check_scope;
+use crate::utils::audit::record_change;
+use crate::utils::common_types::{AuditInfo, SoftDelete};
+use crate::utils::features::FeatureGuard;
+use crate::utils::notifications::{notify, NotificationMessage};
+use crate::utils::realtime::{publish, RealtimeEvent};
+use crate::utils::soft_delete::{find_deleted_by_id, restore_by_id, soft_delete_by_id};
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, DateTime},
+    options::UpdateOptions,
+    Database,
+};
+
+/// Longest notification preview of an announcement, in characters
+const PREVIEW_LENGTH: usize = 140;
+
+#[derive(Default)]
+pub struct AnnouncementMutation;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Messaging)")]
+impl AnnouncementMutation {
+    /// Publish an announcement and notify everyone it reaches
+    /// (requires SendAnnouncements)
+    async fn create_announcement(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateAnnouncementInput,
+    ) -> Result<Announcement> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_messaging(ctx, db, &input.school_id, Permission::SendAnnouncements).await?;
+
+        let title = input.title.trim().to_string();
+        let body = input.body.trim().to_string();
+        if title.is_empty() || body.is_empty() {
+            return Err(Error::new("Announcements need a title and a body"));
+        }
+        let mut scope_id = input.scope_id;
+        check_scope(db, &input.school_id, input.scope, &mut scope_id).await?;
+        let expires_at = input
+            .expires_at
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()?;
+        let now = DateTime::now();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(Error::new("The expiry must be in the future"));
+        }
+
+        let mut roles = Vec::new();
+        for role in input.roles.unwrap_or_default() {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        }
+        let mut announcement = Announcement {
+            id: None,
+            school_id: input.school_id,
+            title,
+            body,
+            scope: input.scope,
+            scope_id,
+            roles,
+            attachments: clean_attachments(input.attachments, &member.user_id)?,
+            pinned: input.pinned.unwrap_or(false),
+            author_id: member.user_id.clone(),
+            recipient_count: 0,
+            published_at: now,
+            expires_at,
+            audit: AuditInfo::new(Some(member.user_id.clone())),
+            soft_delete: SoftDelete::default(),
+        };
+
+        let mut recipients = recipient_user_ids(db, &announcement).await?;
+        recipients.retain(|user_id| *user_id != member.user_id);
+        announcement.recipient_count = recipients.len() as i32;
+
+        let result = db
+            .collection::<Announcement>("announcements")
+            .insert_one(&announcement, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create announcement: {}", e)))?;
+        announcement.id = result.inserted_id.as_object_id();
+
+        let message = NotificationMessage {
+            school_id: Some(&announcement.school_id),
+            kind: NotificationKind::Announcement,
+            title: announcement.title.clone(),
+            body: announcement.body.chars().take(PREVIEW_LENGTH).collect(),
+            link: announcement
+                .id
+                .map(|id| format!("announcement:{}", id.to_hex())),
+        };
+        notify(db, &recipients, &message)
+            .await
+            .map_err(|e| Error::new(format!("Failed to notify recipients: {}", e)))?;
+        publish(RealtimeEvent::AnnouncementPublished {
+            recipient_ids: recipients,
+            announcement: announcement.clone(),
+        });
+
+        record_change(
+            ctx,
+            "Announcement",
+            announcement.id.map(|id| id.to_hex()),
+            Some(&announcement.school_id),
+            None,
+            Some(&announcement),
+        );
+
+        Ok(announcement)
+    }
+
+    /// Edit an announcement (its author or a school leader; requires
+    /// SendAnnouncements). The audience can't be changed once published.
+    async fn update_announcement(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateAnnouncementInput,
+    ) -> Result<Announcement> {
+        let db = ctx.data::<Database>()?;
+        let announcement = find_announcement(db, &input.announcement_id).await?;
+        let member = require_messaging(
+            ctx,
+            db,
+            &announcement.school_id,
+            Permission::SendAnnouncements,
+        )
+        .await?;
+        check_author(&member, &announcement)?;
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(title) = input.title {
+            let title = title.trim().to_string();
+            if title.is_empty() {
+                return Err(Error::new("Announcements need a title and a body"));
+            }
+            set.insert("title", title);
+        }
+        if let Some(body) = input.body {
+            let body = body.trim().to_string();
+            if body.is_empty() {
+                return Err(Error::new("Announcements need a title and a body"));
+            }
+            set.insert("body", body);
+        }
+        if let Some(attachments) = input.attachments {
+            let attachments = clean_attachments(Some(attachments), &member.user_id)?;
+            set.insert("attachments", to_bson(&attachments)?);
+        }
+        if let Some(pinned) = input.pinned {
+            set.insert("pinned", pinned);
+        }
+        if let Some(expires_at) = input.expires_at {
+            set.insert("expires_at", parse_timestamp(&expires_at)?);
+        }
+
+        db.collection::<Announcement>("announcements")
+            .update_one(doc! { "_id": announcement.id }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update announcement: {}", e)))?;
+
+        let updated = find_announcement(db, &input.announcement_id).await?;
+        record_change(
+            ctx,
+            "Announcement",
+            Some(input.announcement_id),
+            Some(&updated.school_id),
+            Some(&announcement),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Remove an announcement (its author or a school leader; requires
+    /// SendAnnouncements)
+    async fn delete_announcement(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let announcement = find_announcement(db, &id).await?;
+        let member = require_messaging(
+            ctx,
+            db,
+            &announcement.school_id,
+            Permission::SendAnnouncements,
+        )
+        .await?;
+        check_author(&member, &announcement)?;
+
+        let oid = announcement
+            .id
+            .ok_or_else(|| Error::new("Announcement not found"))?;
+        soft_delete_by_id(
+            &db.collection::<Announcement>("announcements"),
+            oid,
+            &member.user_id,
+        )
+        .await?;
+
+        record_change(
+            ctx,
+            "Announcement",
+            Some(id),
+            Some(&announcement.school_id),
+            Some(&announcement),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted announcement from the trash (its author or a school
+    /// leader; requires SendAnnouncements). The audience isn't notified again.
+    async fn restore_announcement(&self, ctx: &Context<'_>, id: String) -> Result<Announcement> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<Announcement>("announcements");
+        let announcement = find_deleted_by_id(&collection, &id, "announcement").await?;
+        let member = require_messaging(
+            ctx,
+            db,
+            &announcement.school_id,
+            Permission::SendAnnouncements,
+        )
+        .await?;
+        check_author(&member, &announcement)?;
+
+        let oid = announcement
+            .id
+            .ok_or_else(|| Error::new("Announcement not found"))?;
+        restore_by_id(&collection, oid).await?;
+
+        let restored = find_announcement(db, &id).await?;
+        record_change(
+            ctx,
+            "Announcement",
+            Some(id),
+            Some(&restored.school_id),
+            Some(&announcement),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Record that the caller read an announcement (requires ViewMessages).
+    /// Only the first read is kept.
+    async fn mark_announcement_read(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let announcement = find_announcement(db, &id).await?;
+        let member =
+            require_messaging(ctx, db, &announcement.school_id, Permission::ViewMessages).await?;
+        check_visible(db, &member, &announcement).await?;
+
+        db.collection::<AnnouncementRead>("announcement_reads")
+            .update_one(
+                doc! { "announcement_id": &id, "user_id": &member.user_id },
+                doc! { "$setOnInsert": {
+                    "school_id": &announcement.school_id,
+                    "member_id": member.id.map(|id| id.to_hex()).unwrap_or_default(),
+                    "read_at": DateTime::now(),
+                } },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to record read: {}", e)))?;
+
+        Ok(true)
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| DateTime::from_millis(dt.timestamp_millis()))
+        .map_err(|_| Error::new("Invalid date. Use RFC 3339"))
+}
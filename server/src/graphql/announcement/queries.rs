@@ -0,0 +1,241 @@
+use super::types::{AnnouncementItemType, AnnouncementReceiptsType};
+use crate::graphql::library::queries::{collect, to_bson};
+use crate::graphql::message::queries::require_messaging;
+use crate::models::announcement::{Announcement, AnnouncementRead};
+use crate::models::member::{Member, Permission};
+use crate::models::school::SchoolFeature;
+use crate::utils::audience::{audience_clause, audience_members};
+use crate::utils::features::FeatureGuard;
+use crate::utils::permissions::is_school_leader;
+use crate::utils::soft_delete::find_deleted;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct AnnouncementQuery;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Messaging)")]
+impl AnnouncementQuery {
+    /// Announcements for the caller, pinned first then newest
+    /// (requires ViewMessages)
+    async fn announcements(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        include_expired: Option<bool>,
+        limit: Option<i64>,
+        offset: Option<u64>,
+    ) -> Result<Vec<AnnouncementItemType>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_messaging(ctx, db, &school_id, Permission::ViewMessages).await?;
+
+        let filter =
+            visible_announcements_filter(db, &member, include_expired.unwrap_or(false)).await?;
+        let announcements = collect(
+            &db.collection::<Announcement>("announcements"),
+            filter,
+            FindOptions::builder()
+                .sort(doc! { "pinned": -1, "published_at": -1 })
+                .limit(limit.unwrap_or(50))
+                .skip(offset)
+                .build(),
+        )
+        .await?;
+
+        let ids: Vec<String> = announcements
+            .iter()
+            .filter_map(|a| a.id.map(|id| id.to_hex()))
+            .collect();
+        let read_ids: Vec<String> = collect(
+            &db.collection::<AnnouncementRead>("announcement_reads"),
+            doc! { "announcement_id": { "$in": ids }, "user_id": &member.user_id },
+            FindOptions::default(),
+        )
+        .await?
+        .into_iter()
+        .map(|r| r.announcement_id)
+        .collect();
+
+        Ok(announcements
+            .into_iter()
+            .map(|announcement| {
+                let is_read = announcement
+                    .id
+                    .is_some_and(|id| read_ids.contains(&id.to_hex()));
+                AnnouncementItemType {
+                    announcement,
+                    is_read,
+                }
+            })
+            .collect())
+    }
+
+    /// A single announcement for the caller (requires ViewMessages)
+    async fn announcement(&self, ctx: &Context<'_>, id: String) -> Result<Announcement> {
+        let db = ctx.data::<Database>()?;
+        let announcement = find_announcement(db, &id).await?;
+        let member =
+            require_messaging(ctx, db, &announcement.school_id, Permission::ViewMessages).await?;
+        check_visible(db, &member, &announcement).await?;
+        Ok(announcement)
+    }
+
+    /// Deleted announcements (the trash), most recently deleted first: all of
+    /// the school's for school leaders, otherwise the caller's own (requires
+    /// SendAnnouncements)
+    async fn deleted_announcements(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<Announcement>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_messaging(ctx, db, &school_id, Permission::SendAnnouncements).await?;
+
+        let mut filter = doc! { "school_id": &school_id };
+        if !is_school_leader(&member.role) {
+            filter.insert("author_id", &member.user_id);
+        }
+        find_deleted(&db.collection::<Announcement>("announcements"), filter).await
+    }
+
+    /// Read receipts for an announcement (its author and school leaders)
+    async fn announcement_read_receipts(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+    ) -> Result<AnnouncementReceiptsType> {
+        let db = ctx.data::<Database>()?;
+        let announcement = find_announcement(db, &id).await?;
+        let member = require_messaging(
+            ctx,
+            db,
+            &announcement.school_id,
+            Permission::SendAnnouncements,
+        )
+        .await?;
+        check_author(&member, &announcement)?;
+
+        let reads = collect(
+            &db.collection::<AnnouncementRead>("announcement_reads"),
+            doc! { "announcement_id": &id },
+            FindOptions::builder().sort(doc! { "read_at": 1 }).build(),
+        )
+        .await?;
+
+        let mut recipients = recipient_user_ids(db, &announcement).await?;
+        recipients.retain(|user_id| *user_id != announcement.author_id);
+        let unread_user_ids: Vec<String> = recipients
+            .iter()
+            .filter(|user_id| !reads.iter().any(|r| r.user_id == **user_id))
+            .cloned()
+            .collect();
+
+        Ok(AnnouncementReceiptsType {
+            announcement,
+            recipient_count: recipients.len() as i32,
+            read_count: reads.len() as i32,
+            reads,
+            unread_user_ids,
+        })
+    }
+}
+
+/// Filter on `announcements` for the published ones a member can see. Members
+/// who can send announcements see every announcement in the school.
+async fn visible_announcements_filter(
+    db: &Database,
+    member: &Member,
+    include_expired: bool,
+) -> Result<Document> {
+    let now = DateTime::now();
+    let mut filter = doc! {
+        "school_id": &member.school_id,
+        "published_at": { "$lte": now },
+        "soft_delete.is_deleted": { "$ne": true }
+    };
+    let mut conditions = Vec::new();
+    if !member.has_permission(Permission::SendAnnouncements) {
+        filter.extend(audience_clause(db, member).await?);
+        conditions.push(doc! { "$or": [
+            { "roles": { "$size": 0 } },
+            { "roles": to_bson(&member.role)? }
+        ] });
+    }
+    if !include_expired {
+        conditions.push(doc! { "$or": [
+            { "expires_at": null },
+            { "expires_at": { "$gt": now } }
+        ] });
+    }
+    if !conditions.is_empty() {
+        filter.insert("$and", conditions);
+    }
+    Ok(filter)
+}
+
+/// Fail unless the announcement is for the member
+pub(crate) async fn check_visible(
+    db: &Database,
+    member: &Member,
+    announcement: &Announcement,
+) -> Result<()> {
+    let mut filter = visible_announcements_filter(db, member, true).await?;
+    filter.insert("_id", announcement.id);
+    let visible = db
+        .collection::<Announcement>("announcements")
+        .count_documents(filter, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    if visible == 0 {
+        return Err(Error::new("Announcement not found"));
+    }
+    Ok(())
+}
+
+/// Only the author and school leaders manage an announcement
+pub(crate) fn check_author(member: &Member, announcement: &Announcement) -> Result<()> {
+    if announcement.author_id != member.user_id && !is_school_leader(&member.role) {
+        return Err(Error::new(
+            "Only the author or a school leader can manage this announcement",
+        ));
+    }
+    Ok(())
+}
+
+/// Load an announcement that hasn't been deleted
+pub(crate) async fn find_announcement(db: &Database, id: &str) -> Result<Announcement> {
+    let oid = ObjectId::parse_str(id).map_err(|_| Error::new("Invalid announcement ID"))?;
+    db.collection::<Announcement>("announcements")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Announcement not found"))
+}
+
+/// Users an announcement reaches: its scope, narrowed to its roles
+pub(crate) async fn recipient_user_ids(
+    db: &Database,
+    announcement: &Announcement,
+) -> Result<Vec<String>> {
+    let mut user_ids: Vec<String> = audience_members(
+        db,
+        &announcement.school_id,
+        announcement.scope,
+        announcement.scope_id.as_deref(),
+    )
+    .await?
+    .into_iter()
+    .filter(|m| announcement.is_for_role(m.role))
+    .map(|m| m.user_id)
+    .collect();
+    user_ids.sort();
+    user_ids.dedup();
+    Ok(user_ids)
+}
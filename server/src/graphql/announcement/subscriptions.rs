@@ -0,0 +1,46 @@
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::message::queries::require_messaging;
+use crate::models::announcement::Announcement;
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::realtime::{member_events, RealtimeEvent};
+use async_graphql::*;
+use futures::Stream;
+use mongodb::Database;
+
+#[derive(Default)]
+pub struct AnnouncementSubscription;
+
+#[Subscription(guard = "FeatureGuard::new(SchoolFeature::Messaging)")]
+impl AnnouncementSubscription {
+    /// Announcements that reach the caller, as they are published
+    /// (requires ViewMessages)
+    async fn announcement_published(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<impl Stream<Item = Announcement>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_messaging(ctx, db, &school_id, Permission::ViewMessages).await?;
+        let user = get_graphql_context(ctx)?.require_auth()?.clone();
+
+        Ok(member_events(
+            db.clone(),
+            user,
+            school_id.clone(),
+            Some(Permission::ViewMessages),
+            move |event| match event {
+                RealtimeEvent::AnnouncementPublished {
+                    recipient_ids,
+                    announcement,
+                } if announcement.school_id == school_id
+                    && recipient_ids.contains(&member.user_id) =>
+                {
+                    Some(announcement)
+                }
+                _ => None,
+            },
+        ))
+    }
+}
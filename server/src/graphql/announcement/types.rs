@@ -0,0 +1,21 @@
+use crate::models::announcement::{Announcement, AnnouncementRead};
+use async_graphql::SimpleObject;
+
+/// An announcement with the caller's read state
+#[derive(SimpleObject)]
+pub struct AnnouncementItemType {
+    pub announcement: Announcement,
+    pub is_read: bool,
+}
+
+/// Who has and hasn't read an announcement
+#[derive(SimpleObject)]
+pub struct AnnouncementReceiptsType {
+    pub announcement: Announcement,
+    /// Members the announcement currently reaches
+    pub recipient_count: i32,
+    pub read_count: i32,
+    pub reads: Vec<AnnouncementRead>,
+    /// Users who haven't opened it yet
+    pub unread_user_ids: Vec<String>,
+}
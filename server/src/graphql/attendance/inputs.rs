@@ -1,5 +1,6 @@
 // Attendance GraphQL inputs
 use crate::models::attendance::Attendance;
+use crate::utils::common_types::SoftDelete;
 use async_graphql::*;
 use mongodb::bson::{oid::ObjectId, DateTime};
 
@@ -25,8 +26,11 @@ impl From<AttendanceInput> for Attendance {
             status: input.status,
             remarks: input.remarks,
             marked_by: ObjectId::parse_str(&input.marked_by).unwrap(),
+            checked_in_at: None,
+            device_id: None,
             created_at: now,
             updated_at: now,
+            soft_delete: SoftDelete::default(),
         }
     }
 }
@@ -39,6 +43,16 @@ pub struct AttendanceRecordInput {
     pub remarks: Option<String>,
 }
 
+/// A scanned student ID card at the gate
+#[derive(InputObject)]
+pub struct ScanAttendanceInput {
+    pub school_id: String,
+    /// Code read from the card's QR code (the student's ID number)
+    pub code: String,
+    /// Identifies the scanner (e.g., "gate-1")
+    pub device_id: String,
+}
+
 /// Input for updating an existing attendance record
 #[derive(InputObject)]
 pub struct UpdateAttendanceInput {
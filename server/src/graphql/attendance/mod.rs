@@ -2,8 +2,10 @@ pub mod types;
 pub mod inputs;
 pub mod queries;
 pub mod mutations;
+pub mod subscriptions;
 
 pub use types::*;
 pub use inputs::*;
 pub use queries::*;
 pub use mutations::*;
+pub use subscriptions::*;
@@ -1,11 +1,23 @@
 // Attendance GraphQL mutations
-use super::inputs::{AttendanceInput, AttendanceRecordInput};
-use super::types::{AttendanceType, BulkAttendanceResult};
+use super::inputs::{AttendanceInput, AttendanceRecordInput, ScanAttendanceInput};
+use super::types::{AttendanceType, BulkAttendanceResult, ScanAttendanceResult};
+use crate::graphql::class::queries::class_school_id;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::id_card::queries::find_student_by_card;
 use crate::models;
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::models::student::StudentStatus;
+use crate::utils::audit::record_change;
+use crate::utils::features::FeatureGuard;
+use crate::utils::realtime::{publish, RealtimeEvent};
+use crate::utils::soft_delete::{restore_by_id, soft_delete_by_id};
 use async_graphql::*;
-use chrono::{NaiveDate, TimeZone, Utc};
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use mongodb::{
-    bson::{doc, oid::ObjectId, DateTime},
+    bson::{doc, oid::ObjectId, Bson, DateTime},
+    error::{ErrorKind, WriteFailure},
     options::UpdateOptions,
     Database,
 };
@@ -13,9 +25,9 @@ use mongodb::{
 #[derive(Default)]
 pub struct AttendanceMutation;
 
-#[Object]
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Attendance)")]
 impl AttendanceMutation {
-    /// Create a single attendance record
+    /// Create a single attendance record (MarkAttendance in the class's school)
     async fn create_attendance(
         &self,
         ctx: &Context<'_>,
@@ -23,6 +35,14 @@ impl AttendanceMutation {
     ) -> Result<AttendanceType> {
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<models::attendance::Attendance>("attendances");
+
+        let class_oid = ObjectId::parse_str(&input.class_id)
+            .map_err(|_| Error::new("Invalid class ID format"))?;
+        let school_id = class_school_id(db, class_oid).await?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::MarkAttendance)
+            .await?;
+
         let mut attendance: models::attendance::Attendance = input.into();
 
         let now = DateTime::now();
@@ -41,10 +61,25 @@ impl AttendanceMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Failed to retrieve created attendance"))?;
 
-        Ok(attendance.into())
+        record_change(
+            ctx,
+            "Attendance",
+            Some(id.to_hex()),
+            Some(&school_id),
+            None,
+            Some(&attendance),
+        );
+
+        let attendance = AttendanceType::from(attendance);
+        publish(RealtimeEvent::AttendanceMarked {
+            attendance: attendance.clone(),
+        });
+
+        Ok(attendance)
     }
 
-    /// Mark attendance for multiple students at once (upsert)
+    /// Mark attendance for multiple students at once (upsert; MarkAttendance in
+    /// the class's school)
     async fn mark_bulk_attendance(
         &self,
         ctx: &Context<'_>,
@@ -60,6 +95,10 @@ impl AttendanceMutation {
             ObjectId::parse_str(&class_id).map_err(|_| Error::new("Invalid class ID format"))?;
         let marked_by_oid = ObjectId::parse_str(&marked_by)
             .map_err(|_| Error::new("Invalid marked_by ID format"))?;
+        let school_id = class_school_id(db, class_oid).await?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::MarkAttendance)
+            .await?;
 
         // Parse date
         let parsed_date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
@@ -86,7 +125,11 @@ impl AttendanceMutation {
                     "status": &record.status,
                     "remarks": record.remarks.as_deref(),
                     "marked_by": marked_by_oid,
-                    "updated_at": now
+                    "updated_at": now,
+                    // Marking again brings back a deleted record for the same day
+                    "soft_delete.is_deleted": false,
+                    "soft_delete.deleted_at": mongodb::bson::Bson::Null,
+                    "soft_delete.deleted_by": mongodb::bson::Bson::Null
                 },
                 "$setOnInsert": {
                     "student_id": student_oid,
@@ -98,10 +141,33 @@ impl AttendanceMutation {
 
             let options = UpdateOptions::builder().upsert(true).build();
 
+            let before = collection
+                .find_one(filter.clone(), None)
+                .await
+                .map_err(|e| Error::new(e.to_string()))?;
+
             collection
-                .update_one(filter, update, options)
+                .update_one(filter.clone(), update, options)
+                .await
+                .map_err(|e| Error::new(e.to_string()))?;
+
+            let after = collection
+                .find_one(filter, None)
                 .await
                 .map_err(|e| Error::new(e.to_string()))?;
+            record_change(
+                ctx,
+                "Attendance",
+                after.as_ref().and_then(|a| a.id).map(|oid| oid.to_hex()),
+                Some(&school_id),
+                before.as_ref(),
+                after.as_ref(),
+            );
+            if let Some(after) = after {
+                publish(RealtimeEvent::AttendanceMarked {
+                    attendance: after.into(),
+                });
+            }
 
             count += 1;
         }
@@ -112,7 +178,217 @@ impl AttendanceMutation {
         })
     }
 
-    /// Update an existing attendance record
+    /// Check a student in from a scanned ID card (MarkAttendance). Marks
+    /// today's attendance in the student's current class as present, or late
+    /// after the school's cutoff. Scanning again the same day changes nothing.
+    async fn scan_attendance(
+        &self,
+        ctx: &Context<'_>,
+        input: ScanAttendanceInput,
+    ) -> Result<ScanAttendanceResult> {
+        let db = ctx.data::<Database>()?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &input.school_id, Permission::MarkAttendance)
+            .await?;
+        let device_id = input.device_id.trim();
+        if device_id.is_empty() {
+            return Err(Error::new("Device ID is required"));
+        }
+
+        let student = find_student_by_card(db, &input.school_id, &input.code).await?;
+        if student.status != StudentStatus::Active {
+            return Err(Error::new("This student isn't currently enrolled"));
+        }
+        let class_oid = student
+            .current_class_id
+            .as_deref()
+            .and_then(|id| ObjectId::parse_str(id).ok())
+            .ok_or_else(|| Error::new("This student isn't assigned to a class"))?;
+        let student_oid = student.id.ok_or_else(|| Error::new("Student not found"))?;
+        let marked_by_oid = ObjectId::parse_str(&member.user_id)
+            .map_err(|_| Error::new("Invalid user ID format"))?;
+
+        let school_oid =
+            ObjectId::parse_str(&input.school_id).map_err(|_| Error::new("Invalid school ID"))?;
+        let school = db
+            .collection::<models::school::School>("schools")
+            .find_one(doc! { "_id": school_oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("School not found"))?;
+        let timezone: Tz = school.settings.timezone.parse().unwrap_or(Tz::UTC);
+        let local_now = Utc::now().with_timezone(&timezone);
+        let late_after = NaiveTime::parse_from_str(&school.settings.attendance_late_after, "%H:%M")
+            .map_err(|_| Error::new("The school's late cutoff is not a valid HH:MM time"))?;
+        let late = local_now.time() > late_after;
+
+        // Same day convention as mark_bulk_attendance: noon UTC of the school's date
+        let day = Utc.from_utc_datetime(&local_now.date_naive().and_hms_opt(12, 0, 0).unwrap());
+        let attendance_date = DateTime::from_millis(day.timestamp_millis());
+        let filter = doc! {
+            "student_id": student_oid,
+            "class_id": class_oid,
+            "date": attendance_date
+        };
+
+        let collection = db.collection::<models::attendance::Attendance>("attendances");
+        let before = collection
+            .find_one(filter.clone(), None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        let existing = before.as_ref().filter(|a| !a.soft_delete.is_deleted);
+        if let Some(existing) = existing.filter(|a| a.checked_in_at.is_some()) {
+            return Ok(ScanAttendanceResult {
+                late: existing.status.eq_ignore_ascii_case("late"),
+                attendance: AttendanceType::from(before.unwrap()),
+                student: student.into(),
+                already_checked_in: true,
+            });
+        }
+
+        let now = DateTime::now();
+        let status = if late { "late" } else { "present" };
+        let checked_in = match before.as_ref() {
+            // First mark of the day. The unique (student, class, date) index lets
+            // only one of two simultaneous scans insert the row; the other
+            // matches it, or fails with a duplicate key, and changes nothing.
+            None => {
+                let update = doc! {
+                    "$setOnInsert": {
+                        "student_id": student_oid,
+                        "class_id": class_oid,
+                        "date": attendance_date,
+                        "status": status,
+                        "remarks": Bson::Null,
+                        "marked_by": marked_by_oid,
+                        "checked_in_at": now,
+                        "device_id": device_id,
+                        "created_at": now,
+                        "updated_at": now,
+                        "soft_delete.is_deleted": false,
+                        "soft_delete.deleted_at": Bson::Null,
+                        "soft_delete.deleted_by": Bson::Null
+                    }
+                };
+                let options = UpdateOptions::builder().upsert(true).build();
+                match collection.update_one(filter.clone(), update, options).await {
+                    Ok(result) => result.upserted_id.is_some(),
+                    Err(e) if is_duplicate_key(&e) => false,
+                    Err(e) => return Err(Error::new(e.to_string())),
+                }
+            }
+            // The row exists: check in only if nobody has since the read
+            Some(before) => {
+                let mut set = doc! {
+                    "checked_in_at": now,
+                    "device_id": device_id,
+                    "updated_at": now,
+                    "soft_delete.is_deleted": false,
+                    "soft_delete.deleted_at": Bson::Null,
+                    "soft_delete.deleted_by": Bson::Null
+                };
+                // A status already taken by a teacher (other than absent) is kept
+                let keeps_status = existing.is_some_and(|a| {
+                    matches!(
+                        a.status.to_lowercase().as_str(),
+                        "present" | "late" | "excused"
+                    )
+                });
+                if !keeps_status {
+                    set.insert("status", status);
+                    set.insert("marked_by", marked_by_oid);
+                }
+                let mut guarded_filter = filter.clone();
+                if before.soft_delete.is_deleted {
+                    guarded_filter.insert("soft_delete.is_deleted", true);
+                } else {
+                    guarded_filter.insert("checked_in_at", Bson::Null);
+                }
+                collection
+                    .update_one(guarded_filter, doc! { "$set": set }, None)
+                    .await
+                    .map_err(|e| Error::new(e.to_string()))?
+                    .modified_count
+                    == 1
+            }
+        };
+
+        let after = collection
+            .find_one(filter, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Failed to retrieve attendance"))?;
+        if !checked_in {
+            return Ok(ScanAttendanceResult {
+                late: after.status.eq_ignore_ascii_case("late"),
+                attendance: AttendanceType::from(after),
+                student: student.into(),
+                already_checked_in: true,
+            });
+        }
+
+        record_change(
+            ctx,
+            "Attendance",
+            after.id.map(|oid| oid.to_hex()),
+            Some(&input.school_id),
+            before.as_ref(),
+            Some(&after),
+        );
+
+        let late = after.status.eq_ignore_ascii_case("late");
+        let attendance = AttendanceType::from(after);
+        publish(RealtimeEvent::AttendanceMarked {
+            attendance: attendance.clone(),
+        });
+
+        Ok(ScanAttendanceResult {
+            attendance,
+            student: student.into(),
+            late,
+            already_checked_in: false,
+        })
+    }
+
+    /// Set the local time (HH:MM) after which ID card check-ins count as late
+    /// (ManageSettings)
+    async fn set_attendance_late_after(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        late_after: String,
+    ) -> Result<models::school::School> {
+        let db = ctx.data::<Database>()?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ManageSettings)
+            .await?;
+        let late_after = NaiveTime::parse_from_str(late_after.trim(), "%H:%M")
+            .map_err(|_| Error::new("Invalid time format. Use HH:MM"))?;
+
+        let school_oid =
+            ObjectId::parse_str(&school_id).map_err(|_| Error::new("Invalid school ID"))?;
+        let collection = db.collection::<models::school::School>("schools");
+        collection
+            .update_one(
+                doc! { "_id": school_oid },
+                doc! { "$set": {
+                    "settings.attendance_late_after": late_after.format("%H:%M").to_string(),
+                    "audit.updated_at": DateTime::now(),
+                    "audit.updated_by": &member.user_id,
+                } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update school: {}", e)))?;
+
+        collection
+            .find_one(doc! { "_id": school_oid }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to retrieve school: {}", e)))?
+            .ok_or_else(|| Error::new("School not found"))
+    }
+
+    /// Update an existing attendance record (EditAttendance in the class's school)
     async fn update_attendance(
         &self,
         ctx: &Context<'_>,
@@ -125,6 +401,20 @@ impl AttendanceMutation {
 
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
+        let before = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Attendance record not found"))?;
+
+        let school_id = class_school_id(db, before.class_id).await?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::EditAttendance)
+            .await?;
+
         let now = DateTime::now();
 
         let update = doc! {
@@ -146,21 +436,104 @@ impl AttendanceMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Attendance record not found"))?;
 
-        Ok(attendance.into())
+        record_change(
+            ctx,
+            "Attendance",
+            Some(id),
+            Some(&school_id),
+            Some(&before),
+            Some(&attendance),
+        );
+
+        let attendance = AttendanceType::from(attendance);
+        publish(RealtimeEvent::AttendanceMarked {
+            attendance: attendance.clone(),
+        });
+
+        Ok(attendance)
     }
 
-    /// Delete an attendance record
+    /// Delete an attendance record (moves it to the trash)
     async fn delete_attendance(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<models::attendance::Attendance>("attendances");
 
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
-        let result = collection
-            .delete_one(doc! { "_id": obj_id }, None)
+        let Some(before) = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        else {
+            return Ok(false);
+        };
+        let school_id = class_school_id(db, before.class_id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::EditAttendance)
+            .await?;
+
+        soft_delete_by_id(&collection, obj_id, &member.user_id).await?;
+
+        let after = collection
+            .find_one(doc! { "_id": obj_id }, None)
             .await
             .map_err(|e| Error::new(e.to_string()))?;
+        record_change(
+            ctx,
+            "Attendance",
+            Some(id),
+            Some(&school_id),
+            Some(&before),
+            after.as_ref(),
+        );
 
-        Ok(result.deleted_count > 0)
+        Ok(true)
     }
+
+    /// Restore a deleted attendance record from the trash
+    async fn restore_attendance(&self, ctx: &Context<'_>, id: String) -> Result<AttendanceType> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<models::attendance::Attendance>("attendances");
+
+        let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
+
+        let before = collection
+            .find_one(doc! { "_id": obj_id, "soft_delete.is_deleted": true }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Deleted attendance record not found"))?;
+        let school_id = class_school_id(db, before.class_id).await?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::EditAttendance)
+            .await?;
+
+        restore_by_id(&collection, obj_id).await?;
+
+        let attendance = collection
+            .find_one(doc! { "_id": obj_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Attendance record not found"))?;
+        record_change(
+            ctx,
+            "Attendance",
+            Some(id),
+            Some(&school_id),
+            Some(&before),
+            Some(&attendance),
+        );
+
+        Ok(attendance.into())
+    }
+}
+
+/// Whether a write failed because it would duplicate a unique index key
+fn is_duplicate_key(error: &mongodb::error::Error) -> bool {
+    matches!(
+        error.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(e)) if e.code == 11000
+    )
 }
@@ -1,6 +1,12 @@
 // Attendance GraphQL queries
 use super::types::{AttendanceSummaryType, AttendanceType};
+use crate::graphql::class::queries::school_class_ids;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::find_deleted;
 use async_graphql::*;
 use chrono::{NaiveDate, TimeZone, Utc};
 use futures::stream::TryStreamExt;
@@ -12,7 +18,7 @@ use mongodb::{
 #[derive(Default)]
 pub struct AttendanceQuery;
 
-#[Object]
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Attendance)")]
 impl AttendanceQuery {
     /// Get all attendance records
     async fn attendances(&self, ctx: &Context<'_>) -> Result<Vec<AttendanceType>> {
@@ -20,7 +26,7 @@ impl AttendanceQuery {
         let collection = db.collection::<models::attendance::Attendance>("attendances");
 
         let mut cursor = collection
-            .find(None, None)
+            .find(doc! { "soft_delete.is_deleted": { "$ne": true } }, None)
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -44,7 +50,10 @@ impl AttendanceQuery {
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
         let attendance = collection
-            .find_one(doc! { "_id": obj_id }, None)
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -73,6 +82,7 @@ impl AttendanceQuery {
 
         let filter = doc! {
             "class_id": class_oid,
+            "soft_delete.is_deleted": { "$ne": true },
             "date": {
                 "$gte": DateTime::from_millis(start_of_day.timestamp_millis()),
                 "$lte": DateTime::from_millis(end_of_day.timestamp_millis())
@@ -105,53 +115,11 @@ impl AttendanceQuery {
         end_date: Option<String>,
     ) -> Result<Vec<AttendanceType>> {
         let db = ctx.data::<Database>()?;
-        let collection = db.collection::<models::attendance::Attendance>("attendances");
 
         let student_oid = ObjectId::parse_str(&student_id)
             .map_err(|_| Error::new("Invalid student ID format"))?;
 
-        let mut filter = doc! { "student_id": student_oid };
-
-        // Add date range filter if provided
-        if let Some(start) = start_date {
-            if let Ok(parsed) = NaiveDate::parse_from_str(&start, "%Y-%m-%d") {
-                let start_dt = Utc.from_utc_datetime(&parsed.and_hms_opt(0, 0, 0).unwrap());
-                filter.insert(
-                    "date",
-                    doc! { "$gte": DateTime::from_millis(start_dt.timestamp_millis()) },
-                );
-            }
-        }
-
-        if let Some(end) = end_date {
-            if let Ok(parsed) = NaiveDate::parse_from_str(&end, "%Y-%m-%d") {
-                let end_dt = Utc.from_utc_datetime(&parsed.and_hms_opt(23, 59, 59).unwrap());
-                if let Some(date_filter) = filter.get_mut("date") {
-                    if let Some(doc) = date_filter.as_document_mut() {
-                        doc.insert("$lte", DateTime::from_millis(end_dt.timestamp_millis()));
-                    }
-                } else {
-                    filter.insert(
-                        "date",
-                        doc! { "$lte": DateTime::from_millis(end_dt.timestamp_millis()) },
-                    );
-                }
-            }
-        }
-
-        let mut cursor = collection
-            .find(filter, None)
-            .await
-            .map_err(|e| Error::new(e.to_string()))?;
-
-        let mut attendances = Vec::new();
-        while let Some(attendance) = cursor
-            .try_next()
-            .await
-            .map_err(|e| Error::new(e.to_string()))?
-        {
-            attendances.push(attendance);
-        }
+        let attendances = find_student_attendance(db, student_oid, start_date, end_date).await?;
 
         Ok(attendances.into_iter().map(|a| a.into()).collect())
     }
@@ -184,6 +152,7 @@ impl AttendanceQuery {
 
         let filter = doc! {
             "class_id": class_oid,
+            "soft_delete.is_deleted": { "$ne": true },
             "date": {
                 "$gte": DateTime::from_millis(start_dt.timestamp_millis()),
                 "$lt": DateTime::from_millis(end_dt.timestamp_millis())
@@ -233,4 +202,99 @@ impl AttendanceQuery {
             attendance_rate,
         })
     }
+
+    /// Deleted attendance records (the trash), optionally for one student or class
+    async fn deleted_attendances(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        student_id: Option<String>,
+        class_id: Option<String>,
+    ) -> Result<Vec<AttendanceType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::EditAttendance)
+            .await?;
+
+        // Attendance records belong to a school through their class
+        let class_ids = school_class_ids(db, &school_id).await?;
+        let mut filter = doc! { "class_id": { "$in": &class_ids } };
+        if let Some(id) = student_id {
+            let oid = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid student ID"))?;
+            filter.insert("student_id", oid);
+        }
+        if let Some(id) = class_id {
+            let oid = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid class ID"))?;
+            if !class_ids.contains(&oid) {
+                return Ok(Vec::new());
+            }
+            filter.insert("class_id", oid);
+        }
+
+        let attendances = find_deleted(
+            &db.collection::<models::attendance::Attendance>("attendances"),
+            filter,
+        )
+        .await?;
+
+        Ok(attendances.into_iter().map(|a| a.into()).collect())
+    }
+}
+
+/// Load a student's attendance records, optionally bounded by YYYY-MM-DD dates
+pub(crate) async fn find_student_attendance(
+    db: &Database,
+    student_oid: ObjectId,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<models::attendance::Attendance>> {
+    let collection = db.collection::<models::attendance::Attendance>("attendances");
+
+    let mut filter = doc! {
+        "student_id": student_oid,
+        "soft_delete.is_deleted": { "$ne": true }
+    };
+
+    // Add date range filter if provided
+    if let Some(start) = start_date {
+        if let Ok(parsed) = NaiveDate::parse_from_str(&start, "%Y-%m-%d") {
+            let start_dt = Utc.from_utc_datetime(&parsed.and_hms_opt(0, 0, 0).unwrap());
+            filter.insert(
+                "date",
+                doc! { "$gte": DateTime::from_millis(start_dt.timestamp_millis()) },
+            );
+        }
+    }
+
+    if let Some(end) = end_date {
+        if let Ok(parsed) = NaiveDate::parse_from_str(&end, "%Y-%m-%d") {
+            let end_dt = Utc.from_utc_datetime(&parsed.and_hms_opt(23, 59, 59).unwrap());
+            if let Some(date_filter) = filter.get_mut("date") {
+                if let Some(doc) = date_filter.as_document_mut() {
+                    doc.insert("$lte", DateTime::from_millis(end_dt.timestamp_millis()));
+                }
+            } else {
+                filter.insert(
+                    "date",
+                    doc! { "$lte": DateTime::from_millis(end_dt.timestamp_millis()) },
+                );
+            }
+        }
+    }
+
+    let mut cursor = collection
+        .find(filter, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut attendances = Vec::new();
+    while let Some(attendance) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        attendances.push(attendance);
+    }
+
+    Ok(attendances)
 }
@@ -0,0 +1,66 @@
+// Attendance GraphQL subscriptions
+use super::types::AttendanceType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::class::Class;
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::realtime::{in_scope, member_events, student_scope, RealtimeEvent};
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use futures::Stream;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    Database,
+};
+
+#[derive(Default)]
+pub struct AttendanceSubscription;
+
+#[Subscription(guard = "FeatureGuard::new(SchoolFeature::Attendance)")]
+impl AttendanceSubscription {
+    /// Attendance as it is marked for a class (requires ViewAttendance).
+    /// Parents and students only receive their own children's or their own records.
+    async fn attendance_marked(
+        &self,
+        ctx: &Context<'_>,
+        class_id: String,
+    ) -> Result<impl Stream<Item = AttendanceType>> {
+        let db = ctx.data::<Database>()?;
+        let class_oid =
+            ObjectId::parse_str(&class_id).map_err(|_| Error::new("Invalid class ID format"))?;
+        let class = db
+            .collection::<Class>("classes")
+            .find_one(
+                doc! { "_id": class_oid, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Class not found"))?;
+
+        ensure_feature(db, &class.school_id, SchoolFeature::Attendance).await?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let member = graphql_ctx
+            .require_member_permission(db, &class.school_id, Permission::ViewAttendance)
+            .await?;
+        let user = graphql_ctx.require_auth()?.clone();
+        let scope = student_scope(&member, Permission::ViewStudents);
+
+        Ok(member_events(
+            db.clone(),
+            user,
+            class.school_id,
+            Some(Permission::ViewAttendance),
+            move |event| match event {
+                RealtimeEvent::AttendanceMarked { attendance }
+                    if attendance.class_id == class_id
+                        && in_scope(&scope, &attendance.student_id) =>
+                {
+                    Some(attendance)
+                }
+                _ => None,
+            },
+        ))
+    }
+}
@@ -1,8 +1,10 @@
 // Attendance GraphQL types
+use crate::graphql::student::StudentType;
 use crate::models::attendance::Attendance;
+use crate::utils::common_types::SoftDelete;
 use async_graphql::*;
 
-#[derive(SimpleObject)]
+#[derive(SimpleObject, Debug, Clone)]
 pub struct AttendanceType {
     pub id: String,
     pub student_id: String,
@@ -11,8 +13,14 @@ pub struct AttendanceType {
     pub status: String,
     pub remarks: Option<String>,
     pub marked_by: String,
+    /// When an ID card scan checked the student in
+    pub checked_in_at: Option<String>,
+    /// Scanner that recorded the check-in
+    pub device_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Trash state; set when the record has been deleted
+    pub soft_delete: SoftDelete,
 }
 
 impl From<Attendance> for AttendanceType {
@@ -25,8 +33,11 @@ impl From<Attendance> for AttendanceType {
             status: a.status,
             remarks: a.remarks,
             marked_by: a.marked_by.to_hex(),
+            checked_in_at: a.checked_in_at.and_then(|d| d.try_to_rfc3339_string().ok()),
+            device_id: a.device_id,
             created_at: a.created_at.try_to_rfc3339_string().unwrap_or_default(),
             updated_at: a.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+            soft_delete: a.soft_delete,
         }
     }
 }
@@ -41,8 +52,55 @@ pub struct AttendanceSummaryType {
     pub attendance_rate: f64,
 }
 
+impl AttendanceSummaryType {
+    /// Summarize a set of attendance records covering `total_days` days
+    pub fn from_records(records: &[Attendance], total_days: i32) -> Self {
+        let mut present_count = 0;
+        let mut absent_count = 0;
+        let mut late_count = 0;
+        let mut excused_count = 0;
+
+        for attendance in records {
+            match attendance.status.to_lowercase().as_str() {
+                "present" => present_count += 1,
+                "absent" => absent_count += 1,
+                "late" => late_count += 1,
+                "excused" => excused_count += 1,
+                _ => {}
+            }
+        }
+
+        let total = present_count + absent_count + late_count + excused_count;
+        let attendance_rate = if total > 0 {
+            ((present_count + late_count) as f64 / total as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        AttendanceSummaryType {
+            total_days,
+            present_count,
+            absent_count,
+            late_count,
+            excused_count,
+            attendance_rate,
+        }
+    }
+}
+
 #[derive(SimpleObject)]
 pub struct BulkAttendanceResult {
     pub success: bool,
     pub count: i32,
 }
+
+/// Outcome of a gate scan
+#[derive(SimpleObject)]
+pub struct ScanAttendanceResult {
+    pub attendance: AttendanceType,
+    pub student: StudentType,
+    /// Checked in after the school's late cutoff
+    pub late: bool,
+    /// The student had already been checked in today; nothing changed
+    pub already_checked_in: bool,
+}
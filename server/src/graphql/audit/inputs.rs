@@ -0,0 +1,25 @@
+use async_graphql::{Enum, InputObject};
+
+/// Filters for the audit log. Dates accept RFC 3339 timestamps or YYYY-MM-DD.
+#[derive(InputObject, Default)]
+pub struct AuditLogFilterInput {
+    /// School to read; omit for the platform-wide log (system admins only)
+    pub school_id: Option<String>,
+    pub actor_id: Option<String>,
+    /// Kind of record (e.g., "Grade", "Member")
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    /// Mutation name (e.g., "updateGrade")
+    pub action: Option<String>,
+    pub success: Option<bool>,
+    /// Earliest entry (inclusive)
+    pub from: Option<String>,
+    /// Latest entry (inclusive; a bare date covers the whole day)
+    pub to: Option<String>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum AuditExportFormat {
+    Csv,
+    Json,
+}
@@ -0,0 +1,4 @@
+pub mod inputs;
+pub mod queries;
+
+pub use queries::AuditQuery;
@@ -0,0 +1,234 @@
+use super::inputs::{AuditExportFormat, AuditLogFilterInput};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::audit_log::AuditLog;
+use crate::models::member::Permission;
+use crate::models::user::SystemRole;
+use async_graphql::*;
+use chrono::{NaiveDate, TimeZone, Utc};
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, DateTime, Document},
+    options::FindOptions,
+    Database,
+};
+
+/// Default page size for audit log queries
+const DEFAULT_AUDIT_LIMIT: i64 = 50;
+/// Maximum entries in one export
+const MAX_EXPORT_ENTRIES: i64 = 10_000;
+
+#[derive(Default)]
+pub struct AuditQuery;
+
+#[Object]
+impl AuditQuery {
+    /// Search the audit log, newest first. School logs require ViewAuditLog;
+    /// the platform-wide log requires a system admin.
+    async fn audit_logs(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AuditLogFilterInput>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<AuditLog>> {
+        let db = ctx.data::<Database>()?;
+        let filter = authorized_filter(ctx, db, filter.unwrap_or_default()).await?;
+
+        find_audit_logs(
+            db,
+            filter,
+            limit.unwrap_or(DEFAULT_AUDIT_LIMIT).clamp(1, 500),
+            offset.unwrap_or(0).max(0) as u64,
+        )
+        .await
+    }
+
+    /// Export matching audit entries (oldest first) as CSV or JSON, for
+    /// compliance reporting
+    async fn export_audit_logs(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AuditLogFilterInput>,
+        format: AuditExportFormat,
+    ) -> Result<String> {
+        let db = ctx.data::<Database>()?;
+        let filter = authorized_filter(ctx, db, filter.unwrap_or_default()).await?;
+
+        let mut logs = find_audit_logs(db, filter, MAX_EXPORT_ENTRIES, 0).await?;
+        logs.reverse();
+
+        match format {
+            AuditExportFormat::Csv => Ok(to_csv(&logs)),
+            AuditExportFormat::Json => {
+                let entries: Vec<serde_json::Value> = logs.iter().map(to_json_entry).collect();
+                serde_json::to_string_pretty(&entries).map_err(|e| Error::new(e.to_string()))
+            }
+        }
+    }
+}
+
+/// Check access for the filter's school and build the MongoDB filter
+async fn authorized_filter(
+    ctx: &Context<'_>,
+    db: &Database,
+    input: AuditLogFilterInput,
+) -> Result<Document> {
+    let graphql_ctx = get_graphql_context(ctx)?;
+    match input.school_id.as_deref() {
+        Some(school_id) => {
+            graphql_ctx
+                .require_member_permission(db, school_id, Permission::ViewAuditLog)
+                .await?;
+        }
+        None => {
+            graphql_ctx.require_role(SystemRole::Admin)?;
+        }
+    }
+
+    let mut filter = doc! {};
+    if let Some(school_id) = input.school_id {
+        filter.insert("school_id", school_id);
+    }
+    if let Some(actor_id) = input.actor_id {
+        filter.insert("actor_id", actor_id);
+    }
+    if let Some(entity_type) = input.entity_type {
+        filter.insert("entity_type", entity_type);
+    }
+    if let Some(entity_id) = input.entity_id {
+        filter.insert("entity_id", entity_id);
+    }
+    if let Some(action) = input.action {
+        filter.insert("action", action);
+    }
+    if let Some(success) = input.success {
+        filter.insert("success", success);
+    }
+
+    let mut range = doc! {};
+    if let Some(from) = input.from {
+        range.insert("$gte", parse_date(&from, false)?);
+    }
+    if let Some(to) = input.to {
+        range.insert("$lte", parse_date(&to, true)?);
+    }
+    if !range.is_empty() {
+        filter.insert("created_at", range);
+    }
+
+    Ok(filter)
+}
+
+async fn find_audit_logs(
+    db: &Database,
+    filter: Document,
+    limit: i64,
+    skip: u64,
+) -> Result<Vec<AuditLog>> {
+    let options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .skip(skip)
+        .limit(limit)
+        .build();
+
+    let mut cursor = db
+        .collection::<AuditLog>("audit_logs")
+        .find(filter, options)
+        .await
+        .map_err(|e| Error::new(format!("Failed to fetch audit log: {}", e)))?;
+
+    let mut logs = Vec::new();
+    while let Some(log) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        logs.push(log);
+    }
+
+    Ok(logs)
+}
+
+/// Parse an RFC 3339 timestamp or a YYYY-MM-DD date (start or end of day)
+fn parse_date(value: &str, end_of_day: bool) -> Result<DateTime> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(DateTime::from_millis(dt.timestamp_millis()));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| Error::new("Invalid date. Use YYYY-MM-DD or an RFC 3339 timestamp"))?;
+    let time = if end_of_day {
+        date.and_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    let dt = Utc.from_utc_datetime(&time.unwrap_or_default());
+    Ok(DateTime::from_millis(dt.timestamp_millis()))
+}
+
+fn to_csv(logs: &[AuditLog]) -> String {
+    let mut csv = String::from(
+        "timestamp,actor_id,school_id,action,entity_type,entity_id,success,error,ip,changes\n",
+    );
+    for log in logs {
+        let changes: Vec<String> = log
+            .diff()
+            .into_iter()
+            .map(|c| {
+                format!(
+                    "{}: {} -> {}",
+                    c.field,
+                    c.before.unwrap_or_else(|| "null".to_string()),
+                    c.after.unwrap_or_else(|| "null".to_string())
+                )
+            })
+            .collect();
+        let row = [
+            log.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            log.actor_id.clone().unwrap_or_default(),
+            log.school_id.clone().unwrap_or_default(),
+            log.action.clone(),
+            log.entity_type.clone().unwrap_or_default(),
+            log.entity_id.clone().unwrap_or_default(),
+            log.success.to_string(),
+            log.error.clone().unwrap_or_default(),
+            log.ip.clone().unwrap_or_default(),
+            changes.join("; "),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote a CSV field when needed
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_json_entry(log: &AuditLog) -> serde_json::Value {
+    let to_json = |doc: &Option<Document>| {
+        doc.as_ref()
+            .map(|d| mongodb::bson::Bson::Document(d.clone()).into_relaxed_extjson())
+    };
+    serde_json::json!({
+        "id": log.id.map(|oid| oid.to_hex()),
+        "timestamp": log.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        "actor_id": log.actor_id,
+        "school_id": log.school_id,
+        "ip": log.ip,
+        "action": log.action,
+        "entity_type": log.entity_type,
+        "entity_id": log.entity_id,
+        "success": log.success,
+        "error": log.error,
+        "arguments": to_json(&log.arguments),
+        "before": to_json(&log.before),
+        "after": to_json(&log.after),
+    })
+}
@@ -0,0 +1,18 @@
+use async_graphql::InputObject;
+
+use crate::models::school::SubscriptionPlan;
+
+#[derive(InputObject)]
+pub struct ChangeSubscriptionPlanInput {
+    pub school_id: String,
+    pub plan: SubscriptionPlan,
+    /// Months to pay for up front (default 1)
+    pub months: Option<i32>,
+}
+
+#[derive(InputObject)]
+pub struct RenewSubscriptionInput {
+    pub school_id: String,
+    /// Months to extend the subscription by (default 1)
+    pub months: Option<i32>,
+}
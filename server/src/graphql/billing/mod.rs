@@ -0,0 +1,7 @@
+pub mod inputs;
+pub mod mutations;
+pub mod queries;
+pub mod types;
+
+pub use mutations::BillingMutation;
+pub use queries::BillingQuery;
@@ -0,0 +1,307 @@
+use super::inputs::{ChangeSubscriptionPlanInput, RenewSubscriptionInput};
+use super::types::SubscriptionStatusType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::member::SchoolRole;
+use crate::models::school::{School, SchoolFeature, Subscription, SubscriptionPlan};
+use crate::models::subscription_invoice::{SubscriptionInvoice, SubscriptionInvoiceStatus};
+use crate::utils::audit::record_change;
+use crate::utils::subscription::{count_staff, count_students, find_school, payment_provider};
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, DateTime},
+    Database,
+};
+
+/// Length of one billed month
+const BILLING_MONTH_DAYS: i64 = 30;
+
+/// Longest period that can be paid for at once
+const MAX_BILLED_MONTHS: i32 = 24;
+
+#[derive(Default)]
+pub struct BillingMutation;
+
+#[Object]
+impl BillingMutation {
+    /// Move the school to another plan (Owner only, requires a recent MFA check).
+    /// Paid plans are charged up front and start today; downgrades are refused
+    /// while the school has more students or staff than the new plan allows.
+    async fn change_subscription_plan(
+        &self,
+        ctx: &Context<'_>,
+        input: ChangeSubscriptionPlanInput,
+    ) -> Result<SubscriptionStatusType> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let owner = graphql_ctx
+            .require_member_role(db, &input.school_id, &[SchoolRole::Owner])
+            .await?;
+        graphql_ctx
+            .require_step_up(db, Some(&input.school_id))
+            .await?;
+
+        let months = billed_months(input.months)?;
+        let school = find_school(db, &input.school_id).await?;
+        let current = school.current_subscription();
+
+        if current.plan == input.plan {
+            return Err(Error::new(format!(
+                "The school is already on the {:?} plan; renew it instead",
+                input.plan
+            )));
+        }
+
+        // Downgrades must fit the school's current size
+        let student_count = count_students(db, &input.school_id).await?;
+        let staff_count = count_staff(db, &input.school_id).await?;
+        if let Some(limit) = input.plan.max_students() {
+            if student_count > limit as i64 {
+                return Err(Error::new(format!(
+                    "The {:?} plan allows {} students but the school has {}",
+                    input.plan, limit, student_count
+                )));
+            }
+        }
+        if let Some(limit) = input.plan.max_staff() {
+            if staff_count > limit as i64 {
+                return Err(Error::new(format!(
+                    "The {:?} plan allows {} staff members but the school has {}",
+                    input.plan, limit, staff_count
+                )));
+            }
+        }
+
+        let now = DateTime::now();
+        let expires_at = if input.plan == SubscriptionPlan::Free {
+            None
+        } else {
+            let invoice = bill(db, &school, &owner.user_id, input.plan, months, now).await?;
+            Some(invoice.period_end)
+        };
+
+        let subscription = Subscription {
+            plan: input.plan,
+            started_at: Some(now),
+            expires_at,
+            max_students: input.plan.max_students().unwrap_or(0),
+            max_staff: input.plan.max_staff().unwrap_or(0),
+            is_active: true,
+        };
+        let school = apply_subscription(ctx, db, &school, subscription, &owner.user_id).await?;
+
+        Ok(SubscriptionStatusType::new(
+            &school,
+            student_count,
+            staff_count,
+        ))
+    }
+
+    /// Pay for more months of the current plan (Owner only). Renewing before
+    /// expiry extends the current period; renewing after it starts a new one today.
+    /// Available while the school is read-only.
+    async fn renew_subscription(
+        &self,
+        ctx: &Context<'_>,
+        input: RenewSubscriptionInput,
+    ) -> Result<SubscriptionStatusType> {
+        let db = ctx.data::<Database>()?;
+        let owner = get_graphql_context(ctx)?
+            .require_member_role(db, &input.school_id, &[SchoolRole::Owner])
+            .await?;
+
+        let months = billed_months(input.months)?;
+        let school = find_school(db, &input.school_id).await?;
+        let current = school.current_subscription();
+
+        if current.plan == SubscriptionPlan::Free {
+            return Err(Error::new("The Free plan does not need renewing"));
+        }
+
+        let now = DateTime::now();
+        let start = match current.expires_at {
+            Some(expires) if expires > now => expires,
+            _ => now,
+        };
+        let invoice = bill(db, &school, &owner.user_id, current.plan, months, start).await?;
+
+        let subscription = Subscription {
+            started_at: current.started_at.or(Some(now)),
+            expires_at: Some(invoice.period_end),
+            is_active: true,
+            ..current
+        };
+        let school = apply_subscription(ctx, db, &school, subscription, &owner.user_id).await?;
+
+        Ok(SubscriptionStatusType::new(
+            &school,
+            count_students(db, &input.school_id).await?,
+            count_staff(db, &input.school_id).await?,
+        ))
+    }
+}
+
+/// Validate the number of months to bill
+fn billed_months(months: Option<i32>) -> Result<i32> {
+    let months = months.unwrap_or(1);
+    if !(1..=MAX_BILLED_MONTHS).contains(&months) {
+        return Err(Error::new(format!(
+            "Months must be between 1 and {}",
+            MAX_BILLED_MONTHS
+        )));
+    }
+    Ok(months)
+}
+
+/// Issue an invoice for `months` of a plan starting at `start` and charge it.
+/// Failed charges are kept in the invoice history and reported as an error.
+async fn bill(
+    db: &Database,
+    school: &School,
+    user_id: &str,
+    plan: SubscriptionPlan,
+    months: i32,
+    start: DateTime,
+) -> Result<SubscriptionInvoice> {
+    let collection = db.collection::<SubscriptionInvoice>("subscription_invoices");
+    let school_id = school.id.map(|oid| oid.to_hex()).unwrap_or_default();
+    let provider = payment_provider();
+
+    let period_end = DateTime::from_millis(
+        start.timestamp_millis() + months as i64 * BILLING_MONTH_DAYS * 24 * 60 * 60 * 1000,
+    );
+
+    let mut invoice = SubscriptionInvoice {
+        id: None,
+        school_id,
+        invoice_number: next_invoice_number(db).await?,
+        plan,
+        months,
+        amount: plan.monthly_price() * months as f64,
+        currency: "USD".to_string(),
+        period_start: start,
+        period_end,
+        status: SubscriptionInvoiceStatus::Pending,
+        provider: provider.name().to_string(),
+        provider_reference: None,
+        failure_reason: None,
+        paid_at: None,
+        created_by: user_id.to_string(),
+        created_at: DateTime::now(),
+    };
+
+    let result = collection
+        .insert_one(&invoice, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to create invoice: {}", e)))?;
+    let invoice_id = result
+        .inserted_id
+        .as_object_id()
+        .ok_or_else(|| Error::new("Failed to get inserted invoice ID"))?;
+    invoice.id = Some(invoice_id);
+
+    let update = match provider.charge(&invoice).await {
+        Ok(reference) => {
+            invoice.status = SubscriptionInvoiceStatus::Paid;
+            invoice.paid_at = Some(DateTime::now());
+            doc! {
+                "status": "Paid",
+                "provider_reference": &reference,
+                "paid_at": invoice.paid_at,
+            }
+        }
+        Err(reason) => {
+            invoice.status = SubscriptionInvoiceStatus::Failed;
+            invoice.failure_reason = Some(reason.clone());
+            doc! { "status": "Failed", "failure_reason": &reason }
+        }
+    };
+
+    collection
+        .update_one(doc! { "_id": invoice_id }, doc! { "$set": update }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to update invoice: {}", e)))?;
+
+    if let Some(reason) = invoice.failure_reason.as_ref() {
+        return Err(Error::new(format!(
+            "Payment for invoice {} failed: {}",
+            invoice.invoice_number, reason
+        )));
+    }
+
+    Ok(invoice)
+}
+
+/// Next invoice number for the current month (e.g., "SUB-202610-0001")
+async fn next_invoice_number(db: &Database) -> Result<String> {
+    let prefix = format!("SUB-{}", chrono::Utc::now().format("%Y%m"));
+    let count = db
+        .collection::<SubscriptionInvoice>("subscription_invoices")
+        .count_documents(
+            doc! { "invoice_number": { "$regex": format!("^{}-", prefix) } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to number invoice: {}", e)))?;
+
+    Ok(format!("{}-{:04}", prefix, count + 1))
+}
+
+/// Store a school's new subscription. Modules the new plan doesn't include are
+/// turned off, and modules it adds over the previous plan are turned on.
+async fn apply_subscription(
+    ctx: &Context<'_>,
+    db: &Database,
+    school: &School,
+    subscription: Subscription,
+    user_id: &str,
+) -> Result<School> {
+    let collection = db.collection::<School>("schools");
+    let school_oid = school.id.ok_or_else(|| Error::new("School not found"))?;
+    let previous_plan = school.current_subscription().plan;
+
+    let mut features: Vec<SchoolFeature> = school
+        .features
+        .iter()
+        .copied()
+        .filter(|f| subscription.plan.includes(*f))
+        .collect();
+    for feature in subscription.plan.features() {
+        if !previous_plan.includes(feature) && !features.contains(&feature) {
+            features.push(feature);
+        }
+    }
+
+    let update = doc! {
+        "$set": {
+            "subscription": mongodb::bson::to_bson(&subscription)
+                .map_err(|e| Error::new(e.to_string()))?,
+            "features": mongodb::bson::to_bson(&features)
+                .map_err(|e| Error::new(e.to_string()))?,
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": user_id,
+        }
+    };
+
+    collection
+        .update_one(doc! { "_id": school_oid }, update, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to update subscription: {}", e)))?;
+
+    let updated = collection
+        .find_one(doc! { "_id": school_oid }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to retrieve school: {}", e)))?
+        .ok_or_else(|| Error::new("School not found"))?;
+
+    let school_id = school_oid.to_hex();
+    record_change(
+        ctx,
+        "School",
+        Some(school_id.clone()),
+        Some(&school_id),
+        Some(school),
+        Some(&updated),
+    );
+
+    Ok(updated)
+}
@@ -0,0 +1,83 @@
+use super::types::{SubscriptionPlanType, SubscriptionStatusType};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::member::SchoolRole;
+use crate::models::school::SubscriptionPlan;
+use crate::models::subscription_invoice::SubscriptionInvoice;
+use crate::utils::subscription::{count_staff, count_students, find_school};
+use async_graphql::*;
+use futures::stream::TryStreamExt;
+use mongodb::{bson::doc, options::FindOptions, Database};
+
+#[derive(Default)]
+pub struct BillingQuery;
+
+#[Object]
+impl BillingQuery {
+    /// Plans on offer, cheapest first
+    async fn subscription_plans(&self) -> Vec<SubscriptionPlanType> {
+        [
+            SubscriptionPlan::Free,
+            SubscriptionPlan::Basic,
+            SubscriptionPlan::Standard,
+            SubscriptionPlan::Premium,
+            SubscriptionPlan::Enterprise,
+        ]
+        .into_iter()
+        .map(SubscriptionPlanType::from)
+        .collect()
+    }
+
+    /// A school's plan, expiry and usage against its limits (any member)
+    async fn school_subscription(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<SubscriptionStatusType> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member(db, &school_id)
+            .await?;
+
+        let school = find_school(db, &school_id).await?;
+        let student_count = count_students(db, &school_id).await?;
+        let staff_count = count_staff(db, &school_id).await?;
+
+        Ok(SubscriptionStatusType::new(
+            &school,
+            student_count,
+            staff_count,
+        ))
+    }
+
+    /// The school's subscription invoices, newest first (Owner only)
+    async fn subscription_invoices(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<SubscriptionInvoice>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_role(db, &school_id, &[SchoolRole::Owner])
+            .await?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .build();
+        let mut cursor = db
+            .collection::<SubscriptionInvoice>("subscription_invoices")
+            .find(doc! { "school_id": &school_id }, options)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+
+        let mut invoices = Vec::new();
+        while let Some(invoice) = cursor
+            .try_next()
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        {
+            invoices.push(invoice);
+        }
+
+        Ok(invoices)
+    }
+}
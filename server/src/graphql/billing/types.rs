@@ -0,0 +1,72 @@
+use crate::models::school::{School, SchoolFeature, SubscriptionPlan, SubscriptionState};
+use async_graphql::SimpleObject;
+
+/// A plan on offer, with its price and what it includes
+#[derive(SimpleObject)]
+pub struct SubscriptionPlanType {
+    pub plan: SubscriptionPlan,
+    /// Monthly price in USD
+    pub monthly_price: f64,
+    /// Student limit (none = unlimited)
+    pub max_students: Option<i32>,
+    /// Staff limit (none = unlimited)
+    pub max_staff: Option<i32>,
+    pub features: Vec<SchoolFeature>,
+}
+
+impl From<SubscriptionPlan> for SubscriptionPlanType {
+    fn from(plan: SubscriptionPlan) -> Self {
+        SubscriptionPlanType {
+            plan,
+            monthly_price: plan.monthly_price(),
+            max_students: plan.max_students(),
+            max_staff: plan.max_staff(),
+            features: plan.features(),
+        }
+    }
+}
+
+/// A school's subscription with its current usage
+#[derive(SimpleObject)]
+pub struct SubscriptionStatusType {
+    pub plan: SubscriptionPlan,
+    pub state: SubscriptionState,
+    pub expires_at: Option<String>,
+    /// When the school becomes read-only if the subscription is not renewed
+    pub grace_ends_at: Option<String>,
+    pub max_students: Option<i32>,
+    pub max_staff: Option<i32>,
+    pub student_count: i64,
+    pub staff_count: i64,
+    /// Modules included in the plan
+    pub included_features: Vec<SchoolFeature>,
+    /// Modules the school has turned on (and its plan includes)
+    pub enabled_features: Vec<SchoolFeature>,
+}
+
+impl SubscriptionStatusType {
+    pub fn new(school: &School, student_count: i64, staff_count: i64) -> Self {
+        let subscription = school.current_subscription();
+        SubscriptionStatusType {
+            plan: subscription.plan,
+            state: subscription.state(),
+            expires_at: subscription
+                .expires_at
+                .and_then(|d| d.try_to_rfc3339_string().ok()),
+            grace_ends_at: subscription
+                .grace_ends_at()
+                .and_then(|d| d.try_to_rfc3339_string().ok()),
+            max_students: subscription.student_limit(),
+            max_staff: subscription.staff_limit(),
+            student_count,
+            staff_count,
+            included_features: subscription.plan.features(),
+            enabled_features: school
+                .features
+                .iter()
+                .copied()
+                .filter(|f| school.has_feature(*f))
+                .collect(),
+        }
+    }
+}
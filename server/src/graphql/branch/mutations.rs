@@ -1,7 +1,10 @@
 // Branch GraphQL mutations
 use super::inputs::{BranchInput, UpdateBranchInput};
 use super::types::BranchType;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::soft_delete::{restore_by_id, soft_delete_by_id};
 use async_graphql::*;
 use mongodb::{
     bson::{doc, oid::ObjectId},
@@ -84,17 +87,56 @@ impl BranchMutation {
         Ok(branch.into())
     }
 
+    /// Delete a branch (moves it to the trash)
     async fn delete_branch(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<models::branch::Branch>("branches");
 
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
-        let result = collection
-            .delete_one(doc! { "_id": obj_id }, None)
+        let Some(branch) = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
-            .map_err(|e| Error::new(e.to_string()))?;
+            .map_err(|e| Error::new(e.to_string()))?
+        else {
+            return Ok(false);
+        };
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &branch.school_id.to_hex(), Permission::ManageSettings)
+            .await?;
+
+        soft_delete_by_id(&collection, obj_id, &member.user_id).await
+    }
+
+    /// Restore a deleted branch from the trash
+    async fn restore_branch(&self, ctx: &Context<'_>, id: String) -> Result<BranchType> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<models::branch::Branch>("branches");
+
+        let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
+
+        let branch = collection
+            .find_one(doc! { "_id": obj_id, "soft_delete.is_deleted": true }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Deleted branch not found"))?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &branch.school_id.to_hex(), Permission::ManageSettings)
+            .await?;
+
+        if !restore_by_id(&collection, obj_id).await? {
+            return Err(Error::new("Deleted branch not found"));
+        }
 
-        Ok(result.deleted_count > 0)
+        let branch = collection
+            .find_one(doc! { "_id": obj_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Branch not found"))?;
+
+        Ok(branch.into())
     }
 }
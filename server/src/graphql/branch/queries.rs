@@ -1,6 +1,9 @@
 // Branch GraphQL queries
 use super::types::BranchType;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::soft_delete::find_deleted;
 use async_graphql::*;
 use futures::stream::TryStreamExt;
 use mongodb::{
@@ -18,7 +21,7 @@ impl BranchQuery {
         let collection = db.collection::<models::branch::Branch>("branches");
 
         let mut cursor = collection
-            .find(None, None)
+            .find(doc! { "soft_delete.is_deleted": { "$ne": true } }, None)
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -41,7 +44,10 @@ impl BranchQuery {
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
         let branch = collection
-            .find_one(doc! { "_id": obj_id }, None)
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -59,7 +65,10 @@ impl BranchQuery {
         let obj_id =
             ObjectId::parse_str(&school_id).map_err(|_| Error::new("Invalid school ID"))?;
 
-        let filter = doc! { "school_id": obj_id };
+        let filter = doc! {
+            "school_id": obj_id,
+            "soft_delete.is_deleted": { "$ne": true }
+        };
         let mut cursor = collection
             .find(filter, None)
             .await
@@ -76,4 +85,27 @@ impl BranchQuery {
 
         Ok(branches.into_iter().map(|b| b.into()).collect())
     }
+
+    /// Get a school's deleted branches (the trash), most recently deleted first
+    async fn deleted_branches(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<BranchType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ManageSettings)
+            .await?;
+
+        let obj_id =
+            ObjectId::parse_str(&school_id).map_err(|_| Error::new("Invalid school ID"))?;
+
+        let branches = find_deleted(
+            &db.collection::<models::branch::Branch>("branches"),
+            doc! { "school_id": obj_id },
+        )
+        .await?;
+
+        Ok(branches.into_iter().map(|b| b.into()).collect())
+    }
 }
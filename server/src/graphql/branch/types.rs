@@ -1,6 +1,7 @@
 // Branch GraphQL types
 use async_graphql::*;
 use crate::models::branch::Branch;
+use crate::utils::common_types::SoftDelete;
 use crate::graphql::common::AddressType;
 
 #[derive(SimpleObject)]
@@ -13,6 +14,8 @@ pub struct BranchType {
     pub contact_phone: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Trash state; set when the record has been deleted
+    pub soft_delete: SoftDelete,
 }
 
 impl From<Branch> for BranchType {
@@ -26,6 +29,7 @@ impl From<Branch> for BranchType {
             contact_phone: b.contact_phone,
             created_at: b.created_at,
             updated_at: b.updated_at,
+            soft_delete: b.soft_delete,
         }
     }
 }
@@ -1,7 +1,10 @@
 // Class GraphQL mutations
 use super::inputs::ClassInput;
 use super::types::ClassType;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::soft_delete::{restore_by_id, soft_delete_by_id};
 use async_graphql::*;
 use mongodb::{
     bson::{doc, oid::ObjectId},
@@ -92,6 +95,7 @@ impl ClassMutation {
         Ok(class.into())
     }
 
+    /// Delete a class (moves it to the trash)
     async fn delete_class(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<models::class::Class>("classes");
@@ -99,19 +103,50 @@ impl ClassMutation {
         // Parse ObjectId
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
-        // Soft delete: mark as deleted instead of actually deleting
-        let update_doc = doc! {
-            "$set": {
-                "soft_delete.is_deleted": true,
-                "soft_delete.deleted_at": mongodb::bson::DateTime::now()
-            }
+        let Some(class) = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        else {
+            return Ok(false);
         };
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &class.school_id, Permission::ManageClasses)
+            .await?;
 
-        collection
-            .update_one(doc! { "_id": obj_id }, update_doc, None)
+        // Soft delete: mark as deleted instead of actually deleting
+        soft_delete_by_id(&collection, obj_id, &member.user_id).await
+    }
+
+    /// Restore a deleted class from the trash
+    async fn restore_class(&self, ctx: &Context<'_>, id: String) -> Result<ClassType> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<models::class::Class>("classes");
+
+        let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
+
+        let class = collection
+            .find_one(doc! { "_id": obj_id, "soft_delete.is_deleted": true }, None)
             .await
-            .map_err(|e| Error::new(e.to_string()))?;
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Deleted class not found"))?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &class.school_id, Permission::ManageClasses)
+            .await?;
+
+        if !restore_by_id(&collection, obj_id).await? {
+            return Err(Error::new("Deleted class not found"));
+        }
+
+        let record = collection
+            .find_one(doc! { "_id": obj_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Class not found"))?;
 
-        Ok(true)
+        Ok(record.into())
     }
 }
@@ -1,7 +1,10 @@
 // Class GraphQL queries
 use super::inputs::{ClassFilterInput, ClassSortInput};
 use super::types::{ClassType, PaginatedClassesResult};
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::soft_delete::find_deleted;
 use async_graphql::*;
 use futures::stream::TryStreamExt;
 use mongodb::{
@@ -157,6 +160,26 @@ impl ClassQuery {
             total_pages,
         })
     }
+
+    /// Get a school's deleted classes (the trash), most recently deleted first
+    async fn deleted_classes(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<ClassType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ManageClasses)
+            .await?;
+
+        let records = find_deleted(
+            &db.collection::<models::class::Class>("classes"),
+            doc! { "school_id": school_id },
+        )
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
 }
 
 /// Helper function to build sort document
@@ -187,3 +210,34 @@ fn build_sort_document(sort: &Option<ClassSortInput>) -> Document {
 
     sort_doc
 }
+
+/// The school a class belongs to, whether or not the class is in the trash.
+/// Attendance and grades are scoped to a school through their class.
+pub(crate) async fn class_school_id(db: &Database, class_id: ObjectId) -> Result<String> {
+    db.collection::<models::class::Class>("classes")
+        .find_one(doc! { "_id": class_id }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .map(|class| class.school_id)
+        .ok_or_else(|| Error::new("Class not found"))
+}
+
+/// IDs of every class in a school, including classes in the trash
+pub(crate) async fn school_class_ids(db: &Database, school_id: &str) -> Result<Vec<ObjectId>> {
+    let mut cursor = db
+        .collection::<models::class::Class>("classes")
+        .find(doc! { "school_id": school_id }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut ids = Vec::new();
+    while let Some(class) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        ids.extend(class.id);
+    }
+
+    Ok(ids)
+}
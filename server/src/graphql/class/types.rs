@@ -3,7 +3,7 @@
 // These legacy types are kept for backward compatibility
 
 use crate::models::class::Class;
-use crate::utils::common_types::Status;
+use crate::utils::common_types::{SoftDelete, Status};
 use async_graphql::*;
 
 // Re-export schedule types from the model since they already have GraphQL derives
@@ -25,6 +25,8 @@ pub struct ClassType {
     pub capacity: i32,
     pub current_enrollment: i32,
     pub status: Status,
+    /// Trash state; set when the record has been deleted
+    pub soft_delete: SoftDelete,
 }
 
 impl From<Class> for ClassType {
@@ -42,6 +44,7 @@ impl From<Class> for ClassType {
             capacity: c.capacity,
             current_enrollment: c.current_enrollment,
             status: c.status,
+            soft_delete: c.soft_delete,
         }
     }
 }
@@ -0,0 +1,68 @@
+use async_graphql::InputObject;
+
+use crate::models::event::{EventCategory, RecurrenceFrequency, RsvpResponse};
+use crate::utils::common_types::AudienceScope;
+
+#[derive(InputObject)]
+pub struct RecurrenceInput {
+    pub frequency: RecurrenceFrequency,
+    /// Repeat every N days/weeks/months/years (default 1)
+    pub interval: Option<i32>,
+    /// Stop after this many occurrences
+    pub count: Option<i32>,
+    /// Last day an occurrence may start (RFC 3339 or YYYY-MM-DD)
+    pub until: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct CreateEventInput {
+    pub school_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub category: Option<EventCategory>,
+    pub scope: AudienceScope,
+    /// Branch ID, grade level or class ID; required unless the scope is School
+    pub scope_id: Option<String>,
+    /// RFC 3339, or YYYY-MM-DD for all-day events
+    pub starts_at: String,
+    /// Defaults to the start (all-day events: the same day)
+    pub ends_at: Option<String>,
+    pub all_day: Option<bool>,
+    pub recurrence: Option<RecurrenceInput>,
+    pub rsvp_enabled: Option<bool>,
+    pub rsvp_deadline: Option<String>,
+    /// Minutes before each occurrence to remind the audience (up to one week)
+    pub reminder_minutes: Option<Vec<i32>>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateEventInput {
+    pub event_id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub category: Option<EventCategory>,
+    pub scope: Option<AudienceScope>,
+    pub scope_id: Option<String>,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub all_day: Option<bool>,
+    pub recurrence: Option<RecurrenceInput>,
+    /// Turn a repeating event into a one-off
+    pub clear_recurrence: Option<bool>,
+    pub rsvp_enabled: Option<bool>,
+    pub rsvp_deadline: Option<String>,
+    pub reminder_minutes: Option<Vec<i32>>,
+}
+
+#[derive(InputObject)]
+pub struct RsvpEventInput {
+    pub event_id: String,
+    /// Child the reply is for; required for parents
+    pub student_id: Option<String>,
+    pub response: RsvpResponse,
+    /// Extra people coming along (default 0)
+    pub guests: Option<i32>,
+    pub note: Option<String>,
+}
@@ -0,0 +1,455 @@
+use super::inputs::{CreateEventInput, RecurrenceInput, RsvpEventInput, UpdateEventInput};
+use super::queries::{check_visible, find_event, parse_datetime, require_events};
+use super::types::CalendarFeedType;
+use crate::graphql::library::queries::to_bson;
+use crate::models::event::{CalendarFeed, EventCategory, EventRsvp, RecurrenceRule, SchoolEvent};
+use crate::models::member::Permission;
+use crate::models::notification::NotificationKind;
+use crate::models::school::SchoolFeature;
+use crate::utils::audience::check_scope;
+use crate::utils::audit::record_change;
+use crate::utils::calendar::{audience_user_ids, occurrence_summary, MAX_REMINDER_MINUTES};
+use crate::utils::codes::{hash_token, random_token};
+use crate::utils::common_types::{AuditInfo, SoftDelete};
+use crate::utils::features::FeatureGuard;
+use crate::utils::notifications::{notify, NotificationMessage};
+use crate::utils::soft_delete::{find_deleted_by_id, restore_by_id, soft_delete_by_id};
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, DateTime},
+    options::UpdateOptions,
+    Database,
+};
+use std::env;
+
+#[derive(Default)]
+pub struct EventMutation;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Events)")]
+impl EventMutation {
+    /// Add an event to the calendar (requires ManageEvents)
+    async fn create_event(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateEventInput,
+    ) -> Result<SchoolEvent> {
+        let db = ctx.data::<Database>()?;
+        let member = require_events(ctx, db, &input.school_id, Permission::ManageEvents).await?;
+
+        let all_day = input.all_day.unwrap_or(false);
+        let starts_at = parse_datetime(&input.starts_at)?;
+        let mut event = SchoolEvent {
+            id: None,
+            school_id: input.school_id,
+            title: input.title.trim().to_string(),
+            description: input.description,
+            location: input.location,
+            category: input.category.unwrap_or(EventCategory::Other),
+            scope: input.scope,
+            scope_id: input.scope_id,
+            starts_at,
+            ends_at: match input.ends_at {
+                Some(ends_at) => parse_datetime(&ends_at)?,
+                None => starts_at,
+            },
+            all_day,
+            recurrence: input.recurrence.map(build_recurrence).transpose()?,
+            rsvp_enabled: input.rsvp_enabled.unwrap_or(false),
+            rsvp_deadline: input
+                .rsvp_deadline
+                .as_deref()
+                .map(parse_datetime)
+                .transpose()?,
+            reminder_minutes: input.reminder_minutes.unwrap_or_default(),
+            reminders_sent: vec![],
+            audit: AuditInfo::new(Some(member.user_id.clone())),
+            soft_delete: SoftDelete::default(),
+        };
+        check_event(db, &mut event).await?;
+
+        let result = db
+            .collection::<SchoolEvent>("events")
+            .insert_one(&event, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create event: {}", e)))?;
+        event.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "SchoolEvent",
+            event.id.map(|id| id.to_hex()),
+            Some(&event.school_id),
+            None,
+            Some(&event),
+        );
+
+        Ok(event)
+    }
+
+    /// Update an event (requires ManageEvents). The audience is notified when
+    /// the time or place of an upcoming event changes.
+    async fn update_event(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateEventInput,
+    ) -> Result<SchoolEvent> {
+        let db = ctx.data::<Database>()?;
+        let event = find_event(db, &input.event_id).await?;
+        let member = require_events(ctx, db, &event.school_id, Permission::ManageEvents).await?;
+
+        let mut updated = event.clone();
+        if let Some(title) = input.title {
+            updated.title = title.trim().to_string();
+        }
+        if let Some(description) = input.description {
+            updated.description = Some(description);
+        }
+        if let Some(location) = input.location {
+            updated.location = Some(location);
+        }
+        if let Some(category) = input.category {
+            updated.category = category;
+        }
+        if let Some(scope) = input.scope {
+            updated.scope = scope;
+            updated.scope_id = input.scope_id;
+        } else if let Some(scope_id) = input.scope_id {
+            updated.scope_id = Some(scope_id);
+        }
+        if let Some(starts_at) = input.starts_at {
+            updated.starts_at = parse_datetime(&starts_at)?;
+        }
+        if let Some(ends_at) = input.ends_at {
+            updated.ends_at = parse_datetime(&ends_at)?;
+        }
+        if let Some(all_day) = input.all_day {
+            updated.all_day = all_day;
+        }
+        if input.clear_recurrence.unwrap_or(false) {
+            updated.recurrence = None;
+        } else if let Some(recurrence) = input.recurrence {
+            updated.recurrence = Some(build_recurrence(recurrence)?);
+        }
+        if let Some(rsvp_enabled) = input.rsvp_enabled {
+            updated.rsvp_enabled = rsvp_enabled;
+        }
+        if let Some(deadline) = input.rsvp_deadline {
+            updated.rsvp_deadline = Some(parse_datetime(&deadline)?);
+        }
+        if let Some(reminder_minutes) = input.reminder_minutes {
+            updated.reminder_minutes = reminder_minutes;
+        }
+        check_event(db, &mut updated).await?;
+
+        let rescheduled = updated.starts_at != event.starts_at
+            || updated.ends_at != event.ends_at
+            || updated.all_day != event.all_day
+            || updated.location != event.location
+            || updated.recurrence.as_ref().map(|r| r.to_rrule(false))
+                != event.recurrence.as_ref().map(|r| r.to_rrule(false));
+        if rescheduled {
+            // Reminders follow the new schedule
+            updated.reminders_sent.clear();
+        }
+        updated.audit.touch(Some(member.user_id.clone()));
+
+        db.collection::<SchoolEvent>("events")
+            .update_one(
+                doc! { "_id": event.id },
+                doc! { "$set": {
+                    "title": &updated.title,
+                    "description": &updated.description,
+                    "location": &updated.location,
+                    "category": to_bson(&updated.category)?,
+                    "scope": to_bson(&updated.scope)?,
+                    "scope_id": &updated.scope_id,
+                    "starts_at": updated.starts_at,
+                    "ends_at": updated.ends_at,
+                    "all_day": updated.all_day,
+                    "recurrence": to_bson(&updated.recurrence)?,
+                    "rsvp_enabled": updated.rsvp_enabled,
+                    "rsvp_deadline": updated.rsvp_deadline,
+                    "reminder_minutes": &updated.reminder_minutes,
+                    "reminders_sent": &updated.reminders_sent,
+                    "audit.updated_at": updated.audit.updated_at,
+                    "audit.updated_by": &updated.audit.updated_by,
+                } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update event: {}", e)))?;
+
+        if rescheduled && is_upcoming(&updated) {
+            notify_audience(db, &updated, format!("Changed: {}", updated.title)).await?;
+        }
+
+        record_change(
+            ctx,
+            "SchoolEvent",
+            Some(input.event_id),
+            Some(&updated.school_id),
+            Some(&event),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Cancel an event (requires ManageEvents). The audience of an upcoming
+    /// event is notified.
+    async fn delete_event(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let event = find_event(db, &id).await?;
+        let member = require_events(ctx, db, &event.school_id, Permission::ManageEvents).await?;
+
+        let oid = event.id.ok_or_else(|| Error::new("Event not found"))?;
+        soft_delete_by_id(
+            &db.collection::<SchoolEvent>("events"),
+            oid,
+            &member.user_id,
+        )
+        .await?;
+
+        if is_upcoming(&event) {
+            notify_audience(db, &event, format!("Cancelled: {}", event.title)).await?;
+        }
+
+        record_change(
+            ctx,
+            "SchoolEvent",
+            Some(id),
+            Some(&event.school_id),
+            Some(&event),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a cancelled event from the trash (requires ManageEvents). Its
+    /// replies are kept; the audience isn't notified again.
+    async fn restore_event(&self, ctx: &Context<'_>, id: String) -> Result<SchoolEvent> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<SchoolEvent>("events");
+        let event = find_deleted_by_id(&collection, &id, "event").await?;
+        require_events(ctx, db, &event.school_id, Permission::ManageEvents).await?;
+
+        let oid = event.id.ok_or_else(|| Error::new("Event not found"))?;
+        restore_by_id(&collection, oid).await?;
+
+        let restored = find_event(db, &id).await?;
+        record_change(
+            ctx,
+            "SchoolEvent",
+            Some(id),
+            Some(&restored.school_id),
+            Some(&event),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Reply to an event (requires ViewEvents). Parents reply once per child;
+    /// replying again replaces the earlier answer.
+    async fn rsvp_event(&self, ctx: &Context<'_>, input: RsvpEventInput) -> Result<EventRsvp> {
+        let db = ctx.data::<Database>()?;
+        let event = find_event(db, &input.event_id).await?;
+        let member = require_events(ctx, db, &event.school_id, Permission::ViewEvents).await?;
+        check_visible(db, &member, &event).await?;
+
+        if !event.rsvp_enabled {
+            return Err(Error::new("This event does not take replies"));
+        }
+        let now = DateTime::now();
+        if event.rsvp_deadline.is_some_and(|deadline| deadline < now) {
+            return Err(Error::new("The reply deadline has passed"));
+        }
+        if event.recurrence.is_none() && event.ends_at < now {
+            return Err(Error::new("This event is over"));
+        }
+        match input.student_id.as_deref() {
+            Some(student_id) if !member.parent_of.iter().any(|id| id == student_id) => {
+                return Err(Error::new("You can only reply for your own children"));
+            }
+            None if !member.parent_of.is_empty() => {
+                return Err(Error::new("Choose which child the reply is for"));
+            }
+            _ => {}
+        }
+        let guests = input.guests.unwrap_or(0);
+        if guests < 0 {
+            return Err(Error::new("Guests cannot be negative"));
+        }
+
+        let member_id = member.id.map(|id| id.to_hex()).unwrap_or_default();
+        let filter = doc! {
+            "event_id": &input.event_id,
+            "member_id": &member_id,
+            "student_id": &input.student_id,
+        };
+        let collection = db.collection::<EventRsvp>("event_rsvps");
+        collection
+            .update_one(
+                filter.clone(),
+                doc! {
+                    "$set": {
+                        "school_id": &event.school_id,
+                        "user_id": &member.user_id,
+                        "response": to_bson(&input.response)?,
+                        "guests": guests,
+                        "note": &input.note,
+                        "responded_at": now,
+                    }
+                },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to save reply: {}", e)))?;
+
+        collection
+            .find_one(filter, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Failed to save reply"))
+    }
+
+    /// Create the caller's calendar subscription for a school, replacing any
+    /// earlier one (requires ViewEvents). Add the URL to a phone or desktop
+    /// calendar; anyone holding it can read the caller's events.
+    async fn create_calendar_feed(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<CalendarFeedType> {
+        let db = ctx.data::<Database>()?;
+        let member = require_events(ctx, db, &school_id, Permission::ViewEvents).await?;
+        let member_id = member.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        let collection = db.collection::<CalendarFeed>("calendar_feeds");
+        collection
+            .delete_many(doc! { "member_id": &member_id }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to replace calendar feed: {}", e)))?;
+
+        let token = random_token();
+        collection
+            .insert_one(
+                CalendarFeed {
+                    id: None,
+                    member_id,
+                    user_id: member.user_id,
+                    school_id,
+                    token_hash: hash_token(&token),
+                    created_at: DateTime::now(),
+                    last_fetched_at: None,
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to create calendar feed: {}", e)))?;
+
+        let path = format!("/calendar/{}.ics", token);
+        let url = env::var("PUBLIC_URL")
+            .ok()
+            .map(|base| format!("{}{}", base.trim_end_matches('/'), path));
+        Ok(CalendarFeedType { token, path, url })
+    }
+
+    /// Stop the caller's calendar subscription for a school (requires ViewEvents)
+    async fn revoke_calendar_feed(&self, ctx: &Context<'_>, school_id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let member = require_events(ctx, db, &school_id, Permission::ViewEvents).await?;
+
+        let result = db
+            .collection::<CalendarFeed>("calendar_feeds")
+            .delete_many(
+                doc! { "member_id": member.id.map(|id| id.to_hex()).unwrap_or_default() },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to revoke calendar feed: {}", e)))?;
+
+        Ok(result.deleted_count > 0)
+    }
+}
+
+fn build_recurrence(input: RecurrenceInput) -> Result<RecurrenceRule> {
+    let interval = input.interval.unwrap_or(1);
+    if interval < 1 {
+        return Err(Error::new("Recurrence interval must be at least 1"));
+    }
+    if input.count.is_some_and(|count| count < 1) {
+        return Err(Error::new("Recurrence count must be at least 1"));
+    }
+    if input.count.is_some() && input.until.is_some() {
+        return Err(Error::new(
+            "Set either a recurrence count or an end date, not both",
+        ));
+    }
+    Ok(RecurrenceRule {
+        frequency: input.frequency,
+        interval,
+        count: input.count,
+        until: input.until.as_deref().map(parse_datetime).transpose()?,
+    })
+}
+
+/// Validate an event before saving it; all-day events are snapped to whole days
+async fn check_event(db: &Database, event: &mut SchoolEvent) -> Result<()> {
+    if event.title.is_empty() {
+        return Err(Error::new("Event title is required"));
+    }
+    if event.all_day {
+        event.starts_at = start_of_day(event.starts_at);
+        event.ends_at = start_of_day(event.ends_at);
+    }
+    if event.ends_at < event.starts_at {
+        return Err(Error::new("An event cannot end before it starts"));
+    }
+    if event
+        .reminder_minutes
+        .iter()
+        .any(|m| *m < 0 || *m > MAX_REMINDER_MINUTES)
+    {
+        return Err(Error::new(format!(
+            "Reminders must be between 0 and {} minutes before the event",
+            MAX_REMINDER_MINUTES
+        )));
+    }
+    event.reminder_minutes.sort_unstable();
+    event.reminder_minutes.dedup();
+
+    check_scope(db, &event.school_id, event.scope, &mut event.scope_id).await
+}
+
+/// Midnight UTC at the start of the timestamp's day
+fn start_of_day(value: DateTime) -> DateTime {
+    let millis = value.timestamp_millis();
+    DateTime::from_millis(millis - millis.rem_euclid(86_400_000))
+}
+
+/// Whether an event still has occurrences to come
+fn is_upcoming(event: &SchoolEvent) -> bool {
+    let now = DateTime::now();
+    match &event.recurrence {
+        Some(rule) => rule.until.is_none_or(|until| until >= now),
+        None => event.ends_at >= now,
+    }
+}
+
+/// Tell an event's audience about a change
+async fn notify_audience(db: &Database, event: &SchoolEvent, title: String) -> Result<()> {
+    let user_ids = audience_user_ids(db, event).await?;
+    let message = NotificationMessage {
+        school_id: Some(&event.school_id),
+        kind: NotificationKind::EventUpdate,
+        title,
+        body: occurrence_summary(event, event.starts_at),
+        link: event.id.map(|id| format!("event:{}", id.to_hex())),
+    };
+    notify(db, &user_ids, &message)
+        .await
+        .map_err(|e| Error::new(format!("Failed to notify event audience: {}", e)))?;
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use super::types::{EventOccurrenceType, RsvpSummaryType};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::library::queries::collect;
+use crate::models::event::{EventRsvp, RsvpResponse, SchoolEvent};
+use crate::models::member::{Member, Permission};
+use crate::models::school::SchoolFeature;
+use crate::utils::calendar::visible_events_filter;
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::find_deleted;
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::FindOptions,
+    Database,
+};
+
+/// Widest range the calendar can be queried for at once
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Default)]
+pub struct EventQuery;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Events)")]
+impl EventQuery {
+    /// Occurrences of the events the caller can see between two dates,
+    /// in start order (requires ViewEvents)
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        from: String,
+        to: String,
+    ) -> Result<Vec<EventOccurrenceType>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_events(ctx, db, &school_id, Permission::ViewEvents).await?;
+
+        let from = parse_datetime(&from)?;
+        let to = parse_datetime(&to)?;
+        if to <= from {
+            return Err(Error::new("The end of the range must be after its start"));
+        }
+        if to.timestamp_millis() - from.timestamp_millis() > MAX_RANGE_DAYS * 86_400_000 {
+            return Err(Error::new(format!(
+                "The range cannot be longer than {} days",
+                MAX_RANGE_DAYS
+            )));
+        }
+
+        let mut filter = visible_events_filter(db, &member).await?;
+        filter.insert("starts_at", doc! { "$lt": to });
+        filter.insert(
+            "$and",
+            vec![doc! { "$or": [
+                { "recurrence": { "$ne": null } },
+                { "ends_at": { "$gte": from } }
+            ] }],
+        );
+        let events = collect(
+            &db.collection::<SchoolEvent>("events"),
+            filter,
+            FindOptions::default(),
+        )
+        .await?;
+
+        let mut occurrences = Vec::new();
+        for event in events {
+            let duration = event.ends_at.timestamp_millis() - event.starts_at.timestamp_millis();
+            for start in event.occurrences(from, to) {
+                occurrences.push((start, duration, event.clone()));
+            }
+        }
+        occurrences.sort_by_key(|(start, _, _)| *start);
+
+        Ok(occurrences
+            .into_iter()
+            .map(|(start, duration, event)| EventOccurrenceType {
+                event,
+                starts_at: start.try_to_rfc3339_string().unwrap_or_default(),
+                ends_at: DateTime::from_millis(start.timestamp_millis() + duration)
+                    .try_to_rfc3339_string()
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// A single event the caller can see (requires ViewEvents)
+    async fn event(&self, ctx: &Context<'_>, id: String) -> Result<SchoolEvent> {
+        let db = ctx.data::<Database>()?;
+        let event = find_event(db, &id).await?;
+        let member = require_events(ctx, db, &event.school_id, Permission::ViewEvents).await?;
+        check_visible(db, &member, &event).await?;
+        Ok(event)
+    }
+
+    /// A school's cancelled events (the trash), most recently cancelled first
+    /// (requires ManageEvents)
+    async fn deleted_events(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<SchoolEvent>> {
+        let db = ctx.data::<Database>()?;
+        require_events(ctx, db, &school_id, Permission::ManageEvents).await?;
+
+        find_deleted(
+            &db.collection::<SchoolEvent>("events"),
+            doc! { "school_id": school_id },
+        )
+        .await
+    }
+
+    /// Replies to an event (requires ManageEvents)
+    async fn event_rsvps(&self, ctx: &Context<'_>, event_id: String) -> Result<Vec<EventRsvp>> {
+        let db = ctx.data::<Database>()?;
+        let event = find_event(db, &event_id).await?;
+        require_events(ctx, db, &event.school_id, Permission::ManageEvents).await?;
+
+        collect(
+            &db.collection::<EventRsvp>("event_rsvps"),
+            doc! { "event_id": &event_id },
+            FindOptions::builder()
+                .sort(doc! { "responded_at": -1 })
+                .build(),
+        )
+        .await
+    }
+
+    /// Reply counts for an event (requires ManageEvents)
+    async fn event_rsvp_summary(
+        &self,
+        ctx: &Context<'_>,
+        event_id: String,
+    ) -> Result<RsvpSummaryType> {
+        let db = ctx.data::<Database>()?;
+        let event = find_event(db, &event_id).await?;
+        require_events(ctx, db, &event.school_id, Permission::ManageEvents).await?;
+
+        let rsvps = collect(
+            &db.collection::<EventRsvp>("event_rsvps"),
+            doc! { "event_id": &event_id },
+            FindOptions::default(),
+        )
+        .await?;
+
+        let mut summary = RsvpSummaryType {
+            going: 0,
+            not_going: 0,
+            maybe: 0,
+            guests: 0,
+        };
+        for rsvp in rsvps {
+            match rsvp.response {
+                RsvpResponse::Going => {
+                    summary.going += 1;
+                    summary.guests += rsvp.guests;
+                }
+                RsvpResponse::NotGoing => summary.not_going += 1,
+                RsvpResponse::Maybe => summary.maybe += 1,
+            }
+        }
+        Ok(summary)
+    }
+
+    /// The caller's replies to an event (requires ViewEvents)
+    async fn my_event_rsvps(&self, ctx: &Context<'_>, event_id: String) -> Result<Vec<EventRsvp>> {
+        let db = ctx.data::<Database>()?;
+        let event = find_event(db, &event_id).await?;
+        let member = require_events(ctx, db, &event.school_id, Permission::ViewEvents).await?;
+
+        collect(
+            &db.collection::<EventRsvp>("event_rsvps"),
+            doc! {
+                "event_id": &event_id,
+                "member_id": member.id.map(|id| id.to_hex()).unwrap_or_default()
+            },
+            FindOptions::default(),
+        )
+        .await
+    }
+}
+
+/// Require the Events module for the school and an events permission
+pub(crate) async fn require_events(
+    ctx: &Context<'_>,
+    db: &Database,
+    school_id: &str,
+    permission: Permission,
+) -> Result<Member> {
+    ensure_feature(db, school_id, SchoolFeature::Events).await?;
+    get_graphql_context(ctx)?
+        .require_member_permission(db, school_id, permission)
+        .await
+}
+
+/// Load an event that hasn't been deleted
+pub(crate) async fn find_event(db: &Database, event_id: &str) -> Result<SchoolEvent> {
+    let oid = ObjectId::parse_str(event_id).map_err(|_| Error::new("Invalid event ID"))?;
+    db.collection::<SchoolEvent>("events")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Event not found"))
+}
+
+/// Fail unless the event is for the member
+pub(crate) async fn check_visible(
+    db: &Database,
+    member: &Member,
+    event: &SchoolEvent,
+) -> Result<()> {
+    let mut filter = visible_events_filter(db, member).await?;
+    filter.insert("_id", event.id);
+    let visible = db
+        .collection::<SchoolEvent>("events")
+        .count_documents(filter, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    if visible == 0 {
+        return Err(Error::new("Event not found"));
+    }
+    Ok(())
+}
+
+/// RFC 3339 timestamp, or midnight UTC at the start of a date (YYYY-MM-DD)
+pub(crate) fn parse_datetime(value: &str) -> Result<DateTime> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(DateTime::from_millis(dt.timestamp_millis()));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| Error::new("Invalid date. Use RFC 3339 or YYYY-MM-DD"))?;
+    Ok(DateTime::from_millis(
+        Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+            .timestamp_millis(),
+    ))
+}
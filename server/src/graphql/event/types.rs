@@ -0,0 +1,30 @@
+use crate::models::event::SchoolEvent;
+use async_graphql::SimpleObject;
+
+/// One occurrence of an event (repeating events have many)
+#[derive(SimpleObject)]
+pub struct EventOccurrenceType {
+    pub event: SchoolEvent,
+    pub starts_at: String,
+    pub ends_at: String,
+}
+
+/// Reply counts for an event
+#[derive(SimpleObject)]
+pub struct RsvpSummaryType {
+    pub going: i32,
+    pub not_going: i32,
+    pub maybe: i32,
+    /// Extra people coming with those going
+    pub guests: i32,
+}
+
+/// A new calendar subscription. The token is only shown once.
+#[derive(SimpleObject)]
+pub struct CalendarFeedType {
+    pub token: String,
+    /// Feed path on this server (e.g., "/calendar/<token>.ics")
+    pub path: String,
+    /// Full feed URL, when PUBLIC_URL is configured
+    pub url: Option<String>,
+}
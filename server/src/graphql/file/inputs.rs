@@ -0,0 +1,13 @@
+use async_graphql::{InputObject, Upload};
+
+use crate::models::stored_file::FilePurpose;
+
+/// Sent as a GraphQL multipart request, with the file in the `file` part
+#[derive(InputObject)]
+pub struct UploadFileInput {
+    pub school_id: String,
+    pub purpose: FilePurpose,
+    /// Student the file belongs to (required for student photos and documents)
+    pub student_id: Option<String>,
+    pub file: Upload,
+}
@@ -0,0 +1,269 @@
+use super::inputs::UploadFileInput;
+use super::queries::{download_url, find_file};
+use super::types::UploadedFileType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::transport::queries::find_student;
+use crate::models::member::{Member, Permission};
+use crate::models::stored_file::{FilePurpose, StoredFile};
+use crate::utils::audit::record_change;
+use crate::utils::common_types::{Attachment, AuditInfo, SoftDelete};
+use crate::utils::permissions::is_school_leader;
+use crate::utils::soft_delete::soft_delete_by_id;
+use crate::utils::storage::{detect_mime, file_storage, file_url};
+use crate::utils::thumbnail::{thumbnail, THUMBNAIL_SIZE};
+use async_graphql::*;
+use mongodb::{bson::oid::ObjectId, Database};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+/// Longest kept file name, in characters
+const MAX_FILE_NAME_LENGTH: usize = 200;
+
+#[derive(Default)]
+pub struct FileMutation;
+
+#[Object]
+impl FileMutation {
+    /// Upload a file (GraphQL multipart request). The type is detected from the
+    /// content and must suit the purpose; PNG and JPEG images get a thumbnail.
+    /// Logos and banners need ManageSettings, student photos UpdateStudents,
+    /// student documents UpdateStudents or being the student's parent.
+    async fn upload_file(
+        &self,
+        ctx: &Context<'_>,
+        input: UploadFileInput,
+    ) -> Result<UploadedFileType> {
+        let db = ctx.data::<Database>()?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &input.school_id)
+            .await?;
+        check_can_upload(db, &member, input.purpose, input.student_id.as_deref()).await?;
+
+        let file = store_upload(ctx, &member, input.purpose, input.student_id, &input.file).await?;
+        let attachment = file_attachment(&file);
+        let download = download_url(&file, false);
+        Ok(UploadedFileType {
+            file,
+            attachment,
+            download,
+        })
+    }
+
+    /// Delete an uploaded file and its stored contents (the uploader or a
+    /// school leader). The contents are gone at once, so deleted files can't
+    /// be restored; the record is kept until the trash is purged.
+    async fn delete_file(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let file = find_file(db, &id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &file.school_id)
+            .await?;
+        if file.uploaded_by != member.user_id && !is_school_leader(&member.role) {
+            return Err(Error::new(
+                "Only the uploader or a school leader can delete this file",
+            ));
+        }
+
+        let storage = file_storage().map_err(Error::new)?;
+        storage
+            .delete(&file.storage_key)
+            .await
+            .map_err(Error::new)?;
+        if let Some(thumbnail_key) = file.thumbnail_key.as_deref() {
+            storage.delete(thumbnail_key).await.map_err(Error::new)?;
+        }
+
+        let oid = file.id.ok_or_else(|| Error::new("File not found"))?;
+        soft_delete_by_id(&db.collection::<StoredFile>("files"), oid, &member.user_id).await?;
+
+        record_change(
+            ctx,
+            "File",
+            Some(id),
+            Some(&file.school_id),
+            Some(&file),
+            None,
+        );
+
+        Ok(true)
+    }
+}
+
+/// Check the member may upload a file for the purpose
+pub(crate) async fn check_can_upload(
+    db: &Database,
+    member: &Member,
+    purpose: FilePurpose,
+    student_id: Option<&str>,
+) -> Result<()> {
+    let allowed = match purpose {
+        FilePurpose::SchoolLogo | FilePurpose::SchoolBanner => {
+            member.has_permission(Permission::ManageSettings)
+        }
+        FilePurpose::StudentPhoto | FilePurpose::StudentDocument => {
+            let student_id =
+                student_id.ok_or_else(|| Error::new("Choose the student the file is for"))?;
+            let student = find_student(db, student_id).await?;
+            if student.school_id != member.school_id {
+                return Err(Error::new("Student not found"));
+            }
+            member.has_permission(Permission::UpdateStudents)
+                || (purpose == FilePurpose::StudentDocument
+                    && member.parent_of.iter().any(|id| id == student_id))
+        }
+        FilePurpose::Attachment => true,
+        // Generated by `generateIdCards`, never uploaded
+        FilePurpose::IdCards => false,
+    };
+    if !allowed {
+        return Err(Error::new(
+            "Insufficient permissions to upload this kind of file",
+        ));
+    }
+    Ok(())
+}
+
+/// File name without any directory part, trimmed to a sensible length
+fn clean_file_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    if name.is_empty() {
+        return "file".to_string();
+    }
+    name.chars().take(MAX_FILE_NAME_LENGTH).collect()
+}
+
+/// Read, check and store an upload, recording the file. The caller checks the
+/// member may upload for the purpose.
+pub(crate) async fn store_upload(
+    ctx: &Context<'_>,
+    member: &Member,
+    purpose: FilePurpose,
+    student_id: Option<String>,
+    file: &Upload,
+) -> Result<StoredFile> {
+    let upload = file
+        .value(ctx)
+        .map_err(|e| Error::new(format!("Failed to read upload: {}", e)))?;
+    let max_size = purpose.max_size();
+    let mut bytes = Vec::new();
+    let file_name = clean_file_name(&upload.filename);
+    upload
+        .into_read()
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| Error::new(format!("Failed to read upload: {}", e)))?;
+    if bytes.is_empty() {
+        return Err(Error::new("The file is empty"));
+    }
+    if bytes.len() > max_size {
+        return Err(Error::new(format!(
+            "The file is too large. The limit is {} MB",
+            max_size / (1024 * 1024)
+        )));
+    }
+    let file_type = detect_mime(&bytes, &file_name)
+        .filter(|mime| purpose.allowed_types().contains(mime))
+        .ok_or_else(|| {
+            Error::new(format!(
+                "This type of file can't be uploaded here. Accepted: {}",
+                purpose.allowed_types().join(", ")
+            ))
+        })?;
+
+    store_file(
+        ctx, member, purpose, student_id, file_name, file_type, bytes,
+    )
+    .await
+}
+
+/// Save file contents to the storage backend (with a thumbnail for PNGs and
+/// JPEGs) and record the file
+pub(crate) async fn store_file(
+    ctx: &Context<'_>,
+    member: &Member,
+    purpose: FilePurpose,
+    student_id: Option<String>,
+    file_name: String,
+    file_type: &'static str,
+    bytes: Vec<u8>,
+) -> Result<StoredFile> {
+    let db = ctx.data::<Database>()?;
+    let id = ObjectId::new();
+    let storage_key = format!("{}/{}", member.school_id, id.to_hex());
+    let file_size = bytes.len() as i64;
+    let checksum = hex::encode(Sha256::digest(&bytes));
+    let (bytes, thumbnail) = if matches!(file_type, "image/png" | "image/jpeg") {
+        tokio::task::spawn_blocking(move || {
+            let small = thumbnail(&bytes, file_type, THUMBNAIL_SIZE);
+            (bytes, small)
+        })
+        .await
+        .map_err(|e| Error::new(format!("Failed to create thumbnail: {}", e)))?
+    } else {
+        (bytes, None)
+    };
+
+    let storage = file_storage().map_err(Error::new)?;
+    storage
+        .put(&storage_key, bytes, file_type)
+        .await
+        .map_err(Error::new)?;
+    let mut thumbnail_key = None;
+    if let Some(thumbnail) = thumbnail {
+        let key = format!("{}_thumb.png", storage_key);
+        storage
+            .put(&key, thumbnail, "image/png")
+            .await
+            .map_err(Error::new)?;
+        thumbnail_key = Some(key);
+    }
+
+    let file = StoredFile {
+        id: Some(id),
+        school_id: member.school_id.clone(),
+        purpose,
+        student_id: student_id.filter(|_| {
+            matches!(
+                purpose,
+                FilePurpose::StudentPhoto | FilePurpose::StudentDocument
+            )
+        }),
+        file_name,
+        file_type: file_type.to_string(),
+        file_size,
+        checksum,
+        storage_key,
+        thumbnail_key,
+        backend: storage.name().to_string(),
+        uploaded_by: member.user_id.clone(),
+        audit: AuditInfo::new(Some(member.user_id.clone())),
+        soft_delete: SoftDelete::default(),
+    };
+    db.collection::<StoredFile>("files")
+        .insert_one(&file, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to save file: {}", e)))?;
+
+    record_change(
+        ctx,
+        "File",
+        Some(id.to_hex()),
+        Some(&file.school_id),
+        None,
+        Some(&file),
+    );
+
+    Ok(file)
+}
+
+/// Attachment pointing at an uploaded file
+pub(crate) fn file_attachment(file: &StoredFile) -> Attachment {
+    Attachment {
+        url: file_url(&file.id.map(|id| id.to_hex()).unwrap_or_default()),
+        file_name: file.file_name.clone(),
+        file_type: Some(file.file_type.clone()),
+        file_size: Some(file.file_size),
+        uploaded_at: file.audit.created_at,
+        uploaded_by: Some(file.uploaded_by.clone()),
+    }
+}
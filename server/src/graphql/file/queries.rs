@@ -0,0 +1,102 @@
+use super::types::DownloadUrlType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::member::{Member, Permission};
+use crate::models::stored_file::{FilePurpose, StoredFile};
+use crate::utils::storage::{signed_download_url, DOWNLOAD_URL_MINUTES};
+use async_graphql::*;
+use chrono::{Duration, TimeZone, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    Database,
+};
+
+#[derive(Default)]
+pub struct FileQuery;
+
+#[Object]
+impl FileQuery {
+    /// An uploaded file's details (members who may download it)
+    async fn file(&self, ctx: &Context<'_>, id: String) -> Result<StoredFile> {
+        let db = ctx.data::<Database>()?;
+        let file = find_file(db, &id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &file.school_id)
+            .await?;
+        check_can_download(&member, &file)?;
+        Ok(file)
+    }
+
+    /// A short-lived link to download a file, or its thumbnail when it has one
+    async fn file_download_url(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        thumbnail: Option<bool>,
+    ) -> Result<DownloadUrlType> {
+        let db = ctx.data::<Database>()?;
+        let file = find_file(db, &id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &file.school_id)
+            .await?;
+        check_can_download(&member, &file)?;
+        Ok(download_url(&file, thumbnail.unwrap_or(false)))
+    }
+}
+
+/// Load a file that hasn't been deleted
+pub(crate) async fn find_file(db: &Database, file_id: &str) -> Result<StoredFile> {
+    let oid = ObjectId::parse_str(file_id).map_err(|_| Error::new("Invalid file ID"))?;
+    db.collection::<StoredFile>("files")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("File not found"))
+}
+
+/// Signed link to a file (or its thumbnail, falling back to the file itself)
+pub(crate) fn download_url(file: &StoredFile, thumbnail: bool) -> DownloadUrlType {
+    let file_id = file.id.map(|id| id.to_hex()).unwrap_or_default();
+    let thumbnail = thumbnail && file.thumbnail_key.is_some();
+    let expires_at = Utc::now() + Duration::minutes(DOWNLOAD_URL_MINUTES);
+    let expires = expires_at.timestamp();
+    DownloadUrlType {
+        url: signed_download_url(&file_id, thumbnail, expires),
+        expires_at: Utc
+            .timestamp_opt(expires, 0)
+            .single()
+            .unwrap_or(expires_at)
+            .to_rfc3339(),
+    }
+}
+
+/// Whether a member may see a student's files: staff who can view students,
+/// the student's parents and the student
+pub(crate) fn can_access_student(member: &Member, student_id: &str) -> bool {
+    member.has_permission(Permission::ViewStudents)
+        || member.parent_of.iter().any(|id| id == student_id)
+        || member.student_id.as_deref() == Some(student_id)
+}
+
+/// School branding and attachments are open to the school's members; student
+/// photos and documents to those who can see the student; ID card sheets to
+/// staff who can update students
+pub(crate) fn check_can_download(member: &Member, file: &StoredFile) -> Result<()> {
+    if file.uploaded_by == member.user_id {
+        return Ok(());
+    }
+    let allowed = match file.purpose {
+        FilePurpose::SchoolLogo | FilePurpose::SchoolBanner | FilePurpose::Attachment => true,
+        FilePurpose::StudentPhoto | FilePurpose::StudentDocument => file
+            .student_id
+            .as_deref()
+            .is_some_and(|student_id| can_access_student(member, student_id)),
+        FilePurpose::IdCards => member.has_permission(Permission::UpdateStudents),
+    };
+    if !allowed {
+        return Err(Error::new("File not found"));
+    }
+    Ok(())
+}
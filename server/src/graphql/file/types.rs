@@ -0,0 +1,20 @@
+use async_graphql::SimpleObject;
+
+use crate::models::stored_file::StoredFile;
+use crate::utils::common_types::Attachment;
+
+/// A download link that stops working at `expires_at`
+#[derive(SimpleObject)]
+pub struct DownloadUrlType {
+    pub url: String,
+    pub expires_at: String,
+}
+
+/// An uploaded file, with an attachment ready to set on a logo, photo,
+/// document or message
+#[derive(SimpleObject)]
+pub struct UploadedFileType {
+    pub file: StoredFile,
+    pub attachment: Attachment,
+    pub download: DownloadUrlType,
+}
@@ -0,0 +1,13 @@
+use async_graphql::InputObject;
+
+#[derive(InputObject)]
+pub struct RecordPaymentInput {
+    pub school_id: String,
+    /// Invoice being paid
+    pub invoice_id: String,
+    /// Amount received, in the invoice's currency; at most the outstanding balance
+    pub amount: f64,
+    /// "cash", "bank_transfer" or "card"
+    pub payment_method: String,
+    pub remarks: Option<String>,
+}
@@ -0,0 +1,9 @@
+pub mod inputs;
+pub mod mutations;
+pub mod queries;
+pub mod subscriptions;
+pub mod types;
+
+pub use mutations::FinanceMutation;
+pub use subscriptions::FinanceSubscription;
+pub use types::*;
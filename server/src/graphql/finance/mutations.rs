@@ -0,0 +1,152 @@
+// Finance GraphQL mutations
+use super::inputs::RecordPaymentInput;
+use super::types::PaymentType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::transport::queries::find_student;
+use crate::models::finance::{Invoice, Payment};
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::audit::record_change;
+use crate::utils::codes::random_code;
+use crate::utils::features::FeatureGuard;
+use crate::utils::realtime::{publish, RealtimeEvent};
+use async_graphql::*;
+use chrono::Utc;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Database,
+};
+
+/// Accepted ways of paying
+const PAYMENT_METHODS: &[&str] = &["cash", "bank_transfer", "card"];
+
+/// Balances below this are treated as settled (rounding of cents)
+const SETTLED_EPSILON: f64 = 0.005;
+
+#[derive(Default)]
+pub struct FinanceMutation;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Finance)")]
+impl FinanceMutation {
+    /// Record a payment against a student's invoice and issue a receipt
+    /// (requires RecordPayments)
+    async fn record_payment(
+        &self,
+        ctx: &Context<'_>,
+        input: RecordPaymentInput,
+    ) -> Result<PaymentType> {
+        let db = ctx.data::<Database>()?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &input.school_id, Permission::RecordPayments)
+            .await?;
+
+        if !PAYMENT_METHODS.contains(&input.payment_method.as_str()) {
+            return Err(Error::new(format!(
+                "Unknown payment method. Use one of: {}",
+                PAYMENT_METHODS.join(", ")
+            )));
+        }
+        if !input.amount.is_finite() || input.amount <= 0.0 {
+            return Err(Error::new("The amount must be greater than zero"));
+        }
+
+        let invoice_oid =
+            ObjectId::parse_str(&input.invoice_id).map_err(|_| Error::new("Invalid invoice ID"))?;
+        let invoices = db.collection::<Invoice>("invoices");
+        let invoice = invoices
+            .find_one(doc! { "_id": invoice_oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Invoice not found"))?;
+        let student = find_student(db, &invoice.student_id.to_hex()).await?;
+        if student.school_id != input.school_id {
+            return Err(Error::new("Invoice not found"));
+        }
+        if invoice.balance < SETTLED_EPSILON {
+            return Err(Error::new("This invoice is already paid"));
+        }
+        if input.amount > invoice.balance + SETTLED_EPSILON {
+            return Err(Error::new(format!(
+                "The amount is more than the outstanding balance of {:.2} {}",
+                invoice.balance, invoice.currency
+            )));
+        }
+        let processed_by =
+            ObjectId::parse_str(&member.user_id).map_err(|_| Error::new("Invalid user ID"))?;
+
+        let now = DateTime::now();
+        let mut payment = Payment {
+            id: None,
+            student_id: invoice.student_id,
+            school_id: Some(input.school_id.clone()),
+            fee_id: None,
+            invoice_id: Some(invoice_oid),
+            amount_paid: input.amount,
+            currency: invoice.currency.clone(),
+            payment_method: input.payment_method,
+            payment_date: now,
+            receipt_number: format!("RCP-{}-{}", Utc::now().format("%Y%m%d"), random_code(6)),
+            status: "completed".to_string(),
+            remarks: input.remarks.filter(|r| !r.trim().is_empty()),
+            processed_by,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let result = db
+            .collection::<Payment>("payments")
+            .insert_one(&payment, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to record payment: {}", e)))?;
+        payment.id = result.inserted_id.as_object_id();
+
+        let amount_paid = invoice.amount_paid + input.amount;
+        let balance = (invoice.total_amount - amount_paid).max(0.0);
+        let status = if balance < SETTLED_EPSILON {
+            "paid"
+        } else {
+            "partial"
+        };
+        invoices
+            .update_one(
+                doc! { "_id": invoice_oid },
+                doc! { "$set": {
+                    "amount_paid": amount_paid,
+                    "balance": balance,
+                    "status": status,
+                    "updated_at": now,
+                } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update invoice: {}", e)))?;
+
+        let updated = invoices
+            .find_one(doc! { "_id": invoice_oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        record_change(
+            ctx,
+            "Invoice",
+            Some(input.invoice_id),
+            Some(&input.school_id),
+            Some(&invoice),
+            updated.as_ref(),
+        );
+        record_change(
+            ctx,
+            "Payment",
+            payment.id.map(|id| id.to_hex()),
+            Some(&input.school_id),
+            None,
+            Some(&payment),
+        );
+
+        let payment = PaymentType::from(payment);
+        publish(RealtimeEvent::PaymentReceived {
+            payment: payment.clone(),
+        });
+
+        Ok(payment)
+    }
+}
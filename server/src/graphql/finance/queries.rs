@@ -0,0 +1,37 @@
+// Finance data loaders shared by portal queries
+use crate::models::finance::Invoice;
+use async_graphql::*;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Database,
+};
+
+/// Load a student's invoices, newest first
+pub(crate) async fn find_student_invoices(
+    db: &Database,
+    student_oid: ObjectId,
+) -> Result<Vec<Invoice>> {
+    let collection = db.collection::<Invoice>("invoices");
+
+    let options = FindOptions::builder()
+        .sort(doc! { "issue_date": -1 })
+        .build();
+
+    let mut cursor = collection
+        .find(doc! { "student_id": student_oid }, options)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut invoices = Vec::new();
+    while let Some(invoice) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        invoices.push(invoice);
+    }
+
+    Ok(invoices)
+}
@@ -0,0 +1,49 @@
+// Finance GraphQL subscriptions
+use super::types::PaymentType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::realtime::{in_scope, member_events, student_scope, RealtimeEvent};
+use async_graphql::*;
+use futures::Stream;
+use mongodb::Database;
+
+#[derive(Default)]
+pub struct FinanceSubscription;
+
+#[Subscription(guard = "FeatureGuard::new(SchoolFeature::Finance)")]
+impl FinanceSubscription {
+    /// Payment receipts in a school, as payments are recorded (requires
+    /// ViewFinance). Without ViewFinanceReports only receipts for the caller's
+    /// children, or the caller as a student, are received.
+    async fn payment_received(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<impl Stream<Item = PaymentType>> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let member = graphql_ctx
+            .require_member_permission(db, &school_id, Permission::ViewFinance)
+            .await?;
+        let user = graphql_ctx.require_auth()?.clone();
+        let scope = student_scope(&member, Permission::ViewFinanceReports);
+
+        Ok(member_events(
+            db.clone(),
+            user,
+            school_id.clone(),
+            Some(Permission::ViewFinance),
+            move |event| match event {
+                RealtimeEvent::PaymentReceived { payment }
+                    if payment.school_id.as_deref() == Some(school_id.as_str())
+                        && in_scope(&scope, &payment.student_id) =>
+                {
+                    Some(payment)
+                }
+                _ => None,
+            },
+        ))
+    }
+}
@@ -0,0 +1,126 @@
+// Finance GraphQL types
+use crate::models::finance::{Invoice, Payment};
+use async_graphql::*;
+
+#[derive(SimpleObject)]
+pub struct InvoiceType {
+    pub id: String,
+    pub invoice_number: String,
+    pub student_id: String,
+    pub fee_ids: Vec<String>,
+    pub total_amount: f64,
+    pub amount_paid: f64,
+    pub balance: f64,
+    pub currency: String,
+    pub issue_date: String,
+    pub due_date: String,
+    pub status: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<Invoice> for InvoiceType {
+    fn from(i: Invoice) -> Self {
+        InvoiceType {
+            id: i.id.map(|id| id.to_hex()).unwrap_or_default(),
+            invoice_number: i.invoice_number,
+            student_id: i.student_id.to_hex(),
+            fee_ids: i.fee_ids.iter().map(|id| id.to_hex()).collect(),
+            total_amount: i.total_amount,
+            amount_paid: i.amount_paid,
+            balance: i.balance,
+            currency: i.currency,
+            issue_date: i.issue_date.try_to_rfc3339_string().unwrap_or_default(),
+            due_date: i.due_date.try_to_rfc3339_string().unwrap_or_default(),
+            status: i.status,
+            description: i.description,
+            created_at: i.created_at.try_to_rfc3339_string().unwrap_or_default(),
+            updated_at: i.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+/// A recorded payment and its receipt
+#[derive(SimpleObject, Debug, Clone)]
+pub struct PaymentType {
+    pub id: String,
+    pub school_id: Option<String>,
+    pub student_id: String,
+    pub fee_id: Option<String>,
+    pub invoice_id: Option<String>,
+    pub amount_paid: f64,
+    pub currency: String,
+    pub payment_method: String,
+    pub payment_date: String,
+    pub receipt_number: String,
+    pub status: String,
+    pub remarks: Option<String>,
+    pub processed_by: String,
+    pub created_at: String,
+}
+
+impl From<Payment> for PaymentType {
+    fn from(p: Payment) -> Self {
+        PaymentType {
+            id: p.id.map(|id| id.to_hex()).unwrap_or_default(),
+            school_id: p.school_id,
+            student_id: p.student_id.to_hex(),
+            fee_id: p.fee_id.map(|id| id.to_hex()),
+            invoice_id: p.invoice_id.map(|id| id.to_hex()),
+            amount_paid: p.amount_paid,
+            currency: p.currency,
+            payment_method: p.payment_method,
+            payment_date: p.payment_date.try_to_rfc3339_string().unwrap_or_default(),
+            receipt_number: p.receipt_number,
+            status: p.status,
+            remarks: p.remarks,
+            processed_by: p.processed_by.to_hex(),
+            created_at: p.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
+
+/// Outstanding balance for a student, per currency
+#[derive(SimpleObject)]
+pub struct StudentBalanceType {
+    pub student_id: String,
+    pub currency: String,
+    pub total_invoiced: f64,
+    pub total_paid: f64,
+    pub balance: f64,
+    pub overdue_count: i32,
+}
+
+impl StudentBalanceType {
+    /// Summarize a student's invoices into one balance per currency
+    pub fn from_invoices(student_id: &str, invoices: &[Invoice]) -> Vec<Self> {
+        let mut balances: Vec<Self> = Vec::new();
+
+        for invoice in invoices {
+            let entry = match balances.iter_mut().find(|b| b.currency == invoice.currency) {
+                Some(entry) => entry,
+                None => {
+                    balances.push(StudentBalanceType {
+                        student_id: student_id.to_string(),
+                        currency: invoice.currency.clone(),
+                        total_invoiced: 0.0,
+                        total_paid: 0.0,
+                        balance: 0.0,
+                        overdue_count: 0,
+                    });
+                    balances.last_mut().unwrap()
+                }
+            };
+
+            entry.total_invoiced += invoice.total_amount;
+            entry.total_paid += invoice.amount_paid;
+            entry.balance += invoice.balance;
+            if invoice.status == "overdue" {
+                entry.overdue_count += 1;
+            }
+        }
+
+        balances
+    }
+}
@@ -2,6 +2,7 @@
 use async_graphql::*;
 use mongodb::bson::{oid::ObjectId, DateTime};
 use crate::models::grade::Grade;
+use crate::utils::common_types::SoftDelete;
 
 #[derive(InputObject)]
 pub struct GradeInput {
@@ -39,6 +40,7 @@ impl From<GradeInput> for Grade {
             graded_at: now,
             created_at: now,
             updated_at: now,
+            soft_delete: SoftDelete::default(),
         }
     }
 }
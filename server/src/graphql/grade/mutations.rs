@@ -1,7 +1,14 @@
 // Grade GraphQL mutations
 use super::inputs::GradeInput;
 use super::types::GradeType;
+use crate::graphql::class::queries::class_school_id;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::audit::record_change;
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::{restore_by_id, soft_delete_by_id};
 use async_graphql::*;
 use mongodb::{
     bson::{doc, oid::ObjectId, DateTime},
@@ -11,7 +18,7 @@ use mongodb::{
 #[derive(Default)]
 pub struct GradeMutation;
 
-#[Object]
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Grading)")]
 impl GradeMutation {
     async fn create_grade(&self, ctx: &Context<'_>, input: GradeInput) -> Result<GradeType> {
         let db = ctx.data::<Database>()?;
@@ -34,6 +41,16 @@ impl GradeMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Failed to retrieve created grade"))?;
 
+        let school_id = class_school_id(db, grade.class_id).await.ok();
+        record_change(
+            ctx,
+            "Grade",
+            Some(id.to_hex()),
+            school_id.as_deref(),
+            None,
+            Some(&grade),
+        );
+
         Ok(grade.into())
     }
 
@@ -48,14 +65,24 @@ impl GradeMutation {
 
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
+        let before = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Grade not found"))?;
+
         let mut grade: models::grade::Grade = input.into();
         grade.id = Some(obj_id);
         grade.updated_at = DateTime::now();
 
-        let update_doc = doc! {
-            "$set": mongodb::bson::to_document(&grade)
-                .map_err(|e| Error::new(e.to_string()))?
-        };
+        let mut set_doc =
+            mongodb::bson::to_document(&grade).map_err(|e| Error::new(e.to_string()))?;
+        // Trash state is only changed by delete/restore
+        set_doc.remove("soft_delete");
+        let update_doc = doc! { "$set": set_doc };
 
         collection
             .update_one(doc! { "_id": obj_id }, update_doc, None)
@@ -68,6 +95,16 @@ impl GradeMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Grade not found"))?;
 
+        let school_id = class_school_id(db, grade.class_id).await.ok();
+        record_change(
+            ctx,
+            "Grade",
+            Some(id),
+            school_id.as_deref(),
+            Some(&before),
+            Some(&grade),
+        );
+
         Ok(grade.into())
     }
 
@@ -77,11 +114,70 @@ impl GradeMutation {
 
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
-        collection
-            .delete_one(doc! { "_id": obj_id }, None)
+        let before = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Grade not found"))?;
+        let school_id = class_school_id(db, before.class_id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ApproveGrades)
+            .await?;
+
+        soft_delete_by_id(&collection, obj_id, &member.user_id).await?;
+
+        let after = collection
+            .find_one(doc! { "_id": obj_id }, None)
             .await
             .map_err(|e| Error::new(e.to_string()))?;
+        record_change(
+            ctx,
+            "Grade",
+            Some(id),
+            Some(&school_id),
+            Some(&before),
+            after.as_ref(),
+        );
 
         Ok(true)
     }
+
+    /// Restore a deleted grade from the trash
+    async fn restore_grade(&self, ctx: &Context<'_>, id: String) -> Result<GradeType> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<models::grade::Grade>("grades");
+
+        let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
+
+        let before = collection
+            .find_one(doc! { "_id": obj_id, "soft_delete.is_deleted": true }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Deleted grade not found"))?;
+        let school_id = class_school_id(db, before.class_id).await?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ApproveGrades)
+            .await?;
+
+        restore_by_id(&collection, obj_id).await?;
+
+        let grade = collection
+            .find_one(doc! { "_id": obj_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Grade not found"))?;
+        record_change(
+            ctx,
+            "Grade",
+            Some(id),
+            Some(&school_id),
+            Some(&before),
+            Some(&grade),
+        );
+
+        Ok(grade.into())
+    }
 }
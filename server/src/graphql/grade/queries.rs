@@ -1,24 +1,31 @@
 // Grade GraphQL queries
-use super::types::GradeType;
+use super::types::{GradeType, ReportCardType};
+use crate::graphql::class::queries::school_class_ids;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::find_deleted;
 use async_graphql::*;
 use futures::stream::TryStreamExt;
 use mongodb::{
     bson::{doc, oid::ObjectId},
     Database,
 };
+use std::collections::HashMap;
 
 #[derive(Default)]
 pub struct GradeQuery;
 
-#[Object]
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Grading)")]
 impl GradeQuery {
     async fn grades(&self, ctx: &Context<'_>) -> Result<Vec<GradeType>> {
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<models::grade::Grade>("grades");
 
         let mut cursor = collection
-            .find(None, None)
+            .find(doc! { "soft_delete.is_deleted": { "$ne": true } }, None)
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -41,10 +48,123 @@ impl GradeQuery {
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
         let grade = collection
-            .find_one(doc! { "_id": obj_id }, None)
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
         Ok(grade.map(|g| g.into()))
     }
+
+    /// Deleted grades (the trash), optionally for one student or class
+    async fn deleted_grades(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        student_id: Option<String>,
+        class_id: Option<String>,
+    ) -> Result<Vec<GradeType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ApproveGrades)
+            .await?;
+
+        // Grade records belong to a school through their class
+        let class_ids = school_class_ids(db, &school_id).await?;
+        let mut filter = doc! { "class_id": { "$in": &class_ids } };
+        if let Some(id) = student_id {
+            let oid = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid student ID"))?;
+            filter.insert("student_id", oid);
+        }
+        if let Some(id) = class_id {
+            let oid = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid class ID"))?;
+            if !class_ids.contains(&oid) {
+                return Ok(Vec::new());
+            }
+            filter.insert("class_id", oid);
+        }
+
+        let grades = find_deleted(&db.collection::<models::grade::Grade>("grades"), filter).await?;
+
+        Ok(grades.into_iter().map(|g| g.into()).collect())
+    }
+}
+
+/// Load a student's grades, optionally limited to one academic year and semester
+pub(crate) async fn find_student_grades(
+    db: &Database,
+    student_oid: ObjectId,
+    academic_year: Option<&str>,
+    semester: Option<&str>,
+) -> Result<Vec<models::grade::Grade>> {
+    let collection = db.collection::<models::grade::Grade>("grades");
+
+    let mut filter = doc! {
+        "student_id": student_oid,
+        "soft_delete.is_deleted": { "$ne": true }
+    };
+    if let Some(year) = academic_year {
+        filter.insert("academic_year", year);
+    }
+    if let Some(sem) = semester {
+        filter.insert("semester", sem);
+    }
+
+    let mut cursor = collection
+        .find(filter, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut grades = Vec::new();
+    while let Some(grade) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        grades.push(grade);
+    }
+
+    Ok(grades)
+}
+
+/// Build a student's report card for one term
+pub(crate) async fn build_report_card(
+    db: &Database,
+    student_oid: ObjectId,
+    academic_year: &str,
+    semester: &str,
+) -> Result<ReportCardType> {
+    let grades = find_student_grades(db, student_oid, Some(academic_year), Some(semester)).await?;
+
+    // Resolve subject names for display
+    let mut subject_oids: Vec<ObjectId> = grades.iter().map(|g| g.subject_id).collect();
+    subject_oids.sort();
+    subject_oids.dedup();
+
+    let mut cursor = db
+        .collection::<models::subject::Subject>("subjects")
+        .find(doc! { "_id": { "$in": subject_oids } }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut subject_names = HashMap::new();
+    while let Some(subject) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        if let Some(id) = subject.id {
+            subject_names.insert(id.to_hex(), subject.subject_name);
+        }
+    }
+
+    Ok(ReportCardType::from_grades(
+        &student_oid.to_hex(),
+        academic_year,
+        semester,
+        &grades,
+        &subject_names,
+    ))
 }
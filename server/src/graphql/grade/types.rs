@@ -1,6 +1,8 @@
 // Grade GraphQL types
 use async_graphql::*;
 use crate::models::grade::Grade;
+use crate::utils::common_types::SoftDelete;
+use std::collections::HashMap;
 
 #[derive(SimpleObject)]
 pub struct GradeType {
@@ -20,6 +22,8 @@ pub struct GradeType {
     pub graded_at: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Trash state; set when the record has been deleted
+    pub soft_delete: SoftDelete,
 }
 
 impl From<Grade> for GradeType {
@@ -41,6 +45,75 @@ impl From<Grade> for GradeType {
             graded_at: g.graded_at.try_to_rfc3339_string().unwrap_or_default(),
             created_at: g.created_at.try_to_rfc3339_string().unwrap_or_default(),
             updated_at: g.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+            soft_delete: g.soft_delete,
+        }
+    }
+}
+
+/// Per-subject result on a report card
+#[derive(SimpleObject)]
+pub struct SubjectResultType {
+    pub subject_id: String,
+    pub subject_name: Option<String>,
+    pub assessment_count: i32,
+    pub average_percentage: f64,
+}
+
+/// Term report card for a student
+#[derive(SimpleObject)]
+pub struct ReportCardType {
+    pub student_id: String,
+    pub academic_year: String,
+    pub semester: String,
+    pub subjects: Vec<SubjectResultType>,
+    pub overall_percentage: f64,
+}
+
+impl ReportCardType {
+    /// Build a report card from a student's grades for one term.
+    /// `subject_names` maps subject ObjectId hex strings to display names.
+    pub fn from_grades(
+        student_id: &str,
+        academic_year: &str,
+        semester: &str,
+        grades: &[Grade],
+        subject_names: &HashMap<String, String>,
+    ) -> Self {
+        let mut subjects: Vec<SubjectResultType> = Vec::new();
+
+        for grade in grades {
+            let subject_id = grade.subject_id.to_hex();
+            match subjects.iter_mut().find(|s| s.subject_id == subject_id) {
+                Some(result) => {
+                    // Running total, averaged below
+                    result.average_percentage += grade.percentage;
+                    result.assessment_count += 1;
+                }
+                None => subjects.push(SubjectResultType {
+                    subject_name: subject_names.get(&subject_id).cloned(),
+                    subject_id,
+                    assessment_count: 1,
+                    average_percentage: grade.percentage,
+                }),
+            }
+        }
+
+        for result in subjects.iter_mut() {
+            result.average_percentage /= result.assessment_count as f64;
+        }
+
+        let overall_percentage = if subjects.is_empty() {
+            0.0
+        } else {
+            subjects.iter().map(|s| s.average_percentage).sum::<f64>() / subjects.len() as f64
+        };
+
+        ReportCardType {
+            student_id: student_id.to_string(),
+            academic_year: academic_year.to_string(),
+            semester: semester.to_string(),
+            subjects,
+            overall_percentage,
         }
     }
 }
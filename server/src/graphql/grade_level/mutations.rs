@@ -1,7 +1,10 @@
 // GradeLevel GraphQL mutations
 use super::inputs::{GradeLevelInput, UpdateGradeLevelInput};
 use super::types::GradeLevelType;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::soft_delete::{restore_by_id, soft_delete_by_id};
 use async_graphql::*;
 use mongodb::{
     bson::{doc, oid::ObjectId},
@@ -90,25 +93,58 @@ impl GradeLevelMutation {
     }
 
     /// Delete a grade level (soft delete)
+    /// Delete a grade level (moves it to the trash)
     async fn delete_grade_level(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<models::grade_level::GradeLevel>("grade_levels");
 
+        // Parse ObjectId
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
-        // Soft delete: mark as deleted instead of actually deleting
-        let update_doc = doc! {
-            "$set": {
-                "soft_delete.is_deleted": true,
-                "soft_delete.deleted_at": mongodb::bson::DateTime::now()
-            }
+        let Some(grade_level) = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        else {
+            return Ok(false);
         };
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &grade_level.school_id, Permission::ManageClasses)
+            .await?;
 
-        collection
-            .update_one(doc! { "_id": obj_id }, update_doc, None)
+        // Soft delete: mark as deleted instead of actually deleting
+        soft_delete_by_id(&collection, obj_id, &member.user_id).await
+    }
+
+    /// Restore a deleted grade level from the trash
+    async fn restore_grade_level(&self, ctx: &Context<'_>, id: String) -> Result<GradeLevelType> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<models::grade_level::GradeLevel>("grade_levels");
+
+        let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
+
+        let grade_level = collection
+            .find_one(doc! { "_id": obj_id, "soft_delete.is_deleted": true }, None)
             .await
-            .map_err(|e| Error::new(e.to_string()))?;
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Deleted grade level not found"))?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &grade_level.school_id, Permission::ManageClasses)
+            .await?;
+
+        if !restore_by_id(&collection, obj_id).await? {
+            return Err(Error::new("Deleted grade level not found"));
+        }
+
+        let record = collection
+            .find_one(doc! { "_id": obj_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Grade level not found"))?;
 
-        Ok(true)
+        Ok(record)
     }
 }
@@ -1,7 +1,10 @@
 // GradeLevel GraphQL queries
 use super::inputs::{GradeLevelFilterInput, GradeLevelSortInput};
 use super::types::{GradeLevelType, PaginatedGradeLevelsResult};
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::soft_delete::find_deleted;
 use async_graphql::*;
 use futures::stream::TryStreamExt;
 use mongodb::{
@@ -151,6 +154,26 @@ impl GradeLevelQuery {
             total_pages,
         })
     }
+
+    /// Get a school's deleted grade levels (the trash), most recently deleted first
+    async fn deleted_grade_levels(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<GradeLevelType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ManageClasses)
+            .await?;
+
+        let records = find_deleted(
+            &db.collection::<models::grade_level::GradeLevel>("grade_levels"),
+            doc! { "school_id": school_id },
+        )
+        .await?;
+
+        Ok(records)
+    }
 }
 
 /// Helper function to build sort document
@@ -1,24 +1,87 @@
-use crate::{models::user::SystemRole, utils::jwt_token::verify_token};
+use crate::{
+    models::custom_role::CustomRole,
+    models::member::{Member, Permission, SchoolRole},
+    models::school::School,
+    models::session::Session,
+    models::user::{SystemRole, User},
+    utils::jwt_token::verify_token,
+};
 use actix_web::HttpRequest;
 use async_graphql::Context;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Database,
+};
+
+/// How long an MFA step-up stays valid for sensitive actions
+pub const STEP_UP_MINUTES: i64 = 10;
 
 /// Authenticated user information extracted from JWT
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub id: String,
     pub role: SystemRole,
+    /// Session the access token belongs to
+    pub session_id: Option<String>,
+    /// School selected by the client (active school)
+    pub school_id: Option<String>,
+    /// When the access token expires (Unix seconds)
+    pub expires_at: i64,
 }
 
 /// Custom GraphQL context that includes authenticated user
 pub struct GraphQLContext {
     pub auth_user: Option<AuthUser>,
+    /// Caller's membership in the active school, loaded once per request
+    pub active_member: Option<Member>,
 }
 
 impl GraphQLContext {
-    /// Create context from HTTP request by extracting JWT token
-    pub fn from_request(req: &HttpRequest) -> Self {
-        let auth_user = extract_auth_user(req);
-        Self { auth_user }
+    /// Create context from HTTP request by extracting JWT token.
+    /// Tokens whose session has been revoked (logout) or whose user may no longer
+    /// sign in (suspended, banned, deleted) are ignored.
+    pub async fn from_request(req: &HttpRequest, db: &Database) -> Self {
+        let token = req
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(bearer_token);
+        Self::from_token(token, db).await
+    }
+
+    /// Create context from an access token sent some other way than the
+    /// Authorization header (e.g. a WebSocket's connection_init payload). The
+    /// same checks as `from_request` apply.
+    pub async fn from_token(token: Option<&str>, db: &Database) -> Self {
+        let mut auth_user = token.and_then(auth_user_from_token);
+
+        if let Some(user) = auth_user.as_ref() {
+            let session_ok = match user.session_id.as_deref() {
+                Some(session_id) => is_session_active(db, session_id).await,
+                None => true,
+            };
+            if !session_ok || !is_user_active(db, &user.id).await {
+                auth_user = None;
+            }
+        }
+
+        // Load the membership for the active school; a school the user no longer
+        // belongs to is treated as no school selected
+        let mut active_member = None;
+        if let Some(user) = auth_user.as_ref() {
+            if let Some(school_id) = user.school_id.as_deref() {
+                active_member = find_active_member(db, &user.id, school_id)
+                    .await
+                    .ok()
+                    .flatten();
+            }
+        }
+
+        Self {
+            auth_user,
+            active_member,
+        }
     }
 
     /// Get authenticated user or return error
@@ -46,26 +109,359 @@ impl GraphQLContext {
             ))),
         }
     }
-}
 
-/// Extract authenticated user from HTTP request headers
-fn extract_auth_user(req: &HttpRequest) -> Option<AuthUser> {
-    let auth_header = req.headers().get("Authorization")?.to_str().ok()?;
+    /// Get the caller's membership in the active school
+    pub fn require_active_member(&self) -> async_graphql::Result<&Member> {
+        self.require_auth()?;
+        self.active_member
+            .as_ref()
+            .ok_or_else(|| async_graphql::Error::new("No active school selected"))
+    }
 
-    let token = if auth_header.starts_with("Bearer ") {
-        &auth_header[7..]
-    } else {
-        return None;
+    /// Require a permission in the active school
+    pub fn require_permission(&self, permission: Permission) -> async_graphql::Result<&Member> {
+        let member = self.require_active_member()?;
+        check_permission(member, permission)?;
+        Ok(member)
+    }
+
+    /// Reject access to another school's data while a school is active
+    pub fn require_same_school(&self, school_id: &str) -> async_graphql::Result<()> {
+        match self.auth_user.as_ref().and_then(|u| u.school_id.as_deref()) {
+            Some(active) if active != school_id => Err(async_graphql::Error::new(
+                "Access to another school's data is not allowed",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Load the caller's active membership in a school.
+    /// Uses the cached membership for the active school; other schools are rejected
+    /// while a school is active.
+    pub async fn require_member(
+        &self,
+        db: &Database,
+        school_id: &str,
+    ) -> async_graphql::Result<Member> {
+        let user = self.require_auth()?;
+        self.require_same_school(school_id)?;
+
+        if let Some(member) = self.active_member.as_ref() {
+            if member.school_id == school_id {
+                return Ok(member.clone());
+            }
+        }
+
+        find_active_member(db, &user.id, school_id)
+            .await?
+            .ok_or_else(|| async_graphql::Error::new("You are not a member of this school"))
+    }
+
+    /// Load the caller's membership in a school and check it holds a permission
+    pub async fn require_member_permission(
+        &self,
+        db: &Database,
+        school_id: &str,
+        permission: Permission,
+    ) -> async_graphql::Result<Member> {
+        let member = self.require_member(db, school_id).await?;
+        check_permission(&member, permission)?;
+        Ok(member)
+    }
+
+    /// Load the caller's membership and check it holds one of the given roles
+    pub async fn require_member_role(
+        &self,
+        db: &Database,
+        school_id: &str,
+        roles: &[SchoolRole],
+    ) -> async_graphql::Result<Member> {
+        let member = self.require_member(db, school_id).await?;
+
+        if !roles.contains(&member.role) {
+            return Err(async_graphql::Error::new(format!(
+                "Insufficient permissions. Required school role: {:?}",
+                roles
+            )));
+        }
+
+        Ok(member)
+    }
+
+    /// Require a recent MFA check before a sensitive action (payroll, role changes,
+    /// school approval). MFA is required when the user has enrolled, when they are a
+    /// platform admin, or when the school's settings require it for their role.
+    /// With no `school_id`, the policies of all the user's schools apply.
+    pub async fn require_step_up(
+        &self,
+        db: &Database,
+        school_id: Option<&str>,
+    ) -> async_graphql::Result<()> {
+        let auth_user = self.require_auth()?;
+
+        let user_oid = ObjectId::parse_str(&auth_user.id)
+            .map_err(|_| async_graphql::Error::new("Invalid user ID"))?;
+        let user = db
+            .collection::<User>("users")
+            .find_one(doc! { "_id": user_oid }, None)
+            .await
+            .map_err(|e| async_graphql::Error::new(format!("Database error: {}", e)))?
+            .ok_or_else(|| async_graphql::Error::new("User not found"))?;
+
+        let required = user.mfa.enabled
+            || matches!(user.system_role, SystemRole::SuperAdmin | SystemRole::Admin)
+            || school_requires_mfa(db, &auth_user.id, school_id).await?;
+        if !required {
+            return Ok(());
+        }
+
+        if !user.mfa.enabled {
+            return Err(async_graphql::Error::new(
+                "Multi-factor authentication is required for this action. Please enroll in MFA first.",
+            ));
+        }
+
+        let session_oid = auth_user
+            .session_id
+            .as_deref()
+            .and_then(|id| ObjectId::parse_str(id).ok())
+            .ok_or_else(|| async_graphql::Error::new("Please sign in again to continue"))?;
+        let session = db
+            .collection::<Session>("sessions")
+            .find_one(doc! { "_id": session_oid }, None)
+            .await
+            .map_err(|e| async_graphql::Error::new(format!("Database error: {}", e)))?
+            .ok_or_else(|| async_graphql::Error::new("Please sign in again to continue"))?;
+
+        let step_up_start =
+            DateTime::from_millis(DateTime::now().timestamp_millis() - STEP_UP_MINUTES * 60 * 1000);
+        match session.mfa_verified_at {
+            Some(verified_at) if verified_at > step_up_start => Ok(()),
+            _ => Err(async_graphql::Error::new(
+                "MFA verification required. Please enter your authenticator code to continue.",
+            )),
+        }
+    }
+}
+
+/// Check whether the user's role in a school (or in any of their schools) falls
+/// under that school's MFA policy
+async fn school_requires_mfa(
+    db: &Database,
+    user_id: &str,
+    school_id: Option<&str>,
+) -> async_graphql::Result<bool> {
+    let mut filter = doc! {
+        "user_id": user_id,
+        "status": "Active",
+        "soft_delete.is_deleted": false
     };
+    if let Some(school_id) = school_id {
+        filter.insert("school_id", school_id);
+    }
+
+    let mut cursor = db
+        .collection::<Member>("members")
+        .find(filter, None)
+        .await
+        .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+
+    while let Some(member) = cursor
+        .try_next()
+        .await
+        .map_err(|e| async_graphql::Error::new(e.to_string()))?
+    {
+        let Ok(school_oid) = ObjectId::parse_str(&member.school_id) else {
+            continue;
+        };
+        let school = db
+            .collection::<School>("schools")
+            .find_one(doc! { "_id": school_oid }, None)
+            .await
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+
+        if let Some(school) = school {
+            if school.settings.require_mfa
+                && school.settings.mfa_required_roles.contains(&member.role)
+            {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Token from an Authorization value of the form `Bearer <token>`
+pub fn bearer_token(value: &str) -> Option<&str> {
+    value.strip_prefix("Bearer ")
+}
 
+/// Authenticated user from a signed access token
+fn auth_user_from_token(token: &str) -> Option<AuthUser> {
     let claims = verify_token(token).ok()?;
 
     Some(AuthUser {
         id: claims.sub,
         role: claims.role,
+        session_id: claims.sid,
+        school_id: claims.school_id,
+        expires_at: claims.exp as i64,
     })
 }
 
+/// Check that a signed-in user may still act in a school: the access token has
+/// not expired, the session has not been revoked, the user may still sign in and
+/// their membership is still active (holding `permission`, when given). Long-lived
+/// connections such as subscriptions call this again before each event.
+pub async fn is_still_member(
+    db: &Database,
+    user: &AuthUser,
+    school_id: &str,
+    permission: Option<Permission>,
+) -> bool {
+    if user.expires_at <= chrono::Utc::now().timestamp() {
+        return false;
+    }
+    if let Some(session_id) = user.session_id.as_deref() {
+        if !is_session_active(db, session_id).await {
+            return false;
+        }
+    }
+    if !is_user_active(db, &user.id).await {
+        return false;
+    }
+
+    match find_active_member(db, &user.id, school_id).await {
+        Ok(Some(member)) => permission.is_none_or(|p| member.has_permission(p)),
+        _ => false,
+    }
+}
+
+/// Error unless the member holds the permission
+fn check_permission(member: &Member, permission: Permission) -> async_graphql::Result<()> {
+    if member.has_permission(permission) {
+        Ok(())
+    } else {
+        Err(async_graphql::Error::new(format!(
+            "Insufficient permissions. Required permission: {:?}",
+            permission
+        )))
+    }
+}
+
+/// Load a user's active membership in a school, including the permissions of
+/// its custom roles
+async fn find_active_member(
+    db: &Database,
+    user_id: &str,
+    school_id: &str,
+) -> async_graphql::Result<Option<Member>> {
+    let member = db
+        .collection::<Member>("members")
+        .find_one(
+            doc! {
+                "user_id": user_id,
+                "school_id": school_id,
+                "status": "Active",
+                "soft_delete.is_deleted": false
+            },
+            None,
+        )
+        .await
+        .map_err(|e| async_graphql::Error::new(format!("Failed to check membership: {}", e)))?;
+
+    let Some(mut member) = member else {
+        return Ok(None);
+    };
+    load_custom_role_permissions(db, &mut member).await?;
+    Ok(Some(member))
+}
+
+/// Fill `custom_role_permissions` from the member's assigned custom roles
+pub async fn load_custom_role_permissions(
+    db: &Database,
+    member: &mut Member,
+) -> async_graphql::Result<()> {
+    let role_oids: Vec<ObjectId> = member
+        .custom_role_ids
+        .iter()
+        .filter_map(|id| ObjectId::parse_str(id).ok())
+        .collect();
+    if role_oids.is_empty() {
+        return Ok(());
+    }
+
+    let mut cursor = db
+        .collection::<CustomRole>("custom_roles")
+        .find(
+            doc! {
+                "_id": { "$in": role_oids },
+                "school_id": &member.school_id,
+                "soft_delete.is_deleted": false
+            },
+            None,
+        )
+        .await
+        .map_err(|e| async_graphql::Error::new(format!("Failed to load custom roles: {}", e)))?;
+
+    while let Some(role) = cursor
+        .try_next()
+        .await
+        .map_err(|e| async_graphql::Error::new(e.to_string()))?
+    {
+        for permission in role.permissions {
+            if !member.custom_role_permissions.contains(&permission) {
+                member.custom_role_permissions.push(permission);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that a user exists and is allowed to sign in
+async fn is_user_active(db: &Database, user_id: &str) -> bool {
+    let Ok(oid) = ObjectId::parse_str(user_id) else {
+        return false;
+    };
+
+    matches!(
+        db.collection::<User>("users")
+            .count_documents(
+                doc! {
+                    "_id": oid,
+                    "status": "Active",
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
+            .await,
+        Ok(count) if count > 0
+    )
+}
+
+/// Check that a session exists and has not been revoked or expired
+async fn is_session_active(db: &Database, session_id: &str) -> bool {
+    let Ok(oid) = ObjectId::parse_str(session_id) else {
+        return false;
+    };
+
+    matches!(
+        db.collection::<Session>("sessions")
+            .count_documents(
+                doc! {
+                    "_id": oid,
+                    "revoked_at": null,
+                    "expires_at": { "$gt": DateTime::now() }
+                },
+                None,
+            )
+            .await,
+        Ok(count) if count > 0
+    )
+}
+
 /// Helper function to get GraphQL context from async_graphql Context
 pub fn get_graphql_context<'a>(ctx: &'a Context<'_>) -> async_graphql::Result<&'a GraphQLContext> {
     ctx.data::<GraphQLContext>()
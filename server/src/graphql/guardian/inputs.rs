@@ -0,0 +1,19 @@
+use async_graphql::InputObject;
+
+/// Input for starting a guardian claim.
+/// A verification code is sent to the matching guardian phone on the student record.
+#[derive(InputObject)]
+pub struct RequestGuardianClaimInput {
+    pub school_id: String,
+    /// School-specific student ID (e.g., "STU-2024-001")
+    pub student_id: String,
+    /// Guardian phone number as given to the school
+    pub phone: String,
+}
+
+#[derive(InputObject)]
+pub struct VerifyGuardianClaimInput {
+    pub claim_id: String,
+    /// Verification code received by SMS
+    pub code: String,
+}
@@ -0,0 +1,5 @@
+pub mod inputs;
+pub mod mutations;
+pub mod types;
+
+pub use mutations::GuardianMutation;
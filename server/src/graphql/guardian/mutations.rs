@@ -0,0 +1,360 @@
+use super::inputs::{RequestGuardianClaimInput, VerifyGuardianClaimInput};
+use super::types::GuardianClaimResultType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::guardian_claim::{GuardianClaim, MAX_CLAIM_ATTEMPTS};
+use crate::models::member::{Member, SchoolRole};
+use crate::models::student::Student;
+use crate::utils::codes::{hash_token, random_digits};
+use crate::utils::sms::{normalize_phone, phone_variants, send_sms};
+use crate::utils::subscription::{ensure_writable, find_school};
+use async_graphql::*;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Database,
+};
+use std::collections::BTreeMap;
+
+/// Verification code lifetime in minutes
+const CODE_EXPIRY_MINUTES: i64 = 10;
+/// Minimum seconds between code requests for the same student
+const RESEND_COOLDOWN_SECONDS: i64 = 60;
+/// Length of SMS verification codes
+const CODE_LENGTH: usize = 6;
+
+#[derive(Default)]
+pub struct GuardianMutation;
+
+#[Object]
+impl GuardianMutation {
+    /// Start linking the current user to a student's guardian record.
+    /// Sends a verification code to the guardian phone on file.
+    async fn request_guardian_claim(
+        &self,
+        ctx: &Context<'_>,
+        input: RequestGuardianClaimInput,
+    ) -> Result<GuardianClaim> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+
+        // Same error whether the student or the phone is unknown, so the
+        // flow can't be used to probe student records
+        let not_found =
+            || Error::new("No guardian with this phone number is registered for the student");
+
+        let student = db
+            .collection::<Student>("students")
+            .find_one(
+                doc! {
+                    "school_id": &input.school_id,
+                    "student_id": input.student_id.trim(),
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?
+            .ok_or_else(not_found)?;
+
+        let phone = normalize_phone(&input.phone);
+        let guardian = student
+            .guardians
+            .iter()
+            .find(|g| !phone.is_empty() && normalize_phone(&g.phone) == phone)
+            .ok_or_else(not_found)?;
+
+        if guardian
+            .user_id
+            .as_ref()
+            .is_some_and(|id| id != &auth_user.id)
+        {
+            return Err(Error::new(
+                "This guardian record is already linked to another account. Please contact the school.",
+            ));
+        }
+
+        let student_id = student.id.map(|oid| oid.to_hex()).unwrap_or_default();
+        let collection = db.collection::<GuardianClaim>("guardian_claims");
+
+        let cooldown_start = DateTime::from_millis(
+            DateTime::now().timestamp_millis() - RESEND_COOLDOWN_SECONDS * 1000,
+        );
+        let recent = collection
+            .count_documents(
+                doc! {
+                    "user_id": &auth_user.id,
+                    "student_id": &student_id,
+                    "audit.created_at": { "$gt": cooldown_start }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+        if recent > 0 {
+            return Err(Error::new(
+                "A code was sent recently. Please wait a minute before requesting another.",
+            ));
+        }
+
+        let code = random_digits(CODE_LENGTH);
+        let expires_at = DateTime::from_millis(
+            DateTime::now().timestamp_millis() + CODE_EXPIRY_MINUTES * 60 * 1000,
+        );
+        let mut claim = GuardianClaim::new(
+            auth_user.id.clone(),
+            input.school_id,
+            student_id,
+            guardian.phone.clone(),
+            hash_token(&code),
+            expires_at,
+        );
+
+        let result = collection
+            .insert_one(&claim, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create guardian claim: {}", e)))?;
+        claim.id = result.inserted_id.as_object_id();
+
+        send_sms(
+            &guardian.phone,
+            &format!(
+                "Your verification code is {}. It expires in {} minutes.",
+                code, CODE_EXPIRY_MINUTES
+            ),
+        )
+        .await
+        .map_err(Error::new)?;
+
+        Ok(claim)
+    }
+
+    /// Complete a guardian claim with the code received by SMS.
+    /// Links every student (in any school) whose guardian record has the verified phone,
+    /// and adds them to the user's Parent membership in each school.
+    async fn verify_guardian_claim(
+        &self,
+        ctx: &Context<'_>,
+        input: VerifyGuardianClaimInput,
+    ) -> Result<GuardianClaimResultType> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+        let claims_collection = db.collection::<GuardianClaim>("guardian_claims");
+
+        let claim_oid =
+            ObjectId::parse_str(&input.claim_id).map_err(|_| Error::new("Invalid claim ID"))?;
+        let claim = claims_collection
+            .find_one(doc! { "_id": claim_oid, "user_id": &auth_user.id }, None)
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?
+            .ok_or_else(|| Error::new("Guardian claim not found"))?;
+
+        if !claim.is_open() {
+            return Err(Error::new(
+                "This verification code is no longer valid. Please request a new one.",
+            ));
+        }
+
+        let now = DateTime::now();
+        if hash_token(input.code.trim()) != claim.code_hash {
+            claims_collection
+                .update_one(
+                    doc! { "_id": claim_oid },
+                    doc! { "$inc": { "attempts": 1 }, "$set": { "audit.updated_at": now } },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+
+            let remaining = MAX_CLAIM_ATTEMPTS - claim.attempts - 1;
+            return Err(Error::new(format!(
+                "Incorrect verification code. {} attempt(s) remaining.",
+                remaining.max(0)
+            )));
+        }
+
+        // Only one request can use the code, even if several arrive together
+        let result = claims_collection
+            .update_one(
+                doc! {
+                    "_id": claim_oid,
+                    "verified_at": null,
+                    "attempts": { "$lt": MAX_CLAIM_ATTEMPTS },
+                    "expires_at": { "$gt": now }
+                },
+                doc! { "$set": { "verified_at": now, "audit.updated_at": now } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to verify claim: {}", e)))?;
+        if result.matched_count == 0 {
+            return Err(Error::new(
+                "This verification code is no longer valid. Please request a new one.",
+            ));
+        }
+
+        let students = link_guardian_records(db, &auth_user.id, &claim.guardian_phone).await?;
+
+        // Group the linked students by school
+        let mut by_school: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for student in &students {
+            if let Some(oid) = student.id {
+                by_school
+                    .entry(student.school_id.clone())
+                    .or_default()
+                    .push(oid.to_hex());
+            }
+        }
+
+        let mut memberships = Vec::new();
+        for (school_id, student_ids) in by_school {
+            // A school whose subscription has run out takes no new members
+            if ensure_writable(&find_school(db, &school_id).await?).is_err() {
+                continue;
+            }
+            memberships
+                .push(add_parent_membership(db, &auth_user.id, &school_id, student_ids).await?);
+        }
+
+        Ok(GuardianClaimResultType {
+            students: students.into_iter().map(|s| s.into()).collect(),
+            memberships,
+        })
+    }
+}
+
+/// Set `user_id` on every unlinked guardian record with the given phone number.
+/// Returns the students whose guardian record is now linked to the user.
+async fn link_guardian_records(db: &Database, user_id: &str, phone: &str) -> Result<Vec<Student>> {
+    let collection = db.collection::<Student>("students");
+    let phone = normalize_phone(phone);
+
+    let mut cursor = collection
+        .find(
+            doc! {
+                "guardians.phone": { "$in": phone_variants(&phone) },
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+
+    let mut candidates = Vec::new();
+    while let Some(student) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        candidates.push(student);
+    }
+
+    let now = DateTime::now();
+    let mut linked = Vec::new();
+    for student in candidates {
+        let mut update = doc! {};
+        let mut is_linked = false;
+        for (index, guardian) in student.guardians.iter().enumerate() {
+            if normalize_phone(&guardian.phone) != phone {
+                continue;
+            }
+            match guardian.user_id.as_deref() {
+                None => {
+                    update.insert(format!("guardians.{}.user_id", index), user_id);
+                    is_linked = true;
+                }
+                Some(id) if id == user_id => is_linked = true,
+                // Linked to someone else - leave it for the school to resolve
+                Some(_) => {}
+            }
+        }
+
+        if !update.is_empty() {
+            update.insert("audit.updated_at", now);
+            update.insert("audit.updated_by", user_id);
+            collection
+                .update_one(doc! { "_id": student.id }, doc! { "$set": update }, None)
+                .await
+                .map_err(|e| Error::new(format!("Failed to link guardian: {}", e)))?;
+        }
+
+        if is_linked {
+            linked.push(student);
+        }
+    }
+
+    Ok(linked)
+}
+
+/// Add students to the user's membership in a school, creating a Parent membership
+/// if the user has none (or reviving a removed one)
+async fn add_parent_membership(
+    db: &Database,
+    user_id: &str,
+    school_id: &str,
+    student_ids: Vec<String>,
+) -> Result<Member> {
+    let collection = db.collection::<Member>("members");
+
+    let existing = collection
+        .find_one(doc! { "user_id": user_id, "school_id": school_id }, None)
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+
+    let now = DateTime::now();
+    let member_id = match existing {
+        Some(member) => {
+            let member_id = member.id.ok_or_else(|| Error::new("Member not found"))?;
+            let mut set = doc! {
+                "audit.updated_at": now,
+                "audit.updated_by": user_id,
+            };
+            // A removed membership comes back as an active Parent; any other
+            // membership keeps its role and simply lists the children too
+            if member.soft_delete.is_deleted {
+                set.insert("role", "Parent");
+                set.insert("status", "Active");
+                set.insert("joined_at", now);
+                set.insert("soft_delete.is_deleted", false);
+                set.insert("soft_delete.deleted_at", mongodb::bson::Bson::Null);
+                set.insert("soft_delete.deleted_by", mongodb::bson::Bson::Null);
+            }
+
+            collection
+                .update_one(
+                    doc! { "_id": member_id },
+                    doc! {
+                        "$set": set,
+                        "$addToSet": { "parent_of": { "$each": student_ids } }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(format!("Failed to update membership: {}", e)))?;
+            member_id
+        }
+        None => {
+            let mut member = Member::new(
+                user_id.to_string(),
+                school_id.to_string(),
+                SchoolRole::Parent,
+            );
+            member.parent_of = student_ids;
+            member.audit.created_by = Some(user_id.to_string());
+
+            let result = collection
+                .insert_one(&member, None)
+                .await
+                .map_err(|e| Error::new(format!("Failed to create parent membership: {}", e)))?;
+            result
+                .inserted_id
+                .as_object_id()
+                .ok_or_else(|| Error::new("Failed to get inserted member ID"))?
+        }
+    };
+
+    collection
+        .find_one(doc! { "_id": member_id }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to retrieve member: {}", e)))?
+        .ok_or_else(|| Error::new("Member not found"))
+}
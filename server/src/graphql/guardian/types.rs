@@ -0,0 +1,12 @@
+use crate::graphql::student::StudentType;
+use crate::models::member::Member;
+use async_graphql::SimpleObject;
+
+/// Outcome of a verified guardian claim
+#[derive(SimpleObject)]
+pub struct GuardianClaimResultType {
+    /// Every student whose guardian record was linked, across schools
+    pub students: Vec<StudentType>,
+    /// The Parent memberships that now list those students
+    pub memberships: Vec<Member>,
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(InputObject, Serialize, Deserialize)]
 pub struct CreateStaffInput {
+    pub school_id: String,
     pub staff_id: String,
     pub first_name: String,
     pub last_name: String,
@@ -34,6 +35,7 @@ pub struct UpdateStaffInput {
 
 #[derive(InputObject, Serialize, Deserialize)]
 pub struct CreatePayrollInput {
+    pub school_id: String,
     pub staff_id: String,
     pub month: String,
     pub bonuses: f64,
@@ -1,6 +1,13 @@
 use super::inputs::{CreatePayrollInput, CreateStaffInput, UpdateStaffInput};
 use super::types::{PayrollType, StaffType};
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models::hr::{Payroll, Staff};
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::audit::record_change;
+use crate::utils::common_types::SoftDelete;
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::{restore_by_id, soft_delete_by_id};
 use async_graphql::*;
 use mongodb::{
     bson::{doc, oid::ObjectId, DateTime},
@@ -10,7 +17,7 @@ use mongodb::{
 #[derive(Default)]
 pub struct HRMutation;
 
-#[Object]
+#[Object(guard = "FeatureGuard::new(SchoolFeature::HR)")]
 impl HRMutation {
     /// Create a new staff member
     async fn create_staff(&self, ctx: &Context<'_>, input: CreateStaffInput) -> Result<StaffType> {
@@ -21,6 +28,7 @@ impl HRMutation {
         let staff = Staff {
             id: None,
             staff_id: input.staff_id,
+            school_id: Some(input.school_id),
             first_name: input.first_name,
             last_name: input.last_name,
             email: input.email,
@@ -38,6 +46,7 @@ impl HRMutation {
             profile_photo: None,
             created_at: now,
             updated_at: now,
+            soft_delete: SoftDelete::default(),
         };
 
         let result = collection
@@ -52,6 +61,15 @@ impl HRMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Failed to retrieve created staff"))?;
 
+        record_change(
+            ctx,
+            "Staff",
+            Some(id.to_hex()),
+            staff.school_id.as_deref(),
+            None,
+            Some(&staff),
+        );
+
         Ok(StaffType::from(staff))
     }
 
@@ -67,6 +85,15 @@ impl HRMutation {
 
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
+        let before = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Staff member not found"))?;
+
         let mut update_doc = doc! {};
 
         if let Some(f) = input.first_name {
@@ -113,6 +140,15 @@ impl HRMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Staff member not found"))?;
 
+        record_change(
+            ctx,
+            "Staff",
+            Some(id),
+            staff.school_id.as_deref(),
+            Some(&before),
+            Some(&staff),
+        );
+
         Ok(StaffType::from(staff))
     }
 
@@ -123,6 +159,16 @@ impl HRMutation {
         input: CreatePayrollInput,
     ) -> Result<PayrollType> {
         let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        graphql_ctx
+            .require_member_permission(db, &input.school_id, Permission::ManagePayroll)
+            .await?;
+
+        // Payroll runs need a recent MFA check
+        graphql_ctx
+            .require_step_up(db, Some(&input.school_id))
+            .await?;
+
         let staff_collection = db.collection::<Staff>("staff");
         let payroll_collection = db.collection::<Payroll>("payroll");
 
@@ -130,7 +176,14 @@ impl HRMutation {
             .map_err(|_| Error::new("Invalid staff ID format"))?;
 
         let staff = staff_collection
-            .find_one(doc! { "_id": staff_obj_id }, None)
+            .find_one(
+                doc! {
+                    "_id": staff_obj_id,
+                    "school_id": &input.school_id,
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Staff member not found"))?;
@@ -165,25 +218,98 @@ impl HRMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Failed to retrieve created payroll record"))?;
 
+        record_change(
+            ctx,
+            "Payroll",
+            Some(id.to_hex()),
+            Some(&input.school_id),
+            None,
+            Some(&payroll),
+        );
+
         Ok(PayrollType::from(payroll))
     }
 
-    /// Delete a staff member by ID
+    /// Delete a staff member by ID (moves them to the trash)
     async fn delete_staff(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<Staff>("staff");
 
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
-        let result = collection
-            .delete_one(doc! { "_id": obj_id }, None)
+        let before = collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
-            .map_err(|e| Error::new(e.to_string()))?;
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Staff member not found"))?;
+        let school_id = staff_school_id(&before)?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, school_id, Permission::ManageStaff)
+            .await?;
 
-        if result.deleted_count == 0 {
-            return Err(Error::new("Staff member not found"));
-        }
+        soft_delete_by_id(&collection, obj_id, &member.user_id).await?;
+
+        let after = collection
+            .find_one(doc! { "_id": obj_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        record_change(
+            ctx,
+            "Staff",
+            Some(id),
+            Some(school_id),
+            Some(&before),
+            after.as_ref(),
+        );
 
         Ok(true)
     }
+
+    /// Restore a deleted staff member from the trash
+    async fn restore_staff(&self, ctx: &Context<'_>, id: String) -> Result<StaffType> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<Staff>("staff");
+
+        let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
+
+        let before = collection
+            .find_one(doc! { "_id": obj_id, "soft_delete.is_deleted": true }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Deleted staff member not found"))?;
+        let school_id = staff_school_id(&before)?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, school_id, Permission::ManageStaff)
+            .await?;
+
+        restore_by_id(&collection, obj_id).await?;
+
+        let staff = collection
+            .find_one(doc! { "_id": obj_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Staff member not found"))?;
+        record_change(
+            ctx,
+            "Staff",
+            Some(id),
+            Some(school_id),
+            Some(&before),
+            Some(&staff),
+        );
+
+        Ok(StaffType::from(staff))
+    }
+}
+
+/// The school whose permissions govern a staff record. Records from before
+/// staff were tied to a school can't be checked, so they're refused.
+fn staff_school_id(staff: &Staff) -> Result<&str> {
+    staff
+        .school_id
+        .as_deref()
+        .ok_or_else(|| Error::new("This staff record isn't linked to a school"))
 }
@@ -1,5 +1,10 @@
 use super::types::{PayrollType, StaffType};
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models::hr::{Payroll, Staff};
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::find_deleted;
 use async_graphql::*;
 use futures::stream::TryStreamExt;
 use mongodb::{
@@ -10,7 +15,7 @@ use mongodb::{
 #[derive(Default)]
 pub struct HRQuery;
 
-#[Object]
+#[Object(guard = "FeatureGuard::new(SchoolFeature::HR)")]
 impl HRQuery {
     /// Get all staff for a specific school
     async fn all_staff(&self, ctx: &Context<'_>, _school_id: String) -> Result<Vec<StaffType>> {
@@ -24,7 +29,7 @@ impl HRQuery {
 
         // Let's fetch all staff for now to get things working.
         let mut cursor = collection
-            .find(None, None)
+            .find(doc! { "soft_delete.is_deleted": { "$ne": true } }, None)
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -48,13 +53,32 @@ impl HRQuery {
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
         let staff = collection
-            .find_one(doc! { "_id": obj_id }, None)
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
         Ok(staff.map(StaffType::from))
     }
 
+    /// Get deleted staff members (the trash)
+    async fn deleted_staff(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<StaffType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ManageStaff)
+            .await?;
+
+        let staff = find_deleted(
+            &db.collection::<Staff>("staff"),
+            doc! { "school_id": school_id },
+        )
+        .await?;
+
+        Ok(staff.into_iter().map(StaffType::from).collect())
+    }
+
     /// Get payroll records for a specific staff member
     async fn payroll_for_staff(
         &self,
@@ -1,10 +1,12 @@
 use crate::models::hr::{Payroll, Staff};
+use crate::utils::common_types::SoftDelete;
 use async_graphql::*;
 
 #[derive(SimpleObject)]
 pub struct StaffType {
     pub id: String,
     pub staff_id: String,
+    pub school_id: Option<String>,
     pub first_name: String,
     pub last_name: String,
     pub email: String,
@@ -22,6 +24,8 @@ pub struct StaffType {
     pub profile_photo: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Trash state; set when the record has been deleted
+    pub soft_delete: SoftDelete,
 }
 
 impl From<Staff> for StaffType {
@@ -29,6 +33,7 @@ impl From<Staff> for StaffType {
         StaffType {
             id: s.id.map(|id| id.to_hex()).unwrap_or_default(),
             staff_id: s.staff_id,
+            school_id: s.school_id,
             first_name: s.first_name,
             last_name: s.last_name,
             email: s.email,
@@ -46,6 +51,7 @@ impl From<Staff> for StaffType {
             profile_photo: s.profile_photo,
             created_at: s.created_at.try_to_rfc3339_string().unwrap_or_default(),
             updated_at: s.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+            soft_delete: s.soft_delete,
         }
     }
 }
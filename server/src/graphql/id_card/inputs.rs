@@ -0,0 +1,17 @@
+use async_graphql::InputObject;
+
+/// Which students to print cards for; with no filter, every active student in
+/// the school
+#[derive(InputObject)]
+pub struct GenerateIdCardsInput {
+    pub school_id: String,
+    /// Students currently in this class
+    pub class_id: Option<String>,
+    /// Students in this grade level
+    pub grade_level: Option<String>,
+    /// Only these students (record IDs)
+    pub student_ids: Option<Vec<String>>,
+    /// Validity printed on the cards (e.g., "2025-2026"); defaults to the
+    /// current academic year
+    pub academic_year: Option<String>,
+}
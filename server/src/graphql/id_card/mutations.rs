@@ -0,0 +1,268 @@
+use super::inputs::GenerateIdCardsInput;
+use super::types::IdCardSheetType;
+use crate::graphql::file::mutations::store_file;
+use crate::graphql::file::queries::{download_url, find_file};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::library::queries::collect;
+use crate::models::class::Class;
+use crate::models::member::Permission;
+use crate::models::school::School;
+use crate::models::stored_file::FilePurpose;
+use crate::models::student::Student;
+use crate::utils::id_card::{
+    card_image, load_khmer_font, render_id_cards, CardBranding, CardImage, IdCard,
+};
+use crate::utils::storage::{file_id_from_url, file_storage, FileStorage};
+use async_graphql::*;
+use chrono::{Datelike, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Database,
+};
+use std::collections::HashMap;
+
+/// Most cards in one PDF; print larger schools class by class
+const MAX_CARDS: usize = 1000;
+
+#[derive(Default)]
+pub struct IdCardMutation;
+
+#[Object]
+impl IdCardMutation {
+    /// Print ID cards for active students as A4 PDF sheets (UpdateStudents).
+    /// Cards show the school's logo and colour, the student's photo, Khmer and
+    /// English names, ID number, grade and class, and a QR code of the ID
+    /// number. Khmer names need a Khmer font (`ID_CARD_KHMER_FONT`).
+    async fn generate_id_cards(
+        &self,
+        ctx: &Context<'_>,
+        input: GenerateIdCardsInput,
+    ) -> Result<IdCardSheetType> {
+        let db = ctx.data::<Database>()?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &input.school_id, Permission::UpdateStudents)
+            .await?;
+
+        let school_oid =
+            ObjectId::parse_str(&input.school_id).map_err(|_| Error::new("Invalid school ID"))?;
+        let school = db
+            .collection::<School>("schools")
+            .find_one(doc! { "_id": school_oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("School not found"))?;
+
+        let mut filter = doc! {
+            "school_id": &input.school_id,
+            "status": "Active",
+            "soft_delete.is_deleted": { "$ne": true },
+        };
+        if let Some(class_id) = input.class_id.as_deref() {
+            filter.insert("current_class_id", class_id);
+        }
+        if let Some(grade_level) = input.grade_level.as_deref() {
+            filter.insert("grade_level", grade_level);
+        }
+        if let Some(student_ids) = input.student_ids.as_ref() {
+            let oids = student_ids
+                .iter()
+                .map(|id| ObjectId::parse_str(id).map_err(|_| Error::new("Invalid student ID")))
+                .collect::<Result<Vec<_>>>()?;
+            filter.insert("_id", doc! { "$in": oids });
+        }
+        let students = collect(
+            &db.collection::<Student>("students"),
+            filter,
+            FindOptions::builder()
+                .sort(doc! { "current_class_id": 1, "student_id": 1 })
+                .limit(MAX_CARDS as i64 + 1)
+                .build(),
+        )
+        .await?;
+        if students.is_empty() {
+            return Err(Error::new("No active students match"));
+        }
+        if students.len() > MAX_CARDS {
+            return Err(Error::new(format!(
+                "At most {} cards can be printed at once. Print by class or grade level",
+                MAX_CARDS
+            )));
+        }
+
+        let class_oids: Vec<ObjectId> = students
+            .iter()
+            .filter_map(|s| s.current_class_id.as_deref())
+            .filter_map(|id| ObjectId::parse_str(id).ok())
+            .collect();
+        let class_names: HashMap<String, String> = collect(
+            &db.collection::<Class>("classes"),
+            doc! { "_id": { "$in": class_oids } },
+            FindOptions::default(),
+        )
+        .await?
+        .into_iter()
+        .filter_map(|class| Some((class.id?.to_hex(), class.name)))
+        .collect();
+
+        let storage = file_storage().map_err(Error::new)?;
+        let mut warnings = Vec::new();
+        let logo = match school.logo.as_ref() {
+            Some(logo) => {
+                let image = load_image(db, storage.as_ref(), &input.school_id, &logo.url).await;
+                if image.is_none() {
+                    warnings.push(
+                        "The school logo couldn't be printed (upload it as PNG or JPEG)"
+                            .to_string(),
+                    );
+                }
+                image
+            }
+            None => None,
+        };
+
+        let khmer_font = match load_khmer_font().await {
+            Ok(Some(font)) => Some(font),
+            Ok(None) => {
+                warnings.push(
+                    "Khmer names aren't printed: no Khmer font is configured (ID_CARD_KHMER_FONT)"
+                        .to_string(),
+                );
+                None
+            }
+            Err(e) => {
+                warnings.push(format!("Khmer names aren't printed: {}", e));
+                None
+            }
+        };
+
+        let mut cards = Vec::with_capacity(students.len());
+        for student in students {
+            let name_en = english_name(&student);
+            if name_en.is_none() {
+                warnings.push(format!("{}: no English name", student.student_id));
+            }
+            let photo = match student.profile_photo.as_ref() {
+                Some(photo) => {
+                    let image =
+                        load_image(db, storage.as_ref(), &input.school_id, &photo.url).await;
+                    if image.is_none() {
+                        warnings.push(format!(
+                            "{}: the photo couldn't be printed (upload it as PNG or JPEG)",
+                            student.student_id
+                        ));
+                    }
+                    image
+                }
+                None => {
+                    warnings.push(format!("{}: no photo", student.student_id));
+                    None
+                }
+            };
+            cards.push(IdCard {
+                name_km: khmer_name(&student),
+                name_en,
+                class_name: student
+                    .current_class_id
+                    .as_ref()
+                    .and_then(|id| class_names.get(id).cloned()),
+                code: student.student_id,
+                grade_level: student.grade_level,
+                photo,
+            });
+        }
+
+        let branding = CardBranding {
+            school_name: school.name.en.clone(),
+            logo,
+            primary_color: school.primary_color.clone(),
+            academic_year: input
+                .academic_year
+                .filter(|year| !year.trim().is_empty())
+                .unwrap_or_else(|| current_academic_year(&school)),
+        };
+        let card_count = cards.len() as i32;
+        let (pdf, page_count) = tokio::task::spawn_blocking(move || {
+            render_id_cards(branding, cards, khmer_font.as_deref())
+        })
+        .await
+        .map_err(|e| Error::new(format!("Failed to create ID cards: {}", e)))?
+        .map_err(Error::new)?;
+
+        let file_name = format!("id-cards-{}.pdf", Utc::now().format("%Y-%m-%d"));
+        let file = store_file(
+            ctx,
+            &member,
+            FilePurpose::IdCards,
+            None,
+            file_name,
+            "application/pdf",
+            pdf,
+        )
+        .await?;
+        let download = download_url(&file, false);
+
+        Ok(IdCardSheetType {
+            file,
+            download,
+            card_count,
+            page_count: page_count as i32,
+            warnings,
+        })
+    }
+}
+
+/// An uploaded PNG or JPEG of the school's, ready to print. PNGs with a
+/// thumbnail use it; it's sharp enough at card size.
+async fn load_image(
+    db: &Database,
+    storage: &dyn FileStorage,
+    school_id: &str,
+    url: &str,
+) -> Option<CardImage> {
+    let file = find_file(db, file_id_from_url(url)?).await.ok()?;
+    if file.school_id != school_id {
+        return None;
+    }
+    let (key, file_type) = match file.thumbnail_key.as_deref() {
+        Some(key) => (key, "image/png"),
+        None => (file.storage_key.as_str(), file.file_type.as_str()),
+    };
+    let bytes = storage.get(key).await.ok()?;
+    let file_type = file_type.to_string();
+    tokio::task::spawn_blocking(move || card_image(bytes, &file_type))
+        .await
+        .ok()?
+}
+
+/// The student's name in Khmer, if recorded
+fn khmer_name(student: &Student) -> Option<String> {
+    let name = format!(
+        "{} {}",
+        student.first_name_km.trim(),
+        student.last_name_km.trim()
+    );
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// The student's name in Latin script, if recorded
+fn english_name(student: &Student) -> Option<String> {
+    let parts: Vec<&str> = [&student.first_name_en, &student.last_name_en]
+        .into_iter()
+        .filter_map(|part| part.as_deref().map(str::trim))
+        .filter(|part| !part.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+/// "2025-2026" style label for the academic year under way
+fn current_academic_year(school: &School) -> String {
+    let today = Utc::now();
+    let start_year = if today.month() as i32 >= school.settings.academic_year_start_month {
+        today.year()
+    } else {
+        today.year() - 1
+    };
+    format!("{}-{}", start_year, start_year + 1)
+}
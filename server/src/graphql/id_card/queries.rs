@@ -0,0 +1,62 @@
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::member::Permission;
+use crate::models::student::Student;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    Database,
+};
+
+#[derive(Default)]
+pub struct IdCardQuery;
+
+#[Object]
+impl IdCardQuery {
+    /// The student a scanned ID card belongs to (ViewStudents or ManageLibrary)
+    async fn student_by_card(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        code: String,
+    ) -> Result<Student> {
+        let db = ctx.data::<Database>()?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &school_id)
+            .await?;
+        if !member.has_permission(Permission::ViewStudents)
+            && !member.has_permission(Permission::ManageLibrary)
+        {
+            return Err(Error::new("Insufficient permissions to look up students"));
+        }
+        find_student_by_card(db, &school_id, &code).await
+    }
+}
+
+/// Resolve a scanned ID card code (the student's school ID number) to the
+/// student. A student record ID is accepted too.
+pub(crate) async fn find_student_by_card(
+    db: &Database,
+    school_id: &str,
+    code: &str,
+) -> Result<Student> {
+    let code = code.trim();
+    if code.is_empty() {
+        return Err(Error::new("The card code is empty"));
+    }
+    let mut filter = doc! {
+        "school_id": school_id,
+        "soft_delete.is_deleted": { "$ne": true },
+    };
+    match ObjectId::parse_str(code) {
+        Ok(oid) => filter.insert(
+            "$or",
+            vec![doc! { "_id": oid }, doc! { "student_id": code }],
+        ),
+        Err(_) => filter.insert("student_id", code),
+    };
+    db.collection::<Student>("students")
+        .find_one(filter, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("No student has this card"))
+}
@@ -0,0 +1,14 @@
+use crate::graphql::file::types::DownloadUrlType;
+use crate::models::stored_file::StoredFile;
+use async_graphql::SimpleObject;
+
+/// A generated PDF of ID cards
+#[derive(SimpleObject)]
+pub struct IdCardSheetType {
+    pub file: StoredFile,
+    pub download: DownloadUrlType,
+    pub card_count: i32,
+    pub page_count: i32,
+    /// Cards printed without something (photo, English name, logo, Khmer font)
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,156 @@
+use async_graphql::InputObject;
+
+use crate::models::inventory::{AssetStatus, MaintenancePriority, MaintenanceStatus, StockReason};
+
+#[derive(InputObject)]
+pub struct CreateAssetInput {
+    pub school_id: String,
+    pub branch_id: Option<String>,
+    pub name: String,
+    pub category: String,
+    pub serial_number: Option<String>,
+    pub location: Option<String>,
+    pub room: Option<String>,
+    /// HR staff record the asset is assigned to
+    pub assigned_staff_id: Option<String>,
+    /// Purchase date (YYYY-MM-DD)
+    pub purchase_date: Option<String>,
+    pub purchase_cost: Option<f64>,
+    /// Cost currency (default "USD")
+    pub currency: Option<String>,
+    pub useful_life_years: Option<i32>,
+    pub salvage_value: Option<f64>,
+    pub notes: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateAssetInput {
+    pub asset_id: String,
+    pub branch_id: Option<String>,
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub serial_number: Option<String>,
+    /// Changing location, room or assignee is recorded in the asset's history
+    pub location: Option<String>,
+    pub room: Option<String>,
+    pub assigned_staff_id: Option<String>,
+    /// Only Available, Lost and Retired can be set by hand; check-outs and
+    /// maintenance set the others
+    pub status: Option<AssetStatus>,
+    pub purchase_date: Option<String>,
+    pub purchase_cost: Option<f64>,
+    pub useful_life_years: Option<i32>,
+    pub salvage_value: Option<f64>,
+    pub notes: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct CheckOutAssetInput {
+    pub asset_id: String,
+    /// HR staff record taking the asset
+    pub staff_id: String,
+    pub location: Option<String>,
+    pub room: Option<String>,
+    /// Expected return date (YYYY-MM-DD)
+    pub expected_return: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct CheckInAssetInput {
+    pub asset_id: String,
+    pub location: Option<String>,
+    pub room: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct CreateStockItemInput {
+    pub school_id: String,
+    pub branch_id: Option<String>,
+    pub name: String,
+    pub sku: Option<String>,
+    pub category: String,
+    pub unit: String,
+    /// Opening quantity (default 0)
+    pub quantity: Option<i32>,
+    pub reorder_level: i32,
+    pub location: Option<String>,
+    pub unit_cost: Option<f64>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateStockItemInput {
+    pub item_id: String,
+    pub name: Option<String>,
+    pub sku: Option<String>,
+    pub category: Option<String>,
+    pub unit: Option<String>,
+    pub reorder_level: Option<i32>,
+    pub location: Option<String>,
+    pub unit_cost: Option<f64>,
+}
+
+#[derive(InputObject)]
+pub struct AdjustStockInput {
+    pub item_id: String,
+    /// Units added (positive) or removed (negative)
+    pub change: i32,
+    /// Received, Issued or Adjusted
+    pub reason: StockReason,
+    /// Who the stock was issued to
+    pub issued_to: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct CreateMaintenanceRequestInput {
+    pub asset_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Default Medium
+    pub priority: Option<MaintenancePriority>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateMaintenanceRequestInput {
+    pub request_id: String,
+    pub status: Option<MaintenanceStatus>,
+    pub priority: Option<MaintenancePriority>,
+    pub assigned_to: Option<String>,
+    pub cost: Option<f64>,
+    pub resolution: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct StartStockTakeInput {
+    pub school_id: String,
+    pub branch_id: Option<String>,
+    /// Year the count is for (default the current year)
+    pub year: Option<i32>,
+}
+
+#[derive(InputObject)]
+pub struct CountAssetInput {
+    pub stock_take_id: String,
+    /// Asset tag scanned from the label
+    pub asset_tag: String,
+    pub location_found: Option<String>,
+    pub room_found: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct CountStockItemInput {
+    pub stock_take_id: String,
+    pub item_id: String,
+    pub counted: i32,
+}
+
+#[derive(InputObject)]
+pub struct CompleteStockTakeInput {
+    pub stock_take_id: String,
+    /// Mark assets that weren't counted as Lost
+    pub mark_missing_lost: Option<bool>,
+    /// Set stock levels to the counted quantities
+    pub apply_stock_counts: Option<bool>,
+}
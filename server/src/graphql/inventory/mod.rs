@@ -0,0 +1,7 @@
+pub mod inputs;
+pub mod mutations;
+pub mod queries;
+pub mod types;
+
+pub use mutations::InventoryMutation;
+pub use queries::InventoryQuery;
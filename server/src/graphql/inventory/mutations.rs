@@ -0,0 +1,1180 @@
+use super::inputs::{
+    AdjustStockInput, CheckInAssetInput, CheckOutAssetInput, CompleteStockTakeInput,
+    CountAssetInput, CountStockItemInput, CreateAssetInput, CreateMaintenanceRequestInput,
+    CreateStockItemInput, StartStockTakeInput, UpdateAssetInput, UpdateMaintenanceRequestInput,
+    UpdateStockItemInput,
+};
+use super::queries::{
+    build_stock_take_report, find_asset, find_asset_by_tag, find_stock_item, find_stock_take,
+    require_inventory, scope_filter,
+};
+use super::types::StockTakeReportType;
+use crate::graphql::library::queries::{collect, to_bson};
+use crate::models::branch::Branch;
+use crate::models::hr::Staff;
+use crate::models::inventory::{
+    Asset, AssetAction, AssetCount, AssetMovement, AssetStatus, ItemCount, MaintenancePriority,
+    MaintenanceRequest, MaintenanceStatus, StockItem, StockMovement, StockReason, StockTake,
+    StockTakeStatus,
+};
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::audit::record_change;
+use crate::utils::common_types::{AuditInfo, SoftDelete};
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::{find_deleted_by_id, restore_by_id, soft_delete_by_id};
+use async_graphql::*;
+use chrono::{Datelike, NaiveDate, NaiveTime, TimeZone, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::{FindOneOptions, FindOptions},
+    Database,
+};
+
+#[derive(Default)]
+pub struct InventoryMutation;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Inventory)")]
+impl InventoryMutation {
+    /// Register an asset with the school's next asset tag (requires ManageInventory)
+    async fn create_asset(&self, ctx: &Context<'_>, input: CreateAssetInput) -> Result<Asset> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_inventory(ctx, db, &input.school_id, Permission::ManageInventory).await?;
+
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            return Err(Error::new("Asset name is required"));
+        }
+        if let Some(branch_id) = input.branch_id.as_deref() {
+            check_branch(db, &input.school_id, branch_id).await?;
+        }
+        if let Some(staff_id) = input.assigned_staff_id.as_deref() {
+            check_staff(db, staff_id).await?;
+        }
+        let purchase_cost = input.purchase_cost.unwrap_or(0.0);
+        let salvage_value = input.salvage_value.unwrap_or(0.0);
+        check_value(purchase_cost, salvage_value, input.useful_life_years)?;
+
+        let mut asset = Asset {
+            id: None,
+            asset_tag: next_asset_tag(db, &input.school_id).await?,
+            school_id: input.school_id,
+            branch_id: input.branch_id,
+            name,
+            category: input.category,
+            serial_number: input.serial_number,
+            location: input.location,
+            room: input.room,
+            assigned_staff_id: input.assigned_staff_id,
+            status: AssetStatus::Available,
+            purchase_date: input.purchase_date.as_deref().map(parse_date).transpose()?,
+            purchase_cost,
+            currency: input.currency.unwrap_or_else(|| "USD".to_string()),
+            useful_life_years: input.useful_life_years,
+            salvage_value,
+            notes: input.notes,
+            audit: AuditInfo::new(Some(member.user_id.clone())),
+            soft_delete: SoftDelete::default(),
+        };
+        let result = db
+            .collection::<Asset>("assets")
+            .insert_one(&asset, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create asset: {}", e)))?;
+        asset.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "Asset",
+            asset.id.map(|id| id.to_hex()),
+            Some(&asset.school_id),
+            None,
+            Some(&asset),
+        );
+
+        Ok(asset)
+    }
+
+    /// Update an asset (requires ManageInventory). Moves, reassignments and
+    /// status changes are added to its history.
+    async fn update_asset(&self, ctx: &Context<'_>, input: UpdateAssetInput) -> Result<Asset> {
+        let db = ctx.data::<Database>()?;
+        let asset = find_asset(db, &input.asset_id).await?;
+        let member =
+            require_inventory(ctx, db, &asset.school_id, Permission::ManageInventory).await?;
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(branch_id) = input.branch_id {
+            check_branch(db, &asset.school_id, &branch_id).await?;
+            set.insert("branch_id", branch_id);
+        }
+        if let Some(name) = input.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err(Error::new("Asset name is required"));
+            }
+            set.insert("name", name);
+        }
+        if let Some(category) = input.category {
+            set.insert("category", category);
+        }
+        if let Some(serial_number) = input.serial_number {
+            set.insert("serial_number", serial_number);
+        }
+        if let Some(location) = &input.location {
+            set.insert("location", location);
+        }
+        if let Some(room) = &input.room {
+            set.insert("room", room);
+        }
+        if let Some(staff_id) = &input.assigned_staff_id {
+            check_staff(db, staff_id).await?;
+            set.insert("assigned_staff_id", staff_id);
+        }
+        if let Some(status) = input.status {
+            if !matches!(
+                status,
+                AssetStatus::Available | AssetStatus::Lost | AssetStatus::Retired
+            ) || matches!(
+                asset.status,
+                AssetStatus::CheckedOut | AssetStatus::InMaintenance
+            ) {
+                return Err(Error::new(
+                    "Check-outs and maintenance are managed through their own actions",
+                ));
+            }
+            set.insert("status", to_bson(&status)?);
+        }
+        if let Some(date) = input.purchase_date {
+            set.insert("purchase_date", parse_date(&date)?);
+        }
+        let purchase_cost = input.purchase_cost.unwrap_or(asset.purchase_cost);
+        let salvage_value = input.salvage_value.unwrap_or(asset.salvage_value);
+        let useful_life = input.useful_life_years.or(asset.useful_life_years);
+        check_value(purchase_cost, salvage_value, useful_life)?;
+        if let Some(cost) = input.purchase_cost {
+            set.insert("purchase_cost", cost);
+        }
+        if let Some(life) = input.useful_life_years {
+            set.insert("useful_life_years", life);
+        }
+        if let Some(salvage) = input.salvage_value {
+            set.insert("salvage_value", salvage);
+        }
+        if let Some(notes) = input.notes {
+            set.insert("notes", notes);
+        }
+
+        db.collection::<Asset>("assets")
+            .update_one(doc! { "_id": asset.id }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update asset: {}", e)))?;
+
+        let updated = find_asset(db, &input.asset_id).await?;
+        let action = if updated.status != asset.status {
+            Some(AssetAction::StatusChanged)
+        } else if updated.location != asset.location
+            || updated.room != asset.room
+            || updated.assigned_staff_id != asset.assigned_staff_id
+        {
+            Some(AssetAction::Moved)
+        } else {
+            None
+        };
+        if let Some(action) = action {
+            log_movement(db, &updated, action, None, None, &member.user_id).await?;
+        }
+
+        record_change(
+            ctx,
+            "Asset",
+            Some(input.asset_id),
+            Some(&updated.school_id),
+            Some(&asset),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Remove an asset from the register (requires ManageInventory)
+    async fn delete_asset(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let asset = find_asset(db, &id).await?;
+        let member =
+            require_inventory(ctx, db, &asset.school_id, Permission::ManageInventory).await?;
+
+        if asset.status == AssetStatus::CheckedOut {
+            return Err(Error::new("Check the asset in first"));
+        }
+
+        let oid = asset.id.ok_or_else(|| Error::new("Asset not found"))?;
+        soft_delete_by_id(&db.collection::<Asset>("assets"), oid, &member.user_id).await?;
+
+        record_change(
+            ctx,
+            "Asset",
+            Some(id),
+            Some(&asset.school_id),
+            Some(&asset),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted asset from the trash (requires ManageInventory)
+    async fn restore_asset(&self, ctx: &Context<'_>, id: String) -> Result<Asset> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<Asset>("assets");
+        let asset = find_deleted_by_id(&collection, &id, "asset").await?;
+        require_inventory(ctx, db, &asset.school_id, Permission::ManageInventory).await?;
+
+        let oid = asset.id.ok_or_else(|| Error::new("Asset not found"))?;
+        restore_by_id(&collection, oid).await?;
+
+        let restored = find_asset(db, &id).await?;
+        record_change(
+            ctx,
+            "Asset",
+            Some(id),
+            Some(&asset.school_id),
+            Some(&asset),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Check an asset out to a staff member (requires ManageInventory)
+    async fn check_out_asset(&self, ctx: &Context<'_>, input: CheckOutAssetInput) -> Result<Asset> {
+        let db = ctx.data::<Database>()?;
+        let asset = find_asset(db, &input.asset_id).await?;
+        let member =
+            require_inventory(ctx, db, &asset.school_id, Permission::ManageInventory).await?;
+
+        if asset.status != AssetStatus::Available {
+            return Err(Error::new(format!("This asset is {:?}", asset.status)));
+        }
+        check_staff(db, &input.staff_id).await?;
+        let expected_return = input
+            .expected_return
+            .as_deref()
+            .map(parse_date)
+            .transpose()?;
+
+        let mut set = doc! {
+            "status": "CheckedOut",
+            "assigned_staff_id": &input.staff_id,
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(location) = &input.location {
+            set.insert("location", location);
+        }
+        if let Some(room) = &input.room {
+            set.insert("room", room);
+        }
+        db.collection::<Asset>("assets")
+            .update_one(doc! { "_id": asset.id }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to check out asset: {}", e)))?;
+
+        let updated = find_asset(db, &input.asset_id).await?;
+        log_movement(
+            db,
+            &updated,
+            AssetAction::CheckedOut,
+            expected_return,
+            input.notes,
+            &member.user_id,
+        )
+        .await?;
+
+        Ok(updated)
+    }
+
+    /// Check a checked-out asset back in (requires ManageInventory)
+    async fn check_in_asset(&self, ctx: &Context<'_>, input: CheckInAssetInput) -> Result<Asset> {
+        let db = ctx.data::<Database>()?;
+        let asset = find_asset(db, &input.asset_id).await?;
+        let member =
+            require_inventory(ctx, db, &asset.school_id, Permission::ManageInventory).await?;
+
+        if asset.status != AssetStatus::CheckedOut {
+            return Err(Error::new("This asset is not checked out"));
+        }
+
+        let mut set = doc! {
+            "status": "Available",
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(location) = &input.location {
+            set.insert("location", location);
+        }
+        if let Some(room) = &input.room {
+            set.insert("room", room);
+        }
+        db.collection::<Asset>("assets")
+            .update_one(
+                doc! { "_id": asset.id },
+                doc! { "$set": set, "$unset": { "assigned_staff_id": "" } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to check in asset: {}", e)))?;
+
+        let updated = find_asset(db, &input.asset_id).await?;
+        log_movement(
+            db,
+            &updated,
+            AssetAction::CheckedIn,
+            None,
+            input.notes,
+            &member.user_id,
+        )
+        .await?;
+
+        Ok(updated)
+    }
+
+    /// Add a consumable to stock (requires ManageInventory)
+    async fn create_stock_item(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateStockItemInput,
+    ) -> Result<StockItem> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_inventory(ctx, db, &input.school_id, Permission::ManageInventory).await?;
+
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            return Err(Error::new("Item name is required"));
+        }
+        if let Some(branch_id) = input.branch_id.as_deref() {
+            check_branch(db, &input.school_id, branch_id).await?;
+        }
+        let quantity = input.quantity.unwrap_or(0);
+        if quantity < 0 || input.reorder_level < 0 {
+            return Err(Error::new("Quantity and reorder level cannot be negative"));
+        }
+        if let Some(sku) = input.sku.as_deref() {
+            ensure_sku_unused(db, &input.school_id, sku, None).await?;
+        }
+
+        let mut item = StockItem {
+            id: None,
+            school_id: input.school_id,
+            branch_id: input.branch_id,
+            name,
+            sku: input.sku,
+            category: input.category,
+            unit: input.unit,
+            quantity,
+            reorder_level: input.reorder_level,
+            location: input.location,
+            unit_cost: input.unit_cost,
+            audit: AuditInfo::new(Some(member.user_id.clone())),
+            soft_delete: SoftDelete::default(),
+        };
+        let result = db
+            .collection::<StockItem>("stock_items")
+            .insert_one(&item, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create stock item: {}", e)))?;
+        item.id = result.inserted_id.as_object_id();
+
+        if quantity > 0 {
+            log_stock(
+                db,
+                &item,
+                quantity,
+                StockReason::Received,
+                None,
+                Some("Opening stock".to_string()),
+                &member.user_id,
+            )
+            .await?;
+        }
+        record_change(
+            ctx,
+            "StockItem",
+            item.id.map(|id| id.to_hex()),
+            Some(&item.school_id),
+            None,
+            Some(&item),
+        );
+
+        Ok(item)
+    }
+
+    /// Update a consumable's details (requires ManageInventory). Quantities
+    /// change through `adjustStock`.
+    async fn update_stock_item(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateStockItemInput,
+    ) -> Result<StockItem> {
+        let db = ctx.data::<Database>()?;
+        let item = find_stock_item(db, &input.item_id).await?;
+        let member =
+            require_inventory(ctx, db, &item.school_id, Permission::ManageInventory).await?;
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(name) = input.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err(Error::new("Item name is required"));
+            }
+            set.insert("name", name);
+        }
+        if let Some(sku) = input.sku {
+            ensure_sku_unused(db, &item.school_id, &sku, item.id).await?;
+            set.insert("sku", sku);
+        }
+        if let Some(category) = input.category {
+            set.insert("category", category);
+        }
+        if let Some(unit) = input.unit {
+            set.insert("unit", unit);
+        }
+        if let Some(level) = input.reorder_level {
+            if level < 0 {
+                return Err(Error::new("Reorder level cannot be negative"));
+            }
+            set.insert("reorder_level", level);
+        }
+        if let Some(location) = input.location {
+            set.insert("location", location);
+        }
+        if let Some(cost) = input.unit_cost {
+            set.insert("unit_cost", cost);
+        }
+
+        db.collection::<StockItem>("stock_items")
+            .update_one(doc! { "_id": item.id }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update stock item: {}", e)))?;
+
+        let updated = find_stock_item(db, &input.item_id).await?;
+        record_change(
+            ctx,
+            "StockItem",
+            Some(input.item_id),
+            Some(&updated.school_id),
+            Some(&item),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Remove a consumable (requires ManageInventory)
+    async fn delete_stock_item(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let item = find_stock_item(db, &id).await?;
+        let member =
+            require_inventory(ctx, db, &item.school_id, Permission::ManageInventory).await?;
+
+        let oid = item.id.ok_or_else(|| Error::new("Stock item not found"))?;
+        soft_delete_by_id(
+            &db.collection::<StockItem>("stock_items"),
+            oid,
+            &member.user_id,
+        )
+        .await?;
+
+        record_change(
+            ctx,
+            "StockItem",
+            Some(id),
+            Some(&item.school_id),
+            Some(&item),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted consumable from the trash (requires ManageInventory)
+    async fn restore_stock_item(&self, ctx: &Context<'_>, id: String) -> Result<StockItem> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<StockItem>("stock_items");
+        let item = find_deleted_by_id(&collection, &id, "stock item").await?;
+        require_inventory(ctx, db, &item.school_id, Permission::ManageInventory).await?;
+
+        let oid = item.id.ok_or_else(|| Error::new("Stock item not found"))?;
+        restore_by_id(&collection, oid).await?;
+
+        let restored = find_stock_item(db, &id).await?;
+        record_change(
+            ctx,
+            "StockItem",
+            Some(id),
+            Some(&item.school_id),
+            Some(&item),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Receive, issue or correct stock (requires ManageInventory). The
+    /// returned item's `isLowStock` flags when it needs reordering.
+    async fn adjust_stock(&self, ctx: &Context<'_>, input: AdjustStockInput) -> Result<StockItem> {
+        let db = ctx.data::<Database>()?;
+        let item = find_stock_item(db, &input.item_id).await?;
+        let member =
+            require_inventory(ctx, db, &item.school_id, Permission::ManageInventory).await?;
+
+        let change = match input.reason {
+            StockReason::Received if input.change > 0 => input.change,
+            StockReason::Issued if input.change != 0 => -input.change.abs(),
+            StockReason::Adjusted if input.change != 0 => input.change,
+            StockReason::StockTake => {
+                return Err(Error::new(
+                    "Stock-take corrections are applied when a stock-take is completed",
+                ))
+            }
+            _ => {
+                return Err(Error::new(
+                    "Change must be non-zero (positive when received)",
+                ))
+            }
+        };
+        if item.quantity + change < 0 {
+            return Err(Error::new(format!(
+                "Only {} {} in stock",
+                item.quantity, item.unit
+            )));
+        }
+
+        // Guard against a concurrent issue taking the level below zero
+        let result = db
+            .collection::<StockItem>("stock_items")
+            .update_one(
+                doc! { "_id": item.id, "quantity": { "$gte": -change } },
+                doc! {
+                    "$inc": { "quantity": change },
+                    "$set": {
+                        "audit.updated_at": DateTime::now(),
+                        "audit.updated_by": &member.user_id,
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to adjust stock: {}", e)))?;
+        if result.modified_count == 0 {
+            return Err(Error::new("Stock level changed; please try again"));
+        }
+
+        let updated = find_stock_item(db, &input.item_id).await?;
+        log_stock(
+            db,
+            &updated,
+            change,
+            input.reason,
+            input.issued_to,
+            input.notes,
+            &member.user_id,
+        )
+        .await?;
+
+        Ok(updated)
+    }
+
+    /// Report a problem with an asset (requires ViewInventory)
+    async fn create_maintenance_request(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateMaintenanceRequestInput,
+    ) -> Result<MaintenanceRequest> {
+        let db = ctx.data::<Database>()?;
+        let asset = find_asset(db, &input.asset_id).await?;
+        let member =
+            require_inventory(ctx, db, &asset.school_id, Permission::ViewInventory).await?;
+
+        let title = input.title.trim().to_string();
+        if title.is_empty() {
+            return Err(Error::new("Title is required"));
+        }
+        if asset.status == AssetStatus::Retired {
+            return Err(Error::new("This asset is retired"));
+        }
+
+        let now = DateTime::now();
+        let mut request = MaintenanceRequest {
+            id: None,
+            school_id: asset.school_id.clone(),
+            asset_id: input.asset_id,
+            title,
+            description: input.description,
+            priority: input.priority.unwrap_or(MaintenancePriority::Medium),
+            status: MaintenanceStatus::Open,
+            assigned_to: None,
+            cost: None,
+            resolution: None,
+            previous_asset_status: None,
+            reported_by: member.user_id.clone(),
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+        };
+        let result = db
+            .collection::<MaintenanceRequest>("maintenance_requests")
+            .insert_one(&request, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create maintenance request: {}", e)))?;
+        request.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "MaintenanceRequest",
+            request.id.map(|id| id.to_hex()),
+            Some(&request.school_id),
+            None,
+            Some(&request),
+        );
+
+        Ok(request)
+    }
+
+    /// Move a maintenance request through its workflow or record the repair
+    /// (requires ManageInventory). Starting work takes the asset out of use;
+    /// completing or cancelling puts it back.
+    async fn update_maintenance_request(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateMaintenanceRequestInput,
+    ) -> Result<MaintenanceRequest> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<MaintenanceRequest>("maintenance_requests");
+        let oid = ObjectId::parse_str(&input.request_id)
+            .map_err(|_| Error::new("Invalid maintenance request ID"))?;
+        let request = collection
+            .find_one(doc! { "_id": oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Maintenance request not found"))?;
+        let member =
+            require_inventory(ctx, db, &request.school_id, Permission::ManageInventory).await?;
+
+        let now = DateTime::now();
+        let mut set = doc! { "updated_at": now };
+        if let Some(priority) = input.priority {
+            set.insert("priority", to_bson(&priority)?);
+        }
+        if let Some(assigned_to) = input.assigned_to {
+            set.insert("assigned_to", assigned_to);
+        }
+        if let Some(cost) = input.cost {
+            if cost < 0.0 {
+                return Err(Error::new("Cost cannot be negative"));
+            }
+            set.insert("cost", cost);
+        }
+        if let Some(resolution) = input.resolution {
+            set.insert("resolution", resolution);
+        }
+
+        if let Some(status) = input.status.filter(|s| *s != request.status) {
+            if !request.status.can_move_to(status) {
+                return Err(Error::new(format!(
+                    "A {:?} request cannot move to {:?}",
+                    request.status, status
+                )));
+            }
+            set.insert("status", to_bson(&status)?);
+
+            let asset = find_asset(db, &request.asset_id).await?;
+            match status {
+                MaintenanceStatus::InProgress => {
+                    set.insert("previous_asset_status", to_bson(&asset.status)?);
+                    set_asset_status(db, &asset, AssetStatus::InMaintenance, &member.user_id)
+                        .await?;
+                }
+                MaintenanceStatus::Completed | MaintenanceStatus::Cancelled => {
+                    if status == MaintenanceStatus::Completed {
+                        set.insert("completed_at", now);
+                    }
+                    if asset.status == AssetStatus::InMaintenance {
+                        let restored = request
+                            .previous_asset_status
+                            .unwrap_or(AssetStatus::Available);
+                        set_asset_status(db, &asset, restored, &member.user_id).await?;
+                    }
+                }
+                MaintenanceStatus::Open => {}
+            }
+        }
+
+        collection
+            .update_one(doc! { "_id": oid }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update maintenance request: {}", e)))?;
+
+        let updated = collection
+            .find_one(doc! { "_id": oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Maintenance request not found"))?;
+        record_change(
+            ctx,
+            "MaintenanceRequest",
+            Some(input.request_id),
+            Some(&updated.school_id),
+            Some(&request),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Start a physical count of a school's (or branch's) assets and stock
+    /// (requires ManageInventory)
+    async fn start_stock_take(
+        &self,
+        ctx: &Context<'_>,
+        input: StartStockTakeInput,
+    ) -> Result<StockTake> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_inventory(ctx, db, &input.school_id, Permission::ManageInventory).await?;
+
+        if let Some(branch_id) = input.branch_id.as_deref() {
+            check_branch(db, &input.school_id, branch_id).await?;
+        }
+        let collection = db.collection::<StockTake>("stock_takes");
+        let open = collection
+            .find_one(
+                doc! { "school_id": &input.school_id, "status": "InProgress" },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        if open.is_some() {
+            return Err(Error::new("A stock-take is already in progress"));
+        }
+
+        let mut filter = scope_filter(&input.school_id, input.branch_id.clone());
+        filter.insert("status", doc! { "$ne": "Retired" });
+        let expected_asset_ids = collect(
+            &db.collection::<Asset>("assets"),
+            filter,
+            FindOptions::default(),
+        )
+        .await?
+        .into_iter()
+        .filter_map(|a| a.id.map(|id| id.to_hex()))
+        .collect();
+
+        let mut stock_take = StockTake {
+            id: None,
+            school_id: input.school_id,
+            branch_id: input.branch_id,
+            year: input.year.unwrap_or_else(|| Utc::now().year()),
+            status: StockTakeStatus::InProgress,
+            expected_asset_ids,
+            asset_counts: Vec::new(),
+            item_counts: Vec::new(),
+            started_by: member.user_id.clone(),
+            started_at: DateTime::now(),
+            completed_at: None,
+        };
+        let result = collection
+            .insert_one(&stock_take, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to start stock-take: {}", e)))?;
+        stock_take.id = result.inserted_id.as_object_id();
+
+        Ok(stock_take)
+    }
+
+    /// Record an asset as seen, by the tag scanned from its label
+    /// (requires ManageInventory)
+    async fn count_asset(&self, ctx: &Context<'_>, input: CountAssetInput) -> Result<StockTake> {
+        let db = ctx.data::<Database>()?;
+        let stock_take = open_stock_take(db, &input.stock_take_id).await?;
+        let member =
+            require_inventory(ctx, db, &stock_take.school_id, Permission::ManageInventory).await?;
+
+        let asset = find_asset_by_tag(db, &stock_take.school_id, &input.asset_tag)
+            .await?
+            .ok_or_else(|| Error::new("No asset has this tag"))?;
+        let asset_id = asset.id.map(|id| id.to_hex()).unwrap_or_default();
+        if !stock_take.expected_asset_ids.contains(&asset_id) {
+            return Err(Error::new(
+                "This asset isn't part of the stock-take (another branch, or added since it started)",
+            ));
+        }
+
+        let count = AssetCount {
+            asset_id: asset_id.clone(),
+            location_found: input.location_found,
+            room_found: input.room_found,
+            counted_by: member.user_id.clone(),
+        };
+        // Re-scanning an asset replaces its earlier count
+        let collection = db.collection::<StockTake>("stock_takes");
+        collection
+            .update_one(
+                doc! { "_id": stock_take.id },
+                doc! { "$pull": { "asset_counts": { "asset_id": &asset_id } } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        collection
+            .update_one(
+                doc! { "_id": stock_take.id },
+                doc! { "$push": { "asset_counts": to_bson(&count)? } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to record count: {}", e)))?;
+
+        find_stock_take(db, &input.stock_take_id).await
+    }
+
+    /// Record the counted quantity of a consumable (requires ManageInventory)
+    async fn count_stock_item(
+        &self,
+        ctx: &Context<'_>,
+        input: CountStockItemInput,
+    ) -> Result<StockTake> {
+        let db = ctx.data::<Database>()?;
+        let stock_take = open_stock_take(db, &input.stock_take_id).await?;
+        let member =
+            require_inventory(ctx, db, &stock_take.school_id, Permission::ManageInventory).await?;
+
+        let item = find_stock_item(db, &input.item_id).await?;
+        if item.school_id != stock_take.school_id
+            || (stock_take.branch_id.is_some() && item.branch_id != stock_take.branch_id)
+        {
+            return Err(Error::new("This item isn't part of the stock-take"));
+        }
+        if input.counted < 0 {
+            return Err(Error::new("Counted quantity cannot be negative"));
+        }
+
+        let count = ItemCount {
+            item_id: input.item_id.clone(),
+            expected: item.quantity,
+            counted: input.counted,
+            counted_by: member.user_id.clone(),
+        };
+        let collection = db.collection::<StockTake>("stock_takes");
+        collection
+            .update_one(
+                doc! { "_id": stock_take.id },
+                doc! { "$pull": { "item_counts": { "item_id": &input.item_id } } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        collection
+            .update_one(
+                doc! { "_id": stock_take.id },
+                doc! { "$push": { "item_counts": to_bson(&count)? } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to record count: {}", e)))?;
+
+        find_stock_take(db, &input.stock_take_id).await
+    }
+
+    /// Close a stock-take and return its reconciliation report
+    /// (requires ManageInventory). Optionally marks uncounted assets Lost and
+    /// sets stock levels to the counted quantities.
+    async fn complete_stock_take(
+        &self,
+        ctx: &Context<'_>,
+        input: CompleteStockTakeInput,
+    ) -> Result<StockTakeReportType> {
+        let db = ctx.data::<Database>()?;
+        let stock_take = open_stock_take(db, &input.stock_take_id).await?;
+        let member =
+            require_inventory(ctx, db, &stock_take.school_id, Permission::ManageInventory).await?;
+
+        if input.mark_missing_lost.unwrap_or(false) {
+            for asset_id in &stock_take.expected_asset_ids {
+                if stock_take
+                    .asset_counts
+                    .iter()
+                    .any(|c| &c.asset_id == asset_id)
+                {
+                    continue;
+                }
+                let Ok(asset) = find_asset(db, asset_id).await else {
+                    continue;
+                };
+                if asset.status != AssetStatus::Lost {
+                    set_asset_status(db, &asset, AssetStatus::Lost, &member.user_id).await?;
+                }
+            }
+        }
+
+        if input.apply_stock_counts.unwrap_or(false) {
+            for count in &stock_take.item_counts {
+                let Ok(item) = find_stock_item(db, &count.item_id).await else {
+                    continue;
+                };
+                // Movements since the count are kept: apply the difference found
+                let change = count.counted - count.expected;
+                if change == 0 {
+                    continue;
+                }
+                db.collection::<StockItem>("stock_items")
+                    .update_one(
+                        doc! { "_id": item.id },
+                        doc! { "$inc": { "quantity": change } },
+                        None,
+                    )
+                    .await
+                    .map_err(|e| Error::new(format!("Failed to apply count: {}", e)))?;
+                let updated = find_stock_item(db, &count.item_id).await?;
+                log_stock(
+                    db,
+                    &updated,
+                    change,
+                    StockReason::StockTake,
+                    None,
+                    Some(format!("Stock-take {}", stock_take.year)),
+                    &member.user_id,
+                )
+                .await?;
+            }
+        }
+
+        db.collection::<StockTake>("stock_takes")
+            .update_one(
+                doc! { "_id": stock_take.id },
+                doc! { "$set": { "status": "Completed", "completed_at": DateTime::now() } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to complete stock-take: {}", e)))?;
+
+        let completed = find_stock_take(db, &input.stock_take_id).await?;
+        record_change(
+            ctx,
+            "StockTake",
+            Some(input.stock_take_id),
+            Some(&completed.school_id),
+            Some(&stock_take),
+            Some(&completed),
+        );
+
+        build_stock_take_report(db, completed).await
+    }
+}
+
+/// Midnight UTC at the start of a date (YYYY-MM-DD)
+fn parse_date(date: &str) -> Result<DateTime> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| Error::new("Invalid date. Use YYYY-MM-DD"))?;
+    Ok(DateTime::from_millis(
+        Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+            .timestamp_millis(),
+    ))
+}
+
+fn check_value(purchase_cost: f64, salvage_value: f64, useful_life: Option<i32>) -> Result<()> {
+    if purchase_cost < 0.0 || salvage_value < 0.0 {
+        return Err(Error::new("Cost and salvage value cannot be negative"));
+    }
+    if salvage_value > purchase_cost {
+        return Err(Error::new("Salvage value cannot exceed the purchase cost"));
+    }
+    if useful_life.is_some_and(|years| years < 1) {
+        return Err(Error::new("Useful life must be at least 1 year"));
+    }
+    Ok(())
+}
+
+/// Next asset tag for the school: AST-000001, AST-000002, ...
+async fn next_asset_tag(db: &Database, school_id: &str) -> Result<String> {
+    let last = db
+        .collection::<Asset>("assets")
+        .find_one(
+            doc! { "school_id": school_id, "asset_tag": { "$regex": "^AST-\\d{6}$" } },
+            FindOneOptions::builder()
+                .sort(doc! { "asset_tag": -1 })
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    let next = last
+        .and_then(|a| a.asset_tag[4..].parse::<u32>().ok())
+        .unwrap_or(0)
+        + 1;
+    Ok(format!("AST-{:06}", next))
+}
+
+async fn check_branch(db: &Database, school_id: &str, branch_id: &str) -> Result<()> {
+    let branch_oid = ObjectId::parse_str(branch_id).map_err(|_| Error::new("Invalid branch ID"))?;
+    let school_oid = ObjectId::parse_str(school_id).map_err(|_| Error::new("Invalid school ID"))?;
+    db.collection::<Branch>("branches")
+        .find_one(
+            doc! {
+                "_id": branch_oid,
+                "school_id": school_oid,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Branch not found"))?;
+    Ok(())
+}
+
+async fn check_staff(db: &Database, staff_id: &str) -> Result<()> {
+    let oid = ObjectId::parse_str(staff_id).map_err(|_| Error::new("Invalid staff ID"))?;
+    db.collection::<Staff>("staff")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Staff record not found"))?;
+    Ok(())
+}
+
+async fn ensure_sku_unused(
+    db: &Database,
+    school_id: &str,
+    sku: &str,
+    except: Option<ObjectId>,
+) -> Result<()> {
+    let mut filter = doc! {
+        "school_id": school_id,
+        "sku": sku,
+        "soft_delete.is_deleted": { "$ne": true }
+    };
+    if let Some(id) = except {
+        filter.insert("_id", doc! { "$ne": id });
+    }
+    let existing = db
+        .collection::<StockItem>("stock_items")
+        .find_one(filter, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    if existing.is_some() {
+        return Err(Error::new("Another item already uses this SKU"));
+    }
+    Ok(())
+}
+
+async fn open_stock_take(db: &Database, stock_take_id: &str) -> Result<StockTake> {
+    let stock_take = find_stock_take(db, stock_take_id).await?;
+    if stock_take.status != StockTakeStatus::InProgress {
+        return Err(Error::new("This stock-take is already completed"));
+    }
+    Ok(stock_take)
+}
+
+async fn set_asset_status(
+    db: &Database,
+    asset: &Asset,
+    status: AssetStatus,
+    user_id: &str,
+) -> Result<()> {
+    db.collection::<Asset>("assets")
+        .update_one(
+            doc! { "_id": asset.id },
+            doc! {
+                "$set": {
+                    "status": to_bson(&status)?,
+                    "audit.updated_at": DateTime::now(),
+                    "audit.updated_by": user_id,
+                }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to update asset: {}", e)))?;
+
+    let mut updated = asset.clone();
+    updated.status = status;
+    log_movement(
+        db,
+        &updated,
+        AssetAction::StatusChanged,
+        None,
+        None,
+        user_id,
+    )
+    .await
+}
+
+/// Add an entry to an asset's history, recording where it is after the change
+async fn log_movement(
+    db: &Database,
+    asset: &Asset,
+    action: AssetAction,
+    expected_return: Option<DateTime>,
+    notes: Option<String>,
+    user_id: &str,
+) -> Result<()> {
+    let movement = AssetMovement {
+        id: None,
+        school_id: asset.school_id.clone(),
+        asset_id: asset.id.map(|id| id.to_hex()).unwrap_or_default(),
+        action,
+        staff_id: asset.assigned_staff_id.clone(),
+        location: asset.location.clone(),
+        room: asset.room.clone(),
+        status: asset.status,
+        expected_return,
+        notes,
+        recorded_by: user_id.to_string(),
+        recorded_at: DateTime::now(),
+    };
+    db.collection::<AssetMovement>("asset_movements")
+        .insert_one(&movement, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to record asset history: {}", e)))?;
+    Ok(())
+}
+
+/// Record a stock level change against the item's new quantity
+async fn log_stock(
+    db: &Database,
+    item: &StockItem,
+    change: i32,
+    reason: StockReason,
+    issued_to: Option<String>,
+    notes: Option<String>,
+    user_id: &str,
+) -> Result<()> {
+    let movement = StockMovement {
+        id: None,
+        school_id: item.school_id.clone(),
+        item_id: item.id.map(|id| id.to_hex()).unwrap_or_default(),
+        change,
+        quantity_after: item.quantity,
+        reason,
+        issued_to,
+        notes,
+        recorded_by: user_id.to_string(),
+        recorded_at: DateTime::now(),
+    };
+    db.collection::<StockMovement>("stock_movements")
+        .insert_one(&movement, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to record stock movement: {}", e)))?;
+    Ok(())
+}
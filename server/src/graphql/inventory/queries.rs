@@ -0,0 +1,426 @@
+use super::types::{StockDiscrepancyType, StockTakeReportType};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::library::queries::{collect, regex_escape, to_bson};
+use crate::models::inventory::{
+    Asset, AssetMovement, AssetStatus, MaintenanceRequest, MaintenanceStatus, StockItem,
+    StockMovement, StockTake,
+};
+use crate::models::member::{Member, Permission};
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::find_deleted;
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct InventoryQuery;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Inventory)")]
+impl InventoryQuery {
+    /// A school's assets, optionally filtered; `search` matches name, tag or
+    /// serial number (requires ViewInventory)
+    async fn assets(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        branch_id: Option<String>,
+        category: Option<String>,
+        status: Option<AssetStatus>,
+        search: Option<String>,
+    ) -> Result<Vec<Asset>> {
+        let db = ctx.data::<Database>()?;
+        require_inventory(ctx, db, &school_id, Permission::ViewInventory).await?;
+
+        let mut filter = scope_filter(&school_id, branch_id);
+        if let Some(category) = category {
+            filter.insert("category", category);
+        }
+        if let Some(status) = status {
+            filter.insert("status", to_bson(&status)?);
+        }
+        if let Some(search) = search.filter(|s| !s.trim().is_empty()) {
+            let pattern = regex_escape(search.trim());
+            filter.insert(
+                "$or",
+                vec![
+                    doc! { "name": { "$regex": &pattern, "$options": "i" } },
+                    doc! { "asset_tag": { "$regex": &pattern, "$options": "i" } },
+                    doc! { "serial_number": { "$regex": &pattern, "$options": "i" } },
+                ],
+            );
+        }
+
+        collect(
+            &db.collection::<Asset>("assets"),
+            filter,
+            FindOptions::builder().sort(doc! { "asset_tag": 1 }).build(),
+        )
+        .await
+    }
+
+    /// A single asset (requires ViewInventory)
+    async fn asset(&self, ctx: &Context<'_>, id: String) -> Result<Asset> {
+        let db = ctx.data::<Database>()?;
+        let asset = find_asset(db, &id).await?;
+        require_inventory(ctx, db, &asset.school_id, Permission::ViewInventory).await?;
+        Ok(asset)
+    }
+
+    /// Look up an asset by the tag scanned from its label (requires ViewInventory)
+    async fn asset_by_tag(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        asset_tag: String,
+    ) -> Result<Option<Asset>> {
+        let db = ctx.data::<Database>()?;
+        require_inventory(ctx, db, &school_id, Permission::ViewInventory).await?;
+        find_asset_by_tag(db, &school_id, &asset_tag).await
+    }
+
+    /// An asset's check-in/check-out and movement history, newest first
+    /// (requires ViewInventory)
+    async fn asset_history(
+        &self,
+        ctx: &Context<'_>,
+        asset_id: String,
+    ) -> Result<Vec<AssetMovement>> {
+        let db = ctx.data::<Database>()?;
+        let asset = find_asset(db, &asset_id).await?;
+        require_inventory(ctx, db, &asset.school_id, Permission::ViewInventory).await?;
+
+        collect(
+            &db.collection::<AssetMovement>("asset_movements"),
+            doc! { "asset_id": &asset_id },
+            FindOptions::builder()
+                .sort(doc! { "recorded_at": -1 })
+                .build(),
+        )
+        .await
+    }
+
+    /// A school's consumables (requires ViewInventory)
+    async fn stock_items(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        branch_id: Option<String>,
+        category: Option<String>,
+    ) -> Result<Vec<StockItem>> {
+        let db = ctx.data::<Database>()?;
+        require_inventory(ctx, db, &school_id, Permission::ViewInventory).await?;
+
+        let mut filter = scope_filter(&school_id, branch_id);
+        if let Some(category) = category {
+            filter.insert("category", category);
+        }
+
+        collect(
+            &db.collection::<StockItem>("stock_items"),
+            filter,
+            FindOptions::builder().sort(doc! { "name": 1 }).build(),
+        )
+        .await
+    }
+
+    /// A school's deleted assets (the trash), most recently deleted first
+    /// (requires ManageInventory)
+    async fn deleted_assets(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Asset>> {
+        let db = ctx.data::<Database>()?;
+        require_inventory(ctx, db, &school_id, Permission::ManageInventory).await?;
+
+        find_deleted(
+            &db.collection::<Asset>("assets"),
+            doc! { "school_id": school_id },
+        )
+        .await
+    }
+
+    /// A school's deleted consumables (the trash), most recently deleted first
+    /// (requires ManageInventory)
+    async fn deleted_stock_items(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<StockItem>> {
+        let db = ctx.data::<Database>()?;
+        require_inventory(ctx, db, &school_id, Permission::ManageInventory).await?;
+
+        find_deleted(
+            &db.collection::<StockItem>("stock_items"),
+            doc! { "school_id": school_id },
+        )
+        .await
+    }
+
+    /// Consumables at or below their reorder level (requires ViewInventory)
+    async fn low_stock_items(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        branch_id: Option<String>,
+    ) -> Result<Vec<StockItem>> {
+        let db = ctx.data::<Database>()?;
+        require_inventory(ctx, db, &school_id, Permission::ViewInventory).await?;
+
+        let mut filter = scope_filter(&school_id, branch_id);
+        filter.insert("$expr", doc! { "$lte": ["$quantity", "$reorder_level"] });
+
+        collect(
+            &db.collection::<StockItem>("stock_items"),
+            filter,
+            FindOptions::builder().sort(doc! { "name": 1 }).build(),
+        )
+        .await
+    }
+
+    /// Stock level changes for a consumable, newest first (requires ViewInventory)
+    async fn stock_movements(
+        &self,
+        ctx: &Context<'_>,
+        item_id: String,
+        limit: Option<i64>,
+    ) -> Result<Vec<StockMovement>> {
+        let db = ctx.data::<Database>()?;
+        let item = find_stock_item(db, &item_id).await?;
+        require_inventory(ctx, db, &item.school_id, Permission::ViewInventory).await?;
+
+        collect(
+            &db.collection::<StockMovement>("stock_movements"),
+            doc! { "item_id": &item_id },
+            FindOptions::builder()
+                .sort(doc! { "recorded_at": -1 })
+                .limit(limit.unwrap_or(100))
+                .build(),
+        )
+        .await
+    }
+
+    /// Maintenance requests, newest first (requires ViewInventory)
+    async fn maintenance_requests(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        status: Option<MaintenanceStatus>,
+        asset_id: Option<String>,
+    ) -> Result<Vec<MaintenanceRequest>> {
+        let db = ctx.data::<Database>()?;
+        require_inventory(ctx, db, &school_id, Permission::ViewInventory).await?;
+
+        let mut filter = doc! { "school_id": &school_id };
+        if let Some(status) = status {
+            filter.insert("status", to_bson(&status)?);
+        }
+        if let Some(asset_id) = asset_id {
+            filter.insert("asset_id", asset_id);
+        }
+
+        collect(
+            &db.collection::<MaintenanceRequest>("maintenance_requests"),
+            filter,
+            FindOptions::builder()
+                .sort(doc! { "created_at": -1 })
+                .build(),
+        )
+        .await
+    }
+
+    /// A school's stock-takes, newest first (requires ViewInventory)
+    async fn stock_takes(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<StockTake>> {
+        let db = ctx.data::<Database>()?;
+        require_inventory(ctx, db, &school_id, Permission::ViewInventory).await?;
+
+        collect(
+            &db.collection::<StockTake>("stock_takes"),
+            doc! { "school_id": &school_id },
+            FindOptions::builder()
+                .sort(doc! { "started_at": -1 })
+                .build(),
+        )
+        .await
+    }
+
+    /// Reconcile a stock-take's counts against the asset and stock records
+    /// (requires ViewInventory)
+    async fn stock_take_report(
+        &self,
+        ctx: &Context<'_>,
+        stock_take_id: String,
+    ) -> Result<StockTakeReportType> {
+        let db = ctx.data::<Database>()?;
+        let stock_take = find_stock_take(db, &stock_take_id).await?;
+        require_inventory(ctx, db, &stock_take.school_id, Permission::ViewInventory).await?;
+
+        build_stock_take_report(db, stock_take).await
+    }
+}
+
+/// Require the Inventory module for the school and an inventory permission
+pub(crate) async fn require_inventory(
+    ctx: &Context<'_>,
+    db: &Database,
+    school_id: &str,
+    permission: Permission,
+) -> Result<Member> {
+    ensure_feature(db, school_id, SchoolFeature::Inventory).await?;
+    get_graphql_context(ctx)?
+        .require_member_permission(db, school_id, permission)
+        .await
+}
+
+/// Records in a school, or one of its branches, that haven't been deleted
+pub(crate) fn scope_filter(school_id: &str, branch_id: Option<String>) -> Document {
+    let mut filter = doc! {
+        "school_id": school_id,
+        "soft_delete.is_deleted": { "$ne": true }
+    };
+    if let Some(branch_id) = branch_id {
+        filter.insert("branch_id", branch_id);
+    }
+    filter
+}
+
+pub(crate) async fn find_asset(db: &Database, asset_id: &str) -> Result<Asset> {
+    let oid = ObjectId::parse_str(asset_id).map_err(|_| Error::new("Invalid asset ID"))?;
+    db.collection::<Asset>("assets")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Asset not found"))
+}
+
+pub(crate) async fn find_asset_by_tag(
+    db: &Database,
+    school_id: &str,
+    asset_tag: &str,
+) -> Result<Option<Asset>> {
+    db.collection::<Asset>("assets")
+        .find_one(
+            doc! {
+                "school_id": school_id,
+                "asset_tag": asset_tag.trim().to_uppercase(),
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))
+}
+
+pub(crate) async fn find_stock_item(db: &Database, item_id: &str) -> Result<StockItem> {
+    let oid = ObjectId::parse_str(item_id).map_err(|_| Error::new("Invalid item ID"))?;
+    db.collection::<StockItem>("stock_items")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Stock item not found"))
+}
+
+pub(crate) async fn find_stock_take(db: &Database, stock_take_id: &str) -> Result<StockTake> {
+    let oid =
+        ObjectId::parse_str(stock_take_id).map_err(|_| Error::new("Invalid stock-take ID"))?;
+    db.collection::<StockTake>("stock_takes")
+        .find_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Stock-take not found"))
+}
+
+/// Compare what was counted with what the records expected
+pub(crate) async fn build_stock_take_report(
+    db: &Database,
+    stock_take: StockTake,
+) -> Result<StockTakeReportType> {
+    let asset_oids: Vec<ObjectId> = stock_take
+        .expected_asset_ids
+        .iter()
+        .filter_map(|id| ObjectId::parse_str(id).ok())
+        .collect();
+    let assets = collect(
+        &db.collection::<Asset>("assets"),
+        doc! { "_id": { "$in": asset_oids } },
+        FindOptions::builder().sort(doc! { "asset_tag": 1 }).build(),
+    )
+    .await?;
+
+    let at = stock_take.completed_at.unwrap_or_else(DateTime::now);
+    let mut total_book_value = 0.0;
+    let mut missing_book_value = 0.0;
+    let mut missing_assets = Vec::new();
+    let mut relocated_assets = Vec::new();
+    for asset in assets {
+        let asset_id = asset.id.map(|id| id.to_hex()).unwrap_or_default();
+        let book_value = asset.book_value_at(at);
+        total_book_value += book_value;
+        match stock_take
+            .asset_counts
+            .iter()
+            .find(|c| c.asset_id == asset_id)
+        {
+            None => {
+                missing_book_value += book_value;
+                missing_assets.push(asset);
+            }
+            Some(count) => {
+                let moved = |found: &Option<String>, recorded: &Option<String>| {
+                    found.is_some() && found != recorded
+                };
+                if moved(&count.location_found, &asset.location)
+                    || moved(&count.room_found, &asset.room)
+                {
+                    relocated_assets.push(asset);
+                }
+            }
+        }
+    }
+
+    let items = collect(
+        &db.collection::<StockItem>("stock_items"),
+        scope_filter(&stock_take.school_id, stock_take.branch_id.clone()),
+        FindOptions::builder().sort(doc! { "name": 1 }).build(),
+    )
+    .await?;
+    let mut stock_discrepancies = Vec::new();
+    let mut uncounted_items = Vec::new();
+    for item in items {
+        let item_id = item.id.map(|id| id.to_hex()).unwrap_or_default();
+        match stock_take.item_counts.iter().find(|c| c.item_id == item_id) {
+            None => uncounted_items.push(item),
+            Some(count) if count.counted != count.expected => {
+                let difference = count.counted - count.expected;
+                stock_discrepancies.push(StockDiscrepancyType {
+                    value_difference: difference as f64 * item.unit_cost.unwrap_or(0.0),
+                    item,
+                    expected: count.expected,
+                    counted: count.counted,
+                    difference,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(StockTakeReportType {
+        assets_expected: stock_take.expected_asset_ids.len() as i32,
+        assets_found: stock_take.asset_counts.len() as i32,
+        missing_assets,
+        relocated_assets,
+        total_book_value: (total_book_value * 100.0).round() / 100.0,
+        missing_book_value: (missing_book_value * 100.0).round() / 100.0,
+        items_counted: stock_take.item_counts.len() as i32,
+        stock_discrepancies,
+        uncounted_items,
+        stock_take,
+    })
+}
@@ -0,0 +1,34 @@
+use crate::models::inventory::{Asset, StockItem, StockTake};
+use async_graphql::SimpleObject;
+
+/// A consumable whose count differs from the records
+#[derive(SimpleObject)]
+pub struct StockDiscrepancyType {
+    pub item: StockItem,
+    pub expected: i32,
+    pub counted: i32,
+    /// Counted minus expected
+    pub difference: i32,
+    /// Difference valued at the item's unit cost
+    pub value_difference: f64,
+}
+
+/// Reconciliation of a stock-take against the records
+#[derive(SimpleObject)]
+pub struct StockTakeReportType {
+    pub stock_take: StockTake,
+    pub assets_expected: i32,
+    pub assets_found: i32,
+    /// Assets on record that weren't counted
+    pub missing_assets: Vec<Asset>,
+    /// Counted assets found somewhere other than their recorded location
+    pub relocated_assets: Vec<Asset>,
+    /// Book value of all expected assets
+    pub total_book_value: f64,
+    /// Book value of the missing assets
+    pub missing_book_value: f64,
+    pub items_counted: i32,
+    pub stock_discrepancies: Vec<StockDiscrepancyType>,
+    /// Items on record that weren't counted
+    pub uncounted_items: Vec<StockItem>,
+}
@@ -0,0 +1,32 @@
+use async_graphql::InputObject;
+
+use crate::models::member::SchoolRole;
+
+/// Input for inviting someone to a school.
+/// Provide an email and/or phone for a personal single-use invitation,
+/// or neither for a shareable join code.
+#[derive(InputObject)]
+pub struct CreateInvitationInput {
+    pub school_id: String,
+    /// Role the invitee will receive
+    pub role: SchoolRole,
+    /// Optional branch ID for branch-level assignment
+    pub branch_id: Option<String>,
+    /// Optional custom title (e.g., "Head of Math Dept")
+    pub title: Option<String>,
+    /// Invitee email
+    pub email: Option<String>,
+    /// Invitee phone number
+    pub phone: Option<String>,
+    /// Days until the invitation expires (default 7)
+    pub expires_in_days: Option<i32>,
+    /// Maximum acceptances for a shareable code (default unlimited)
+    pub max_uses: Option<i32>,
+}
+
+#[derive(InputObject)]
+pub struct ResendInvitationInput {
+    pub invitation_id: String,
+    /// Extend the expiry by this many days from now (default 7)
+    pub expires_in_days: Option<i32>,
+}
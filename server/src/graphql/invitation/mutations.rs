@@ -0,0 +1,570 @@
+use super::inputs::{CreateInvitationInput, ResendInvitationInput};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::invitation::{Invitation, InvitationStatus};
+use crate::models::member::{Member, MemberStatus, SchoolRole};
+use crate::models::school::School;
+use crate::models::user::User;
+use crate::utils::codes::random_code;
+use crate::utils::mail::send_email;
+use crate::utils::permissions::{can_manage_branch, can_manage_members};
+use crate::utils::sms::send_sms;
+use crate::utils::subscription::ensure_staff_capacity;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+    Database,
+};
+
+/// Default invitation lifetime in days
+const DEFAULT_EXPIRY_DAYS: i32 = 7;
+/// Length of generated join codes
+const CODE_LENGTH: usize = 8;
+
+#[derive(Default)]
+pub struct InvitationMutation;
+
+#[Object]
+impl InvitationMutation {
+    /// Invite someone to a school by email, phone, or shareable join code
+    /// (Owner, Director, or DeputyDirector)
+    async fn create_invitation(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateInvitationInput,
+    ) -> Result<Invitation> {
+        let db = ctx.data::<Database>()?;
+        let auth_member =
+            require_invitation_manager(ctx, db, &input.school_id, input.branch_id.as_deref())
+                .await?;
+
+        // Only owners can hand out ownership
+        if input.role == SchoolRole::Owner && auth_member.role != SchoolRole::Owner {
+            return Err(Error::new("Only owners can invite other owners."));
+        }
+        ensure_staff_capacity(db, &input.school_id, input.role).await?;
+
+        let email = input
+            .email
+            .map(|e| e.trim().to_lowercase())
+            .filter(|e| !e.is_empty());
+        let phone = input
+            .phone
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty());
+
+        let collection = db.collection::<Invitation>("invitations");
+        let code = generate_unique_code(db).await?;
+        let expires_at = expiry_from_now(input.expires_in_days)?;
+
+        let mut invitation = Invitation::new(
+            input.school_id,
+            input.role,
+            code,
+            auth_member.user_id.clone(),
+            expires_at,
+        );
+        invitation.branch_id = input.branch_id;
+        invitation.title = input.title;
+        invitation.email = email;
+        invitation.phone = phone;
+        invitation.max_uses = if invitation.is_shareable() {
+            input.max_uses
+        } else {
+            // Personal invitations are single-use
+            Some(1)
+        };
+
+        let result = collection
+            .insert_one(&invitation, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create invitation: {}", e)))?;
+        invitation.id = result.inserted_id.as_object_id();
+
+        // If the invitee already has an account, add them as a pending member
+        // so they show up in the school's member list until they accept
+        if let Some(user) = find_invited_user(db, &invitation).await? {
+            let user_id = user.id.map(|oid| oid.to_hex()).unwrap_or_default();
+            let members_collection = db.collection::<Member>("members");
+
+            let existing = members_collection
+                .find_one(
+                    doc! { "user_id": &user_id, "school_id": &invitation.school_id },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+
+            if existing.is_none() {
+                let mut member =
+                    Member::new(user_id, invitation.school_id.clone(), invitation.role);
+                member.branch_id = invitation.branch_id.clone();
+                member.title = invitation.title.clone();
+                member.status = MemberStatus::Pending;
+                member.invited_by = Some(invitation.invited_by.clone());
+                member.invitation_code = Some(invitation.code.clone());
+
+                members_collection
+                    .insert_one(&member, None)
+                    .await
+                    .map_err(|e| Error::new(format!("Failed to add pending member: {}", e)))?;
+            }
+        }
+
+        if !invitation.is_shareable() {
+            deliver_invitation(db, &invitation, invitation.expires_at)
+                .await
+                .map_err(|e| {
+                    Error::new(format!(
+                        "The invitation was created but couldn't be sent ({}). Try resending it.",
+                        e
+                    ))
+                })?;
+
+            let now = DateTime::now();
+            collection
+                .update_one(
+                    doc! { "_id": invitation.id },
+                    doc! { "$set": { "last_sent_at": now }, "$inc": { "send_count": 1 } },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(format!("Failed to update invitation: {}", e)))?;
+            invitation.send_count += 1;
+            invitation.last_sent_at = Some(now);
+        }
+
+        Ok(invitation)
+    }
+
+    /// Revoke a pending invitation (Owner, Director, or DeputyDirector)
+    async fn revoke_invitation(
+        &self,
+        ctx: &Context<'_>,
+        invitation_id: String,
+    ) -> Result<Invitation> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<Invitation>("invitations");
+
+        let invitation = find_invitation(db, &invitation_id).await?;
+        let auth_member = require_invitation_manager(
+            ctx,
+            db,
+            &invitation.school_id,
+            invitation.branch_id.as_deref(),
+        )
+        .await?;
+
+        if invitation.status != InvitationStatus::Pending {
+            return Err(Error::new("Only pending invitations can be revoked"));
+        }
+
+        let now = DateTime::now();
+        collection
+            .update_one(
+                doc! { "_id": invitation.id },
+                doc! {
+                    "$set": {
+                        "status": "Revoked",
+                        "audit.updated_at": now,
+                        "audit.updated_by": &auth_member.user_id,
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to revoke invitation: {}", e)))?;
+
+        // Drop any pending memberships created for this invitation
+        db.collection::<Member>("members")
+            .update_many(
+                doc! {
+                    "school_id": &invitation.school_id,
+                    "invitation_code": &invitation.code,
+                    "status": "Pending"
+                },
+                doc! {
+                    "$set": {
+                        "status": "Inactive",
+                        "soft_delete.is_deleted": true,
+                        "soft_delete.deleted_at": now,
+                        "soft_delete.deleted_by": &auth_member.user_id,
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to remove pending members: {}", e)))?;
+
+        find_invitation(db, &invitation_id).await
+    }
+
+    /// Resend a pending invitation, extending its expiry
+    /// (Owner, Director, or DeputyDirector)
+    async fn resend_invitation(
+        &self,
+        ctx: &Context<'_>,
+        input: ResendInvitationInput,
+    ) -> Result<Invitation> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<Invitation>("invitations");
+
+        let invitation = find_invitation(db, &input.invitation_id).await?;
+        let auth_member = require_invitation_manager(
+            ctx,
+            db,
+            &invitation.school_id,
+            invitation.branch_id.as_deref(),
+        )
+        .await?;
+
+        if invitation.status != InvitationStatus::Pending {
+            return Err(Error::new("Only pending invitations can be resent"));
+        }
+        if invitation.is_shareable() {
+            return Err(Error::new(
+                "Shareable join codes have no recipient to resend to",
+            ));
+        }
+
+        let expires_at = expiry_from_now(input.expires_in_days)?;
+        deliver_invitation(db, &invitation, expires_at)
+            .await
+            .map_err(Error::new)?;
+
+        let now = DateTime::now();
+        collection
+            .update_one(
+                doc! { "_id": invitation.id },
+                doc! {
+                    "$set": {
+                        "expires_at": expires_at,
+                        "last_sent_at": now,
+                        "audit.updated_at": now,
+                        "audit.updated_by": &auth_member.user_id,
+                    },
+                    "$inc": { "send_count": 1 }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to resend invitation: {}", e)))?;
+
+        find_invitation(db, &input.invitation_id).await
+    }
+
+    /// Accept an invitation or join code as the logged-in user
+    async fn accept_invitation(&self, ctx: &Context<'_>, code: String) -> Result<Member> {
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let auth_user = graphql_ctx.require_auth()?;
+
+        let db = ctx.data::<Database>()?;
+        let invitations_collection = db.collection::<Invitation>("invitations");
+        let members_collection = db.collection::<Member>("members");
+
+        let code = code.trim().to_uppercase();
+        let invitation = invitations_collection
+            .find_one(doc! { "code": &code }, None)
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?
+            .ok_or_else(|| Error::new("Invalid invitation code"))?;
+
+        if !invitation.can_accept() {
+            return Err(Error::new("This invitation is no longer valid"));
+        }
+
+        // Personal invitations only work for the person they were sent to
+        if !invitation.is_shareable() {
+            let user_oid = ObjectId::parse_str(&auth_user.id)
+                .map_err(|_| Error::new("Invalid user ID in token"))?;
+            let user = db
+                .collection::<User>("users")
+                .find_one(doc! { "_id": user_oid }, None)
+                .await
+                .map_err(|e| Error::new(format!("Database error: {}", e)))?
+                .ok_or_else(|| Error::new("User not found"))?;
+            let email_matches = invitation
+                .email
+                .as_deref()
+                .is_some_and(|email| user.has_verified_email(email));
+            let phone_matches = invitation
+                .phone
+                .as_deref()
+                .is_some_and(|phone| user.has_verified_phone(phone));
+            if !email_matches && !phone_matches {
+                return Err(Error::new(
+                    "This invitation was sent to a different email address or phone number. Sign in with that one to accept it.",
+                ));
+            }
+        }
+
+        let existing = members_collection
+            .find_one(
+                doc! { "user_id": &auth_user.id, "school_id": &invitation.school_id },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+
+        if existing.as_ref().is_some_and(|member| {
+            member.status != MemberStatus::Pending && !member.soft_delete.is_deleted
+        }) {
+            return Err(Error::new("You are already a member of this school"));
+        }
+
+        // Pending memberships already count against the plan's staff limit
+        let needs_seat = match existing.as_ref() {
+            Some(member) => member.soft_delete.is_deleted,
+            None => true,
+        };
+        if needs_seat {
+            ensure_staff_capacity(db, &invitation.school_id, invitation.role).await?;
+        }
+
+        // Take one use of the invitation before joining, so concurrent accepts
+        // can't go past max_uses
+        let now = DateTime::now();
+        let mut filter = doc! {
+            "_id": invitation.id,
+            "status": "Pending",
+            "expires_at": { "$gt": now }
+        };
+        if let Some(max) = invitation.max_uses {
+            filter.insert("use_count", doc! { "$lt": max });
+        }
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let claimed = invitations_collection
+            .find_one_and_update(filter, doc! { "$inc": { "use_count": 1 } }, options)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update invitation: {}", e)))?
+            .ok_or_else(|| Error::new("This invitation is no longer valid"))?;
+
+        let member_id = match join_school(db, &invitation, existing, &auth_user.id, now).await {
+            Ok(member_id) => member_id,
+            Err(e) => {
+                // Give the use back so the invitation can still be accepted
+                let _ = invitations_collection
+                    .update_one(
+                        doc! { "_id": invitation.id },
+                        doc! { "$inc": { "use_count": -1 } },
+                        None,
+                    )
+                    .await;
+                return Err(e);
+            }
+        };
+
+        // Record the acceptance; close the invitation once it is used up
+        let mut set = doc! { "audit.updated_at": now };
+        if claimed.max_uses.is_some_and(|max| claimed.use_count >= max) {
+            set.insert(
+                "status",
+                mongodb::bson::to_bson(&InvitationStatus::Accepted).unwrap(),
+            );
+        }
+        invitations_collection
+            .update_one(
+                doc! { "_id": invitation.id },
+                doc! {
+                    "$set": set,
+                    "$addToSet": { "accepted_by": &auth_user.id }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update invitation: {}", e)))?;
+
+        members_collection
+            .find_one(doc! { "_id": member_id }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to retrieve member: {}", e)))?
+            .ok_or_else(|| Error::new("Member not found"))
+    }
+}
+
+/// Make the user an active member of the invitation's school, reviving a
+/// pending or removed membership if there is one
+async fn join_school(
+    db: &Database,
+    invitation: &Invitation,
+    existing: Option<Member>,
+    user_id: &str,
+    now: DateTime,
+) -> Result<ObjectId> {
+    let members_collection = db.collection::<Member>("members");
+    match existing {
+        // Turn a pending (or previously removed) membership into an active one
+        Some(member) => {
+            let member_id = member.id.ok_or_else(|| Error::new("Member not found"))?;
+            members_collection
+                .update_one(
+                    doc! { "_id": member_id },
+                    doc! {
+                        "$set": {
+                            "role": mongodb::bson::to_bson(&invitation.role).unwrap(),
+                            "branch_id": invitation.branch_id.as_deref(),
+                            "title": invitation.title.as_deref(),
+                            "status": "Active",
+                            "joined_at": now,
+                            "invited_by": &invitation.invited_by,
+                            "invitation_code": &invitation.code,
+                            "soft_delete.is_deleted": false,
+                            "soft_delete.deleted_at": null,
+                            "soft_delete.deleted_by": null,
+                            "audit.updated_at": now,
+                            "audit.updated_by": user_id,
+                        }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(format!("Failed to activate membership: {}", e)))?;
+            Ok(member_id)
+        }
+        None => {
+            let mut member = Member::new(
+                user_id.to_string(),
+                invitation.school_id.clone(),
+                invitation.role,
+            );
+            member.branch_id = invitation.branch_id.clone();
+            member.title = invitation.title.clone();
+            member.invited_by = Some(invitation.invited_by.clone());
+            member.invitation_code = Some(invitation.code.clone());
+
+            let result = members_collection
+                .insert_one(&member, None)
+                .await
+                .map_err(|e| Error::new(format!("Failed to join school: {}", e)))?;
+            result
+                .inserted_id
+                .as_object_id()
+                .ok_or_else(|| Error::new("Failed to get inserted member ID"))
+        }
+    }
+}
+
+/// Require the caller to manage members for the school (and target branch)
+async fn require_invitation_manager(
+    ctx: &Context<'_>,
+    db: &Database,
+    school_id: &str,
+    branch_id: Option<&str>,
+) -> Result<Member> {
+    let auth_member = get_graphql_context(ctx)?
+        .require_member(db, school_id)
+        .await?;
+
+    if !can_manage_members(&auth_member.role) {
+        return Err(Error::new(
+            "You don't have permission to manage invitations. Only owners and directors can invite members.",
+        ));
+    }
+
+    if !can_manage_branch(
+        &auth_member.role,
+        auth_member.branch_id.as_deref(),
+        branch_id,
+    ) {
+        return Err(Error::new(
+            "You can only manage invitations for your assigned branch.",
+        ));
+    }
+
+    Ok(auth_member)
+}
+
+/// Load an invitation by ID
+async fn find_invitation(db: &Database, invitation_id: &str) -> Result<Invitation> {
+    let oid =
+        ObjectId::parse_str(invitation_id).map_err(|_| Error::new("Invalid invitation ID"))?;
+
+    db.collection::<Invitation>("invitations")
+        .find_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))?
+        .ok_or_else(|| Error::new("Invitation not found"))
+}
+
+/// Find an existing user matching a personal invitation's email or phone
+async fn find_invited_user(db: &Database, invitation: &Invitation) -> Result<Option<User>> {
+    let mut conditions = Vec::new();
+    if let Some(ref email) = invitation.email {
+        conditions.push(doc! { "email": email });
+    }
+    if let Some(ref phone) = invitation.phone {
+        conditions.push(doc! { "phone": phone });
+    }
+    if conditions.is_empty() {
+        return Ok(None);
+    }
+
+    db.collection::<User>("users")
+        .find_one(doc! { "$or": conditions }, None)
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))
+}
+
+/// Send a personal invitation's join code to its email address and phone
+async fn deliver_invitation(
+    db: &Database,
+    invitation: &Invitation,
+    expires_at: DateTime,
+) -> std::result::Result<(), String> {
+    let school_oid = ObjectId::parse_str(&invitation.school_id).map_err(|e| e.to_string())?;
+    let school = db
+        .collection::<School>("schools")
+        .find_one(doc! { "_id": school_oid }, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or("School not found")?;
+
+    let expires = expires_at.to_chrono().format("%Y-%m-%d").to_string();
+    let message = format!(
+        "You're invited to join {}. Sign in and enter the code {} to accept. The invitation expires on {}.",
+        school.name.en, invitation.code, expires
+    );
+
+    if let Some(email) = invitation.email.as_deref() {
+        let subject = format!("Invitation to join {}", school.name.en);
+        send_email(email, &subject, &message).await?;
+    }
+    if let Some(phone) = invitation.phone.as_deref() {
+        send_sms(phone, &message).await?;
+    }
+
+    Ok(())
+}
+
+/// Generate a join code not used by any other invitation
+async fn generate_unique_code(db: &Database) -> Result<String> {
+    let collection = db.collection::<Invitation>("invitations");
+
+    for _ in 0..5 {
+        let code = random_code(CODE_LENGTH);
+        let taken = collection
+            .count_documents(doc! { "code": &code }, None)
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+        if taken == 0 {
+            return Ok(code);
+        }
+    }
+
+    Err(Error::new("Failed to generate a unique invitation code"))
+}
+
+/// Compute an expiry time `days` from now (defaults to 7 days)
+fn expiry_from_now(days: Option<i32>) -> Result<DateTime> {
+    let days = days.unwrap_or(DEFAULT_EXPIRY_DAYS);
+    if !(1..=90).contains(&days) {
+        return Err(Error::new(
+            "Invitation expiry must be between 1 and 90 days",
+        ));
+    }
+
+    let expires = chrono::Utc::now() + chrono::Duration::days(days as i64);
+    Ok(DateTime::from_millis(expires.timestamp_millis()))
+}
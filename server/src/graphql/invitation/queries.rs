@@ -0,0 +1,112 @@
+use super::types::InvitationPreview;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::invitation::{Invitation, InvitationStatus};
+use crate::models::school::School;
+use crate::utils::permissions::can_manage_members;
+use async_graphql::*;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct InvitationQuery;
+
+#[Object]
+impl InvitationQuery {
+    /// List a school's invitations, pending ones by default
+    /// (Owner, Director, or DeputyDirector)
+    async fn school_invitations(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        status: Option<InvitationStatus>,
+    ) -> Result<Vec<Invitation>> {
+        let db = ctx.data::<Database>()?;
+        let auth_member = get_graphql_context(ctx)?
+            .require_member(db, &school_id)
+            .await?;
+
+        if !can_manage_members(&auth_member.role) {
+            return Err(Error::new(
+                "You don't have permission to view invitations. Only owners and directors can view invitations.",
+            ));
+        }
+
+        let status = status.unwrap_or(InvitationStatus::Pending);
+        let mut filter = doc! {
+            "school_id": &school_id,
+            "status": mongodb::bson::to_bson(&status).unwrap(),
+        };
+
+        // Branch-scoped managers only see their branch's invitations
+        if let Some(ref branch_id) = auth_member.branch_id {
+            filter.insert("branch_id", branch_id);
+        }
+
+        let options = FindOptions::builder()
+            .sort(doc! { "audit.created_at": -1 })
+            .build();
+
+        let mut cursor = db
+            .collection::<Invitation>("invitations")
+            .find(filter, options)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+
+        let mut invitations = Vec::new();
+        while let Some(invitation) = cursor
+            .try_next()
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        {
+            invitations.push(invitation);
+        }
+
+        Ok(invitations)
+    }
+
+    /// Preview an invitation by its code before accepting it
+    async fn invitation_by_code(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+    ) -> Result<Option<InvitationPreview>> {
+        get_graphql_context(ctx)?.require_auth()?;
+
+        let db = ctx.data::<Database>()?;
+
+        let Some(invitation) = db
+            .collection::<Invitation>("invitations")
+            .find_one(doc! { "code": code.trim().to_uppercase() }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+
+        let school_oid = ObjectId::parse_str(&invitation.school_id)
+            .map_err(|_| Error::new("Invalid school ID"))?;
+        let school = db
+            .collection::<School>("schools")
+            .find_one(doc! { "_id": school_oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("School not found"))?;
+
+        Ok(Some(InvitationPreview {
+            school_id: invitation.school_id.clone(),
+            school_name: school.name.en,
+            role: invitation.role,
+            branch_id: invitation.branch_id.clone(),
+            title: invitation.title.clone(),
+            expires_at: invitation
+                .expires_at
+                .try_to_rfc3339_string()
+                .unwrap_or_default(),
+            is_open: invitation.can_accept(),
+        }))
+    }
+}
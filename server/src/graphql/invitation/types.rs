@@ -0,0 +1,15 @@
+use crate::models::member::SchoolRole;
+use async_graphql::SimpleObject;
+
+/// What an invitee sees before accepting a join code
+#[derive(SimpleObject)]
+pub struct InvitationPreview {
+    pub school_id: String,
+    pub school_name: String,
+    pub role: SchoolRole,
+    pub branch_id: Option<String>,
+    pub title: Option<String>,
+    pub expires_at: String,
+    /// Whether the invitation can still be accepted
+    pub is_open: bool,
+}
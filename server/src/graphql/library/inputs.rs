@@ -0,0 +1,85 @@
+use async_graphql::InputObject;
+
+use crate::models::library::{BorrowerType, CopyStatus};
+
+#[derive(InputObject)]
+pub struct CreateBookInput {
+    pub school_id: String,
+    /// Title in English
+    pub title: String,
+    /// Title in Khmer
+    pub title_km: Option<String>,
+    pub authors: Option<Vec<String>>,
+    pub isbn: Option<String>,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub category: Option<String>,
+    /// Language of the book (default "en")
+    pub language: Option<String>,
+    pub description: Option<String>,
+    pub replacement_cost: Option<f64>,
+    /// Number of copies to add to the shelf right away
+    pub copies: Option<i32>,
+    pub shelf: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateBookInput {
+    pub book_id: String,
+    pub title: Option<String>,
+    pub title_km: Option<String>,
+    pub authors: Option<Vec<String>>,
+    pub isbn: Option<String>,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub category: Option<String>,
+    pub language: Option<String>,
+    pub description: Option<String>,
+    pub replacement_cost: Option<f64>,
+}
+
+#[derive(InputObject)]
+pub struct AddBookCopiesInput {
+    pub book_id: String,
+    pub count: i32,
+    pub shelf: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateBookCopyInput {
+    pub copy_id: String,
+    /// Only Available, Damaged, Lost and Withdrawn can be set by hand; loans
+    /// and holds are managed by checkout, return and reservations
+    pub status: Option<CopyStatus>,
+    pub shelf: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct CheckoutBookInput {
+    pub school_id: String,
+    /// Barcode or QR value scanned from the copy
+    pub barcode: String,
+    pub borrower_type: BorrowerType,
+    /// Student record ID or scanned ID card code, or staff membership ID
+    pub borrower_id: String,
+    /// Due date (YYYY-MM-DD); defaults to the standard loan period
+    pub due_date: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct ReturnBookInput {
+    pub school_id: String,
+    /// Barcode or QR value scanned from the copy
+    pub barcode: String,
+    /// Take the copy out of circulation as damaged
+    pub damaged: Option<bool>,
+}
+
+#[derive(InputObject)]
+pub struct ReserveBookInput {
+    pub book_id: String,
+    /// Reserve on behalf of a borrower (requires ManageLibrary); defaults to the caller
+    pub borrower_type: Option<BorrowerType>,
+    pub borrower_id: Option<String>,
+}
@@ -0,0 +1,1156 @@
+use super::inputs::{
+    AddBookCopiesInput, CheckoutBookInput, CreateBookInput, ReserveBookInput, ReturnBookInput,
+    UpdateBookCopyInput, UpdateBookInput,
+};
+use super::queries::{
+    collect, find_book, find_copy_by_barcode, normalize_isbn, require_library, self_borrower,
+    to_bson,
+};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::id_card::queries::find_student_by_card;
+use crate::models::finance::Invoice;
+use crate::models::library::{
+    days_after, Book, BookCopy, BorrowerType, CopyStatus, Loan, LoanStatus, Reservation,
+    ReservationStatus, HOLD_DAYS, LOAN_DAYS, MAX_RENEWALS, MAX_STUDENT_LOANS,
+};
+use crate::models::member::{Member, Permission};
+use crate::models::school::SchoolFeature;
+use crate::models::student::Student;
+use crate::utils::audit::record_change;
+use crate::utils::common_types::{AuditInfo, LocalizedText, SoftDelete};
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::{restore_by_id, soft_delete_by_id};
+use crate::utils::subscription::{find_school, is_staff_role};
+use async_graphql::*;
+use chrono::{NaiveDate, TimeZone, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::{FindOneOptions, FindOptions},
+    Database,
+};
+
+/// Most copies that can be added in one go
+const MAX_COPIES_PER_BATCH: i32 = 100;
+/// Days a student has to pay a library fine
+const FINE_DUE_DAYS: i64 = 14;
+
+#[derive(Default)]
+pub struct LibraryMutation;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Library)")]
+impl LibraryMutation {
+    /// Add a book to the catalog, optionally with copies (requires ManageLibrary)
+    async fn create_book(&self, ctx: &Context<'_>, input: CreateBookInput) -> Result<Book> {
+        let db = ctx.data::<Database>()?;
+        let member = require_library(ctx, db, &input.school_id, Permission::ManageLibrary).await?;
+
+        let title = input.title.trim().to_string();
+        if title.is_empty() {
+            return Err(Error::new("Title is required"));
+        }
+
+        let mut book = Book::new(
+            input.school_id,
+            match input.title_km.filter(|t| !t.trim().is_empty()) {
+                Some(km) => LocalizedText::with_khmer(title, km.trim()),
+                None => LocalizedText::new(title),
+            },
+        );
+        book.authors = input.authors.unwrap_or_default();
+        book.isbn = parse_isbn(input.isbn)?;
+        book.publisher = input.publisher;
+        book.published_year = input.published_year;
+        book.category = input.category;
+        if let Some(language) = input.language {
+            book.language = language;
+        }
+        book.description = input.description;
+        book.replacement_cost = input.replacement_cost;
+        book.audit = AuditInfo::new(Some(member.user_id.clone()));
+
+        let result = db
+            .collection::<Book>("books")
+            .insert_one(&book, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create book: {}", e)))?;
+        book.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "Book",
+            book.id.map(|id| id.to_hex()),
+            Some(&book.school_id),
+            None,
+            Some(&book),
+        );
+
+        if let Some(count) = input.copies.filter(|c| *c > 0) {
+            add_copies(db, &book, count, input.shelf, &member.user_id).await?;
+        }
+
+        Ok(book)
+    }
+
+    /// Update a catalog entry (requires ManageLibrary)
+    async fn update_book(&self, ctx: &Context<'_>, input: UpdateBookInput) -> Result<Book> {
+        let db = ctx.data::<Database>()?;
+        let book = find_book(db, &input.book_id).await?;
+        let member = require_library(ctx, db, &book.school_id, Permission::ManageLibrary).await?;
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(title) = input.title {
+            let title = title.trim().to_string();
+            if title.is_empty() {
+                return Err(Error::new("Title is required"));
+            }
+            set.insert("title.en", title);
+        }
+        if let Some(km) = input.title_km {
+            set.insert("title.km", km);
+        }
+        if let Some(authors) = input.authors {
+            set.insert("authors", authors);
+        }
+        if input.isbn.is_some() {
+            set.insert("isbn", parse_isbn(input.isbn)?);
+        }
+        if let Some(publisher) = input.publisher {
+            set.insert("publisher", publisher);
+        }
+        if let Some(year) = input.published_year {
+            set.insert("published_year", year);
+        }
+        if let Some(category) = input.category {
+            set.insert("category", category);
+        }
+        if let Some(language) = input.language {
+            set.insert("language", language);
+        }
+        if let Some(description) = input.description {
+            set.insert("description", description);
+        }
+        if let Some(cost) = input.replacement_cost {
+            set.insert("replacement_cost", cost);
+        }
+
+        let collection = db.collection::<Book>("books");
+        collection
+            .update_one(doc! { "_id": book.id }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update book: {}", e)))?;
+
+        let updated = find_book(db, &input.book_id).await?;
+        record_change(
+            ctx,
+            "Book",
+            Some(input.book_id),
+            Some(&updated.school_id),
+            Some(&book),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Remove a book and its copies from the catalog (requires ManageLibrary).
+    /// Refused while any copy is on loan; open reservations are cancelled.
+    async fn delete_book(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let book = find_book(db, &id).await?;
+        let member = require_library(ctx, db, &book.school_id, Permission::ManageLibrary).await?;
+
+        let on_loan = db
+            .collection::<Loan>("loans")
+            .count_documents(doc! { "book_id": &id, "status": "Active" }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        if on_loan > 0 {
+            return Err(Error::new(format!(
+                "{} copies of this book are on loan; return them first",
+                on_loan
+            )));
+        }
+
+        let oid = book.id.ok_or_else(|| Error::new("Book not found"))?;
+        soft_delete_by_id(&db.collection::<Book>("books"), oid, &member.user_id).await?;
+
+        let now = DateTime::now();
+        db.collection::<BookCopy>("book_copies")
+            .update_many(
+                doc! { "book_id": &id, "soft_delete.is_deleted": { "$ne": true } },
+                doc! {
+                    "$set": {
+                        "status": "Withdrawn",
+                        "soft_delete.is_deleted": true,
+                        "soft_delete.deleted_at": now,
+                        "soft_delete.deleted_by": &member.user_id,
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to withdraw copies: {}", e)))?;
+        db.collection::<Reservation>("reservations")
+            .update_many(
+                doc! { "book_id": &id, "status": { "$in": ["Waiting", "Ready"] } },
+                doc! { "$set": { "status": "Cancelled" } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to cancel reservations: {}", e)))?;
+
+        record_change(
+            ctx,
+            "Book",
+            Some(id),
+            Some(&book.school_id),
+            Some(&book),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted book from the trash with the copies deleted along with
+    /// it, back on the shelf (requires ManageLibrary). Cancelled reservations
+    /// stay cancelled.
+    async fn restore_book(&self, ctx: &Context<'_>, id: String) -> Result<Book> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<Book>("books");
+        let oid = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid book ID"))?;
+        let book = collection
+            .find_one(doc! { "_id": oid, "soft_delete.is_deleted": true }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Deleted book not found"))?;
+        require_library(ctx, db, &book.school_id, Permission::ManageLibrary).await?;
+
+        if !restore_by_id(&collection, oid).await? {
+            return Err(Error::new("Deleted book not found"));
+        }
+        db.collection::<BookCopy>("book_copies")
+            .update_many(
+                doc! {
+                    "book_id": &id,
+                    "soft_delete.is_deleted": true,
+                    // Copies are deleted right after their book
+                    "soft_delete.deleted_at": { "$gte": book.soft_delete.deleted_at }
+                },
+                doc! {
+                    "$set": {
+                        "status": to_bson(&CopyStatus::Available)?,
+                        "soft_delete.is_deleted": false,
+                        "soft_delete.deleted_at": null,
+                        "soft_delete.deleted_by": null,
+                        "audit.updated_at": DateTime::now(),
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to restore copies: {}", e)))?;
+
+        let restored = find_book(db, &id).await?;
+        record_change(
+            ctx,
+            "Book",
+            Some(id),
+            Some(&book.school_id),
+            Some(&book),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Add labelled copies of a book (requires ManageLibrary). Waiting
+    /// reservations are served from the new copies first.
+    async fn add_book_copies(
+        &self,
+        ctx: &Context<'_>,
+        input: AddBookCopiesInput,
+    ) -> Result<Vec<BookCopy>> {
+        let db = ctx.data::<Database>()?;
+        let book = find_book(db, &input.book_id).await?;
+        let member = require_library(ctx, db, &book.school_id, Permission::ManageLibrary).await?;
+
+        if !(1..=MAX_COPIES_PER_BATCH).contains(&input.count) {
+            return Err(Error::new(format!(
+                "Count must be between 1 and {}",
+                MAX_COPIES_PER_BATCH
+            )));
+        }
+
+        let copies = add_copies(db, &book, input.count, input.shelf, &member.user_id).await?;
+        fill_reservations(db, &input.book_id).await?;
+
+        Ok(copies)
+    }
+
+    /// Change a copy's shelf, notes or status (requires ManageLibrary)
+    async fn update_book_copy(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateBookCopyInput,
+    ) -> Result<BookCopy> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<BookCopy>("book_copies");
+        let oid = ObjectId::parse_str(&input.copy_id).map_err(|_| Error::new("Invalid copy ID"))?;
+        let copy = collection
+            .find_one(
+                doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Copy not found"))?;
+        let member = require_library(ctx, db, &copy.school_id, Permission::ManageLibrary).await?;
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(status) = input.status {
+            if matches!(status, CopyStatus::OnLoan | CopyStatus::OnHold)
+                || matches!(copy.status, CopyStatus::OnLoan | CopyStatus::OnHold)
+            {
+                return Err(Error::new(
+                    "Loans and holds are managed through checkout, return and reservations",
+                ));
+            }
+            set.insert("status", to_bson(&status)?);
+        }
+        if let Some(shelf) = input.shelf {
+            set.insert("shelf", shelf);
+        }
+        if let Some(notes) = input.notes {
+            set.insert("notes", notes);
+        }
+
+        collection
+            .update_one(doc! { "_id": oid }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update copy: {}", e)))?;
+
+        if input.status == Some(CopyStatus::Available) {
+            fill_reservations(db, &copy.book_id).await?;
+        }
+
+        collection
+            .find_one(doc! { "_id": oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Copy not found"))
+    }
+
+    /// Lend a copy to a student or staff member (requires ManageLibrary)
+    async fn checkout_book(&self, ctx: &Context<'_>, input: CheckoutBookInput) -> Result<Loan> {
+        let db = ctx.data::<Database>()?;
+        let member = require_library(ctx, db, &input.school_id, Permission::ManageLibrary).await?;
+        let mut input = input;
+        if input.borrower_type == BorrowerType::Student {
+            let student = find_student_by_card(db, &input.school_id, &input.borrower_id).await?;
+            input.borrower_id = student.id.map(|id| id.to_hex()).unwrap_or_default();
+        }
+
+        let copy = find_copy_by_barcode(db, &input.school_id, &input.barcode)
+            .await?
+            .ok_or_else(|| Error::new("No copy has this barcode"))?;
+        expire_holds(db, &copy.book_id).await?;
+        let copy = find_copy_by_barcode(db, &input.school_id, &input.barcode)
+            .await?
+            .ok_or_else(|| Error::new("No copy has this barcode"))?;
+        let copy_id = copy.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        check_borrower(
+            db,
+            &input.school_id,
+            input.borrower_type,
+            &input.borrower_id,
+        )
+        .await?;
+
+        let reservations = db.collection::<Reservation>("reservations");
+        let borrower_filter = doc! {
+            "book_id": &copy.book_id,
+            "borrower_type": to_bson(&input.borrower_type)?,
+            "borrower_id": &input.borrower_id,
+        };
+        match copy.status {
+            CopyStatus::Available => {}
+            CopyStatus::OnHold => {
+                let mut held_for_borrower = borrower_filter.clone();
+                held_for_borrower.insert("status", "Ready");
+                held_for_borrower.insert("copy_id", &copy_id);
+                let held = reservations
+                    .find_one(held_for_borrower, None)
+                    .await
+                    .map_err(|e| Error::new(e.to_string()))?;
+                if held.is_none() {
+                    return Err(Error::new("This copy is on hold for another reservation"));
+                }
+            }
+            status => return Err(Error::new(format!("This copy is {:?}", status))),
+        }
+
+        if input.borrower_type == BorrowerType::Student {
+            let active = db
+                .collection::<Loan>("loans")
+                .count_documents(
+                    doc! {
+                        "school_id": &input.school_id,
+                        "borrower_type": "Student",
+                        "borrower_id": &input.borrower_id,
+                        "status": "Active"
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?;
+            if active >= MAX_STUDENT_LOANS {
+                return Err(Error::new(format!(
+                    "Students can borrow at most {} books at a time",
+                    MAX_STUDENT_LOANS
+                )));
+            }
+        }
+
+        let now = DateTime::now();
+        let due_at = match input.due_date {
+            Some(date) => {
+                let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                    .map_err(|_| Error::new("Invalid due date. Use YYYY-MM-DD"))?;
+                let due = Utc.from_utc_datetime(&date.and_hms_opt(23, 59, 59).unwrap());
+                if due.timestamp_millis() <= now.timestamp_millis() {
+                    return Err(Error::new("Due date must be in the future"));
+                }
+                DateTime::from_millis(due.timestamp_millis())
+            }
+            None => days_after(now, LOAN_DAYS),
+        };
+
+        let mut loan = Loan {
+            id: None,
+            school_id: input.school_id.clone(),
+            book_id: copy.book_id.clone(),
+            copy_id: copy_id.clone(),
+            barcode: copy.barcode.clone(),
+            borrower_type: input.borrower_type,
+            borrower_id: input.borrower_id,
+            status: LoanStatus::Active,
+            borrowed_at: now,
+            due_at,
+            returned_at: None,
+            renewals: 0,
+            fine_amount: 0.0,
+            fine_invoice_id: None,
+            issued_by: member.user_id.clone(),
+            returned_to: None,
+        };
+
+        // Claim the copy; of two checkouts of the same copy, the second stops here
+        claim_copy(db, &copy_id, copy.status).await?;
+        let result = match db.collection::<Loan>("loans").insert_one(&loan, None).await {
+            Ok(result) => result,
+            Err(e) => {
+                set_copy_status(db, &copy_id, copy.status).await?;
+                return Err(Error::new(format!("Failed to record loan: {}", e)));
+            }
+        };
+        loan.id = result.inserted_id.as_object_id();
+
+        // The borrower's own reservation for this book is now fulfilled
+        let mut open_for_borrower = borrower_filter;
+        open_for_borrower.insert("status", doc! { "$in": ["Waiting", "Ready"] });
+        reservations
+            .update_many(
+                open_for_borrower,
+                doc! { "$set": { "status": "Fulfilled" } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+
+        record_change(
+            ctx,
+            "Loan",
+            loan.id.map(|id| id.to_hex()),
+            Some(&loan.school_id),
+            None,
+            Some(&loan),
+        );
+
+        Ok(loan)
+    }
+
+    /// Check a copy back in (requires ManageLibrary). Overdue fines for students
+    /// are posted to their finance account; the copy goes to the next
+    /// reservation or back on the shelf.
+    async fn return_book(&self, ctx: &Context<'_>, input: ReturnBookInput) -> Result<Loan> {
+        let db = ctx.data::<Database>()?;
+        let member = require_library(ctx, db, &input.school_id, Permission::ManageLibrary).await?;
+
+        let copy = find_copy_by_barcode(db, &input.school_id, &input.barcode)
+            .await?
+            .ok_or_else(|| Error::new("No copy has this barcode"))?;
+        let copy_id = copy.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        let loan = db
+            .collection::<Loan>("loans")
+            .find_one(doc! { "copy_id": &copy_id, "status": "Active" }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("This copy is not on loan"))?;
+
+        let now = DateTime::now();
+        let fine = loan.overdue_fine_at(now);
+        let description = format!(
+            "Library fine: {} ({}), {} days overdue",
+            book_title(db, &loan.book_id).await,
+            loan.barcode,
+            loan.days_overdue_at(now)
+        );
+        let updated = close_loan(
+            ctx,
+            db,
+            &loan,
+            LoanStatus::Returned,
+            fine,
+            &description,
+            &member,
+        )
+        .await?;
+
+        if input.damaged.unwrap_or(false) {
+            set_copy_status(db, &copy_id, CopyStatus::Damaged).await?;
+        } else {
+            set_copy_status(db, &copy_id, CopyStatus::Available).await?;
+            fill_reservations(db, &loan.book_id).await?;
+        }
+
+        Ok(updated)
+    }
+
+    /// Extend a loan by another loan period (requires ManageLibrary). Not
+    /// allowed for overdue loans or books others are waiting for.
+    async fn renew_loan(&self, ctx: &Context<'_>, loan_id: String) -> Result<Loan> {
+        let db = ctx.data::<Database>()?;
+        let loan = find_active_loan(db, &loan_id).await?;
+        require_library(ctx, db, &loan.school_id, Permission::ManageLibrary).await?;
+
+        if loan.renewals >= MAX_RENEWALS {
+            return Err(Error::new(format!(
+                "Loans can be renewed at most {} times",
+                MAX_RENEWALS
+            )));
+        }
+        if loan.days_overdue_at(DateTime::now()) > 0 {
+            return Err(Error::new("Overdue loans cannot be renewed"));
+        }
+        let waiting = db
+            .collection::<Reservation>("reservations")
+            .count_documents(doc! { "book_id": &loan.book_id, "status": "Waiting" }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        if waiting > 0 {
+            return Err(Error::new("Other borrowers are waiting for this book"));
+        }
+
+        let collection = db.collection::<Loan>("loans");
+        collection
+            .update_one(
+                doc! { "_id": loan.id },
+                doc! {
+                    "$set": { "due_at": days_after(loan.due_at, LOAN_DAYS) },
+                    "$inc": { "renewals": 1 }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to renew loan: {}", e)))?;
+
+        let renewed = find_loan(db, &loan_id).await?;
+        record_change(
+            ctx,
+            "Loan",
+            Some(loan_id),
+            Some(&renewed.school_id),
+            Some(&loan),
+            Some(&renewed),
+        );
+
+        Ok(renewed)
+    }
+
+    /// Close a loan whose copy has been lost (requires ManageLibrary). Students
+    /// are charged the overdue fine plus the book's replacement cost.
+    async fn mark_loan_lost(&self, ctx: &Context<'_>, loan_id: String) -> Result<Loan> {
+        let db = ctx.data::<Database>()?;
+        let loan = find_active_loan(db, &loan_id).await?;
+        let member = require_library(ctx, db, &loan.school_id, Permission::ManageLibrary).await?;
+
+        let now = DateTime::now();
+        let book = find_book(db, &loan.book_id).await.ok();
+        let replacement = book
+            .as_ref()
+            .and_then(|b| b.replacement_cost)
+            .unwrap_or(0.0);
+        let fine = loan.overdue_fine_at(now) + replacement;
+        let description = format!(
+            "Library fine: lost book {} ({})",
+            book.map(|b| b.title.en).unwrap_or_default(),
+            loan.barcode
+        );
+
+        let updated = close_loan(
+            ctx,
+            db,
+            &loan,
+            LoanStatus::Lost,
+            fine,
+            &description,
+            &member,
+        )
+        .await?;
+        set_copy_status(db, &loan.copy_id, CopyStatus::Lost).await?;
+
+        Ok(updated)
+    }
+
+    /// Join the queue for a book. Borrowers reserve for themselves; reserving
+    /// for someone else requires ManageLibrary. If a copy is on the shelf it is
+    /// held right away.
+    async fn reserve_book(
+        &self,
+        ctx: &Context<'_>,
+        input: ReserveBookInput,
+    ) -> Result<Reservation> {
+        let db = ctx.data::<Database>()?;
+        let book = find_book(db, &input.book_id).await?;
+
+        let (borrower_type, borrower_id) = match (input.borrower_type, input.borrower_id) {
+            (Some(borrower_type), Some(borrower_id)) => {
+                require_library(ctx, db, &book.school_id, Permission::ManageLibrary).await?;
+                check_borrower(db, &book.school_id, borrower_type, &borrower_id).await?;
+                (borrower_type, borrower_id)
+            }
+            (None, None) => {
+                let member =
+                    require_library(ctx, db, &book.school_id, Permission::ViewLibrary).await?;
+                self_borrower(&member)?
+            }
+            _ => {
+                return Err(Error::new(
+                    "Give both borrowerType and borrowerId, or neither",
+                ))
+            }
+        };
+
+        let collection = db.collection::<Reservation>("reservations");
+        let existing = collection
+            .find_one(
+                doc! {
+                    "book_id": &input.book_id,
+                    "borrower_type": to_bson(&borrower_type)?,
+                    "borrower_id": &borrower_id,
+                    "status": { "$in": ["Waiting", "Ready"] }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        if existing.is_some() {
+            return Err(Error::new("This book is already reserved for the borrower"));
+        }
+
+        let on_loan = db
+            .collection::<Loan>("loans")
+            .find_one(
+                doc! {
+                    "book_id": &input.book_id,
+                    "borrower_type": to_bson(&borrower_type)?,
+                    "borrower_id": &borrower_id,
+                    "status": "Active"
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        if on_loan.is_some() {
+            return Err(Error::new("The borrower already has this book on loan"));
+        }
+
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let mut reservation = Reservation {
+            id: None,
+            school_id: book.school_id.clone(),
+            book_id: input.book_id.clone(),
+            borrower_type,
+            borrower_id,
+            status: ReservationStatus::Waiting,
+            copy_id: None,
+            reserved_at: DateTime::now(),
+            hold_until: None,
+            created_by: auth_user.id.clone(),
+        };
+        let result = collection
+            .insert_one(&reservation, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to reserve book: {}", e)))?;
+        let reservation_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| Error::new("Failed to get inserted reservation ID"))?;
+        reservation.id = Some(reservation_id);
+
+        expire_holds(db, &input.book_id).await?;
+        fill_reservations(db, &input.book_id).await?;
+
+        collection
+            .find_one(doc! { "_id": reservation_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Reservation not found"))
+    }
+
+    /// Cancel a reservation (the borrower, or ManageLibrary). A held copy
+    /// passes to the next reservation.
+    async fn cancel_reservation(
+        &self,
+        ctx: &Context<'_>,
+        reservation_id: String,
+    ) -> Result<Reservation> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<Reservation>("reservations");
+        let oid = ObjectId::parse_str(&reservation_id)
+            .map_err(|_| Error::new("Invalid reservation ID"))?;
+        let reservation = collection
+            .find_one(doc! { "_id": oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Reservation not found"))?;
+
+        let member =
+            require_library(ctx, db, &reservation.school_id, Permission::ViewLibrary).await?;
+        let own = self_borrower(&member)
+            .map(|(t, id)| t == reservation.borrower_type && id == reservation.borrower_id)
+            .unwrap_or(false);
+        if !own && !member.has_permission(Permission::ManageLibrary) {
+            return Err(Error::new("You can only cancel your own reservations"));
+        }
+        if !matches!(
+            reservation.status,
+            ReservationStatus::Waiting | ReservationStatus::Ready
+        ) {
+            return Err(Error::new("This reservation is no longer open"));
+        }
+
+        collection
+            .update_one(
+                doc! { "_id": oid },
+                doc! { "$set": { "status": "Cancelled" } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to cancel reservation: {}", e)))?;
+
+        if let Some(copy_id) = reservation.copy_id.as_deref() {
+            if reservation.status == ReservationStatus::Ready {
+                set_copy_status(db, copy_id, CopyStatus::Available).await?;
+                fill_reservations(db, &reservation.book_id).await?;
+            }
+        }
+
+        collection
+            .find_one(doc! { "_id": oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Reservation not found"))
+    }
+}
+
+/// Normalize an optional ISBN, rejecting malformed values
+fn parse_isbn(isbn: Option<String>) -> Result<Option<String>> {
+    match isbn.filter(|i| !i.trim().is_empty()) {
+        Some(isbn) => {
+            let normalized = normalize_isbn(&isbn);
+            if normalized.is_empty() {
+                return Err(Error::new("ISBN must have 10 or 13 digits"));
+            }
+            Ok(Some(normalized))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Create `count` copies of a book with the school's next barcodes
+async fn add_copies(
+    db: &Database,
+    book: &Book,
+    count: i32,
+    shelf: Option<String>,
+    user_id: &str,
+) -> Result<Vec<BookCopy>> {
+    let collection = db.collection::<BookCopy>("book_copies");
+    let book_id = book.id.map(|id| id.to_hex()).unwrap_or_default();
+
+    // Barcodes are LIB-000001, LIB-000002, ... per school
+    let options = FindOneOptions::builder()
+        .sort(doc! { "barcode": -1 })
+        .build();
+    let last = collection
+        .find_one(
+            doc! {
+                "school_id": &book.school_id,
+                "barcode": { "$regex": "^LIB-\\d{6}$" }
+            },
+            options,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    let first = last
+        .and_then(|c| c.barcode[4..].parse::<u32>().ok())
+        .unwrap_or(0)
+        + 1;
+
+    let mut copies = Vec::new();
+    for next in first..first + count as u32 {
+        let mut copy = BookCopy {
+            id: None,
+            school_id: book.school_id.clone(),
+            book_id: book_id.clone(),
+            barcode: format!("LIB-{:06}", next),
+            status: CopyStatus::Available,
+            shelf: shelf.clone(),
+            notes: None,
+            audit: AuditInfo::new(Some(user_id.to_string())),
+            soft_delete: SoftDelete::default(),
+        };
+        let result = collection
+            .insert_one(&copy, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to add copy: {}", e)))?;
+        copy.id = result.inserted_id.as_object_id();
+        copies.push(copy);
+    }
+
+    Ok(copies)
+}
+
+/// Check the borrower exists in the school and may borrow
+async fn check_borrower(
+    db: &Database,
+    school_id: &str,
+    borrower_type: BorrowerType,
+    borrower_id: &str,
+) -> Result<()> {
+    let oid = ObjectId::parse_str(borrower_id).map_err(|_| Error::new("Invalid borrower ID"))?;
+    match borrower_type {
+        BorrowerType::Student => {
+            db.collection::<Student>("students")
+                .find_one(
+                    doc! {
+                        "_id": oid,
+                        "school_id": school_id,
+                        "soft_delete.is_deleted": { "$ne": true }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?
+                .ok_or_else(|| Error::new("Student not found"))?;
+        }
+        BorrowerType::Staff => {
+            let member = db
+                .collection::<Member>("members")
+                .find_one(
+                    doc! {
+                        "_id": oid,
+                        "school_id": school_id,
+                        "status": "Active",
+                        "soft_delete.is_deleted": { "$ne": true }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?
+                .ok_or_else(|| Error::new("Staff member not found"))?;
+            if !is_staff_role(member.role) {
+                return Err(Error::new("This member is not staff"));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn find_loan(db: &Database, loan_id: &str) -> Result<Loan> {
+    let oid = ObjectId::parse_str(loan_id).map_err(|_| Error::new("Invalid loan ID"))?;
+    db.collection::<Loan>("loans")
+        .find_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Loan not found"))
+}
+
+async fn find_active_loan(db: &Database, loan_id: &str) -> Result<Loan> {
+    let loan = find_loan(db, loan_id).await?;
+    if loan.status != LoanStatus::Active {
+        return Err(Error::new("This loan is already closed"));
+    }
+    Ok(loan)
+}
+
+/// English title of a book, for fine descriptions
+async fn book_title(db: &Database, book_id: &str) -> String {
+    find_book(db, book_id)
+        .await
+        .map(|b| b.title.en)
+        .unwrap_or_default()
+}
+
+async fn set_copy_status(db: &Database, copy_id: &str, status: CopyStatus) -> Result<()> {
+    let oid = ObjectId::parse_str(copy_id).map_err(|_| Error::new("Invalid copy ID"))?;
+    db.collection::<BookCopy>("book_copies")
+        .update_one(
+            doc! { "_id": oid },
+            doc! { "$set": { "status": to_bson(&status)?, "audit.updated_at": DateTime::now() } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to update copy: {}", e)))?;
+    Ok(())
+}
+
+/// Put a copy on loan if it still has the status it was checked out from
+async fn claim_copy(db: &Database, copy_id: &str, from: CopyStatus) -> Result<()> {
+    let oid = ObjectId::parse_str(copy_id).map_err(|_| Error::new("Invalid copy ID"))?;
+    let result = db
+        .collection::<BookCopy>("book_copies")
+        .update_one(
+            doc! { "_id": oid, "status": to_bson(&from)? },
+            doc! {
+                "$set": {
+                    "status": to_bson(&CopyStatus::OnLoan)?,
+                    "audit.updated_at": DateTime::now()
+                }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to update copy: {}", e)))?;
+    if result.matched_count == 0 {
+        return Err(Error::new("This copy was just checked out"));
+    }
+    Ok(())
+}
+
+/// Close a loan, posting any fine to the student's finance account
+async fn close_loan(
+    ctx: &Context<'_>,
+    db: &Database,
+    loan: &Loan,
+    status: LoanStatus,
+    fine: f64,
+    description: &str,
+    member: &Member,
+) -> Result<Loan> {
+    let now = DateTime::now();
+    let fine = (fine * 100.0).round() / 100.0;
+
+    // Only one return can close the loan, so a fine is never posted twice
+    let loans = db.collection::<Loan>("loans");
+    let result = loans
+        .update_one(
+            doc! { "_id": loan.id, "status": "Active" },
+            doc! {
+                "$set": {
+                    "status": to_bson(&status)?,
+                    "returned_at": now,
+                    "fine_amount": fine,
+                    "returned_to": &member.user_id,
+                }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to close loan: {}", e)))?;
+    if result.matched_count == 0 {
+        return Err(Error::new("This loan is already closed"));
+    }
+
+    let loan_id = loan.id.map(|id| id.to_hex()).unwrap_or_default();
+    if fine > 0.0 && loan.borrower_type == BorrowerType::Student {
+        let fine_invoice_id = match post_fine(db, loan, fine, description).await {
+            Ok(id) => id,
+            Err(e) => {
+                reopen_loan(db, loan).await;
+                return Err(e);
+            }
+        };
+        loans
+            .update_one(
+                doc! { "_id": loan.id },
+                doc! { "$set": { "fine_invoice_id": fine_invoice_id } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to close loan: {}", e)))?;
+    }
+
+    let updated = find_loan(db, &loan_id).await?;
+    record_change(
+        ctx,
+        "Loan",
+        Some(loan_id),
+        Some(&updated.school_id),
+        Some(loan),
+        Some(&updated),
+    );
+
+    Ok(updated)
+}
+
+/// Put a loan back to active after its fine could not be posted
+async fn reopen_loan(db: &Database, loan: &Loan) {
+    let _ = db
+        .collection::<Loan>("loans")
+        .update_one(
+            doc! { "_id": loan.id },
+            doc! {
+                "$set": { "status": "Active", "fine_amount": 0.0 },
+                "$unset": { "returned_at": "", "returned_to": "" }
+            },
+            None,
+        )
+        .await;
+}
+
+/// Create an unpaid invoice on the student's finance account for a fine
+async fn post_fine(db: &Database, loan: &Loan, amount: f64, description: &str) -> Result<String> {
+    let student_oid =
+        ObjectId::parse_str(&loan.borrower_id).map_err(|_| Error::new("Invalid student ID"))?;
+    let now = DateTime::now();
+    let loan_hex = loan.id.map(|id| id.to_hex()).unwrap_or_default();
+    let currency = find_school(db, &loan.school_id).await?.settings.currency;
+
+    let invoice = Invoice {
+        id: None,
+        invoice_number: format!(
+            "FINE-{}-{}",
+            Utc::now().format("%Y%m%d"),
+            &loan_hex[loan_hex.len().saturating_sub(6)..].to_uppercase()
+        ),
+        student_id: student_oid,
+        fee_ids: Vec::new(),
+        total_amount: amount,
+        amount_paid: 0.0,
+        balance: amount,
+        currency,
+        issue_date: now,
+        due_date: days_after(now, FINE_DUE_DAYS),
+        status: "unpaid".to_string(),
+        description: Some(description.to_string()),
+        created_at: now,
+        updated_at: now,
+    };
+
+    let result = db
+        .collection::<Invoice>("invoices")
+        .insert_one(&invoice, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to post fine: {}", e)))?;
+
+    result
+        .inserted_id
+        .as_object_id()
+        .map(|id| id.to_hex())
+        .ok_or_else(|| Error::new("Failed to get inserted invoice ID"))
+}
+
+/// Expire holds the borrower didn't collect in time and pass the copies on
+async fn expire_holds(db: &Database, book_id: &str) -> Result<()> {
+    let collection = db.collection::<Reservation>("reservations");
+    let expired = collect(
+        &collection,
+        doc! {
+            "book_id": book_id,
+            "status": "Ready",
+            "hold_until": { "$lt": DateTime::now() }
+        },
+        FindOptions::default(),
+    )
+    .await?;
+    if expired.is_empty() {
+        return Ok(());
+    }
+
+    for reservation in expired {
+        collection
+            .update_one(
+                doc! { "_id": reservation.id },
+                doc! { "$set": { "status": "Expired" } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        if let Some(copy_id) = reservation.copy_id.as_deref() {
+            set_copy_status(db, copy_id, CopyStatus::Available).await?;
+        }
+    }
+
+    fill_reservations(db, book_id).await
+}
+
+/// Hold shelf copies of a book for waiting reservations, first come first served
+async fn fill_reservations(db: &Database, book_id: &str) -> Result<()> {
+    let reservations = db.collection::<Reservation>("reservations");
+    let copies = db.collection::<BookCopy>("book_copies");
+
+    let waiting = collect(
+        &reservations,
+        doc! { "book_id": book_id, "status": "Waiting" },
+        FindOptions::builder()
+            .sort(doc! { "reserved_at": 1 })
+            .build(),
+    )
+    .await?;
+
+    for reservation in waiting {
+        let Some(copy) = copies
+            .find_one(
+                doc! {
+                    "book_id": book_id,
+                    "status": "Available",
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        else {
+            break;
+        };
+        let copy_id = copy.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        set_copy_status(db, &copy_id, CopyStatus::OnHold).await?;
+        reservations
+            .update_one(
+                doc! { "_id": reservation.id },
+                doc! {
+                    "$set": {
+                        "status": "Ready",
+                        "copy_id": &copy_id,
+                        "hold_until": days_after(DateTime::now(), HOLD_DAYS),
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+    }
+
+    Ok(())
+}
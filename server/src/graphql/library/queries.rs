@@ -0,0 +1,408 @@
+use super::types::LibraryLookupType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::library::{
+    Book, BookCopy, BorrowerType, Loan, LoanStatus, Reservation, ReservationStatus,
+};
+use crate::models::member::{Member, Permission, SchoolRole};
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::find_deleted;
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::FindOptions,
+    Collection, Database,
+};
+use serde::de::DeserializeOwned;
+
+#[derive(Default)]
+pub struct LibraryQuery;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Library)")]
+impl LibraryQuery {
+    /// Search a school's catalog by title (English or Khmer), author or ISBN
+    /// (requires ViewLibrary)
+    async fn books(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        search: Option<String>,
+        category: Option<String>,
+        limit: Option<i64>,
+        offset: Option<u64>,
+    ) -> Result<Vec<Book>> {
+        let db = ctx.data::<Database>()?;
+        require_library(ctx, db, &school_id, Permission::ViewLibrary).await?;
+
+        let mut filter = doc! {
+            "school_id": &school_id,
+            "soft_delete.is_deleted": { "$ne": true }
+        };
+        if let Some(search) = search.filter(|s| !s.trim().is_empty()) {
+            let pattern = regex_escape(search.trim());
+            let isbn = normalize_isbn(&search);
+            let mut any = vec![
+                doc! { "title.en": { "$regex": &pattern, "$options": "i" } },
+                doc! { "title.km": { "$regex": &pattern, "$options": "i" } },
+                doc! { "authors": { "$regex": &pattern, "$options": "i" } },
+            ];
+            if !isbn.is_empty() {
+                any.push(doc! { "isbn": isbn });
+            }
+            filter.insert("$or", any);
+        }
+        if let Some(category) = category {
+            filter.insert("category", category);
+        }
+
+        let options = FindOptions::builder()
+            .sort(doc! { "title.en": 1 })
+            .limit(limit.unwrap_or(50))
+            .skip(offset)
+            .build();
+        collect(&db.collection::<Book>("books"), filter, options).await
+    }
+
+    /// Get a book by ID (requires ViewLibrary)
+    async fn book(&self, ctx: &Context<'_>, id: String) -> Result<Option<Book>> {
+        let db = ctx.data::<Database>()?;
+        let Some(book) = find_book(db, &id).await.ok() else {
+            return Ok(None);
+        };
+        require_library(ctx, db, &book.school_id, Permission::ViewLibrary).await?;
+        Ok(Some(book))
+    }
+
+    /// Get a school's deleted books (the trash), most recently deleted first
+    /// (requires ManageLibrary)
+    async fn deleted_books(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Book>> {
+        let db = ctx.data::<Database>()?;
+        require_library(ctx, db, &school_id, Permission::ManageLibrary).await?;
+
+        find_deleted(
+            &db.collection::<Book>("books"),
+            doc! { "school_id": school_id },
+        )
+        .await
+    }
+
+    /// Physical copies of a book (requires ViewLibrary)
+    async fn book_copies(&self, ctx: &Context<'_>, book_id: String) -> Result<Vec<BookCopy>> {
+        let db = ctx.data::<Database>()?;
+        let book = find_book(db, &book_id).await?;
+        require_library(ctx, db, &book.school_id, Permission::ViewLibrary).await?;
+
+        let options = FindOptions::builder().sort(doc! { "barcode": 1 }).build();
+        collect(
+            &db.collection::<BookCopy>("book_copies"),
+            doc! { "book_id": &book_id, "soft_delete.is_deleted": { "$ne": true } },
+            options,
+        )
+        .await
+    }
+
+    /// Look up a scanned copy barcode / QR value or an ISBN (requires ViewLibrary)
+    async fn library_lookup(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        code: String,
+    ) -> Result<LibraryLookupType> {
+        let db = ctx.data::<Database>()?;
+        require_library(ctx, db, &school_id, Permission::ViewLibrary).await?;
+
+        if let Some(copy) = find_copy_by_barcode(db, &school_id, &code).await? {
+            let book = find_book(db, &copy.book_id).await.ok();
+            let active_loan = db
+                .collection::<Loan>("loans")
+                .find_one(
+                    doc! {
+                        "copy_id": copy.id.map(|id| id.to_hex()),
+                        "status": "Active"
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?;
+            return Ok(LibraryLookupType {
+                book,
+                copy: Some(copy),
+                active_loan,
+            });
+        }
+
+        let isbn = normalize_isbn(&code);
+        let book = if isbn.is_empty() {
+            None
+        } else {
+            db.collection::<Book>("books")
+                .find_one(
+                    doc! {
+                        "school_id": &school_id,
+                        "isbn": isbn,
+                        "soft_delete.is_deleted": { "$ne": true }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?
+        };
+
+        match book {
+            Some(book) => Ok(LibraryLookupType {
+                book: Some(book),
+                copy: None,
+                active_loan: None,
+            }),
+            None => Err(Error::new("No copy or book matches this code")),
+        }
+    }
+
+    /// A school's loans, newest first, optionally for one borrower (requires ViewLibrary)
+    async fn loans(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        status: Option<LoanStatus>,
+        borrower_id: Option<String>,
+        limit: Option<i64>,
+        offset: Option<u64>,
+    ) -> Result<Vec<Loan>> {
+        let db = ctx.data::<Database>()?;
+        require_library(ctx, db, &school_id, Permission::ViewLibrary).await?;
+
+        let mut filter = doc! { "school_id": &school_id };
+        if let Some(status) = status {
+            filter.insert("status", to_bson(&status)?);
+        }
+        if let Some(borrower_id) = borrower_id {
+            filter.insert("borrower_id", borrower_id);
+        }
+
+        let options = FindOptions::builder()
+            .sort(doc! { "borrowed_at": -1 })
+            .limit(limit.unwrap_or(50))
+            .skip(offset)
+            .build();
+        collect(&db.collection::<Loan>("loans"), filter, options).await
+    }
+
+    /// Active loans past their due date, most overdue first (requires ViewLibrary)
+    async fn overdue_loans(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Loan>> {
+        let db = ctx.data::<Database>()?;
+        require_library(ctx, db, &school_id, Permission::ViewLibrary).await?;
+
+        let options = FindOptions::builder().sort(doc! { "due_at": 1 }).build();
+        collect(
+            &db.collection::<Loan>("loans"),
+            doc! {
+                "school_id": &school_id,
+                "status": "Active",
+                "due_at": { "$lt": DateTime::now() }
+            },
+            options,
+        )
+        .await
+    }
+
+    /// The caller's own loans in a school, newest first
+    async fn my_loans(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Loan>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_library(ctx, db, &school_id, Permission::ViewLibrary).await?;
+        let (borrower_type, borrower_id) = self_borrower(&member)?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "borrowed_at": -1 })
+            .build();
+        collect(
+            &db.collection::<Loan>("loans"),
+            doc! {
+                "school_id": &school_id,
+                "borrower_type": to_bson(&borrower_type)?,
+                "borrower_id": borrower_id
+            },
+            options,
+        )
+        .await
+    }
+
+    /// Open reservations in a school, oldest first (requires ViewLibrary)
+    async fn reservations(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        book_id: Option<String>,
+        status: Option<ReservationStatus>,
+    ) -> Result<Vec<Reservation>> {
+        let db = ctx.data::<Database>()?;
+        require_library(ctx, db, &school_id, Permission::ViewLibrary).await?;
+
+        let mut filter = doc! { "school_id": &school_id };
+        match status {
+            Some(status) => filter.insert("status", to_bson(&status)?),
+            None => filter.insert("status", doc! { "$in": ["Waiting", "Ready"] }),
+        };
+        if let Some(book_id) = book_id {
+            filter.insert("book_id", book_id);
+        }
+
+        let options = FindOptions::builder()
+            .sort(doc! { "reserved_at": 1 })
+            .build();
+        collect(
+            &db.collection::<Reservation>("reservations"),
+            filter,
+            options,
+        )
+        .await
+    }
+
+    /// The caller's own reservations in a school, newest first
+    async fn my_reservations(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<Reservation>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_library(ctx, db, &school_id, Permission::ViewLibrary).await?;
+        let (borrower_type, borrower_id) = self_borrower(&member)?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "reserved_at": -1 })
+            .build();
+        collect(
+            &db.collection::<Reservation>("reservations"),
+            doc! {
+                "school_id": &school_id,
+                "borrower_type": to_bson(&borrower_type)?,
+                "borrower_id": borrower_id
+            },
+            options,
+        )
+        .await
+    }
+}
+
+/// Require the Library module for the school and a library permission
+pub(crate) async fn require_library(
+    ctx: &Context<'_>,
+    db: &Database,
+    school_id: &str,
+    permission: Permission,
+) -> Result<Member> {
+    ensure_feature(db, school_id, SchoolFeature::Library).await?;
+    get_graphql_context(ctx)?
+        .require_member_permission(db, school_id, permission)
+        .await
+}
+
+/// The borrower record for the caller's own membership: their student record
+/// for students, their membership for staff
+pub(crate) fn self_borrower(member: &Member) -> Result<(BorrowerType, String)> {
+    match member.role {
+        SchoolRole::Student => member
+            .student_id
+            .clone()
+            .map(|id| (BorrowerType::Student, id))
+            .ok_or_else(|| Error::new("Your membership is not linked to a student record")),
+        SchoolRole::Parent => Err(Error::new("Parents cannot borrow from the library")),
+        _ => member
+            .id
+            .map(|id| (BorrowerType::Staff, id.to_hex()))
+            .ok_or_else(|| Error::new("Member not found")),
+    }
+}
+
+/// Load a catalog entry that hasn't been deleted
+pub(crate) async fn find_book(db: &Database, book_id: &str) -> Result<Book> {
+    let oid = ObjectId::parse_str(book_id).map_err(|_| Error::new("Invalid book ID"))?;
+    db.collection::<Book>("books")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Book not found"))
+}
+
+/// Find a copy by the value scanned from its barcode or QR label
+pub(crate) async fn find_copy_by_barcode(
+    db: &Database,
+    school_id: &str,
+    code: &str,
+) -> Result<Option<BookCopy>> {
+    db.collection::<BookCopy>("book_copies")
+        .find_one(
+            doc! {
+                "school_id": school_id,
+                "barcode": code.trim().to_uppercase(),
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))
+}
+
+/// ISBN digits (and a trailing X), or empty when `value` isn't a plausible ISBN
+pub(crate) fn normalize_isbn(value: &str) -> String {
+    let isbn: String = value
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let valid = matches!(isbn.len(), 10 | 13)
+        && isbn
+            .char_indices()
+            .all(|(i, c)| c.is_ascii_digit() || (c == 'X' && i == isbn.len() - 1));
+    if valid {
+        isbn
+    } else {
+        String::new()
+    }
+}
+
+pub(crate) fn to_bson<T: serde::Serialize>(value: &T) -> Result<mongodb::bson::Bson> {
+    mongodb::bson::to_bson(value).map_err(|e| Error::new(e.to_string()))
+}
+
+/// Run a find and collect the results
+pub(crate) async fn collect<T>(
+    collection: &Collection<T>,
+    filter: Document,
+    options: FindOptions,
+) -> Result<Vec<T>>
+where
+    T: DeserializeOwned + Unpin + Send + Sync,
+{
+    let mut cursor = collection
+        .find(filter, options)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut items = Vec::new();
+    while let Some(item) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+/// Escape regex metacharacters so user input matches literally
+pub(crate) fn regex_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,12 @@
+use crate::models::library::{Book, BookCopy, Loan};
+use async_graphql::SimpleObject;
+
+/// What a scanned barcode, QR code or ISBN refers to
+#[derive(SimpleObject)]
+pub struct LibraryLookupType {
+    pub book: Option<Book>,
+    /// Set when the code is a copy's barcode
+    pub copy: Option<BookCopy>,
+    /// The copy's current loan, if it is checked out
+    pub active_loan: Option<Loan>,
+}
@@ -1,6 +1,8 @@
 use super::inputs::{AddMemberInput, RemoveMemberInput, UpdateMemberRoleInput};
 use crate::models::member::{Member, SchoolRole};
+use crate::utils::audit::record_change;
 use crate::utils::permissions::{can_manage_branch, can_manage_members};
+use crate::utils::subscription::ensure_staff_capacity;
 use async_graphql::*;
 use mongodb::{
     bson::{doc, oid::ObjectId},
@@ -75,14 +77,26 @@ impl MemberMutation {
             return Err(Error::new("User is already a member of this school"));
         }
 
+        ensure_staff_capacity(db, &input.school_id, school_role).await?;
+
         // Create new member with branch assignment
         let mut member = Member::new(input.user_id, input.school_id, school_role);
         member.branch_id = input.branch_id;
 
-        members_collection
+        let result = members_collection
             .insert_one(&member, None)
             .await
             .map_err(|e| Error::new(format!("Failed to add member: {}", e)))?;
+        member.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "Member",
+            member.id.map(|oid| oid.to_hex()),
+            Some(&member.school_id),
+            None,
+            Some(&member),
+        );
 
         Ok(member)
     }
@@ -133,6 +147,11 @@ impl MemberMutation {
             ));
         }
 
+        // Role changes need a recent MFA check
+        graphql_ctx
+            .require_step_up(db, Some(&member.school_id))
+            .await?;
+
         // Parse new role
         let new_role: SchoolRole = serde_json::from_str(&format!("\"{}\"", input.role))
             .map_err(|_| Error::new("Invalid role"))?;
@@ -159,6 +178,15 @@ impl MemberMutation {
             .map_err(|e| Error::new(format!("Failed to retrieve member: {}", e)))?
             .ok_or_else(|| Error::new("Member not found"))?;
 
+        record_change(
+            ctx,
+            "Member",
+            Some(input.member_id),
+            Some(&member.school_id),
+            Some(&member),
+            Some(&updated_member),
+        );
+
         Ok(updated_member)
     }
 
@@ -220,6 +248,15 @@ impl MemberMutation {
             .await
             .map_err(|e| Error::new(format!("Failed to remove member: {}", e)))?;
 
+        record_change(
+            ctx,
+            "Member",
+            Some(input.member_id),
+            Some(&member.school_id),
+            Some(&member),
+            None,
+        );
+
         Ok(true)
     }
 }
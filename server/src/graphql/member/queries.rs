@@ -40,6 +40,16 @@ impl MemberQuery {
         Ok(members)
     }
 
+    /// Get the current user's membership in the active school, if one is selected
+    async fn active_membership(&self, ctx: &Context<'_>) -> Result<Option<Member>> {
+        use crate::graphql::graphql_context::get_graphql_context;
+
+        let graphql_ctx = get_graphql_context(ctx)?;
+        graphql_ctx.require_auth()?;
+
+        Ok(graphql_ctx.active_member.clone())
+    }
+
     /// Get all members of a specific school
     async fn school_members(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Member>> {
         let db = ctx.data::<Database>()?;
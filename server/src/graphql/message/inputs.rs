@@ -0,0 +1,24 @@
+use async_graphql::InputObject;
+
+use crate::utils::common_types::Attachment;
+
+#[derive(InputObject)]
+pub struct StartThreadInput {
+    pub school_id: String,
+    /// Members to write to (besides the caller). One makes a direct thread;
+    /// several make a group, which only staff can start.
+    pub member_ids: Vec<String>,
+    pub subject: Option<String>,
+    /// Student the conversation is about
+    pub student_id: Option<String>,
+    /// First message
+    pub body: String,
+    pub attachments: Option<Vec<Attachment>>,
+}
+
+#[derive(InputObject)]
+pub struct SendMessageInput {
+    pub thread_id: String,
+    pub body: String,
+    pub attachments: Option<Vec<Attachment>>,
+}
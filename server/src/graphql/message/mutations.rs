@@ -0,0 +1,545 @@
+use super::inputs::{SendMessageInput, StartThreadInput};
+use super::queries::{
+    check_can_read, clean_attachments, find_message, find_thread, require_messaging,
+    require_moderator,
+};
+use crate::graphql::library::queries::{collect, to_bson};
+use crate::models::member::{Member, Permission};
+use crate::models::message::{Message, MessageThread, ThreadKind, ThreadParticipant};
+use crate::models::notification::NotificationKind;
+use crate::models::school::SchoolFeature;
+use crate::models::student::Student;
+use crate::utils::audit::record_change;
+use crate::utils::common_types::{Attachment, AuditInfo, SoftDelete};
+use crate::utils::features::FeatureGuard;
+use crate::utils::notifications::{notify, NotificationMessage};
+use crate::utils::permissions::is_staff;
+use crate::utils::realtime::{publish, RealtimeEvent};
+use crate::utils::soft_delete::{find_deleted_by_id, restore_by_id, soft_delete_by_id};
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::FindOptions,
+    Database,
+};
+
+/// Longest message body, in characters
+const MAX_MESSAGE_LENGTH: usize = 5000;
+
+/// Longest thread preview, in characters
+const PREVIEW_LENGTH: usize = 100;
+
+#[derive(Default)]
+pub struct MessageMutation;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Messaging)")]
+impl MessageMutation {
+    /// Start a conversation, or continue the existing direct thread with the
+    /// same person about the same student (requires SendMessages). Parents and
+    /// students can only write to staff.
+    async fn start_thread(
+        &self,
+        ctx: &Context<'_>,
+        input: StartThreadInput,
+    ) -> Result<MessageThread> {
+        let db = ctx.data::<Database>()?;
+        let member = require_messaging(ctx, db, &input.school_id, Permission::SendMessages).await?;
+        let sender_id = member.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        let mut member_ids = input.member_ids.clone();
+        member_ids.retain(|id| *id != sender_id);
+        member_ids.sort();
+        member_ids.dedup();
+        if member_ids.is_empty() {
+            return Err(Error::new("Choose who to write to"));
+        }
+        let others = find_members(db, &input.school_id, &member_ids).await?;
+
+        let kind = if others.len() == 1 {
+            ThreadKind::Direct
+        } else {
+            ThreadKind::Group
+        };
+        if !is_staff(&member.role) {
+            if kind == ThreadKind::Group {
+                return Err(Error::new("Only staff can start group conversations"));
+            }
+            if others.iter().any(|m| !is_staff(&m.role)) {
+                return Err(Error::new("You can only write to school staff"));
+            }
+        }
+        if let Some(student_id) = input.student_id.as_deref() {
+            check_student(db, &member, &input.school_id, student_id).await?;
+        }
+
+        let collection = db.collection::<MessageThread>("message_threads");
+        let existing = if kind == ThreadKind::Direct {
+            collection
+                .find_one(
+                    doc! {
+                        "school_id": &input.school_id,
+                        "kind": "Direct",
+                        "student_id": &input.student_id,
+                        "participants.user_id": { "$all": [&member.user_id, &others[0].user_id] },
+                        "soft_delete.is_deleted": { "$ne": true }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?
+        } else {
+            None
+        };
+
+        let thread = match existing {
+            Some(thread) => thread,
+            None => {
+                let mut participants = vec![participant(&member)];
+                participants.extend(others.iter().map(participant));
+                let mut thread = MessageThread {
+                    id: None,
+                    school_id: input.school_id.clone(),
+                    kind,
+                    subject: input
+                        .subject
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty()),
+                    student_id: input.student_id,
+                    participants,
+                    last_message_at: None,
+                    last_message_preview: None,
+                    locked: false,
+                    locked_by: None,
+                    audit: AuditInfo::new(Some(member.user_id.clone())),
+                    soft_delete: SoftDelete::default(),
+                };
+                let result = collection
+                    .insert_one(&thread, None)
+                    .await
+                    .map_err(|e| Error::new(format!("Failed to start thread: {}", e)))?;
+                thread.id = result.inserted_id.as_object_id();
+                thread
+            }
+        };
+
+        let attachments = clean_attachments(input.attachments, &member.user_id)?;
+        post_message(db, &thread, &member, input.body, attachments).await?;
+
+        find_thread(db, &thread.id.map(|id| id.to_hex()).unwrap_or_default()).await
+    }
+
+    /// Write in a thread the caller takes part in (requires SendMessages)
+    async fn send_message(&self, ctx: &Context<'_>, input: SendMessageInput) -> Result<Message> {
+        let db = ctx.data::<Database>()?;
+        let thread = find_thread(db, &input.thread_id).await?;
+        let member =
+            require_messaging(ctx, db, &thread.school_id, Permission::SendMessages).await?;
+        if thread.participant(&member.user_id).is_none() {
+            return Err(Error::new("Thread not found"));
+        }
+
+        let attachments = clean_attachments(input.attachments, &member.user_id)?;
+        post_message(db, &thread, &member, input.body, attachments).await
+    }
+
+    /// Mark everything in a thread as read for the caller (requires ViewMessages)
+    async fn mark_thread_read(&self, ctx: &Context<'_>, thread_id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let thread = find_thread(db, &thread_id).await?;
+        let member =
+            require_messaging(ctx, db, &thread.school_id, Permission::ViewMessages).await?;
+        if thread.participant(&member.user_id).is_none() {
+            return Err(Error::new("Thread not found"));
+        }
+
+        db.collection::<MessageThread>("message_threads")
+            .update_one(
+                doc! { "_id": thread.id, "participants.user_id": &member.user_id },
+                doc! { "$set": { "participants.$.last_read_at": DateTime::now() } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update thread: {}", e)))?;
+
+        Ok(true)
+    }
+
+    /// Add people to a group thread (staff participants and school leaders;
+    /// requires SendMessages)
+    async fn add_thread_participants(
+        &self,
+        ctx: &Context<'_>,
+        thread_id: String,
+        member_ids: Vec<String>,
+    ) -> Result<MessageThread> {
+        let db = ctx.data::<Database>()?;
+        let thread = find_thread(db, &thread_id).await?;
+        let member =
+            require_messaging(ctx, db, &thread.school_id, Permission::SendMessages).await?;
+        check_can_read(&member, &thread)?;
+        if !is_staff(&member.role) {
+            return Err(Error::new("Only staff can add people to a conversation"));
+        }
+        if thread.kind != ThreadKind::Group {
+            return Err(Error::new(
+                "People can only be added to group conversations",
+            ));
+        }
+
+        let new_members: Vec<Member> = find_members(db, &thread.school_id, &member_ids)
+            .await?
+            .into_iter()
+            .filter(|m| thread.participant(&m.user_id).is_none())
+            .collect();
+        if !new_members.is_empty() {
+            let participants: Vec<ThreadParticipant> =
+                new_members.iter().map(participant).collect();
+            db.collection::<MessageThread>("message_threads")
+                .update_one(
+                    doc! { "_id": thread.id },
+                    doc! {
+                        "$push": { "participants": { "$each": to_bson(&participants)? } },
+                        "$set": {
+                            "audit.updated_at": DateTime::now(),
+                            "audit.updated_by": &member.user_id,
+                        }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(format!("Failed to update thread: {}", e)))?;
+        }
+
+        find_thread(db, &thread_id).await
+    }
+
+    /// Leave a group thread (requires ViewMessages)
+    async fn leave_thread(&self, ctx: &Context<'_>, thread_id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let thread = find_thread(db, &thread_id).await?;
+        let member =
+            require_messaging(ctx, db, &thread.school_id, Permission::ViewMessages).await?;
+        if thread.participant(&member.user_id).is_none() {
+            return Err(Error::new("Thread not found"));
+        }
+        if thread.kind != ThreadKind::Group {
+            return Err(Error::new("You can only leave group conversations"));
+        }
+
+        db.collection::<MessageThread>("message_threads")
+            .update_one(
+                doc! { "_id": thread.id },
+                doc! { "$pull": { "participants": { "user_id": &member.user_id } } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to leave thread: {}", e)))?;
+
+        Ok(true)
+    }
+
+    /// Delete one of the caller's own messages (requires SendMessages)
+    async fn delete_message(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let message = find_message(db, &id).await?;
+        let member =
+            require_messaging(ctx, db, &message.school_id, Permission::SendMessages).await?;
+        if message.sender_id != member.user_id {
+            return Err(Error::new("You can only delete your own messages"));
+        }
+
+        let oid = message.id.ok_or_else(|| Error::new("Message not found"))?;
+        soft_delete_by_id(&db.collection::<Message>("messages"), oid, &member.user_id).await?;
+
+        Ok(true)
+    }
+
+    /// Restore one of the caller's own deleted messages (requires SendMessages)
+    async fn restore_message(&self, ctx: &Context<'_>, id: String) -> Result<Message> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<Message>("messages");
+        let message = find_deleted_by_id(&collection, &id, "message").await?;
+        let member =
+            require_messaging(ctx, db, &message.school_id, Permission::SendMessages).await?;
+        if message.sender_id != member.user_id {
+            return Err(Error::new("You can only restore your own messages"));
+        }
+
+        let oid = message.id.ok_or_else(|| Error::new("Message not found"))?;
+        restore_by_id(&collection, oid).await?;
+
+        find_message(db, &id).await
+    }
+
+    /// Report a message to school leaders (requires ViewMessages)
+    async fn report_message(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let message = find_message(db, &id).await?;
+        let thread = find_thread(db, &message.thread_id).await?;
+        let member =
+            require_messaging(ctx, db, &message.school_id, Permission::ViewMessages).await?;
+        if thread.participant(&member.user_id).is_none() {
+            return Err(Error::new("Message not found"));
+        }
+        if message.sender_id == member.user_id {
+            return Err(Error::new("You cannot report your own message"));
+        }
+
+        db.collection::<Message>("messages")
+            .update_one(
+                doc! { "_id": message.id },
+                doc! { "$addToSet": { "reported_by": &member.user_id } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to report message: {}", e)))?;
+
+        Ok(true)
+    }
+
+    /// Hide or restore a message (school leaders only). Hidden messages stay
+    /// in the thread with their content removed for participants.
+    async fn moderate_message(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        hidden: bool,
+        reason: Option<String>,
+    ) -> Result<Message> {
+        let db = ctx.data::<Database>()?;
+        let message = find_message(db, &id).await?;
+        let member = require_moderator(ctx, db, &message.school_id).await?;
+
+        let update = if hidden {
+            doc! { "$set": {
+                "hidden": true,
+                "hidden_by": &member.user_id,
+                "hidden_reason": reason,
+                "reported_by": [],
+            } }
+        } else {
+            doc! {
+                "$set": { "hidden": false, "reported_by": [] },
+                "$unset": { "hidden_by": "", "hidden_reason": "" }
+            }
+        };
+        db.collection::<Message>("messages")
+            .update_one(doc! { "_id": message.id }, update, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to moderate message: {}", e)))?;
+
+        let updated = find_message(db, &id).await?;
+        record_change(
+            ctx,
+            "Message",
+            Some(id),
+            Some(&updated.school_id),
+            Some(&message),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Lock or unlock a thread (school leaders only). Nobody can write in a
+    /// locked thread.
+    async fn lock_thread(
+        &self,
+        ctx: &Context<'_>,
+        thread_id: String,
+        locked: bool,
+    ) -> Result<MessageThread> {
+        let db = ctx.data::<Database>()?;
+        let thread = find_thread(db, &thread_id).await?;
+        let member = require_moderator(ctx, db, &thread.school_id).await?;
+
+        let locked_by = locked.then(|| member.user_id.clone());
+        db.collection::<MessageThread>("message_threads")
+            .update_one(
+                doc! { "_id": thread.id },
+                doc! { "$set": {
+                    "locked": locked,
+                    "locked_by": locked_by,
+                    "audit.updated_at": DateTime::now(),
+                    "audit.updated_by": &member.user_id,
+                } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update thread: {}", e)))?;
+
+        let updated = find_thread(db, &thread_id).await?;
+        record_change(
+            ctx,
+            "MessageThread",
+            Some(thread_id),
+            Some(&updated.school_id),
+            Some(&thread),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+}
+
+fn participant(member: &Member) -> ThreadParticipant {
+    ThreadParticipant {
+        user_id: member.user_id.clone(),
+        member_id: member.id.map(|id| id.to_hex()).unwrap_or_default(),
+        role: member.role,
+        last_read_at: None,
+    }
+}
+
+/// Load active members of a school; fails if any is missing
+async fn find_members(
+    db: &Database,
+    school_id: &str,
+    member_ids: &[String],
+) -> Result<Vec<Member>> {
+    let oids = member_ids
+        .iter()
+        .map(|id| ObjectId::parse_str(id).map_err(|_| Error::new("Invalid member ID")))
+        .collect::<Result<Vec<_>>>()?;
+    let members = collect(
+        &db.collection::<Member>("members"),
+        doc! {
+            "_id": { "$in": &oids },
+            "school_id": school_id,
+            "status": "Active",
+            "soft_delete.is_deleted": false
+        },
+        FindOptions::default(),
+    )
+    .await?;
+    if members.len() != oids.len() {
+        return Err(Error::new("Member not found"));
+    }
+    Ok(members)
+}
+
+/// The student must be in the school; parents can only name their own children
+async fn check_student(
+    db: &Database,
+    member: &Member,
+    school_id: &str,
+    student_id: &str,
+) -> Result<()> {
+    if !is_staff(&member.role)
+        && !member.parent_of.iter().any(|id| id == student_id)
+        && member.student_id.as_deref() != Some(student_id)
+    {
+        return Err(Error::new("You can only write about your own children"));
+    }
+    let oid = ObjectId::parse_str(student_id).map_err(|_| Error::new("Invalid student ID"))?;
+    db.collection::<Student>("students")
+        .find_one(
+            doc! {
+                "_id": oid,
+                "school_id": school_id,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Student not found"))?;
+    Ok(())
+}
+
+/// Save a message, bump the thread and notify the other participants
+async fn post_message(
+    db: &Database,
+    thread: &MessageThread,
+    sender: &Member,
+    body: String,
+    attachments: Vec<Attachment>,
+) -> Result<Message> {
+    if thread.locked {
+        return Err(Error::new(
+            "This conversation has been locked by the school",
+        ));
+    }
+    let body = body.trim().to_string();
+    if body.is_empty() && attachments.is_empty() {
+        return Err(Error::new("Write a message or add an attachment"));
+    }
+    if body.chars().count() > MAX_MESSAGE_LENGTH {
+        return Err(Error::new(format!(
+            "Messages cannot be longer than {} characters",
+            MAX_MESSAGE_LENGTH
+        )));
+    }
+    let thread_id = thread.id.map(|id| id.to_hex()).unwrap_or_default();
+
+    let now = DateTime::now();
+    let mut message = Message {
+        id: None,
+        thread_id: thread_id.clone(),
+        school_id: thread.school_id.clone(),
+        sender_id: sender.user_id.clone(),
+        body,
+        attachments,
+        sent_at: now,
+        hidden: false,
+        hidden_by: None,
+        hidden_reason: None,
+        reported_by: vec![],
+        soft_delete: SoftDelete::default(),
+    };
+    let result = db
+        .collection::<Message>("messages")
+        .insert_one(&message, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to send message: {}", e)))?;
+    message.id = result.inserted_id.as_object_id();
+
+    let preview = if message.body.is_empty() {
+        "Attachment".to_string()
+    } else {
+        message.body.chars().take(PREVIEW_LENGTH).collect()
+    };
+    db.collection::<MessageThread>("message_threads")
+        .update_one(
+            doc! { "_id": thread.id, "participants.user_id": &sender.user_id },
+            doc! { "$set": {
+                "last_message_at": now,
+                "last_message_preview": &preview,
+                "participants.$.last_read_at": now,
+            } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to update thread: {}", e)))?;
+
+    let recipients: Vec<String> = thread
+        .participants
+        .iter()
+        .filter(|p| p.user_id != sender.user_id)
+        .map(|p| p.user_id.clone())
+        .collect();
+    let notification = NotificationMessage {
+        school_id: Some(&thread.school_id),
+        kind: NotificationKind::Message,
+        title: thread
+            .subject
+            .clone()
+            .unwrap_or_else(|| "New message".to_string()),
+        body: preview,
+        link: Some(format!("thread:{}", thread_id)),
+    };
+    notify(db, &recipients, &notification)
+        .await
+        .map_err(|e| Error::new(format!("Failed to notify participants: {}", e)))?;
+
+    publish(RealtimeEvent::MessagePosted {
+        participant_ids: thread
+            .participants
+            .iter()
+            .map(|p| p.user_id.clone())
+            .collect(),
+        message: message.clone(),
+    });
+
+    Ok(message)
+}
@@ -0,0 +1,282 @@
+use super::types::ThreadSummaryType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::library::queries::collect;
+use crate::models::member::{Member, Permission};
+use crate::models::message::{Message, MessageThread};
+use crate::models::school::SchoolFeature;
+use crate::utils::common_types::Attachment;
+use crate::utils::features::FeatureGuard;
+use crate::utils::permissions::is_school_leader;
+use crate::utils::soft_delete::find_deleted;
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::FindOptions,
+    Database,
+};
+
+/// Most attachments on one message
+const MAX_ATTACHMENTS: usize = 10;
+
+#[derive(Default)]
+pub struct MessageQuery;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Messaging)")]
+impl MessageQuery {
+    /// The caller's threads in a school, most recent first (requires ViewMessages)
+    async fn my_threads(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        limit: Option<i64>,
+        offset: Option<u64>,
+    ) -> Result<Vec<ThreadSummaryType>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_messaging(ctx, db, &school_id, Permission::ViewMessages).await?;
+
+        let threads = collect(
+            &db.collection::<MessageThread>("message_threads"),
+            doc! {
+                "school_id": &school_id,
+                "participants.user_id": &member.user_id,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            FindOptions::builder()
+                .sort(doc! { "last_message_at": -1 })
+                .limit(limit.unwrap_or(50))
+                .skip(offset)
+                .build(),
+        )
+        .await?;
+
+        let mut summaries = Vec::new();
+        for thread in threads {
+            let unread_count = unread_count(db, &thread, &member.user_id).await?;
+            summaries.push(ThreadSummaryType {
+                thread,
+                unread_count,
+            });
+        }
+        Ok(summaries)
+    }
+
+    /// A thread the caller takes part in; school leaders can open any thread
+    /// (requires ViewMessages)
+    async fn thread(&self, ctx: &Context<'_>, id: String) -> Result<MessageThread> {
+        let db = ctx.data::<Database>()?;
+        let thread = find_thread(db, &id).await?;
+        let member =
+            require_messaging(ctx, db, &thread.school_id, Permission::ViewMessages).await?;
+        check_can_read(&member, &thread)?;
+        Ok(thread)
+    }
+
+    /// Messages in a thread, newest first; pass `before` (RFC 3339) to page back
+    /// (requires ViewMessages)
+    async fn thread_messages(
+        &self,
+        ctx: &Context<'_>,
+        thread_id: String,
+        before: Option<String>,
+        limit: Option<i64>,
+    ) -> Result<Vec<Message>> {
+        let db = ctx.data::<Database>()?;
+        let thread = find_thread(db, &thread_id).await?;
+        let member =
+            require_messaging(ctx, db, &thread.school_id, Permission::ViewMessages).await?;
+        check_can_read(&member, &thread)?;
+
+        let mut filter = doc! {
+            "thread_id": &thread_id,
+            "soft_delete.is_deleted": { "$ne": true }
+        };
+        if let Some(before) = before {
+            let before = chrono::DateTime::parse_from_rfc3339(&before)
+                .map_err(|_| Error::new("Invalid date. Use RFC 3339"))?;
+            filter.insert(
+                "sent_at",
+                doc! { "$lt": DateTime::from_millis(before.timestamp_millis()) },
+            );
+        }
+
+        let messages = collect(
+            &db.collection::<Message>("messages"),
+            filter,
+            FindOptions::builder()
+                .sort(doc! { "sent_at": -1 })
+                .limit(limit.unwrap_or(50))
+                .build(),
+        )
+        .await?;
+
+        let moderator = is_school_leader(&member.role);
+        Ok(messages
+            .into_iter()
+            .map(|m| if moderator { m } else { m.redacted() })
+            .collect())
+    }
+
+    /// Every thread in a school, for moderation (school leaders only)
+    async fn school_threads(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        limit: Option<i64>,
+        offset: Option<u64>,
+    ) -> Result<Vec<MessageThread>> {
+        let db = ctx.data::<Database>()?;
+        require_moderator(ctx, db, &school_id).await?;
+
+        collect(
+            &db.collection::<MessageThread>("message_threads"),
+            doc! { "school_id": &school_id, "soft_delete.is_deleted": { "$ne": true } },
+            FindOptions::builder()
+                .sort(doc! { "last_message_at": -1 })
+                .limit(limit.unwrap_or(50))
+                .skip(offset)
+                .build(),
+        )
+        .await
+    }
+
+    /// The caller's deleted messages in a school (the trash), most recently
+    /// deleted first (requires SendMessages)
+    async fn deleted_messages(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Message>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_messaging(ctx, db, &school_id, Permission::SendMessages).await?;
+
+        find_deleted(
+            &db.collection::<Message>("messages"),
+            doc! { "school_id": &school_id, "sender_id": &member.user_id },
+        )
+        .await
+    }
+
+    /// Messages reported by participants, newest first (school leaders only)
+    async fn reported_messages(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<Message>> {
+        let db = ctx.data::<Database>()?;
+        require_moderator(ctx, db, &school_id).await?;
+
+        collect(
+            &db.collection::<Message>("messages"),
+            doc! {
+                "school_id": &school_id,
+                "reported_by.0": { "$exists": true },
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            FindOptions::builder()
+                .sort(doc! { "sent_at": -1 })
+                .limit(200)
+                .build(),
+        )
+        .await
+    }
+}
+
+/// Require the Messaging module for the school and a messaging permission
+pub(crate) async fn require_messaging(
+    ctx: &Context<'_>,
+    db: &Database,
+    school_id: &str,
+    permission: Permission,
+) -> Result<Member> {
+    ensure_feature(db, school_id, SchoolFeature::Messaging).await?;
+    get_graphql_context(ctx)?
+        .require_member_permission(db, school_id, permission)
+        .await
+}
+
+/// Require a school leader (Owner, Director, DeputyDirector) for moderation
+pub(crate) async fn require_moderator(
+    ctx: &Context<'_>,
+    db: &Database,
+    school_id: &str,
+) -> Result<Member> {
+    let member = require_messaging(ctx, db, school_id, Permission::ViewMessages).await?;
+    if !is_school_leader(&member.role) {
+        return Err(Error::new("Only school leaders can moderate messages"));
+    }
+    Ok(member)
+}
+
+/// Participants read their threads; school leaders read every thread
+pub(crate) fn check_can_read(member: &Member, thread: &MessageThread) -> Result<()> {
+    if thread.participant(&member.user_id).is_none() && !is_school_leader(&member.role) {
+        return Err(Error::new("Thread not found"));
+    }
+    Ok(())
+}
+
+/// Load a thread that hasn't been deleted
+pub(crate) async fn find_thread(db: &Database, thread_id: &str) -> Result<MessageThread> {
+    let oid = ObjectId::parse_str(thread_id).map_err(|_| Error::new("Invalid thread ID"))?;
+    db.collection::<MessageThread>("message_threads")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Thread not found"))
+}
+
+/// Load a message that hasn't been deleted
+pub(crate) async fn find_message(db: &Database, message_id: &str) -> Result<Message> {
+    let oid = ObjectId::parse_str(message_id).map_err(|_| Error::new("Invalid message ID"))?;
+    db.collection::<Message>("messages")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Message not found"))
+}
+
+/// Check uploaded files and stamp them with the uploader
+pub(crate) fn clean_attachments(
+    attachments: Option<Vec<Attachment>>,
+    user_id: &str,
+) -> Result<Vec<Attachment>> {
+    let attachments = attachments.unwrap_or_default();
+    if attachments.len() > MAX_ATTACHMENTS {
+        return Err(Error::new(format!(
+            "At most {} attachments can be added",
+            MAX_ATTACHMENTS
+        )));
+    }
+    let now = DateTime::now();
+    attachments
+        .into_iter()
+        .map(|mut attachment| {
+            if attachment.url.trim().is_empty() || attachment.file_name.trim().is_empty() {
+                return Err(Error::new("Attachments need a URL and a file name"));
+            }
+            attachment.uploaded_at = Some(now);
+            attachment.uploaded_by = Some(user_id.to_string());
+            Ok(attachment)
+        })
+        .collect()
+}
+
+/// Messages from others the user hasn't read yet
+async fn unread_count(db: &Database, thread: &MessageThread, user_id: &str) -> Result<i32> {
+    let mut filter = doc! {
+        "thread_id": thread.id.map(|id| id.to_hex()).unwrap_or_default(),
+        "sender_id": { "$ne": user_id },
+        "soft_delete.is_deleted": { "$ne": true }
+    };
+    if let Some(read_at) = thread.participant(user_id).and_then(|p| p.last_read_at) {
+        filter.insert("sent_at", doc! { "$gt": read_at });
+    }
+    db.collection::<Message>("messages")
+        .count_documents(filter, None)
+        .await
+        .map(|n| n as i32)
+        .map_err(|e| Error::new(e.to_string()))
+}
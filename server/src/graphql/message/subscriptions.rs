@@ -0,0 +1,46 @@
+use super::queries::require_messaging;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::member::Permission;
+use crate::models::message::Message;
+use crate::models::school::SchoolFeature;
+use crate::utils::features::FeatureGuard;
+use crate::utils::realtime::{member_events, RealtimeEvent};
+use async_graphql::*;
+use futures::Stream;
+use mongodb::Database;
+
+#[derive(Default)]
+pub struct MessageSubscription;
+
+#[Subscription(guard = "FeatureGuard::new(SchoolFeature::Messaging)")]
+impl MessageSubscription {
+    /// New messages in the caller's threads in a school, as they are posted
+    /// (requires ViewMessages)
+    async fn message_received(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<impl Stream<Item = Message>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_messaging(ctx, db, &school_id, Permission::ViewMessages).await?;
+        let user = get_graphql_context(ctx)?.require_auth()?.clone();
+
+        Ok(member_events(
+            db.clone(),
+            user,
+            school_id.clone(),
+            Some(Permission::ViewMessages),
+            move |event| match event {
+                RealtimeEvent::MessagePosted {
+                    participant_ids,
+                    message,
+                } if message.school_id == school_id
+                    && participant_ids.contains(&member.user_id) =>
+                {
+                    Some(message.redacted())
+                }
+                _ => None,
+            },
+        ))
+    }
+}
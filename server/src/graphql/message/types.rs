@@ -0,0 +1,9 @@
+use crate::models::message::MessageThread;
+use async_graphql::SimpleObject;
+
+/// A thread with the caller's unread count
+#[derive(SimpleObject)]
+pub struct ThreadSummaryType {
+    pub thread: MessageThread,
+    pub unread_count: i32,
+}
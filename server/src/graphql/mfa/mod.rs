@@ -0,0 +1,4 @@
+pub mod mutations;
+pub mod types;
+
+pub use mutations::MfaMutation;
@@ -0,0 +1,356 @@
+use super::types::MfaEnrollmentType;
+use crate::graphql::graphql_context::{get_graphql_context, AuthUser};
+use crate::models::member::SchoolRole;
+use crate::models::school::School;
+use crate::models::session::Session;
+use crate::models::user::{User, MAX_MFA_ATTEMPTS, MFA_LOCKOUT_MINUTES};
+use crate::utils::codes::{hash_token, random_code};
+use crate::utils::totp;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+    Database,
+};
+
+/// Issuer name shown in authenticator apps
+const MFA_ISSUER: &str = "SMS";
+/// Number of recovery codes issued at enrollment
+const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Default)]
+pub struct MfaMutation;
+
+#[Object]
+impl MfaMutation {
+    /// Start MFA enrollment: returns a new TOTP secret for an authenticator app.
+    /// Enrollment completes with `confirm_mfa_enrollment`.
+    async fn begin_mfa_enrollment(&self, ctx: &Context<'_>) -> Result<MfaEnrollmentType> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+        let user = find_user(db, &auth_user.id).await?;
+
+        if user.mfa.enabled {
+            return Err(Error::new(
+                "MFA is already enabled. Disable it first to re-enroll.",
+            ));
+        }
+
+        let secret = totp::generate_secret();
+        update_user(db, &user, doc! { "mfa.pending_secret": &secret }).await?;
+
+        let account = user.email.clone().unwrap_or_else(|| user.username.clone());
+        Ok(MfaEnrollmentType {
+            otpauth_uri: totp::provisioning_uri(&secret, &account, MFA_ISSUER),
+            secret,
+        })
+    }
+
+    /// Finish MFA enrollment with a code from the authenticator app.
+    /// Returns recovery codes; they are shown only once.
+    async fn confirm_mfa_enrollment(&self, ctx: &Context<'_>, code: String) -> Result<Vec<String>> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+        let user = find_user(db, &auth_user.id).await?;
+
+        let secret = user
+            .mfa
+            .pending_secret
+            .clone()
+            .ok_or_else(|| Error::new("No MFA enrollment in progress"))?;
+        let step =
+            totp::verify_code(&secret, &code).ok_or_else(|| Error::new("Invalid MFA code"))?;
+
+        let (recovery_codes, hashes) = generate_recovery_codes();
+        update_user(
+            db,
+            &user,
+            doc! {
+                "mfa.enabled": true,
+                "mfa.secret": &secret,
+                "mfa.pending_secret": null,
+                "mfa.recovery_code_hashes": hashes,
+                "mfa.enrolled_at": DateTime::now(),
+                "mfa.last_used_step": step,
+            },
+        )
+        .await?;
+        mark_session_verified(db, auth_user).await?;
+
+        Ok(recovery_codes)
+    }
+
+    /// Step-up: verify an authenticator or recovery code before sensitive actions.
+    /// The verification lasts for a few minutes in the current session.
+    async fn verify_mfa(&self, ctx: &Context<'_>, code: String) -> Result<bool> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+        let user = find_user(db, &auth_user.id).await?;
+
+        check_code(db, &user, &code).await?;
+        mark_session_verified(db, auth_user).await?;
+
+        Ok(true)
+    }
+
+    /// Replace all recovery codes (requires a current MFA code)
+    async fn regenerate_mfa_recovery_codes(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+    ) -> Result<Vec<String>> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+        let user = find_user(db, &auth_user.id).await?;
+
+        check_code(db, &user, &code).await?;
+
+        let (recovery_codes, hashes) = generate_recovery_codes();
+        update_user(db, &user, doc! { "mfa.recovery_code_hashes": hashes }).await?;
+
+        Ok(recovery_codes)
+    }
+
+    /// Turn off MFA (requires a current MFA code)
+    async fn disable_mfa(&self, ctx: &Context<'_>, code: String) -> Result<bool> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+        let user = find_user(db, &auth_user.id).await?;
+
+        check_code(db, &user, &code).await?;
+
+        update_user(
+            db,
+            &user,
+            doc! {
+                "mfa.enabled": false,
+                "mfa.secret": null,
+                "mfa.pending_secret": null,
+                "mfa.recovery_code_hashes": [],
+                "mfa.enrolled_at": null,
+                "mfa.last_used_step": null,
+            },
+        )
+        .await?;
+
+        Ok(true)
+    }
+
+    /// Set a school's MFA policy (Owner only).
+    /// `roles` defaults to the school's current list (leaders and accountants).
+    async fn set_school_mfa_policy(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        require_mfa: bool,
+        roles: Option<Vec<SchoolRole>>,
+    ) -> Result<School> {
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let auth_user = graphql_ctx.require_auth()?;
+        let db = ctx.data::<Database>()?;
+
+        graphql_ctx
+            .require_member_role(db, &school_id, &[SchoolRole::Owner])
+            .await?;
+
+        // Owners must have MFA themselves before requiring it of others
+        let user = find_user(db, &auth_user.id).await?;
+        if require_mfa && !user.mfa.enabled {
+            return Err(Error::new(
+                "Enroll in MFA yourself before requiring it for your school",
+            ));
+        }
+        graphql_ctx.require_step_up(db, Some(&school_id)).await?;
+
+        let school_oid =
+            ObjectId::parse_str(&school_id).map_err(|_| Error::new("Invalid school ID"))?;
+        let mut set = doc! {
+            "settings.require_mfa": require_mfa,
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &auth_user.id,
+        };
+        if let Some(roles) = roles {
+            set.insert(
+                "settings.mfa_required_roles",
+                mongodb::bson::to_bson(&roles)?,
+            );
+        }
+
+        let collection = db.collection::<School>("schools");
+        collection
+            .update_one(doc! { "_id": school_oid }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update school: {}", e)))?;
+
+        collection
+            .find_one(doc! { "_id": school_oid }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to retrieve school: {}", e)))?
+            .ok_or_else(|| Error::new("School not found"))
+    }
+}
+
+/// Load a user by ID
+async fn find_user(db: &Database, user_id: &str) -> Result<User> {
+    let oid = ObjectId::parse_str(user_id).map_err(|_| Error::new("Invalid user ID"))?;
+
+    db.collection::<User>("users")
+        .find_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))?
+        .ok_or_else(|| Error::new("User not found"))
+}
+
+/// Apply a `$set` to a user
+async fn update_user(db: &Database, user: &User, mut set: mongodb::bson::Document) -> Result<()> {
+    set.insert("audit.updated_at", DateTime::now());
+
+    db.collection::<User>("users")
+        .update_one(doc! { "_id": user.id }, doc! { "$set": set }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to update MFA settings: {}", e)))?;
+
+    Ok(())
+}
+
+/// Accept a TOTP code (not reused) or an unused recovery code, consuming it.
+/// After `MAX_MFA_ATTEMPTS` wrong codes in a row, checks are refused for
+/// `MFA_LOCKOUT_MINUTES`.
+async fn check_code(db: &Database, user: &User, code: &str) -> Result<()> {
+    let secret = match (user.mfa.enabled, user.mfa.secret.as_deref()) {
+        (true, Some(secret)) => secret,
+        _ => return Err(Error::new("MFA is not enabled")),
+    };
+    if let Some(locked_until) = user.mfa.locked_until {
+        if locked_until > DateTime::now() {
+            return Err(locked_error(locked_until));
+        }
+    }
+
+    if let Some(step) = totp::verify_code(secret, code) {
+        if user.mfa.last_used_step.is_some_and(|last| step <= last) {
+            return Err(Error::new(
+                "This MFA code was already used. Wait for the next one.",
+            ));
+        }
+        return update_user(
+            db,
+            user,
+            doc! {
+                "mfa.last_used_step": step,
+                "mfa.failed_attempts": 0,
+                "mfa.locked_until": null,
+            },
+        )
+        .await;
+    }
+
+    let code_hash = hash_token(&normalize_recovery_code(code));
+    if user.mfa.recovery_code_hashes.contains(&code_hash) {
+        db.collection::<User>("users")
+            .update_one(
+                doc! { "_id": user.id },
+                doc! {
+                    "$pull": { "mfa.recovery_code_hashes": &code_hash },
+                    "$set": {
+                        "mfa.failed_attempts": 0,
+                        "mfa.locked_until": null,
+                        "audit.updated_at": DateTime::now()
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update MFA settings: {}", e)))?;
+        return Ok(());
+    }
+
+    Err(record_failed_attempt(db, user).await?)
+}
+
+/// Count a wrong MFA code, locking MFA checks once the limit is reached.
+/// Returns the error to show the caller.
+async fn record_failed_attempt(db: &Database, user: &User) -> Result<Error> {
+    let collection = db.collection::<User>("users");
+    let options = FindOneAndUpdateOptions::builder()
+        .return_document(ReturnDocument::After)
+        .build();
+    let failed_attempts = collection
+        .find_one_and_update(
+            doc! { "_id": user.id },
+            doc! { "$inc": { "mfa.failed_attempts": 1 } },
+            options,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to update MFA settings: {}", e)))?
+        .map(|u| u.mfa.failed_attempts)
+        .unwrap_or(MAX_MFA_ATTEMPTS);
+
+    if failed_attempts < MAX_MFA_ATTEMPTS {
+        return Ok(Error::new(format!(
+            "Invalid MFA code. {} attempt(s) remaining.",
+            MAX_MFA_ATTEMPTS - failed_attempts
+        )));
+    }
+
+    let locked_until =
+        DateTime::from_millis(DateTime::now().timestamp_millis() + MFA_LOCKOUT_MINUTES * 60 * 1000);
+    update_user(
+        db,
+        user,
+        doc! { "mfa.failed_attempts": 0, "mfa.locked_until": locked_until },
+    )
+    .await?;
+    Ok(locked_error(locked_until))
+}
+
+fn locked_error(locked_until: DateTime) -> Error {
+    let remaining_ms = locked_until.timestamp_millis() - DateTime::now().timestamp_millis();
+    let minutes = ((remaining_ms + 59_999) / 60_000).max(1);
+    Error::new(format!(
+        "Too many incorrect MFA codes. Try again in {} minute(s).",
+        minutes
+    ))
+}
+
+/// Record a successful MFA check on the caller's session
+async fn mark_session_verified(db: &Database, auth_user: &AuthUser) -> Result<()> {
+    let Some(session_oid) = auth_user
+        .session_id
+        .as_deref()
+        .and_then(|id| ObjectId::parse_str(id).ok())
+    else {
+        return Err(Error::new("Please sign in again to continue"));
+    };
+
+    db.collection::<Session>("sessions")
+        .update_one(
+            doc! { "_id": session_oid, "user_id": &auth_user.id },
+            doc! { "$set": { "mfa_verified_at": DateTime::now() } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to update session: {}", e)))?;
+
+    Ok(())
+}
+
+/// Generate recovery codes ("XXXXX-XXXXX") and their hashes
+fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| format!("{}-{}", random_code(5), random_code(5)))
+        .collect();
+    let hashes = codes
+        .iter()
+        .map(|c| hash_token(&normalize_recovery_code(c)))
+        .collect();
+    (codes, hashes)
+}
+
+/// Recovery codes are compared without dashes, spaces or case
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
@@ -0,0 +1,10 @@
+use async_graphql::SimpleObject;
+
+/// Secret for setting up an authenticator app
+#[derive(SimpleObject)]
+pub struct MfaEnrollmentType {
+    /// Base32 secret for manual entry
+    pub secret: String,
+    /// otpauth:// URI to render as a QR code
+    pub otpauth_uri: String,
+}
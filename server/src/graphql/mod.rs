@@ -1,20 +1,41 @@
 // GraphQL module - modular domain-based structure
+pub mod announcement;
 pub mod attendance;
+pub mod audit;
+pub mod billing;
 pub mod branch;
 pub mod class;
 pub mod common;
+pub mod event;
+pub mod file;
+pub mod finance;
 pub mod grade;
 pub mod grade_level;
 pub mod graphql_context;
+pub mod guardian;
 pub mod hr;
+pub mod id_card;
+pub mod inventory;
+pub mod invitation;
+pub mod library;
 pub mod member;
+pub mod message;
+pub mod mfa;
+pub mod notification;
+pub mod parent;
+pub mod role;
 pub mod schema;
 pub mod school;
+pub mod session;
 pub mod student;
+pub mod student_document;
+pub mod student_portal;
 pub mod subject;
+pub mod teacher;
+pub mod transport;
 pub mod user;
 
-use async_graphql::MergedObject;
+use async_graphql::{MergedObject, MergedSubscription};
 
 // Re-export common types for convenience
 pub use common::*;
@@ -33,6 +54,24 @@ pub struct QueryRoot(
     member::MemberQuery,
     user::UserQuery,
     hr::HRQuery,
+    teacher::TeacherQuery,
+    parent::ParentQuery,
+    student_portal::StudentPortalQuery,
+    invitation::InvitationQuery,
+    session::SessionQuery,
+    role::RoleQuery,
+    audit::AuditQuery,
+    billing::BillingQuery,
+    library::LibraryQuery,
+    transport::TransportQuery,
+    inventory::InventoryQuery,
+    event::EventQuery,
+    notification::NotificationQuery,
+    file::FileQuery,
+    announcement::AnnouncementQuery,
+    message::MessageQuery,
+    student_document::StudentDocumentQuery,
+    id_card::IdCardQuery,
 );
 
 // Merged Mutation combining all domain mutations
@@ -48,4 +87,32 @@ pub struct MutationRoot(
     grade_level::GradeLevelMutation,
     member::MemberMutation,
     hr::HRMutation,
+    invitation::InvitationMutation,
+    guardian::GuardianMutation,
+    session::SessionMutation,
+    user::UserMutation,
+    mfa::MfaMutation,
+    role::RoleMutation,
+    billing::BillingMutation,
+    finance::FinanceMutation,
+    library::LibraryMutation,
+    transport::TransportMutation,
+    inventory::InventoryMutation,
+    event::EventMutation,
+    notification::NotificationMutation,
+    file::FileMutation,
+    announcement::AnnouncementMutation,
+    message::MessageMutation,
+    student_document::StudentDocumentMutation,
+    id_card::IdCardMutation,
+);
+
+// Merged Subscription combining all domain subscriptions
+#[derive(MergedSubscription, Default)]
+pub struct SubscriptionRoot(
+    school::SchoolSubscription,
+    attendance::AttendanceSubscription,
+    finance::FinanceSubscription,
+    announcement::AnnouncementSubscription,
+    message::MessageSubscription,
 );
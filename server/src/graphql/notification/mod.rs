@@ -0,0 +1,5 @@
+pub mod mutations;
+pub mod queries;
+
+pub use mutations::NotificationMutation;
+pub use queries::NotificationQuery;
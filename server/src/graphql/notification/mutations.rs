@@ -0,0 +1,50 @@
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::notification::Notification;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Database,
+};
+
+#[derive(Default)]
+pub struct NotificationMutation;
+
+#[Object]
+impl NotificationMutation {
+    /// Mark one of the caller's notifications as read
+    async fn mark_notification_read(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let oid = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid notification ID"))?;
+
+        let result = db
+            .collection::<Notification>("notifications")
+            .update_one(
+                doc! { "_id": oid, "user_id": &auth_user.id, "read_at": { "$exists": false } },
+                doc! { "$set": { "read_at": DateTime::now() } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update notification: {}", e)))?;
+
+        Ok(result.modified_count > 0)
+    }
+
+    /// Mark all of the caller's notifications as read; returns how many changed
+    async fn mark_all_notifications_read(&self, ctx: &Context<'_>) -> Result<i64> {
+        let db = ctx.data::<Database>()?;
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+
+        let result = db
+            .collection::<Notification>("notifications")
+            .update_many(
+                doc! { "user_id": &auth_user.id, "read_at": { "$exists": false } },
+                doc! { "$set": { "read_at": DateTime::now() } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update notifications: {}", e)))?;
+
+        Ok(result.modified_count as i64)
+    }
+}
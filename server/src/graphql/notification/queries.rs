@@ -0,0 +1,55 @@
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::library::queries::collect;
+use crate::models::notification::Notification;
+use async_graphql::*;
+use mongodb::{bson::doc, options::FindOptions, Database};
+
+#[derive(Default)]
+pub struct NotificationQuery;
+
+#[Object]
+impl NotificationQuery {
+    /// The caller's notifications, newest first
+    async fn my_notifications(
+        &self,
+        ctx: &Context<'_>,
+        unread_only: Option<bool>,
+        limit: Option<i64>,
+        offset: Option<u64>,
+    ) -> Result<Vec<Notification>> {
+        let db = ctx.data::<Database>()?;
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+
+        let mut filter = doc! { "user_id": &auth_user.id };
+        if unread_only.unwrap_or(false) {
+            filter.insert("read_at", doc! { "$exists": false });
+        }
+
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .limit(limit.unwrap_or(50))
+            .skip(offset)
+            .build();
+        collect(
+            &db.collection::<Notification>("notifications"),
+            filter,
+            options,
+        )
+        .await
+    }
+
+    /// Number of unread notifications for the caller
+    async fn unread_notification_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let db = ctx.data::<Database>()?;
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+
+        db.collection::<Notification>("notifications")
+            .count_documents(
+                doc! { "user_id": &auth_user.id, "read_at": { "$exists": false } },
+                None,
+            )
+            .await
+            .map(|n| n as i64)
+            .map_err(|e| Error::new(e.to_string()))
+    }
+}
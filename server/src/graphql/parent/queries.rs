@@ -0,0 +1,377 @@
+// Parent portal GraphQL queries
+use super::types::ChildOverviewType;
+use crate::graphql::attendance::queries::find_student_attendance;
+use crate::graphql::attendance::{AttendanceSummaryType, AttendanceType};
+use crate::graphql::finance::queries::find_student_invoices;
+use crate::graphql::finance::{InvoiceType, StudentBalanceType};
+use crate::graphql::grade::queries::{build_report_card, find_student_grades};
+use crate::graphql::grade::{GradeType, ReportCardType};
+use crate::graphql::graphql_context::{get_graphql_context, load_custom_role_permissions};
+use crate::graphql::library::queries::collect;
+use crate::graphql::student::StudentType;
+use crate::graphql::transport::queries::find_assignment;
+use crate::models::member::{Member, Permission};
+use crate::models::school::SchoolFeature;
+use crate::models::student::Student;
+use crate::models::transport::{BoardingLog, TransportAssignment};
+use crate::utils::features::permission_feature;
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use chrono::{Duration, NaiveDate, Utc};
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct ParentQuery;
+
+#[Object]
+impl ParentQuery {
+    /// Get all children linked to the current parent across their schools
+    async fn my_children(&self, ctx: &Context<'_>) -> Result<Vec<StudentType>> {
+        let db = ctx.data::<Database>()?;
+        let memberships = portal_memberships(ctx, db).await?;
+        let children = find_children(db, &memberships).await?;
+
+        Ok(children.into_iter().map(|s| s.into()).collect())
+    }
+
+    /// Get a child's profile
+    async fn child_profile(&self, ctx: &Context<'_>, student_id: String) -> Result<StudentType> {
+        let db = ctx.data::<Database>()?;
+        let student = require_child(ctx, db, &student_id, Permission::ViewDashboard).await?;
+
+        Ok(student.into())
+    }
+
+    /// Get a child's attendance history, optionally within a date range (YYYY-MM-DD)
+    async fn child_attendance(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+        start_date: Option<String>,
+        end_date: Option<String>,
+    ) -> Result<Vec<AttendanceType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_child(ctx, db, &student_id, Permission::ViewAttendance).await?;
+
+        let attendances =
+            find_student_attendance(db, student.id.unwrap(), start_date, end_date).await?;
+
+        Ok(attendances.into_iter().map(|a| a.into()).collect())
+    }
+
+    /// Get a child's grades, optionally for one academic year and semester
+    async fn child_grades(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+        academic_year: Option<String>,
+        semester: Option<String>,
+    ) -> Result<Vec<GradeType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_child(ctx, db, &student_id, Permission::ViewGrades).await?;
+
+        let grades = find_student_grades(
+            db,
+            student.id.unwrap(),
+            academic_year.as_deref(),
+            semester.as_deref(),
+        )
+        .await?;
+
+        Ok(grades.into_iter().map(|g| g.into()).collect())
+    }
+
+    /// Get a child's report card for a term
+    async fn child_report_card(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+        academic_year: String,
+        semester: String,
+    ) -> Result<ReportCardType> {
+        let db = ctx.data::<Database>()?;
+        let student = require_child(ctx, db, &student_id, Permission::ViewGrades).await?;
+
+        build_report_card(db, student.id.unwrap(), &academic_year, &semester).await
+    }
+
+    /// Get a child's invoices, newest first
+    async fn child_invoices(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+    ) -> Result<Vec<InvoiceType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_child(ctx, db, &student_id, Permission::ViewFinance).await?;
+
+        let invoices = find_student_invoices(db, student.id.unwrap()).await?;
+
+        Ok(invoices.into_iter().map(|i| i.into()).collect())
+    }
+
+    /// Get a child's outstanding balances, one per currency
+    async fn child_balances(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+    ) -> Result<Vec<StudentBalanceType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_child(ctx, db, &student_id, Permission::ViewFinance).await?;
+
+        let invoices = find_student_invoices(db, student.id.unwrap()).await?;
+
+        Ok(StudentBalanceType::from_invoices(&student_id, &invoices))
+    }
+
+    /// Get a child's bus route assignment, if they ride school transport
+    async fn child_transport(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+    ) -> Result<Option<TransportAssignment>> {
+        let db = ctx.data::<Database>()?;
+        require_child(ctx, db, &student_id, Permission::ViewTransport).await?;
+
+        find_assignment(db, &student_id).await
+    }
+
+    /// Get a child's recent boarding and drop-off logs, newest first
+    async fn child_boarding_logs(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+        limit: Option<i64>,
+    ) -> Result<Vec<BoardingLog>> {
+        let db = ctx.data::<Database>()?;
+        require_child(ctx, db, &student_id, Permission::ViewTransport).await?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "recorded_at": -1 })
+            .limit(limit.unwrap_or(20))
+            .build();
+        collect(
+            &db.collection::<BoardingLog>("boarding_logs"),
+            doc! { "student_id": &student_id },
+            options,
+        )
+        .await
+    }
+
+    /// Consolidated view across all children: attendance and balances.
+    /// Attendance covers the given date range, or the last 30 days by default.
+    async fn my_children_overview(
+        &self,
+        ctx: &Context<'_>,
+        start_date: Option<String>,
+        end_date: Option<String>,
+    ) -> Result<Vec<ChildOverviewType>> {
+        let db = ctx.data::<Database>()?;
+        let memberships = portal_memberships(ctx, db).await?;
+        let children = find_children(db, &memberships).await?;
+
+        let today = Utc::now().date_naive();
+        let start = start_date
+            .unwrap_or_else(|| (today - Duration::days(30)).format("%Y-%m-%d").to_string());
+        let end = end_date.unwrap_or_else(|| today.format("%Y-%m-%d").to_string());
+        let total_days = match (
+            NaiveDate::parse_from_str(&start, "%Y-%m-%d"),
+            NaiveDate::parse_from_str(&end, "%Y-%m-%d"),
+        ) {
+            (Ok(s), Ok(e)) => (e - s).num_days() as i32 + 1,
+            _ => return Err(Error::new("Invalid date format. Use YYYY-MM-DD")),
+        };
+
+        let mut overview = Vec::new();
+        for student in children {
+            let student_oid = student.id.unwrap();
+            let student_hex = student_oid.to_hex();
+            let member = memberships
+                .iter()
+                .find(|m| m.school_id == student.school_id && m.parent_of.contains(&student_hex));
+
+            // Only include the sections this membership is allowed to see and
+            // the school has enabled
+            let attendance = match member {
+                Some(m) if can_view(db, m, Permission::ViewAttendance).await => {
+                    let records = find_student_attendance(
+                        db,
+                        student_oid,
+                        Some(start.clone()),
+                        Some(end.clone()),
+                    )
+                    .await?;
+                    AttendanceSummaryType::from_records(&records, total_days)
+                }
+                _ => AttendanceSummaryType::from_records(&[], total_days),
+            };
+
+            let balances = match member {
+                Some(m) if can_view(db, m, Permission::ViewFinance).await => {
+                    let invoices = find_student_invoices(db, student_oid).await?;
+                    StudentBalanceType::from_invoices(&student_hex, &invoices)
+                }
+                _ => Vec::new(),
+            };
+
+            overview.push(ChildOverviewType {
+                student: student.into(),
+                attendance,
+                balances,
+            });
+        }
+
+        Ok(overview)
+    }
+}
+
+/// Load the caller's active memberships that have children linked
+/// (Parent memberships, or e.g. a teacher who is also a parent at their school)
+async fn find_parent_memberships(ctx: &Context<'_>, db: &Database) -> Result<Vec<Member>> {
+    let auth_user = get_graphql_context(ctx)?.require_auth()?;
+
+    let mut cursor = db
+        .collection::<Member>("members")
+        .find(
+            doc! {
+                "user_id": &auth_user.id,
+                "$or": [
+                    { "role": "Parent" },
+                    { "parent_of.0": { "$exists": true } }
+                ],
+                "status": "Active",
+                "soft_delete.is_deleted": false
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut members = Vec::new();
+    while let Some(member) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        members.push(member);
+    }
+    for member in &mut members {
+        load_custom_role_permissions(db, member).await?;
+    }
+
+    Ok(members)
+}
+
+/// The caller's parent memberships in schools that have the parent portal enabled
+async fn portal_memberships(ctx: &Context<'_>, db: &Database) -> Result<Vec<Member>> {
+    let mut memberships = Vec::new();
+    for member in find_parent_memberships(ctx, db).await? {
+        if ensure_feature(db, &member.school_id, SchoolFeature::ParentPortal)
+            .await
+            .is_ok()
+        {
+            memberships.push(member);
+        }
+    }
+    Ok(memberships)
+}
+
+/// Load every child listed in the given Parent memberships.
+/// Each child must belong to the same school as the membership that lists it.
+async fn find_children(db: &Database, memberships: &[Member]) -> Result<Vec<Student>> {
+    let collection = db.collection::<Student>("students");
+
+    let mut children = Vec::new();
+    for member in memberships {
+        let student_oids: Vec<ObjectId> = member
+            .parent_of
+            .iter()
+            .filter_map(|id| ObjectId::parse_str(id).ok())
+            .collect();
+
+        if student_oids.is_empty() {
+            continue;
+        }
+
+        let mut cursor = collection
+            .find(
+                doc! {
+                    "_id": { "$in": student_oids },
+                    "school_id": &member.school_id,
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+
+        while let Some(student) = cursor
+            .try_next()
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        {
+            children.push(student);
+        }
+    }
+
+    Ok(children)
+}
+
+/// Whether the membership holds the permission and its school has the module
+/// the permission reads from
+async fn can_view(db: &Database, member: &Member, permission: Permission) -> bool {
+    if !member.has_permission(permission) {
+        return false;
+    }
+    match permission_feature(permission) {
+        Some(feature) => ensure_feature(db, &member.school_id, feature).await.is_ok(),
+        None => true,
+    }
+}
+
+/// Require the caller to be a parent of the student, holding the given permission
+/// in the student's school
+async fn require_child(
+    ctx: &Context<'_>,
+    db: &Database,
+    student_id: &str,
+    permission: Permission,
+) -> Result<Student> {
+    let student_oid =
+        ObjectId::parse_str(student_id).map_err(|_| Error::new("Invalid student ID format"))?;
+
+    let memberships = find_parent_memberships(ctx, db).await?;
+
+    let student = db
+        .collection::<Student>("students")
+        .find_one(
+            doc! { "_id": student_oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("You are not a guardian of this student"))?;
+
+    let member = memberships
+        .iter()
+        .find(|m| m.school_id == student.school_id && m.parent_of.iter().any(|id| id == student_id))
+        .ok_or_else(|| Error::new("You are not a guardian of this student"))?;
+
+    if !member.has_permission(permission) {
+        return Err(Error::new(format!(
+            "Insufficient permissions. Required permission: {:?}",
+            permission
+        )));
+    }
+
+    ensure_feature(db, &student.school_id, SchoolFeature::ParentPortal).await?;
+    if let Some(feature) = permission_feature(permission) {
+        ensure_feature(db, &student.school_id, feature).await?;
+    }
+
+    Ok(student)
+}
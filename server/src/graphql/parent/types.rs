@@ -0,0 +1,15 @@
+// Parent portal GraphQL types
+use crate::graphql::attendance::AttendanceSummaryType;
+use crate::graphql::finance::StudentBalanceType;
+use crate::graphql::student::StudentType;
+use async_graphql::*;
+
+/// At-a-glance summary of one child for the parent dashboard
+#[derive(SimpleObject)]
+pub struct ChildOverviewType {
+    pub student: StudentType,
+    /// Attendance over the requested period
+    pub attendance: AttendanceSummaryType,
+    /// Outstanding balances, one per currency
+    pub balances: Vec<StudentBalanceType>,
+}
@@ -0,0 +1,34 @@
+use async_graphql::InputObject;
+
+use crate::models::member::Permission;
+
+#[derive(InputObject)]
+pub struct CreateCustomRoleInput {
+    pub school_id: String,
+    /// Role name (e.g., "Exam Officer"), unique within the school
+    pub name: String,
+    pub description: Option<String>,
+    /// Permissions granted by the role
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateCustomRoleInput {
+    pub role_id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Replaces the role's permission set when provided
+    pub permissions: Option<Vec<Permission>>,
+}
+
+#[derive(InputObject)]
+pub struct MemberCustomRoleInput {
+    pub member_id: String,
+    pub role_id: String,
+}
+
+#[derive(InputObject)]
+pub struct MemberPermissionInput {
+    pub member_id: String,
+    pub permission: Permission,
+}
@@ -0,0 +1,501 @@
+use super::inputs::{
+    CreateCustomRoleInput, MemberCustomRoleInput, MemberPermissionInput, UpdateCustomRoleInput,
+};
+use crate::graphql::graphql_context::{get_graphql_context, GraphQLContext};
+use crate::models::custom_role::CustomRole;
+use crate::models::member::{Member, Permission, SchoolRole};
+use crate::utils::audit::record_change;
+use crate::utils::soft_delete::restore_by_id;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    Database,
+};
+
+#[derive(Default)]
+pub struct RoleMutation;
+
+#[Object]
+impl RoleMutation {
+    /// Define a custom role for a school (requires ManageRoles)
+    async fn create_custom_role(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateCustomRoleInput,
+    ) -> Result<CustomRole> {
+        let db = ctx.data::<Database>()?;
+        let auth_member = get_graphql_context(ctx)?
+            .require_member_permission(db, &input.school_id, Permission::ManageRoles)
+            .await?;
+
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            return Err(Error::new("Role name is required"));
+        }
+        ensure_unique_name(db, &input.school_id, &name, None).await?;
+        check_can_grant(&auth_member, &input.permissions)?;
+
+        let mut role = CustomRole::new(input.school_id, name, dedup(input.permissions));
+        role.description = input.description;
+        role.audit.created_by = Some(auth_member.user_id.clone());
+
+        let collection = db.collection::<CustomRole>("custom_roles");
+        let result = collection
+            .insert_one(&role, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create role: {}", e)))?;
+        role.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "CustomRole",
+            role.id.map(|oid| oid.to_hex()),
+            Some(&role.school_id),
+            None,
+            Some(&role),
+        );
+
+        Ok(role)
+    }
+
+    /// Rename a custom role or change its permissions (requires ManageRoles)
+    async fn update_custom_role(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateCustomRoleInput,
+    ) -> Result<CustomRole> {
+        let db = ctx.data::<Database>()?;
+        let role = find_role(db, &input.role_id).await?;
+        let auth_member = get_graphql_context(ctx)?
+            .require_member_permission(db, &role.school_id, Permission::ManageRoles)
+            .await?;
+
+        let mut set = doc! {
+            "audit.updated_at": mongodb::bson::DateTime::now(),
+            "audit.updated_by": &auth_member.user_id,
+        };
+        if let Some(name) = input.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err(Error::new("Role name is required"));
+            }
+            ensure_unique_name(db, &role.school_id, &name, role.id).await?;
+            set.insert("name", name);
+        }
+        if let Some(description) = input.description {
+            set.insert("description", description);
+        }
+        if let Some(permissions) = input.permissions {
+            check_can_grant(&auth_member, &permissions)?;
+            set.insert(
+                "permissions",
+                mongodb::bson::to_bson(&dedup(permissions))
+                    .map_err(|e| Error::new(e.to_string()))?,
+            );
+        }
+
+        let updated = update_role(db, role.id, set).await?;
+        record_change(
+            ctx,
+            "CustomRole",
+            Some(input.role_id),
+            Some(&role.school_id),
+            Some(&role),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Delete a custom role and remove it from every member (requires ManageRoles)
+    async fn delete_custom_role(&self, ctx: &Context<'_>, role_id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let role = find_role(db, &role_id).await?;
+        let auth_member = get_graphql_context(ctx)?
+            .require_member_permission(db, &role.school_id, Permission::ManageRoles)
+            .await?;
+
+        let now = mongodb::bson::DateTime::now();
+        update_role(
+            db,
+            role.id,
+            doc! {
+                "soft_delete.is_deleted": true,
+                "soft_delete.deleted_at": now,
+                "soft_delete.deleted_by": &auth_member.user_id,
+            },
+        )
+        .await?;
+
+        db.collection::<Member>("members")
+            .update_many(
+                doc! { "school_id": &role.school_id, "custom_role_ids": &role_id },
+                doc! { "$pull": { "custom_role_ids": &role_id } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to unassign role: {}", e)))?;
+
+        record_change(
+            ctx,
+            "CustomRole",
+            Some(role_id),
+            Some(&role.school_id),
+            Some(&role),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted custom role from the trash (requires ManageRoles).
+    /// Members it was assigned to don't get it back; assign it again as needed.
+    async fn restore_custom_role(&self, ctx: &Context<'_>, role_id: String) -> Result<CustomRole> {
+        let db = ctx.data::<Database>()?;
+        let collection = db.collection::<CustomRole>("custom_roles");
+        let role_oid = ObjectId::parse_str(&role_id).map_err(|_| Error::new("Invalid role ID"))?;
+        let role = collection
+            .find_one(
+                doc! { "_id": role_oid, "soft_delete.is_deleted": true },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?
+            .ok_or_else(|| Error::new("Deleted custom role not found"))?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &role.school_id, Permission::ManageRoles)
+            .await?;
+        ensure_unique_name(db, &role.school_id, &role.name, None).await?;
+
+        if !restore_by_id(&collection, role_oid).await? {
+            return Err(Error::new("Deleted custom role not found"));
+        }
+
+        let restored = find_role(db, &role_id).await?;
+        record_change(
+            ctx,
+            "CustomRole",
+            Some(role_id),
+            Some(&role.school_id),
+            Some(&role),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Give a member a custom role (requires ManageRoles)
+    async fn assign_custom_role(
+        &self,
+        ctx: &Context<'_>,
+        input: MemberCustomRoleInput,
+    ) -> Result<Member> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let role = find_role(db, &input.role_id).await?;
+        let (auth_member, member) = load_managed_member(graphql_ctx, db, &input.member_id).await?;
+
+        if role.school_id != member.school_id {
+            return Err(Error::new("Custom role not found"));
+        }
+        check_can_grant(&auth_member, &role.permissions)?;
+
+        update_member(
+            ctx,
+            db,
+            &auth_member,
+            &member,
+            doc! { "$addToSet": { "custom_role_ids": &input.role_id } },
+        )
+        .await
+    }
+
+    /// Take a custom role away from a member (requires ManageRoles)
+    async fn unassign_custom_role(
+        &self,
+        ctx: &Context<'_>,
+        input: MemberCustomRoleInput,
+    ) -> Result<Member> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let (auth_member, member) = load_managed_member(graphql_ctx, db, &input.member_id).await?;
+
+        update_member(
+            ctx,
+            db,
+            &auth_member,
+            &member,
+            doc! { "$pull": { "custom_role_ids": &input.role_id } },
+        )
+        .await
+    }
+
+    /// Grant a member a single permission on top of their roles, lifting any
+    /// denial of it (requires ManageRoles)
+    async fn grant_member_permission(
+        &self,
+        ctx: &Context<'_>,
+        input: MemberPermissionInput,
+    ) -> Result<Member> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let (auth_member, member) = load_managed_member(graphql_ctx, db, &input.member_id).await?;
+        check_can_grant(&auth_member, &[input.permission])?;
+
+        let permission = permission_bson(input.permission)?;
+        update_member(
+            ctx,
+            db,
+            &auth_member,
+            &member,
+            doc! {
+                "$addToSet": { "permissions": &permission },
+                "$pull": { "denied_permissions": &permission }
+            },
+        )
+        .await
+    }
+
+    /// Remove a permission granted directly to a member. Permissions from the
+    /// member's roles are unaffected; use a denial to block those.
+    async fn revoke_member_permission(
+        &self,
+        ctx: &Context<'_>,
+        input: MemberPermissionInput,
+    ) -> Result<Member> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let (auth_member, member) = load_managed_member(graphql_ctx, db, &input.member_id).await?;
+
+        update_member(
+            ctx,
+            db,
+            &auth_member,
+            &member,
+            doc! { "$pull": { "permissions": permission_bson(input.permission)? } },
+        )
+        .await
+    }
+
+    /// Explicitly deny a member a permission, overriding their roles and grants
+    /// (requires ManageRoles)
+    async fn deny_member_permission(
+        &self,
+        ctx: &Context<'_>,
+        input: MemberPermissionInput,
+    ) -> Result<Member> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let (auth_member, member) = load_managed_member(graphql_ctx, db, &input.member_id).await?;
+
+        let permission = permission_bson(input.permission)?;
+        update_member(
+            ctx,
+            db,
+            &auth_member,
+            &member,
+            doc! {
+                "$addToSet": { "denied_permissions": &permission },
+                "$pull": { "permissions": &permission }
+            },
+        )
+        .await
+    }
+
+    /// Lift an explicit denial so the member's roles apply again (requires ManageRoles)
+    async fn clear_member_permission_denial(
+        &self,
+        ctx: &Context<'_>,
+        input: MemberPermissionInput,
+    ) -> Result<Member> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let (auth_member, member) = load_managed_member(graphql_ctx, db, &input.member_id).await?;
+
+        update_member(
+            ctx,
+            db,
+            &auth_member,
+            &member,
+            doc! { "$pull": { "denied_permissions": permission_bson(input.permission)? } },
+        )
+        .await
+    }
+}
+
+/// Load a member whose permissions the caller wants to change, together with the
+/// caller's own membership. Owners and the caller's own membership can't be
+/// changed, and permission changes need a recent MFA check.
+async fn load_managed_member(
+    graphql_ctx: &GraphQLContext,
+    db: &Database,
+    member_id: &str,
+) -> Result<(Member, Member)> {
+    let member_oid = ObjectId::parse_str(member_id).map_err(|_| Error::new("Invalid member ID"))?;
+    let member = db
+        .collection::<Member>("members")
+        .find_one(
+            doc! { "_id": member_oid, "soft_delete.is_deleted": false },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))?
+        .ok_or_else(|| Error::new("Member not found"))?;
+
+    let auth_member = graphql_ctx
+        .require_member_permission(db, &member.school_id, Permission::ManageRoles)
+        .await?;
+
+    if member.user_id == auth_member.user_id {
+        return Err(Error::new("You cannot change your own permissions"));
+    }
+    if member.role == SchoolRole::Owner {
+        return Err(Error::new(
+            "The school owner's permissions cannot be changed",
+        ));
+    }
+
+    graphql_ctx
+        .require_step_up(db, Some(&member.school_id))
+        .await?;
+
+    Ok((auth_member, member))
+}
+
+/// Apply an update to a member and return the updated record
+async fn update_member(
+    ctx: &Context<'_>,
+    db: &Database,
+    auth_member: &Member,
+    member: &Member,
+    mut update: Document,
+) -> Result<Member> {
+    let collection = db.collection::<Member>("members");
+    update.insert(
+        "$set",
+        doc! {
+            "audit.updated_at": mongodb::bson::DateTime::now(),
+            "audit.updated_by": &auth_member.user_id,
+        },
+    );
+
+    collection
+        .update_one(doc! { "_id": member.id }, update, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to update member: {}", e)))?;
+
+    let updated = collection
+        .find_one(doc! { "_id": member.id }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to retrieve member: {}", e)))?
+        .ok_or_else(|| Error::new("Member not found"))?;
+
+    record_change(
+        ctx,
+        "Member",
+        member.id.map(|oid| oid.to_hex()),
+        Some(&member.school_id),
+        Some(member),
+        Some(&updated),
+    );
+
+    Ok(updated)
+}
+
+async fn find_role(db: &Database, role_id: &str) -> Result<CustomRole> {
+    let role_oid = ObjectId::parse_str(role_id).map_err(|_| Error::new("Invalid role ID"))?;
+    db.collection::<CustomRole>("custom_roles")
+        .find_one(
+            doc! { "_id": role_oid, "soft_delete.is_deleted": false },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))?
+        .ok_or_else(|| Error::new("Custom role not found"))
+}
+
+async fn update_role(
+    db: &Database,
+    role_id: Option<ObjectId>,
+    set: Document,
+) -> Result<CustomRole> {
+    let collection = db.collection::<CustomRole>("custom_roles");
+    collection
+        .update_one(doc! { "_id": role_id }, doc! { "$set": set }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to update role: {}", e)))?;
+
+    collection
+        .find_one(doc! { "_id": role_id }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to retrieve role: {}", e)))?
+        .ok_or_else(|| Error::new("Custom role not found"))
+}
+
+/// Role names are unique (case-insensitive) among a school's live roles
+async fn ensure_unique_name(
+    db: &Database,
+    school_id: &str,
+    name: &str,
+    exclude: Option<ObjectId>,
+) -> Result<()> {
+    let mut filter = doc! {
+        "school_id": school_id,
+        "name": { "$regex": format!("^{}$", regex_escape(name)), "$options": "i" },
+        "soft_delete.is_deleted": false
+    };
+    if let Some(id) = exclude {
+        filter.insert("_id", doc! { "$ne": id });
+    }
+
+    let count = db
+        .collection::<CustomRole>("custom_roles")
+        .count_documents(filter, None)
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+    if count > 0 {
+        return Err(Error::new("A role with this name already exists"));
+    }
+    Ok(())
+}
+
+/// Managers can only hand out permissions they hold themselves
+fn check_can_grant(auth_member: &Member, permissions: &[Permission]) -> Result<()> {
+    let missing: Vec<&Permission> = permissions
+        .iter()
+        .filter(|p| !auth_member.has_permission(**p))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "You cannot grant permissions you don't have: {:?}",
+            missing
+        )))
+    }
+}
+
+fn dedup(permissions: Vec<Permission>) -> Vec<Permission> {
+    let mut unique = Vec::new();
+    for permission in permissions {
+        if !unique.contains(&permission) {
+            unique.push(permission);
+        }
+    }
+    unique
+}
+
+fn permission_bson(permission: Permission) -> Result<mongodb::bson::Bson> {
+    mongodb::bson::to_bson(&permission).map_err(|e| Error::new(e.to_string()))
+}
+
+fn regex_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
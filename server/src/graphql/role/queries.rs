@@ -0,0 +1,184 @@
+use super::types::{EffectivePermissionType, PermissionSourceKind, PermissionSourceType};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::custom_role::CustomRole;
+use crate::models::member::{Member, Permission};
+use crate::utils::soft_delete::find_deleted;
+use async_graphql::*;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct RoleQuery;
+
+#[Object]
+impl RoleQuery {
+    /// List a school's custom roles (requires ViewSettings)
+    async fn custom_roles(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<CustomRole>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ViewSettings)
+            .await?;
+
+        let options = FindOptions::builder().sort(doc! { "name": 1 }).build();
+        let mut cursor = db
+            .collection::<CustomRole>("custom_roles")
+            .find(
+                doc! { "school_id": &school_id, "soft_delete.is_deleted": false },
+                options,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+
+        let mut roles = Vec::new();
+        while let Some(role) = cursor
+            .try_next()
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        {
+            roles.push(role);
+        }
+
+        Ok(roles)
+    }
+
+    /// Get a school's deleted custom roles (the trash), most recently deleted
+    /// first (requires ManageRoles)
+    async fn deleted_custom_roles(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<CustomRole>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ManageRoles)
+            .await?;
+
+        find_deleted(
+            &db.collection::<CustomRole>("custom_roles"),
+            doc! { "school_id": school_id },
+        )
+        .await
+    }
+
+    /// Explain a member's permissions: every permission with the role, custom
+    /// roles, grants and denials it comes from. Defaults to the caller's membership
+    /// in the active school; other members require ManageRoles.
+    async fn effective_permissions(
+        &self,
+        ctx: &Context<'_>,
+        member_id: Option<String>,
+    ) -> Result<Vec<EffectivePermissionType>> {
+        let graphql_ctx = get_graphql_context(ctx)?;
+        let auth_user = graphql_ctx.require_auth()?;
+        let db = ctx.data::<Database>()?;
+
+        let member = match member_id {
+            Some(member_id) => {
+                let member_oid =
+                    ObjectId::parse_str(&member_id).map_err(|_| Error::new("Invalid member ID"))?;
+                let member = db
+                    .collection::<Member>("members")
+                    .find_one(
+                        doc! { "_id": member_oid, "soft_delete.is_deleted": false },
+                        None,
+                    )
+                    .await
+                    .map_err(|e| Error::new(format!("Database error: {}", e)))?
+                    .ok_or_else(|| Error::new("Member not found"))?;
+
+                if member.user_id == auth_user.id {
+                    graphql_ctx.require_member(db, &member.school_id).await?;
+                } else {
+                    graphql_ctx
+                        .require_member_permission(db, &member.school_id, Permission::ManageRoles)
+                        .await?;
+                }
+                member
+            }
+            None => graphql_ctx.require_active_member()?.clone(),
+        };
+
+        explain_permissions(db, &member).await
+    }
+}
+
+/// Build the per-permission source list for a member
+async fn explain_permissions(
+    db: &Database,
+    member: &Member,
+) -> Result<Vec<EffectivePermissionType>> {
+    let mut explained: Vec<EffectivePermissionType> = Vec::new();
+    let mut add = |permission: Permission, kind: PermissionSourceKind, name: Option<String>| {
+        let index = match explained.iter().position(|p| p.permission == permission) {
+            Some(index) => index,
+            None => {
+                explained.push(EffectivePermissionType {
+                    permission,
+                    allowed: true,
+                    sources: Vec::new(),
+                });
+                explained.len() - 1
+            }
+        };
+        let entry = &mut explained[index];
+        if kind == PermissionSourceKind::Deny {
+            entry.allowed = false;
+        }
+        entry.sources.push(PermissionSourceType { kind, name });
+    };
+
+    let role_name = format!("{:?}", member.role);
+    for permission in Member::default_permissions(member.role) {
+        add(
+            permission,
+            PermissionSourceKind::Role,
+            Some(role_name.clone()),
+        );
+    }
+
+    let role_oids: Vec<ObjectId> = member
+        .custom_role_ids
+        .iter()
+        .filter_map(|id| ObjectId::parse_str(id).ok())
+        .collect();
+    if !role_oids.is_empty() {
+        let mut cursor = db
+            .collection::<CustomRole>("custom_roles")
+            .find(
+                doc! {
+                    "_id": { "$in": role_oids },
+                    "school_id": &member.school_id,
+                    "soft_delete.is_deleted": false
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        while let Some(role) = cursor
+            .try_next()
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        {
+            for permission in role.permissions {
+                add(
+                    permission,
+                    PermissionSourceKind::CustomRole,
+                    Some(role.name.clone()),
+                );
+            }
+        }
+    }
+
+    for permission in &member.permissions {
+        add(*permission, PermissionSourceKind::Grant, None);
+    }
+    for permission in &member.denied_permissions {
+        add(*permission, PermissionSourceKind::Deny, None);
+    }
+
+    Ok(explained)
+}
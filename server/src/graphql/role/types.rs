@@ -0,0 +1,31 @@
+use crate::models::member::Permission;
+use async_graphql::{Enum, SimpleObject};
+
+/// Where a member's permission comes from
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PermissionSourceKind {
+    /// Default permission of the member's built-in school role
+    Role,
+    /// Granted by a school-defined custom role
+    CustomRole,
+    /// Granted to the member directly
+    Grant,
+    /// Denied to the member directly
+    Deny,
+}
+
+#[derive(SimpleObject)]
+pub struct PermissionSourceType {
+    pub kind: PermissionSourceKind,
+    /// Role or custom role name; empty for direct grants and denials
+    pub name: Option<String>,
+}
+
+/// One permission with the reasons the member has (or is denied) it
+#[derive(SimpleObject)]
+pub struct EffectivePermissionType {
+    pub permission: Permission,
+    /// Whether the member ends up with the permission (false when denied)
+    pub allowed: bool,
+    pub sources: Vec<PermissionSourceType>,
+}
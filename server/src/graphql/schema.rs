@@ -1,12 +1,16 @@
 // GraphQL schema creation
-use async_graphql::{Schema, EmptySubscription};
+use async_graphql::Schema;
 use mongodb::Database;
-use super::{QueryRoot, MutationRoot};
+use super::{QueryRoot, MutationRoot, SubscriptionRoot};
+use crate::utils::audit::AuditLogger;
+use crate::utils::subscription::SubscriptionGuard;
 
-pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+pub type AppSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
 
 pub fn create_schema(db: Database) -> AppSchema {
-    Schema::build(QueryRoot::default(), MutationRoot::default(), EmptySubscription)
-        .data(db)
+    Schema::build(QueryRoot::default(), MutationRoot::default(), SubscriptionRoot::default())
+        .data(db.clone())
+        .extension(AuditLogger::new(db.clone()))
+        .extension(SubscriptionGuard::new(db))
         .finish()
 }
@@ -1,6 +1,6 @@
 use async_graphql::InputObject;
 
-use crate::models::school::{EducationLevel, SchoolType};
+use crate::models::school::{EducationLevel, SchoolFeature, SchoolType};
 use crate::utils::common_types::{Address, ContactInfo, LocalizedText};
 
 /// Input for school registration
@@ -50,3 +50,10 @@ pub struct UpdateSchoolInput {
     pub website: Option<String>,
     pub description: Option<String>,
 }
+
+/// Input for turning a school module on or off
+#[derive(InputObject)]
+pub struct SchoolFeatureInput {
+    pub school_id: String,
+    pub feature: SchoolFeature,
+}
@@ -1,6 +1,8 @@
 pub mod inputs;
 pub mod mutations;
 pub mod queries;
+pub mod subscriptions;
 
 pub use mutations::SchoolMutation;
 pub use queries::SchoolQuery;
+pub use subscriptions::SchoolSubscription;
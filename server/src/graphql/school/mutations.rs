@@ -1,7 +1,15 @@
 // School GraphQL mutations
-use super::inputs::{ApproveSchoolInput, RegisterSchoolInput, RejectSchoolInput};
+use super::inputs::{
+    ApproveSchoolInput, RegisterSchoolInput, RejectSchoolInput, SchoolFeatureInput,
+};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::member::SchoolRole;
 use crate::models::school::{School, SchoolFeature, SchoolStatus};
+use crate::utils::audit::record_change;
 use crate::utils::common_types::{AuditInfo, LocalizedText, SoftDelete};
+use crate::utils::features::dependents;
+use crate::utils::realtime::{publish, RealtimeEvent};
+use crate::utils::subscription::find_school;
 use async_graphql::*;
 use mongodb::bson::DateTime;
 use mongodb::{
@@ -112,6 +120,7 @@ impl SchoolMutation {
         let auth_user = graphql_ctx.require_role(SystemRole::SuperAdmin)?;
 
         let db = ctx.data::<Database>()?;
+        graphql_ctx.require_step_up(db, None).await?;
         let collection = db.collection::<School>("schools");
 
         let school_id =
@@ -140,6 +149,10 @@ impl SchoolMutation {
             .map_err(|e| Error::new(format!("Failed to retrieve school: {}", e)))?
             .ok_or_else(|| Error::new("School not found"))?;
 
+        publish(RealtimeEvent::SchoolStatusChanged {
+            school: Box::new(school.clone()),
+        });
+
         Ok(school)
     }
 
@@ -179,6 +192,123 @@ impl SchoolMutation {
             .map_err(|e| Error::new(format!("Failed to retrieve school: {}", e)))?
             .ok_or_else(|| Error::new("School not found"))?;
 
+        publish(RealtimeEvent::SchoolStatusChanged {
+            school: Box::new(school.clone()),
+        });
+
         Ok(school)
     }
+    /// Owner only - turn on a module the school's plan includes. Modules it
+    /// depends on must be enabled first.
+    async fn enable_school_feature(
+        &self,
+        ctx: &Context<'_>,
+        input: SchoolFeatureInput,
+    ) -> Result<School> {
+        let db = ctx.data::<Database>()?;
+        let owner = get_graphql_context(ctx)?
+            .require_member_role(db, &input.school_id, &[SchoolRole::Owner])
+            .await?;
+        let school = find_school(db, &input.school_id).await?;
+
+        if !school.current_subscription().plan.includes(input.feature) {
+            return Err(Error::new(format!(
+                "The {:?} module is not included in this school's plan",
+                input.feature
+            )));
+        }
+
+        let missing: Vec<String> = input
+            .feature
+            .dependencies()
+            .iter()
+            .filter(|f| !school.has_feature(**f))
+            .map(|f| format!("{:?}", f))
+            .collect();
+        if !missing.is_empty() {
+            return Err(Error::new(format!(
+                "Enable {} before {:?}",
+                missing.join(", "),
+                input.feature
+            )));
+        }
+
+        let mut updated = school.clone();
+        updated.enable_feature(input.feature);
+        save_features(ctx, db, &school, &updated, &owner.user_id).await
+    }
+
+    /// Owner only - turn off a module. Modules that depend on it must be
+    /// disabled first. The module's data is kept.
+    async fn disable_school_feature(
+        &self,
+        ctx: &Context<'_>,
+        input: SchoolFeatureInput,
+    ) -> Result<School> {
+        let db = ctx.data::<Database>()?;
+        let owner = get_graphql_context(ctx)?
+            .require_member_role(db, &input.school_id, &[SchoolRole::Owner])
+            .await?;
+        let school = find_school(db, &input.school_id).await?;
+
+        let blocking: Vec<String> = dependents(&school.features, input.feature)
+            .iter()
+            .map(|f| format!("{:?}", f))
+            .collect();
+        if !blocking.is_empty() {
+            return Err(Error::new(format!(
+                "{:?} is used by {}; disable those first",
+                input.feature,
+                blocking.join(", ")
+            )));
+        }
+
+        let mut updated = school.clone();
+        updated.disable_feature(input.feature);
+        save_features(ctx, db, &school, &updated, &owner.user_id).await
+    }
+}
+
+/// Store a school's enabled features and return the updated school
+async fn save_features(
+    ctx: &Context<'_>,
+    db: &Database,
+    before: &School,
+    school: &School,
+    user_id: &str,
+) -> Result<School> {
+    let collection = db.collection::<School>("schools");
+    let school_oid = school.id.ok_or_else(|| Error::new("School not found"))?;
+
+    let update = doc! {
+        "$set": {
+            "features": mongodb::bson::to_bson(&school.features)
+                .map_err(|e| Error::new(e.to_string()))?,
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": user_id,
+        }
+    };
+
+    collection
+        .update_one(doc! { "_id": school_oid }, update, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to update features: {}", e)))?;
+
+    let updated = collection
+        .find_one(doc! { "_id": school_oid }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to retrieve school: {}", e)))?
+        .ok_or_else(|| Error::new("School not found"))?;
+
+    let school_id = school_oid.to_hex();
+    record_change(
+        ctx,
+        "School",
+        Some(school_id.clone()),
+        Some(&school_id),
+        Some(before),
+        Some(&updated),
+    );
+
+    Ok(updated)
 }
@@ -0,0 +1,40 @@
+// School GraphQL subscriptions
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::school::School;
+use crate::utils::realtime::{member_events, RealtimeEvent};
+use async_graphql::*;
+use futures::Stream;
+use mongodb::Database;
+
+#[derive(Default)]
+pub struct SchoolSubscription;
+
+#[Subscription]
+impl SchoolSubscription {
+    /// The school whenever its approval status changes (members of the school)
+    async fn school_status_changed(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<impl Stream<Item = School>> {
+        let db = ctx.data::<Database>()?;
+        let graphql_ctx = get_graphql_context(ctx)?;
+        graphql_ctx.require_member(db, &school_id).await?;
+        let user = graphql_ctx.require_auth()?.clone();
+
+        Ok(member_events(
+            db.clone(),
+            user,
+            school_id.clone(),
+            None,
+            move |event| match event {
+                RealtimeEvent::SchoolStatusChanged { school }
+                    if school.id.map(|id| id.to_hex()).as_deref() == Some(school_id.as_str()) =>
+                {
+                    Some(*school)
+                }
+                _ => None,
+            },
+        ))
+    }
+}
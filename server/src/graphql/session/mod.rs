@@ -0,0 +1,6 @@
+pub mod mutations;
+pub mod queries;
+pub mod types;
+
+pub use mutations::SessionMutation;
+pub use queries::SessionQuery;
@@ -0,0 +1,71 @@
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::session::Session;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Database,
+};
+
+#[derive(Default)]
+pub struct SessionMutation;
+
+#[Object]
+impl SessionMutation {
+    /// Sign out one of the current user's sessions
+    async fn revoke_session(&self, ctx: &Context<'_>, session_id: String) -> Result<bool> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+
+        let oid = ObjectId::parse_str(&session_id).map_err(|_| Error::new("Invalid session ID"))?;
+
+        let result = db
+            .collection::<Session>("sessions")
+            .update_one(
+                doc! { "_id": oid, "user_id": &auth_user.id, "revoked_at": null },
+                doc! { "$set": { "revoked_at": DateTime::now() } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to revoke session: {}", e)))?;
+
+        if result.matched_count == 0 {
+            return Err(Error::new("Session not found"));
+        }
+
+        Ok(true)
+    }
+
+    /// Sign out all of the current user's sessions.
+    /// Set `keep_current` to stay signed in on this device.
+    async fn revoke_all_sessions(
+        &self,
+        ctx: &Context<'_>,
+        keep_current: Option<bool>,
+    ) -> Result<i32> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+
+        let mut filter = doc! { "user_id": &auth_user.id, "revoked_at": null };
+        if keep_current.unwrap_or(false) {
+            if let Some(oid) = auth_user
+                .session_id
+                .as_deref()
+                .and_then(|id| ObjectId::parse_str(id).ok())
+            {
+                filter.insert("_id", doc! { "$ne": oid });
+            }
+        }
+
+        let result = db
+            .collection::<Session>("sessions")
+            .update_many(
+                filter,
+                doc! { "$set": { "revoked_at": DateTime::now() } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to revoke sessions: {}", e)))?;
+
+        Ok(result.modified_count as i32)
+    }
+}
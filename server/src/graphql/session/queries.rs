@@ -0,0 +1,53 @@
+use super::types::SessionType;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::session::Session;
+use async_graphql::*;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, DateTime},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct SessionQuery;
+
+#[Object]
+impl SessionQuery {
+    /// List the current user's active sessions, most recently used first
+    async fn my_sessions(&self, ctx: &Context<'_>) -> Result<Vec<SessionType>> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "last_used_at": -1 })
+            .build();
+
+        let mut cursor = db
+            .collection::<Session>("sessions")
+            .find(
+                doc! {
+                    "user_id": &auth_user.id,
+                    "revoked_at": null,
+                    "expires_at": { "$gt": DateTime::now() }
+                },
+                options,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+
+        let mut sessions = Vec::new();
+        while let Some(session) = cursor
+            .try_next()
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+        {
+            sessions.push(SessionType::from_session(
+                session,
+                auth_user.session_id.as_deref(),
+            ));
+        }
+
+        Ok(sessions)
+    }
+}
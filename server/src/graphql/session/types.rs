@@ -0,0 +1,41 @@
+use crate::models::session::Session;
+use async_graphql::SimpleObject;
+
+/// A signed-in device, as shown in the user's session list
+#[derive(SimpleObject)]
+pub struct SessionType {
+    pub id: String,
+    /// User agent of the device
+    pub device: Option<String>,
+    /// Last seen IP address
+    pub ip: Option<String>,
+    pub created_at: Option<String>,
+    pub last_used_at: String,
+    pub expires_at: String,
+    /// Whether this is the session making the request
+    pub is_current: bool,
+}
+
+impl SessionType {
+    pub fn from_session(session: Session, current_session_id: Option<&str>) -> Self {
+        let id = session.id.map(|oid| oid.to_hex()).unwrap_or_default();
+        Self {
+            is_current: current_session_id == Some(id.as_str()),
+            id,
+            device: session.device,
+            ip: session.ip,
+            created_at: session
+                .audit
+                .created_at
+                .and_then(|d| d.try_to_rfc3339_string().ok()),
+            last_used_at: session
+                .last_used_at
+                .try_to_rfc3339_string()
+                .unwrap_or_default(),
+            expires_at: session
+                .expires_at
+                .try_to_rfc3339_string()
+                .unwrap_or_default(),
+        }
+    }
+}
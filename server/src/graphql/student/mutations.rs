@@ -1,7 +1,12 @@
 // Student GraphQL mutations
 use super::inputs::{CreateStudentInput, UpdateStudentInput};
 use super::types::StudentType;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::audit::record_change;
+use crate::utils::soft_delete::{restore_by_id, soft_delete_by_id};
+use crate::utils::subscription::ensure_student_capacity;
 use async_graphql::*;
 use mongodb::{
     bson::{doc, oid::ObjectId, DateTime},
@@ -19,10 +24,16 @@ impl StudentMutation {
         input: CreateStudentInput,
     ) -> Result<StudentType> {
         let db = ctx.data::<Database>()?;
+        let auth_member = get_graphql_context(ctx)?
+            .require_member_permission(db, &input.school_id, Permission::CreateStudents)
+            .await?;
+        ensure_student_capacity(db, &input.school_id).await?;
         let student_collection = db.collection::<models::student::Student>("students");
         let class_collection = db.collection::<models::class::Class>("classes");
 
         let mut student = input.into_student();
+        student.audit.created_by = Some(auth_member.user_id.clone());
+        student.audit.updated_by = Some(auth_member.user_id);
 
         // Auto-generate student ID if not provided
         if student.student_id.trim().is_empty() {
@@ -48,6 +59,15 @@ impl StudentMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Failed to retrieve created student"))?;
 
+        record_change(
+            ctx,
+            "Student",
+            Some(id.to_hex()),
+            Some(&student.school_id),
+            None,
+            Some(&student),
+        );
+
         // Sync class student_ids if class is assigned
         if let Some(ref cid) = class_id {
             let _ = class_collection
@@ -76,10 +96,16 @@ impl StudentMutation {
 
         // Get the current student to check for class change
         let current_student = student_collection
-            .find_one(doc! { "_id": obj_id }, None)
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Student not found"))?;
+        let auth_member = get_graphql_context(ctx)?
+            .require_member_permission(db, &current_student.school_id, Permission::UpdateStudents)
+            .await?;
 
         let old_class_id = current_student.current_class_id.clone();
         let new_class_id = input.current_class_id.clone();
@@ -112,6 +138,7 @@ impl StudentMutation {
         }
 
         update_doc.insert("audit.updated_at", DateTime::now());
+        update_doc.insert("audit.updated_by", &auth_member.user_id);
 
         // Update the student document
         student_collection
@@ -152,6 +179,15 @@ impl StudentMutation {
             .map_err(|e| Error::new(e.to_string()))?
             .ok_or_else(|| Error::new("Student not found"))?;
 
+        record_change(
+            ctx,
+            "Student",
+            Some(id),
+            Some(&student.school_id),
+            Some(&current_student),
+            Some(&student),
+        );
+
         Ok(student.into())
     }
 
@@ -162,30 +198,96 @@ impl StudentMutation {
 
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
-        // Get the student first to check for class enrollment
-        if let Some(student) = student_collection
-            .find_one(doc! { "_id": obj_id }, None)
+        let student = student_collection
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?
-        {
-            // Remove from class if enrolled
-            if let Some(ref class_id) = student.current_class_id {
-                let _ = class_collection
-                    .update_one(
-                        doc! { "_id": class_id },
-                        doc! { "$pull": { "student_ids": &id } },
-                        None,
-                    )
-                    .await;
-            }
+            .ok_or_else(|| Error::new("Student not found"))?;
+
+        let auth_member = get_graphql_context(ctx)?
+            .require_member_permission(db, &student.school_id, Permission::DeleteStudents)
+            .await?;
+
+        if !soft_delete_by_id(&student_collection, obj_id, &auth_member.user_id).await? {
+            return Ok(false);
         }
 
-        let result = student_collection
-            .delete_one(doc! { "_id": obj_id }, None)
+        // Remove from class if enrolled
+        if let Some(ref class_id) = student.current_class_id {
+            let _ = class_collection
+                .update_one(
+                    doc! { "_id": class_id },
+                    doc! { "$pull": { "student_ids": &id } },
+                    None,
+                )
+                .await;
+        }
+
+        let deleted = student_collection
+            .find_one(doc! { "_id": obj_id }, None)
             .await
             .map_err(|e| Error::new(e.to_string()))?;
+        record_change(
+            ctx,
+            "Student",
+            Some(id),
+            Some(&student.school_id),
+            Some(&student),
+            deleted.as_ref(),
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted student from the trash and re-enroll them in their class
+    async fn restore_student(&self, ctx: &Context<'_>, id: String) -> Result<StudentType> {
+        let db = ctx.data::<Database>()?;
+        let student_collection = db.collection::<models::student::Student>("students");
+        let class_collection = db.collection::<models::class::Class>("classes");
+
+        let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
+
+        let student = student_collection
+            .find_one(doc! { "_id": obj_id, "soft_delete.is_deleted": true }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Deleted student not found"))?;
+
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &student.school_id, Permission::DeleteStudents)
+            .await?;
+
+        restore_by_id(&student_collection, obj_id).await?;
+
+        if let Some(ref class_id) = student.current_class_id {
+            let _ = class_collection
+                .update_one(
+                    doc! { "_id": class_id, "soft_delete.is_deleted": { "$ne": true } },
+                    doc! { "$addToSet": { "student_ids": &id } },
+                    None,
+                )
+                .await;
+        }
+
+        let restored = student_collection
+            .find_one(doc! { "_id": obj_id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Student not found"))?;
+
+        record_change(
+            ctx,
+            "Student",
+            Some(id),
+            Some(&restored.school_id),
+            Some(&student),
+            Some(&restored),
+        );
 
-        Ok(result.deleted_count > 0)
+        Ok(restored.into())
     }
 }
 
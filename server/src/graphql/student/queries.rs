@@ -1,6 +1,9 @@
 // Student GraphQL queries
 use super::types::StudentType;
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::soft_delete::find_deleted;
 use async_graphql::*;
 use futures::stream::TryStreamExt;
 use mongodb::{
@@ -13,13 +16,20 @@ pub struct StudentQuery;
 
 #[Object]
 impl StudentQuery {
+    /// Students of the active school
     async fn students(&self, ctx: &Context<'_>) -> Result<Vec<StudentType>> {
+        let member = get_graphql_context(ctx)?.require_permission(Permission::ViewStudents)?;
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<models::student::Student>("students");
 
-        // Fetch all students from database
         let mut cursor = collection
-            .find(None, None)
+            .find(
+                doc! {
+                    "school_id": &member.school_id,
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -42,12 +52,21 @@ impl StudentQuery {
         // Parse and validate ObjectId
         let obj_id = ObjectId::parse_str(&id).map_err(|_| Error::new("Invalid ID format"))?;
 
-        // Find student by ID
+        // Find student by ID (excluding deleted)
         let student = collection
-            .find_one(doc! { "_id": obj_id }, None)
+            .find_one(
+                doc! { "_id": obj_id, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
+        if let Some(ref student) = student {
+            get_graphql_context(ctx)?
+                .require_member_permission(db, &student.school_id, Permission::ViewStudents)
+                .await?;
+        }
+
         Ok(student.map(|s| s.into()))
     }
 
@@ -57,10 +76,19 @@ impl StudentQuery {
         school_id: String,
     ) -> Result<Vec<StudentType>> {
         let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ViewStudents)
+            .await?;
         let collection = db.collection::<models::student::Student>("students");
 
         let mut cursor = collection
-            .find(doc! { "school_id": school_id }, None)
+            .find(
+                doc! {
+                    "school_id": school_id,
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -84,8 +112,26 @@ impl StudentQuery {
         let db = ctx.data::<Database>()?;
         let collection = db.collection::<models::student::Student>("students");
 
+        let class_oid =
+            ObjectId::parse_str(&class_id).map_err(|_| Error::new("Invalid class ID format"))?;
+        let class = db
+            .collection::<models::class::Class>("classes")
+            .find_one(doc! { "_id": class_oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Class not found"))?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &class.school_id, Permission::ViewStudents)
+            .await?;
+
         let mut cursor = collection
-            .find(doc! { "current_class_id": class_id }, None)
+            .find(
+                doc! {
+                    "current_class_id": class_id,
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
             .await
             .map_err(|e| Error::new(e.to_string()))?;
 
@@ -100,4 +146,24 @@ impl StudentQuery {
 
         Ok(students.into_iter().map(|s| s.into()).collect())
     }
+
+    /// Deleted students of a school (the trash), most recently deleted first
+    async fn deleted_students(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<StudentType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::DeleteStudents)
+            .await?;
+
+        let students = find_deleted(
+            &db.collection::<models::student::Student>("students"),
+            doc! { "school_id": school_id },
+        )
+        .await?;
+
+        Ok(students.into_iter().map(|s| s.into()).collect())
+    }
 }
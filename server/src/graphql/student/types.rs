@@ -4,7 +4,7 @@
 // should use the Student model directly now.
 
 use crate::models::student::{Guardian, Student, StudentStatus};
-use crate::utils::common_types::{ContactInfo, DateOfBirth, Gender};
+use crate::utils::common_types::{ContactInfo, DateOfBirth, Gender, SoftDelete};
 use async_graphql::*;
 
 // Re-export the student model types since they already have GraphQL derives
@@ -31,6 +31,8 @@ pub struct StudentType {
     pub grade_level: String,
     pub status: StudentStatus,
     pub guardians: Vec<Guardian>,
+    /// Trash state; set when the record has been deleted
+    pub soft_delete: SoftDelete,
 }
 
 #[ComplexObject]
@@ -66,6 +68,7 @@ impl From<Student> for StudentType {
             grade_level: s.grade_level,
             status: s.status,
             guardians: s.guardians,
+            soft_delete: s.soft_delete,
         }
     }
 }
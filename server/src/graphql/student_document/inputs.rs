@@ -0,0 +1,27 @@
+use async_graphql::{InputObject, Upload};
+
+use crate::models::student::DocumentType;
+
+/// Upload a document straight onto a student's record
+#[derive(InputObject)]
+pub struct UploadStudentDocumentInput {
+    pub student_id: String,
+    pub doc_type: DocumentType,
+    pub file: Upload,
+}
+
+/// Attach a file already uploaded with the StudentDocument purpose
+#[derive(InputObject)]
+pub struct AttachStudentDocumentInput {
+    pub student_id: String,
+    pub doc_type: DocumentType,
+    pub file_id: String,
+}
+
+#[derive(InputObject)]
+pub struct RejectStudentDocumentInput {
+    pub student_id: String,
+    pub document_id: String,
+    /// Shown to whoever uploaded the document
+    pub reason: String,
+}
@@ -0,0 +1,426 @@
+use super::inputs::{
+    AttachStudentDocumentInput, RejectStudentDocumentInput, UploadStudentDocumentInput,
+};
+use crate::graphql::file::mutations::{check_can_upload, file_attachment, store_upload};
+use crate::graphql::file::queries::find_file;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::library::queries::to_bson;
+use crate::graphql::transport::queries::find_student;
+use crate::models::member::{Member, Permission};
+use crate::models::notification::NotificationKind;
+use crate::models::school::School;
+use crate::models::stored_file::{FilePurpose, StoredFile};
+use crate::models::student::{DocumentType, Student, StudentDocument};
+use crate::utils::audit::record_change;
+use crate::utils::notifications::{notify, NotificationMessage};
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Database,
+};
+
+#[derive(Default)]
+pub struct StudentDocumentMutation;
+
+#[Object]
+impl StudentDocumentMutation {
+    /// Upload a document onto a student's record (GraphQL multipart request).
+    /// Staff with UpdateStudents and the student's parents may upload; the
+    /// document waits in the verification queue.
+    async fn upload_student_document(
+        &self,
+        ctx: &Context<'_>,
+        input: UploadStudentDocumentInput,
+    ) -> Result<StudentDocument> {
+        let db = ctx.data::<Database>()?;
+        let student = find_student(db, &input.student_id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &student.school_id)
+            .await?;
+        check_can_upload(
+            db,
+            &member,
+            FilePurpose::StudentDocument,
+            Some(&input.student_id),
+        )
+        .await?;
+
+        let file = store_upload(
+            ctx,
+            &member,
+            FilePurpose::StudentDocument,
+            Some(input.student_id),
+            &input.file,
+        )
+        .await?;
+        add_document(ctx, db, &member, &student, input.doc_type, &file).await
+    }
+
+    /// Attach a file uploaded with `uploadFile` (purpose StudentDocument) as
+    /// one of the student's documents
+    async fn attach_student_document(
+        &self,
+        ctx: &Context<'_>,
+        input: AttachStudentDocumentInput,
+    ) -> Result<StudentDocument> {
+        let db = ctx.data::<Database>()?;
+        let student = find_student(db, &input.student_id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &student.school_id)
+            .await?;
+        check_can_upload(
+            db,
+            &member,
+            FilePurpose::StudentDocument,
+            Some(&input.student_id),
+        )
+        .await?;
+
+        let file = find_file(db, &input.file_id).await?;
+        if file.school_id != student.school_id
+            || file.purpose != FilePurpose::StudentDocument
+            || file.student_id.as_deref() != Some(input.student_id.as_str())
+        {
+            return Err(Error::new(
+                "The file wasn't uploaded as a document for this student",
+            ));
+        }
+        if student
+            .documents
+            .iter()
+            .any(|document| document.file_id.as_deref() == Some(input.file_id.as_str()))
+        {
+            return Err(Error::new("The file is already attached"));
+        }
+
+        add_document(ctx, db, &member, &student, input.doc_type, &file).await
+    }
+
+    /// Mark a document as checked and accepted (UpdateStudents)
+    async fn verify_student_document(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+        document_id: String,
+    ) -> Result<StudentDocument> {
+        let db = ctx.data::<Database>()?;
+        let (student, document) = find_document(db, &student_id, &document_id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &student.school_id, Permission::UpdateStudents)
+            .await?;
+
+        update_document(
+            db,
+            &student,
+            &document_id,
+            doc! {
+                "$set": {
+                    "documents.$.verified": true,
+                    "documents.$.verified_by": &member.user_id,
+                    "documents.$.verified_at": DateTime::now(),
+                    "audit.updated_at": DateTime::now(),
+                    "audit.updated_by": &member.user_id,
+                },
+                "$unset": {
+                    "documents.$.rejection_reason": "",
+                    "documents.$.rejected_by": "",
+                    "documents.$.rejected_at": "",
+                },
+            },
+        )
+        .await?;
+
+        let (_, verified) = find_document(db, &student_id, &document_id).await?;
+        record_change(
+            ctx,
+            "StudentDocument",
+            Some(document_id),
+            Some(&student.school_id),
+            Some(&document),
+            Some(&verified),
+        );
+
+        Ok(verified)
+    }
+
+    /// Turn a document down with a reason (UpdateStudents). Whoever uploaded it
+    /// is notified so a replacement can be sent.
+    async fn reject_student_document(
+        &self,
+        ctx: &Context<'_>,
+        input: RejectStudentDocumentInput,
+    ) -> Result<StudentDocument> {
+        let db = ctx.data::<Database>()?;
+        let reason = input.reason.trim();
+        if reason.is_empty() {
+            return Err(Error::new("Give a reason for rejecting the document"));
+        }
+        let (student, document) = find_document(db, &input.student_id, &input.document_id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &student.school_id, Permission::UpdateStudents)
+            .await?;
+
+        update_document(
+            db,
+            &student,
+            &input.document_id,
+            doc! {
+                "$set": {
+                    "documents.$.verified": false,
+                    "documents.$.rejection_reason": reason,
+                    "documents.$.rejected_by": &member.user_id,
+                    "documents.$.rejected_at": DateTime::now(),
+                    "audit.updated_at": DateTime::now(),
+                    "audit.updated_by": &member.user_id,
+                },
+                "$unset": {
+                    "documents.$.verified_by": "",
+                    "documents.$.verified_at": "",
+                },
+            },
+        )
+        .await?;
+
+        let (_, rejected) = find_document(db, &input.student_id, &input.document_id).await?;
+        if let Some(uploader) = rejected
+            .attachment
+            .uploaded_by
+            .clone()
+            .filter(|uploader| *uploader != member.user_id)
+        {
+            let message = NotificationMessage {
+                school_id: Some(&student.school_id),
+                kind: NotificationKind::General,
+                title: "Document rejected".to_string(),
+                body: format!(
+                    "{} for {} {} was rejected: {}",
+                    rejected.attachment.file_name,
+                    student.first_name_km,
+                    student.last_name_km,
+                    reason
+                ),
+                link: Some(format!("student:{}", input.student_id)),
+            };
+            notify(db, &[uploader], &message)
+                .await
+                .map_err(|e| Error::new(format!("Failed to notify uploader: {}", e)))?;
+        }
+
+        record_change(
+            ctx,
+            "StudentDocument",
+            Some(input.document_id),
+            Some(&student.school_id),
+            Some(&document),
+            Some(&rejected),
+        );
+
+        Ok(rejected)
+    }
+
+    /// Take a document off a student's record (UpdateStudents, or the uploader
+    /// while it isn't verified). The uploaded file itself is kept.
+    async fn remove_student_document(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+        document_id: String,
+    ) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let (student, document) = find_document(db, &student_id, &document_id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &student.school_id)
+            .await?;
+        let can_withdraw = !document.verified
+            && document.attachment.uploaded_by.as_deref() == Some(&member.user_id);
+        if !can_withdraw && !member.has_permission(Permission::UpdateStudents) {
+            return Err(Error::new(
+                "Insufficient permissions to remove this document",
+            ));
+        }
+
+        let oid = student.id.ok_or_else(|| Error::new("Student not found"))?;
+        db.collection::<Student>("students")
+            .update_one(
+                doc! { "_id": oid },
+                doc! {
+                    "$pull": { "documents": { "document_id": &document_id } },
+                    "$set": {
+                        "audit.updated_at": DateTime::now(),
+                        "audit.updated_by": &member.user_id,
+                    },
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to remove document: {}", e)))?;
+
+        record_change(
+            ctx,
+            "StudentDocument",
+            Some(document_id),
+            Some(&student.school_id),
+            Some(&document),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Choose the documents every student must have on file (ManageSettings)
+    async fn set_required_documents(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        doc_types: Vec<DocumentType>,
+    ) -> Result<School> {
+        let db = ctx.data::<Database>()?;
+        let member = get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ManageSettings)
+            .await?;
+
+        let mut required: Vec<DocumentType> = Vec::new();
+        for doc_type in doc_types {
+            if !required.contains(&doc_type) {
+                required.push(doc_type);
+            }
+        }
+
+        let school_oid =
+            ObjectId::parse_str(&school_id).map_err(|_| Error::new("Invalid school ID"))?;
+        let collection = db.collection::<School>("schools");
+        collection
+            .update_one(
+                doc! { "_id": school_oid },
+                doc! { "$set": {
+                    "settings.required_documents": to_bson(&required)?,
+                    "audit.updated_at": DateTime::now(),
+                    "audit.updated_by": &member.user_id,
+                } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update school: {}", e)))?;
+
+        collection
+            .find_one(doc! { "_id": school_oid }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to retrieve school: {}", e)))?
+            .ok_or_else(|| Error::new("School not found"))
+    }
+}
+
+/// Put an uploaded file on the student's record as a pending document. Types
+/// that allow one document replace the previous one; a verified one can only
+/// be replaced by staff with UpdateStudents.
+async fn add_document(
+    ctx: &Context<'_>,
+    db: &Database,
+    member: &Member,
+    student: &Student,
+    doc_type: DocumentType,
+    file: &StoredFile,
+) -> Result<StudentDocument> {
+    let oid = student.id.ok_or_else(|| Error::new("Student not found"))?;
+    let collection = db.collection::<Student>("students");
+
+    let replaced: Vec<&StudentDocument> = if doc_type.allows_multiple() {
+        Vec::new()
+    } else {
+        student
+            .documents
+            .iter()
+            .filter(|document| document.doc_type == doc_type)
+            .collect()
+    };
+    if replaced.iter().any(|document| document.verified)
+        && !member.has_permission(Permission::UpdateStudents)
+    {
+        return Err(Error::new(
+            "A verified document of this type is already on file. Ask the school to replace it",
+        ));
+    }
+    if !replaced.is_empty() {
+        collection
+            .update_one(
+                doc! { "_id": oid },
+                doc! { "$pull": { "documents": { "doc_type": to_bson(&doc_type)? } } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to replace document: {}", e)))?;
+    }
+
+    let document = StudentDocument {
+        document_id: ObjectId::new().to_hex(),
+        doc_type,
+        attachment: file_attachment(file),
+        file_id: file.id.map(|id| id.to_hex()),
+        verified: false,
+        verified_by: None,
+        verified_at: None,
+        rejection_reason: None,
+        rejected_by: None,
+        rejected_at: None,
+    };
+    collection
+        .update_one(
+            doc! { "_id": oid },
+            doc! {
+                "$push": { "documents": to_bson(&document)? },
+                "$set": {
+                    "audit.updated_at": DateTime::now(),
+                    "audit.updated_by": &member.user_id,
+                },
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to add document: {}", e)))?;
+
+    record_change(
+        ctx,
+        "StudentDocument",
+        Some(document.document_id.clone()),
+        Some(&student.school_id),
+        replaced.first().copied(),
+        Some(&document),
+    );
+
+    Ok(document)
+}
+
+/// Load a student and one of their documents
+async fn find_document(
+    db: &Database,
+    student_id: &str,
+    document_id: &str,
+) -> Result<(Student, StudentDocument)> {
+    let student = find_student(db, student_id).await?;
+    let document = student
+        .documents
+        .iter()
+        .find(|document| document.document_id == document_id)
+        .cloned()
+        .ok_or_else(|| Error::new("Document not found"))?;
+    Ok((student, document))
+}
+
+/// Apply an update to one document, addressed with the positional operator
+async fn update_document(
+    db: &Database,
+    student: &Student,
+    document_id: &str,
+    update: mongodb::bson::Document,
+) -> Result<()> {
+    let oid = student.id.ok_or_else(|| Error::new("Student not found"))?;
+    db.collection::<Student>("students")
+        .update_one(
+            doc! { "_id": oid, "documents.document_id": document_id },
+            update,
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to update document: {}", e)))?;
+    Ok(())
+}
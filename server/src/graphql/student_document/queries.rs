@@ -0,0 +1,191 @@
+use super::types::{MissingDocumentsReportType, PendingDocumentType, StudentDocumentStatusType};
+use crate::graphql::file::queries::can_access_student;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::library::queries::{collect, to_bson};
+use crate::graphql::transport::queries::find_student;
+use crate::models::class::Class;
+use crate::models::member::Permission;
+use crate::models::school::School;
+use crate::models::student::{DocumentStatus, DocumentType, Student, StudentDocument};
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct StudentDocumentQuery;
+
+#[Object]
+impl StudentDocumentQuery {
+    /// A student's documents (staff who can view students, the student's
+    /// parents and the student)
+    async fn student_documents(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+    ) -> Result<Vec<StudentDocument>> {
+        let db = ctx.data::<Database>()?;
+        let student = find_student(db, &student_id).await?;
+        let member = get_graphql_context(ctx)?
+            .require_member(db, &student.school_id)
+            .await?;
+        if !can_access_student(&member, &student_id) {
+            return Err(Error::new("Student not found"));
+        }
+        Ok(student.documents)
+    }
+
+    /// Documents waiting for verification, oldest upload first (UpdateStudents)
+    async fn document_verification_queue(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        doc_type: Option<DocumentType>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<PendingDocumentType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::UpdateStudents)
+            .await?;
+
+        let mut pending = doc! {
+            "verified": { "$ne": true },
+            "rejection_reason": null,
+        };
+        if let Some(doc_type) = doc_type {
+            pending.insert("doc_type", to_bson(&doc_type)?);
+        }
+        let students = collect(
+            &db.collection::<Student>("students"),
+            doc! {
+                "school_id": &school_id,
+                "soft_delete.is_deleted": { "$ne": true },
+                "documents": { "$elemMatch": pending },
+            },
+            FindOptions::default(),
+        )
+        .await?;
+
+        let mut queue: Vec<PendingDocumentType> = students
+            .into_iter()
+            .flat_map(|student| {
+                student
+                    .documents
+                    .iter()
+                    .filter(|document| {
+                        document.verification_status() == DocumentStatus::Pending
+                            && doc_type.is_none_or(|doc_type| document.doc_type == doc_type)
+                    })
+                    .map(|document| PendingDocumentType {
+                        student: student.clone(),
+                        document: document.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        queue.sort_by_key(|item| item.document.attachment.uploaded_at);
+
+        Ok(queue
+            .into_iter()
+            .skip(offset.unwrap_or(0).max(0) as usize)
+            .take(limit.unwrap_or(50).clamp(1, 200) as usize)
+            .collect())
+    }
+
+    /// Which of the school's required documents each active student in a
+    /// class lacks (ViewStudents)
+    async fn missing_documents_report(
+        &self,
+        ctx: &Context<'_>,
+        class_id: String,
+    ) -> Result<MissingDocumentsReportType> {
+        let db = ctx.data::<Database>()?;
+        let class_oid =
+            ObjectId::parse_str(&class_id).map_err(|_| Error::new("Invalid class ID format"))?;
+        let class = db
+            .collection::<Class>("classes")
+            .find_one(
+                doc! { "_id": class_oid, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Class not found"))?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &class.school_id, Permission::ViewStudents)
+            .await?;
+
+        let school_oid =
+            ObjectId::parse_str(&class.school_id).map_err(|_| Error::new("Invalid school ID"))?;
+        let school = db
+            .collection::<School>("schools")
+            .find_one(doc! { "_id": school_oid }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("School not found"))?;
+        let required = school.settings.required_documents;
+
+        let students = collect(
+            &db.collection::<Student>("students"),
+            doc! {
+                "current_class_id": &class_id,
+                "status": "Active",
+                "soft_delete.is_deleted": { "$ne": true },
+            },
+            FindOptions::builder()
+                .sort(doc! { "student_id": 1 })
+                .build(),
+        )
+        .await?;
+
+        let students: Vec<StudentDocumentStatusType> = students
+            .into_iter()
+            .map(|student| document_status(student, &required))
+            .collect();
+        let complete_count = students.iter().filter(|s| s.complete).count() as i32;
+
+        Ok(MissingDocumentsReportType {
+            class_id,
+            class_name: class.name,
+            required_documents: required,
+            incomplete_count: students.len() as i32 - complete_count,
+            students,
+            complete_count,
+        })
+    }
+}
+
+/// Sort each required type by the best document the student has for it
+fn document_status(student: Student, required: &[DocumentType]) -> StudentDocumentStatusType {
+    let mut missing = Vec::new();
+    let mut pending = Vec::new();
+    let mut rejected = Vec::new();
+    for doc_type in required {
+        let statuses: Vec<DocumentStatus> = student
+            .documents
+            .iter()
+            .filter(|document| document.doc_type == *doc_type)
+            .map(|document| document.verification_status())
+            .collect();
+        if statuses.contains(&DocumentStatus::Verified) {
+            continue;
+        } else if statuses.contains(&DocumentStatus::Pending) {
+            pending.push(*doc_type);
+        } else if statuses.contains(&DocumentStatus::Rejected) {
+            rejected.push(*doc_type);
+        } else {
+            missing.push(*doc_type);
+        }
+    }
+
+    StudentDocumentStatusType {
+        complete: missing.is_empty() && pending.is_empty() && rejected.is_empty(),
+        student,
+        missing,
+        pending,
+        rejected,
+    }
+}
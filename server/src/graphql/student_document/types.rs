@@ -0,0 +1,34 @@
+use crate::models::student::{DocumentType, Student, StudentDocument};
+use async_graphql::SimpleObject;
+
+/// A document waiting for verification, with the student it belongs to
+#[derive(SimpleObject)]
+pub struct PendingDocumentType {
+    pub student: Student,
+    pub document: StudentDocument,
+}
+
+/// Where a student stands on the school's required documents
+#[derive(SimpleObject)]
+pub struct StudentDocumentStatusType {
+    pub student: Student,
+    /// Required types with nothing on file
+    pub missing: Vec<DocumentType>,
+    /// Required types uploaded but not yet verified
+    pub pending: Vec<DocumentType>,
+    /// Required types whose documents were all rejected
+    pub rejected: Vec<DocumentType>,
+    /// Every required type has a verified document
+    pub complete: bool,
+}
+
+/// Required documents for each student in a class
+#[derive(SimpleObject)]
+pub struct MissingDocumentsReportType {
+    pub class_id: String,
+    pub class_name: String,
+    pub required_documents: Vec<DocumentType>,
+    pub students: Vec<StudentDocumentStatusType>,
+    pub complete_count: i32,
+    pub incomplete_count: i32,
+}
@@ -0,0 +1,316 @@
+// Student portal GraphQL queries
+use super::types::{StudentTimetableType, TeacherContactType};
+use crate::graphql::attendance::queries::find_student_attendance;
+use crate::graphql::attendance::AttendanceType;
+use crate::graphql::grade::queries::{build_report_card, find_student_grades};
+use crate::graphql::grade::{GradeType, ReportCardType};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::student::StudentType;
+use crate::models;
+use crate::models::member::{Member, Permission, SchoolRole};
+use crate::models::school::SchoolFeature;
+use crate::models::student::Student;
+use crate::models::user::User;
+use crate::utils::features::permission_feature;
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    Database,
+};
+
+#[derive(Default)]
+pub struct StudentPortalQuery;
+
+#[Object]
+impl StudentPortalQuery {
+    /// Get the current student's own profile
+    async fn my_student_profile(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<StudentType> {
+        let db = ctx.data::<Database>()?;
+        let student = require_student(ctx, db, &school_id, Permission::ViewDashboard).await?;
+
+        Ok(student.into())
+    }
+
+    /// Get the weekly timetable of the current student's class
+    async fn my_timetable(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Option<StudentTimetableType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_student(ctx, db, &school_id, Permission::ViewDashboard).await?;
+
+        let Some(class) = find_current_class(db, &student).await? else {
+            return Ok(None);
+        };
+
+        let schedule = class.schedule.clone();
+        Ok(Some(StudentTimetableType {
+            class: class.into(),
+            schedule,
+        }))
+    }
+
+    /// Get the current student's grades, optionally for one academic year and semester
+    async fn my_grades(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        academic_year: Option<String>,
+        semester: Option<String>,
+    ) -> Result<Vec<GradeType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_student(ctx, db, &school_id, Permission::ViewGrades).await?;
+
+        let grades = find_student_grades(
+            db,
+            student.id.unwrap(),
+            academic_year.as_deref(),
+            semester.as_deref(),
+        )
+        .await?;
+
+        Ok(grades.into_iter().map(|g| g.into()).collect())
+    }
+
+    /// Get the current student's report card for a term
+    async fn my_report_card(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        academic_year: String,
+        semester: String,
+    ) -> Result<ReportCardType> {
+        let db = ctx.data::<Database>()?;
+        let student = require_student(ctx, db, &school_id, Permission::ViewGrades).await?;
+
+        build_report_card(db, student.id.unwrap(), &academic_year, &semester).await
+    }
+
+    /// Get the current student's attendance record, optionally within a date range (YYYY-MM-DD)
+    async fn my_attendance(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        start_date: Option<String>,
+        end_date: Option<String>,
+    ) -> Result<Vec<AttendanceType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_student(ctx, db, &school_id, Permission::ViewAttendance).await?;
+
+        let attendances =
+            find_student_attendance(db, student.id.unwrap(), start_date, end_date).await?;
+
+        Ok(attendances.into_iter().map(|a| a.into()).collect())
+    }
+
+    /// Get the current student's assignments (grades with assessment type "assignment")
+    async fn my_assignments(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        academic_year: Option<String>,
+        semester: Option<String>,
+    ) -> Result<Vec<GradeType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_student(ctx, db, &school_id, Permission::ViewGrades).await?;
+
+        let grades = find_student_grades(
+            db,
+            student.id.unwrap(),
+            academic_year.as_deref(),
+            semester.as_deref(),
+        )
+        .await?;
+
+        Ok(grades
+            .into_iter()
+            .filter(|g| g.assessment_type.eq_ignore_ascii_case("assignment"))
+            .map(|g| g.into())
+            .collect())
+    }
+
+    /// Get contact details for the current student's homeroom and subject teachers
+    async fn my_teachers(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<TeacherContactType>> {
+        let db = ctx.data::<Database>()?;
+        let student = require_student(ctx, db, &school_id, Permission::ViewDashboard).await?;
+
+        let Some(class) = find_current_class(db, &student).await? else {
+            return Ok(Vec::new());
+        };
+
+        // Collect each teacher referenced by the class with the subjects they teach
+        let mut contacts: Vec<TeacherContactType> = Vec::new();
+        if let Some(ref homeroom_id) = class.homeroom_teacher_id {
+            contacts.push(TeacherContactType {
+                teacher_id: homeroom_id.clone(),
+                name: None,
+                email: None,
+                phone: None,
+                subject_ids: Vec::new(),
+                is_homeroom: true,
+            });
+        }
+        for period in class.schedule.iter().flat_map(|s| s.periods.iter()) {
+            match contacts
+                .iter_mut()
+                .find(|c| c.teacher_id == period.teacher_id)
+            {
+                Some(contact) => {
+                    if !contact.subject_ids.contains(&period.subject_id) {
+                        contact.subject_ids.push(period.subject_id.clone());
+                    }
+                }
+                None => contacts.push(TeacherContactType {
+                    teacher_id: period.teacher_id.clone(),
+                    name: None,
+                    email: None,
+                    phone: None,
+                    subject_ids: vec![period.subject_id.clone()],
+                    is_homeroom: false,
+                }),
+            }
+        }
+
+        for contact in contacts.iter_mut() {
+            if let Some(user) = find_teacher_user(db, &school_id, &contact.teacher_id).await? {
+                contact.name = Some(display_name(&user));
+                contact.email = user.email;
+                contact.phone = user.phone;
+            }
+        }
+
+        Ok(contacts)
+    }
+}
+
+/// Require the caller to be a Student member of the school, linked to a student record,
+/// holding the given permission
+async fn require_student(
+    ctx: &Context<'_>,
+    db: &Database,
+    school_id: &str,
+    permission: Permission,
+) -> Result<Student> {
+    let member = get_graphql_context(ctx)?
+        .require_member_role(db, school_id, &[SchoolRole::Student])
+        .await?;
+    ensure_feature(db, school_id, SchoolFeature::StudentPortal).await?;
+
+    if !member.has_permission(permission) {
+        return Err(Error::new(format!(
+            "Insufficient permissions. Required permission: {:?}",
+            permission
+        )));
+    }
+    if let Some(feature) = permission_feature(permission) {
+        ensure_feature(db, school_id, feature).await?;
+    }
+
+    let student_oid = member
+        .student_id
+        .as_deref()
+        .and_then(|id| ObjectId::parse_str(id).ok())
+        .ok_or_else(|| Error::new("Your membership is not linked to a student record"))?;
+
+    let student = db
+        .collection::<Student>("students")
+        .find_one(
+            doc! {
+                "_id": student_oid,
+                "school_id": school_id,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Student record not found"))?;
+
+    Ok(student)
+}
+
+/// Load the student's current class, if assigned
+async fn find_current_class(
+    db: &Database,
+    student: &Student,
+) -> Result<Option<models::class::Class>> {
+    let Some(class_oid) = student
+        .current_class_id
+        .as_deref()
+        .and_then(|id| ObjectId::parse_str(id).ok())
+    else {
+        return Ok(None);
+    };
+
+    db.collection::<models::class::Class>("classes")
+        .find_one(
+            doc! {
+                "_id": class_oid,
+                "school_id": &student.school_id,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))
+}
+
+/// Resolve a schedule teacher reference (member, user or staff ID) to the teacher's user account
+async fn find_teacher_user(
+    db: &Database,
+    school_id: &str,
+    teacher_id: &str,
+) -> Result<Option<User>> {
+    let mut conditions = vec![
+        doc! { "user_id": teacher_id },
+        doc! { "staff_id": teacher_id },
+    ];
+    if let Ok(oid) = ObjectId::parse_str(teacher_id) {
+        conditions.push(doc! { "_id": oid });
+    }
+
+    let member = db
+        .collection::<Member>("members")
+        .find_one(
+            doc! {
+                "school_id": school_id,
+                "soft_delete.is_deleted": false,
+                "$or": conditions
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let Some(user_oid) = member.and_then(|m| ObjectId::parse_str(&m.user_id).ok()) else {
+        return Ok(None);
+    };
+
+    db.collection::<User>("users")
+        .find_one(doc! { "_id": user_oid }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))
+}
+
+/// Best available display name for a user
+fn display_name(user: &User) -> String {
+    match (&user.first_name, &user.last_name) {
+        (Some(first), Some(last)) => format!("{} {}", first, last),
+        (Some(first), None) => first.clone(),
+        (None, Some(last)) => last.clone(),
+        (None, None) => user
+            .display_name
+            .clone()
+            .unwrap_or_else(|| user.username.clone()),
+    }
+}
@@ -0,0 +1,24 @@
+// Student portal GraphQL types
+use crate::graphql::class::ClassType;
+use crate::models::class::ClassSchedule;
+use async_graphql::*;
+
+/// The weekly timetable of the student's current class
+#[derive(SimpleObject)]
+pub struct StudentTimetableType {
+    pub class: ClassType,
+    pub schedule: Vec<ClassSchedule>,
+}
+
+/// Contact details for one of the student's teachers
+#[derive(SimpleObject)]
+pub struct TeacherContactType {
+    /// Teacher ID as referenced by the class schedule
+    pub teacher_id: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    /// Subjects this teacher teaches the class
+    pub subject_ids: Vec<String>,
+    pub is_homeroom: bool,
+}
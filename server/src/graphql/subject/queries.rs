@@ -1,7 +1,10 @@
 // Subject GraphQL queries
 use super::inputs::{SubjectFilterInput, SubjectSortInput};
 use super::types::{PaginatedSubjectsResult, SubjectType};
+use crate::graphql::graphql_context::get_graphql_context;
 use crate::models;
+use crate::models::member::Permission;
+use crate::utils::soft_delete::find_deleted;
 use async_graphql::*;
 use futures::stream::TryStreamExt;
 use mongodb::{
@@ -160,6 +163,26 @@ impl SubjectQuery {
             total_pages,
         })
     }
+
+    /// Get a school's deleted subjects (the trash), most recently deleted first
+    async fn deleted_subjects(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<SubjectType>> {
+        let db = ctx.data::<Database>()?;
+        get_graphql_context(ctx)?
+            .require_member_permission(db, &school_id, Permission::ManageClasses)
+            .await?;
+
+        let records = find_deleted(
+            &db.collection::<models::subject::Subject>("subjects"),
+            doc! { "school_id": school_id },
+        )
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
 }
 
 /// Helper function to build sort document
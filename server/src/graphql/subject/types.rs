@@ -1,6 +1,6 @@
 // Subject GraphQL types
 use crate::models::subject::Subject;
-use crate::utils::common_types::Status;
+use crate::utils::common_types::{SoftDelete, Status};
 use async_graphql::*;
 
 #[derive(SimpleObject)]
@@ -17,6 +17,8 @@ pub struct SubjectType {
     pub status: Status,
     pub created_at: String,
     pub updated_at: String,
+    /// Trash state; set when the record has been deleted
+    pub soft_delete: SoftDelete,
 }
 
 impl From<Subject> for SubjectType {
@@ -42,6 +44,7 @@ impl From<Subject> for SubjectType {
                 .updated_at
                 .map(|dt| dt.try_to_rfc3339_string().unwrap_or_default())
                 .unwrap_or_default(),
+            soft_delete: s.soft_delete,
         }
     }
 }
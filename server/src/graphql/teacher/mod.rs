@@ -0,0 +1,4 @@
+pub mod queries;
+pub mod types;
+
+pub use queries::TeacherQuery;
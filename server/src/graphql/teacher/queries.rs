@@ -0,0 +1,349 @@
+// Teacher portal GraphQL queries
+use super::types::{
+    ClassRosterType, PendingAttendanceType, PendingGradeEntryType, TeacherPeriodType,
+};
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models;
+use crate::models::member::{Member, SchoolRole};
+use crate::utils::common_types::DayOfWeek;
+use async_graphql::*;
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Database,
+};
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct TeacherQuery;
+
+#[Object]
+impl TeacherQuery {
+    /// Get the periods the current teacher teaches on a day (defaults to today)
+    async fn my_teaching_periods(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        date: Option<String>,
+    ) -> Result<Vec<TeacherPeriodType>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_teacher(ctx, db, &school_id).await?;
+        let teacher_ids = teacher_identifiers(&member);
+        let day = DayOfWeek::from_weekday(parse_date_or_today(date)?.weekday());
+
+        let classes = find_teacher_classes(db, &school_id, &teacher_ids).await?;
+
+        let mut periods = Vec::new();
+        for class in &classes {
+            for schedule in class.schedule.iter().filter(|s| s.day == day) {
+                for period in schedule
+                    .periods
+                    .iter()
+                    .filter(|p| teacher_ids.contains(&p.teacher_id))
+                {
+                    periods.push(TeacherPeriodType {
+                        class_id: class.id.map(|id| id.to_hex()).unwrap_or_default(),
+                        class_name: class.name.clone(),
+                        period_number: period.period_number,
+                        subject_id: period.subject_id.clone(),
+                        start_time: period.start_time.clone(),
+                        end_time: period.end_time.clone(),
+                        room: period.room.clone().or_else(|| class.room_number.clone()),
+                    });
+                }
+            }
+        }
+
+        periods.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+
+        Ok(periods)
+    }
+
+    /// Get the rosters of every class the current teacher teaches or homerooms
+    async fn my_class_rosters(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<ClassRosterType>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_teacher(ctx, db, &school_id).await?;
+        let teacher_ids = teacher_identifiers(&member);
+        let student_collection = db.collection::<models::student::Student>("students");
+
+        let classes = find_teacher_classes(db, &school_id, &teacher_ids).await?;
+
+        let mut rosters = Vec::new();
+        for class in classes {
+            let class_id = class.id.map(|id| id.to_hex()).unwrap_or_default();
+
+            let mut cursor = student_collection
+                .find(
+                    doc! {
+                        "current_class_id": &class_id,
+                        "soft_delete.is_deleted": { "$ne": true }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?;
+
+            let mut students = Vec::new();
+            while let Some(student) = cursor
+                .try_next()
+                .await
+                .map_err(|e| Error::new(e.to_string()))?
+            {
+                students.push(student.into());
+            }
+
+            rosters.push(ClassRosterType {
+                class: class.into(),
+                students,
+            });
+        }
+
+        Ok(rosters)
+    }
+
+    /// Get classes where the current teacher still needs to take attendance (defaults to today)
+    async fn my_pending_attendance(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        date: Option<String>,
+    ) -> Result<Vec<PendingAttendanceType>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_teacher(ctx, db, &school_id).await?;
+        let teacher_ids = teacher_identifiers(&member);
+        let attendance_collection =
+            db.collection::<models::attendance::Attendance>("attendances");
+
+        let parsed_date = parse_date_or_today(date)?;
+        let day = DayOfWeek::from_weekday(parsed_date.weekday());
+        let start_of_day = Utc.from_utc_datetime(&parsed_date.and_hms_opt(0, 0, 0).unwrap());
+        let end_of_day = Utc.from_utc_datetime(&parsed_date.and_hms_opt(23, 59, 59).unwrap());
+
+        let classes = find_teacher_classes(db, &school_id, &teacher_ids).await?;
+
+        let mut pending = Vec::new();
+        for class in classes {
+            // Only classes the teacher homerooms or teaches on this day
+            let is_homeroom = class
+                .homeroom_teacher_id
+                .as_ref()
+                .is_some_and(|id| teacher_ids.contains(id));
+            let teaches_today = class.schedule.iter().any(|s| {
+                s.day == day
+                    && s.periods
+                        .iter()
+                        .any(|p| teacher_ids.contains(&p.teacher_id))
+            });
+            if !is_homeroom && !teaches_today {
+                continue;
+            }
+
+            let Some(class_oid) = class.id else {
+                continue;
+            };
+
+            let filter = doc! {
+                "class_id": class_oid,
+                "soft_delete.is_deleted": { "$ne": true },
+                "date": {
+                    "$gte": DateTime::from_millis(start_of_day.timestamp_millis()),
+                    "$lte": DateTime::from_millis(end_of_day.timestamp_millis())
+                }
+            };
+
+            let mut cursor = attendance_collection
+                .find(filter, None)
+                .await
+                .map_err(|e| Error::new(e.to_string()))?;
+
+            let mut marked = HashSet::new();
+            while let Some(attendance) = cursor
+                .try_next()
+                .await
+                .map_err(|e| Error::new(e.to_string()))?
+            {
+                marked.insert(attendance.student_id.to_hex());
+            }
+
+            let unmarked_student_ids: Vec<String> = class
+                .student_ids
+                .iter()
+                .filter(|id| !marked.contains(*id))
+                .cloned()
+                .collect();
+
+            if unmarked_student_ids.is_empty() {
+                continue;
+            }
+
+            pending.push(PendingAttendanceType {
+                class_id: class_oid.to_hex(),
+                class_name: class.name,
+                date: parsed_date.format("%Y-%m-%d").to_string(),
+                expected_count: class.student_ids.len() as i32,
+                marked_count: (class.student_ids.len() - unmarked_student_ids.len()) as i32,
+                unmarked_student_ids,
+            });
+        }
+
+        Ok(pending)
+    }
+
+    /// Get class/subject pairs where the current teacher still has grades to enter for a term
+    async fn my_pending_grade_entries(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        academic_year: String,
+        semester: String,
+    ) -> Result<Vec<PendingGradeEntryType>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_teacher(ctx, db, &school_id).await?;
+        let teacher_ids = teacher_identifiers(&member);
+        let grade_collection = db.collection::<models::grade::Grade>("grades");
+
+        let classes = find_teacher_classes(db, &school_id, &teacher_ids).await?;
+
+        let mut pending = Vec::new();
+        for class in classes {
+            let Some(class_oid) = class.id else {
+                continue;
+            };
+
+            // Distinct subjects this teacher teaches in the class
+            let mut subject_ids: Vec<String> = class
+                .schedule
+                .iter()
+                .flat_map(|s| s.periods.iter())
+                .filter(|p| teacher_ids.contains(&p.teacher_id))
+                .map(|p| p.subject_id.clone())
+                .collect();
+            subject_ids.sort();
+            subject_ids.dedup();
+
+            for subject_id in subject_ids {
+                let Ok(subject_oid) = ObjectId::parse_str(&subject_id) else {
+                    continue;
+                };
+
+                let filter = doc! {
+                    "class_id": class_oid,
+                    "subject_id": subject_oid,
+                    "academic_year": &academic_year,
+                    "semester": &semester,
+                    "soft_delete.is_deleted": { "$ne": true }
+                };
+
+                let mut cursor = grade_collection
+                    .find(filter, None)
+                    .await
+                    .map_err(|e| Error::new(e.to_string()))?;
+
+                let mut graded = HashSet::new();
+                while let Some(grade) = cursor
+                    .try_next()
+                    .await
+                    .map_err(|e| Error::new(e.to_string()))?
+                {
+                    graded.insert(grade.student_id.to_hex());
+                }
+
+                let missing_student_ids: Vec<String> = class
+                    .student_ids
+                    .iter()
+                    .filter(|id| !graded.contains(*id))
+                    .cloned()
+                    .collect();
+
+                if missing_student_ids.is_empty() {
+                    continue;
+                }
+
+                pending.push(PendingGradeEntryType {
+                    class_id: class_oid.to_hex(),
+                    class_name: class.name.clone(),
+                    subject_id,
+                    academic_year: academic_year.clone(),
+                    semester: semester.clone(),
+                    missing_count: missing_student_ids.len() as i32,
+                    missing_student_ids,
+                });
+            }
+        }
+
+        Ok(pending)
+    }
+}
+
+/// Require the caller to be a Teacher or HeadTeacher in the school
+async fn require_teacher(ctx: &Context<'_>, db: &Database, school_id: &str) -> Result<Member> {
+    get_graphql_context(ctx)?
+        .require_member_role(
+            db,
+            school_id,
+            &[SchoolRole::Teacher, SchoolRole::HeadTeacher],
+        )
+        .await
+}
+
+/// IDs a class schedule may use to refer to this teacher
+/// (member ID, user ID, or linked staff record)
+pub(crate) fn teacher_identifiers(member: &Member) -> Vec<String> {
+    let mut ids = vec![member.user_id.clone()];
+    if let Some(id) = member.id {
+        ids.push(id.to_hex());
+    }
+    if let Some(ref staff_id) = member.staff_id {
+        ids.push(staff_id.clone());
+    }
+    ids
+}
+
+/// Parse a YYYY-MM-DD date, falling back to today (UTC)
+fn parse_date_or_today(date: Option<String>) -> Result<NaiveDate> {
+    match date {
+        Some(d) => NaiveDate::parse_from_str(&d, "%Y-%m-%d")
+            .map_err(|_| Error::new("Invalid date format. Use YYYY-MM-DD")),
+        None => Ok(Utc::now().date_naive()),
+    }
+}
+
+/// Find active classes in a school where the teacher is homeroom teacher or teaches a period
+pub(crate) async fn find_teacher_classes(
+    db: &Database,
+    school_id: &str,
+    teacher_ids: &[String],
+) -> Result<Vec<models::class::Class>> {
+    let collection = db.collection::<models::class::Class>("classes");
+
+    let filter = doc! {
+        "school_id": school_id,
+        "status": "Active",
+        "soft_delete.is_deleted": { "$ne": true },
+        "$or": [
+            { "homeroom_teacher_id": { "$in": teacher_ids } },
+            { "schedule.periods.teacher_id": { "$in": teacher_ids } }
+        ]
+    };
+
+    let mut cursor = collection
+        .find(filter, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut classes = Vec::new();
+    while let Some(class) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        classes.push(class);
+    }
+
+    Ok(classes)
+}
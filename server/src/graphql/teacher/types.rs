@@ -0,0 +1,48 @@
+// Teacher portal GraphQL types
+use crate::graphql::class::ClassType;
+use crate::graphql::student::StudentType;
+use async_graphql::*;
+
+/// A period the teacher is scheduled to teach on a given day
+#[derive(SimpleObject)]
+pub struct TeacherPeriodType {
+    pub class_id: String,
+    pub class_name: String,
+    pub period_number: i32,
+    pub subject_id: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub room: Option<String>,
+}
+
+/// A class the teacher is responsible for, with its enrolled students
+#[derive(SimpleObject)]
+pub struct ClassRosterType {
+    pub class: ClassType,
+    pub students: Vec<StudentType>,
+}
+
+/// A class whose attendance has not been fully marked for a day
+#[derive(SimpleObject)]
+pub struct PendingAttendanceType {
+    pub class_id: String,
+    pub class_name: String,
+    pub date: String,
+    pub expected_count: i32,
+    pub marked_count: i32,
+    /// Students without an attendance record for the day
+    pub unmarked_student_ids: Vec<String>,
+}
+
+/// A class/subject pair with students still missing grades for the term
+#[derive(SimpleObject)]
+pub struct PendingGradeEntryType {
+    pub class_id: String,
+    pub class_name: String,
+    pub subject_id: String,
+    pub academic_year: String,
+    pub semester: String,
+    pub missing_count: i32,
+    /// Students without any grade recorded for the subject this term
+    pub missing_student_ids: Vec<String>,
+}
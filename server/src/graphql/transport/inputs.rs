@@ -0,0 +1,125 @@
+use async_graphql::InputObject;
+
+use crate::models::transport::{BoardingEvent, TripDirection, VehicleStatus};
+use crate::utils::common_types::GpsCoordinates;
+
+#[derive(InputObject)]
+pub struct CreateVehicleInput {
+    pub school_id: String,
+    pub plate_number: String,
+    pub name: Option<String>,
+    pub capacity: i32,
+    pub make_model: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateVehicleInput {
+    pub vehicle_id: String,
+    pub plate_number: Option<String>,
+    pub name: Option<String>,
+    pub capacity: Option<i32>,
+    pub make_model: Option<String>,
+    pub status: Option<VehicleStatus>,
+}
+
+#[derive(InputObject)]
+pub struct CreateDriverInput {
+    pub school_id: String,
+    /// HR staff record of the driver
+    pub staff_id: String,
+    /// School membership of the driver, if they use the app
+    pub member_id: Option<String>,
+    pub license_number: String,
+    /// License expiry (YYYY-MM-DD)
+    pub license_expiry: Option<String>,
+    pub phone: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateDriverInput {
+    pub driver_id: String,
+    pub member_id: Option<String>,
+    pub license_number: Option<String>,
+    /// License expiry (YYYY-MM-DD)
+    pub license_expiry: Option<String>,
+    pub phone: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// A stop in route order; the first stop is the first morning pickup
+#[derive(InputObject)]
+pub struct RouteStopInput {
+    /// ID of an existing stop to keep (students assigned to it stay assigned)
+    pub stop_id: Option<String>,
+    pub name: String,
+    pub location: GpsCoordinates,
+    /// Scheduled morning pickup (HH:MM)
+    pub pickup_time: Option<String>,
+    /// Scheduled afternoon drop-off (HH:MM)
+    pub dropoff_time: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct CreateBusRouteInput {
+    pub school_id: String,
+    pub name: String,
+    pub code: Option<String>,
+    pub vehicle_id: Option<String>,
+    pub driver_id: Option<String>,
+    pub stops: Vec<RouteStopInput>,
+    pub monthly_fee: Option<f64>,
+    /// Fee currency (default "USD")
+    pub currency: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateBusRouteInput {
+    pub route_id: String,
+    pub name: Option<String>,
+    pub code: Option<String>,
+    pub vehicle_id: Option<String>,
+    pub driver_id: Option<String>,
+    /// Replaces all stops. Stops with students assigned must be kept.
+    pub stops: Option<Vec<RouteStopInput>>,
+    pub monthly_fee: Option<f64>,
+    pub currency: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(InputObject)]
+pub struct AssignStudentTransportInput {
+    pub school_id: String,
+    pub student_id: String,
+    pub route_id: String,
+    pub pickup_stop_id: String,
+    /// Afternoon drop-off stop (defaults to the pickup stop)
+    pub dropoff_stop_id: Option<String>,
+    /// Monthly fee instead of the route's fee
+    pub monthly_fee_override: Option<f64>,
+    /// First day on the route (YYYY-MM-DD, default today)
+    pub start_date: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct RecordBoardingInput {
+    pub school_id: String,
+    pub route_id: String,
+    pub student_id: String,
+    pub direction: TripDirection,
+    pub event: BoardingEvent,
+    /// Stop the event happened at (defaults to the student's stop for the trip)
+    pub stop_id: Option<String>,
+    /// Current position of the bus
+    pub location: Option<GpsCoordinates>,
+    /// Send the student's guardians an SMS (default true)
+    pub notify_guardians: Option<bool>,
+}
+
+#[derive(InputObject)]
+pub struct GenerateTransportInvoicesInput {
+    pub school_id: String,
+    /// Month to bill (YYYY-MM)
+    pub month: String,
+    /// Payment due date (YYYY-MM-DD, default the 10th of the month)
+    pub due_date: Option<String>,
+}
@@ -0,0 +1,1157 @@
+use super::inputs::{
+    AssignStudentTransportInput, CreateBusRouteInput, CreateDriverInput, CreateVehicleInput,
+    GenerateTransportInvoicesInput, RecordBoardingInput, RouteStopInput, UpdateBusRouteInput,
+    UpdateDriverInput, UpdateVehicleInput,
+};
+use super::queries::{find_assignment, find_route, find_student, require_transport};
+use super::types::TransportInvoiceRunType;
+use crate::graphql::library::queries::{collect, to_bson};
+use crate::models::finance::Invoice;
+use crate::models::hr::Staff;
+use crate::models::member::{Member, Permission};
+use crate::models::school::SchoolFeature;
+use crate::models::student::Student;
+use crate::models::transport::{
+    BoardingEvent, BoardingLog, BusRoute, Driver, RouteStop, TransportAssignment, TripDirection,
+    Vehicle, VehicleStatus,
+};
+use crate::utils::audit::record_change;
+use crate::utils::common_types::{AuditInfo, SoftDelete};
+use crate::utils::features::FeatureGuard;
+use crate::utils::sms::send_sms;
+use crate::utils::soft_delete::{find_deleted_by_id, restore_by_id, soft_delete_by_id};
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use chrono::{Datelike, NaiveDate, NaiveTime, TimeZone, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct TransportMutation;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Transport)")]
+impl TransportMutation {
+    /// Register a vehicle (requires ManageTransport)
+    async fn create_vehicle(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateVehicleInput,
+    ) -> Result<Vehicle> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_transport(ctx, db, &input.school_id, Permission::ManageTransport).await?;
+
+        let plate_number = normalize_plate(&input.plate_number)?;
+        if input.capacity < 1 {
+            return Err(Error::new("Capacity must be at least 1"));
+        }
+        ensure_plate_unused(db, &input.school_id, &plate_number, None).await?;
+
+        let mut vehicle = Vehicle {
+            id: None,
+            school_id: input.school_id,
+            plate_number,
+            name: input.name,
+            capacity: input.capacity,
+            make_model: input.make_model,
+            status: VehicleStatus::Active,
+            audit: AuditInfo::new(Some(member.user_id.clone())),
+            soft_delete: SoftDelete::default(),
+        };
+        let result = db
+            .collection::<Vehicle>("vehicles")
+            .insert_one(&vehicle, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create vehicle: {}", e)))?;
+        vehicle.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "Vehicle",
+            vehicle.id.map(|id| id.to_hex()),
+            Some(&vehicle.school_id),
+            None,
+            Some(&vehicle),
+        );
+
+        Ok(vehicle)
+    }
+
+    /// Update a vehicle (requires ManageTransport)
+    async fn update_vehicle(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateVehicleInput,
+    ) -> Result<Vehicle> {
+        let db = ctx.data::<Database>()?;
+        let vehicle = find_vehicle(db, &input.vehicle_id).await?;
+        let member =
+            require_transport(ctx, db, &vehicle.school_id, Permission::ManageTransport).await?;
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(plate_number) = input.plate_number {
+            let plate_number = normalize_plate(&plate_number)?;
+            ensure_plate_unused(db, &vehicle.school_id, &plate_number, vehicle.id).await?;
+            set.insert("plate_number", plate_number);
+        }
+        if let Some(name) = input.name {
+            set.insert("name", name);
+        }
+        if let Some(capacity) = input.capacity {
+            let riders = riders_for_vehicle(db, &input.vehicle_id).await?;
+            if capacity < 1 || (capacity as u64) < riders {
+                return Err(Error::new(format!(
+                    "Capacity must be at least 1 and fit the {} students on its routes",
+                    riders
+                )));
+            }
+            set.insert("capacity", capacity);
+        }
+        if let Some(make_model) = input.make_model {
+            set.insert("make_model", make_model);
+        }
+        if let Some(status) = input.status {
+            set.insert("status", to_bson(&status)?);
+        }
+
+        db.collection::<Vehicle>("vehicles")
+            .update_one(doc! { "_id": vehicle.id }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update vehicle: {}", e)))?;
+
+        let updated = find_vehicle(db, &input.vehicle_id).await?;
+        record_change(
+            ctx,
+            "Vehicle",
+            Some(input.vehicle_id),
+            Some(&updated.school_id),
+            Some(&vehicle),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Remove a vehicle (requires ManageTransport). Refused while an active
+    /// route uses it.
+    async fn delete_vehicle(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let vehicle = find_vehicle(db, &id).await?;
+        let member =
+            require_transport(ctx, db, &vehicle.school_id, Permission::ManageTransport).await?;
+
+        ensure_not_on_route(db, "vehicle_id", &id).await?;
+
+        let oid = vehicle.id.ok_or_else(|| Error::new("Vehicle not found"))?;
+        soft_delete_by_id(&db.collection::<Vehicle>("vehicles"), oid, &member.user_id).await?;
+
+        record_change(
+            ctx,
+            "Vehicle",
+            Some(id),
+            Some(&vehicle.school_id),
+            Some(&vehicle),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted vehicle from the trash (requires ManageTransport)
+    async fn restore_vehicle(&self, ctx: &Context<'_>, id: String) -> Result<Vehicle> {
+        let db = ctx.data::<Database>()?;
+        let vehicle: Vehicle =
+            find_deleted_by_id(&db.collection("vehicles"), &id, "vehicle").await?;
+        require_transport(ctx, db, &vehicle.school_id, Permission::ManageTransport).await?;
+        ensure_plate_unused(db, &vehicle.school_id, &vehicle.plate_number, vehicle.id).await?;
+
+        let oid = vehicle.id.ok_or_else(|| Error::new("Vehicle not found"))?;
+        restore_by_id(&db.collection::<Vehicle>("vehicles"), oid).await?;
+
+        let restored = find_vehicle(db, &id).await?;
+        record_change(
+            ctx,
+            "Vehicle",
+            Some(id),
+            Some(&vehicle.school_id),
+            Some(&vehicle),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Register a staff member as a driver (requires ManageTransport)
+    async fn create_driver(&self, ctx: &Context<'_>, input: CreateDriverInput) -> Result<Driver> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_transport(ctx, db, &input.school_id, Permission::ManageTransport).await?;
+
+        let staff_oid =
+            ObjectId::parse_str(&input.staff_id).map_err(|_| Error::new("Invalid staff ID"))?;
+        let staff = db
+            .collection::<Staff>("staff")
+            .find_one(
+                doc! { "_id": staff_oid, "soft_delete.is_deleted": { "$ne": true } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Staff record not found"))?;
+
+        let existing = db
+            .collection::<Driver>("drivers")
+            .find_one(
+                doc! {
+                    "school_id": &input.school_id,
+                    "staff_id": &input.staff_id,
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        if existing.is_some() {
+            return Err(Error::new("This staff member is already a driver"));
+        }
+
+        if let Some(member_id) = input.member_id.as_deref() {
+            check_member(db, &input.school_id, member_id).await?;
+        }
+        let license_number = input.license_number.trim().to_string();
+        if license_number.is_empty() {
+            return Err(Error::new("License number is required"));
+        }
+
+        let mut driver = Driver {
+            id: None,
+            school_id: input.school_id,
+            staff_id: input.staff_id,
+            member_id: input.member_id,
+            license_number,
+            license_expiry: input
+                .license_expiry
+                .as_deref()
+                .map(parse_date)
+                .transpose()?,
+            phone: input.phone.or(Some(staff.phone)),
+            is_active: true,
+            audit: AuditInfo::new(Some(member.user_id.clone())),
+            soft_delete: SoftDelete::default(),
+        };
+        let result = db
+            .collection::<Driver>("drivers")
+            .insert_one(&driver, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create driver: {}", e)))?;
+        driver.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "Driver",
+            driver.id.map(|id| id.to_hex()),
+            Some(&driver.school_id),
+            None,
+            Some(&driver),
+        );
+
+        Ok(driver)
+    }
+
+    /// Update a driver (requires ManageTransport)
+    async fn update_driver(&self, ctx: &Context<'_>, input: UpdateDriverInput) -> Result<Driver> {
+        let db = ctx.data::<Database>()?;
+        let driver = find_driver(db, &input.driver_id).await?;
+        let member =
+            require_transport(ctx, db, &driver.school_id, Permission::ManageTransport).await?;
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(member_id) = input.member_id {
+            check_member(db, &driver.school_id, &member_id).await?;
+            set.insert("member_id", member_id);
+        }
+        if let Some(license_number) = input.license_number {
+            set.insert("license_number", license_number.trim());
+        }
+        if let Some(expiry) = input.license_expiry {
+            set.insert("license_expiry", parse_date(&expiry)?);
+        }
+        if let Some(phone) = input.phone {
+            set.insert("phone", phone);
+        }
+        if let Some(is_active) = input.is_active {
+            if !is_active {
+                ensure_not_on_route(db, "driver_id", &input.driver_id).await?;
+            }
+            set.insert("is_active", is_active);
+        }
+
+        db.collection::<Driver>("drivers")
+            .update_one(doc! { "_id": driver.id }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update driver: {}", e)))?;
+
+        let updated = find_driver(db, &input.driver_id).await?;
+        record_change(
+            ctx,
+            "Driver",
+            Some(input.driver_id),
+            Some(&updated.school_id),
+            Some(&driver),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Remove a driver (requires ManageTransport). Refused while they drive an
+    /// active route.
+    async fn delete_driver(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let driver = find_driver(db, &id).await?;
+        let member =
+            require_transport(ctx, db, &driver.school_id, Permission::ManageTransport).await?;
+
+        ensure_not_on_route(db, "driver_id", &id).await?;
+
+        let oid = driver.id.ok_or_else(|| Error::new("Driver not found"))?;
+        soft_delete_by_id(&db.collection::<Driver>("drivers"), oid, &member.user_id).await?;
+
+        record_change(
+            ctx,
+            "Driver",
+            Some(id),
+            Some(&driver.school_id),
+            Some(&driver),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted driver from the trash (requires ManageTransport)
+    async fn restore_driver(&self, ctx: &Context<'_>, id: String) -> Result<Driver> {
+        let db = ctx.data::<Database>()?;
+        let driver: Driver = find_deleted_by_id(&db.collection("drivers"), &id, "driver").await?;
+        require_transport(ctx, db, &driver.school_id, Permission::ManageTransport).await?;
+
+        let collection = db.collection::<Driver>("drivers");
+        let existing = collection
+            .find_one(
+                doc! {
+                    "school_id": &driver.school_id,
+                    "staff_id": &driver.staff_id,
+                    "soft_delete.is_deleted": { "$ne": true }
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(e.to_string()))?;
+        if existing.is_some() {
+            return Err(Error::new("This staff member is already a driver"));
+        }
+
+        let oid = driver.id.ok_or_else(|| Error::new("Driver not found"))?;
+        restore_by_id(&collection, oid).await?;
+
+        let restored = find_driver(db, &id).await?;
+        record_change(
+            ctx,
+            "Driver",
+            Some(id),
+            Some(&driver.school_id),
+            Some(&driver),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Create a bus route with its stops in order (requires ManageTransport)
+    async fn create_bus_route(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateBusRouteInput,
+    ) -> Result<BusRoute> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_transport(ctx, db, &input.school_id, Permission::ManageTransport).await?;
+
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            return Err(Error::new("Route name is required"));
+        }
+        if let Some(vehicle_id) = input.vehicle_id.as_deref() {
+            check_vehicle(db, &input.school_id, vehicle_id, 0).await?;
+        }
+        if let Some(driver_id) = input.driver_id.as_deref() {
+            check_driver(db, &input.school_id, driver_id).await?;
+        }
+        let monthly_fee = input.monthly_fee.unwrap_or(0.0);
+        if monthly_fee < 0.0 {
+            return Err(Error::new("Monthly fee cannot be negative"));
+        }
+
+        let mut route = BusRoute {
+            id: None,
+            school_id: input.school_id,
+            name,
+            code: input.code,
+            vehicle_id: input.vehicle_id,
+            driver_id: input.driver_id,
+            stops: build_stops(input.stops)?,
+            monthly_fee,
+            currency: input.currency.unwrap_or_else(|| "USD".to_string()),
+            is_active: true,
+            audit: AuditInfo::new(Some(member.user_id.clone())),
+            soft_delete: SoftDelete::default(),
+        };
+        let result = db
+            .collection::<BusRoute>("bus_routes")
+            .insert_one(&route, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create route: {}", e)))?;
+        route.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "BusRoute",
+            route.id.map(|id| id.to_hex()),
+            Some(&route.school_id),
+            None,
+            Some(&route),
+        );
+
+        Ok(route)
+    }
+
+    /// Update a route, its vehicle, driver or stops (requires ManageTransport)
+    async fn update_bus_route(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateBusRouteInput,
+    ) -> Result<BusRoute> {
+        let db = ctx.data::<Database>()?;
+        let route = find_route(db, &input.route_id).await?;
+        let member =
+            require_transport(ctx, db, &route.school_id, Permission::ManageTransport).await?;
+
+        let assignments = collect(
+            &db.collection::<TransportAssignment>("transport_assignments"),
+            doc! { "route_id": &input.route_id, "is_active": true },
+            FindOptions::default(),
+        )
+        .await?;
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &member.user_id,
+        };
+        if let Some(name) = input.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err(Error::new("Route name is required"));
+            }
+            set.insert("name", name);
+        }
+        if let Some(code) = input.code {
+            set.insert("code", code);
+        }
+        if let Some(vehicle_id) = input.vehicle_id {
+            check_vehicle(db, &route.school_id, &vehicle_id, assignments.len()).await?;
+            set.insert("vehicle_id", vehicle_id);
+        }
+        if let Some(driver_id) = input.driver_id {
+            check_driver(db, &route.school_id, &driver_id).await?;
+            set.insert("driver_id", driver_id);
+        }
+        if let Some(stops) = input.stops {
+            let stops = build_stops(stops)?;
+            let kept = |stop_id: &str| stops.iter().any(|s| s.stop_id == stop_id);
+            if assignments
+                .iter()
+                .any(|a| !kept(&a.pickup_stop_id) || !kept(&a.dropoff_stop_id))
+            {
+                return Err(Error::new(
+                    "Stops with students assigned must be kept; reassign the students first",
+                ));
+            }
+            set.insert("stops", to_bson(&stops)?);
+        }
+        if let Some(fee) = input.monthly_fee {
+            if fee < 0.0 {
+                return Err(Error::new("Monthly fee cannot be negative"));
+            }
+            set.insert("monthly_fee", fee);
+        }
+        if let Some(currency) = input.currency {
+            set.insert("currency", currency);
+        }
+        if let Some(is_active) = input.is_active {
+            if !is_active && !assignments.is_empty() {
+                return Err(Error::new(
+                    "Students are assigned to this route; reassign them first",
+                ));
+            }
+            set.insert("is_active", is_active);
+        }
+
+        db.collection::<BusRoute>("bus_routes")
+            .update_one(doc! { "_id": route.id }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update route: {}", e)))?;
+
+        let updated = find_route(db, &input.route_id).await?;
+        record_change(
+            ctx,
+            "BusRoute",
+            Some(input.route_id),
+            Some(&updated.school_id),
+            Some(&route),
+            Some(&updated),
+        );
+
+        Ok(updated)
+    }
+
+    /// Remove a route (requires ManageTransport). Refused while students are
+    /// assigned to it.
+    async fn delete_bus_route(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let db = ctx.data::<Database>()?;
+        let route = find_route(db, &id).await?;
+        let member =
+            require_transport(ctx, db, &route.school_id, Permission::ManageTransport).await?;
+
+        if riders_on_route(db, &id).await? > 0 {
+            return Err(Error::new(
+                "Students are assigned to this route; reassign them first",
+            ));
+        }
+
+        let oid = route.id.ok_or_else(|| Error::new("Route not found"))?;
+        soft_delete_by_id(
+            &db.collection::<BusRoute>("bus_routes"),
+            oid,
+            &member.user_id,
+        )
+        .await?;
+
+        record_change(
+            ctx,
+            "BusRoute",
+            Some(id),
+            Some(&route.school_id),
+            Some(&route),
+            None,
+        );
+
+        Ok(true)
+    }
+
+    /// Restore a deleted route from the trash (requires ManageTransport)
+    async fn restore_bus_route(&self, ctx: &Context<'_>, id: String) -> Result<BusRoute> {
+        let db = ctx.data::<Database>()?;
+        let route: BusRoute =
+            find_deleted_by_id(&db.collection("bus_routes"), &id, "route").await?;
+        require_transport(ctx, db, &route.school_id, Permission::ManageTransport).await?;
+
+        let oid = route.id.ok_or_else(|| Error::new("Route not found"))?;
+        restore_by_id(&db.collection::<BusRoute>("bus_routes"), oid).await?;
+
+        let restored = find_route(db, &id).await?;
+        record_change(
+            ctx,
+            "BusRoute",
+            Some(id),
+            Some(&route.school_id),
+            Some(&route),
+            Some(&restored),
+        );
+
+        Ok(restored)
+    }
+
+    /// Put a student on a route, replacing any current assignment
+    /// (requires ManageTransport)
+    async fn assign_student_transport(
+        &self,
+        ctx: &Context<'_>,
+        input: AssignStudentTransportInput,
+    ) -> Result<TransportAssignment> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_transport(ctx, db, &input.school_id, Permission::ManageTransport).await?;
+
+        let student = find_student(db, &input.student_id).await?;
+        let route = find_route(db, &input.route_id).await?;
+        if student.school_id != input.school_id || route.school_id != input.school_id {
+            return Err(Error::new("Student and route must belong to this school"));
+        }
+        if !route.is_active {
+            return Err(Error::new("This route is not active"));
+        }
+
+        let dropoff_stop_id = input
+            .dropoff_stop_id
+            .unwrap_or_else(|| input.pickup_stop_id.clone());
+        if route.find_stop(&input.pickup_stop_id).is_none()
+            || route.find_stop(&dropoff_stop_id).is_none()
+        {
+            return Err(Error::new("Stop not found on this route"));
+        }
+        if input.monthly_fee_override.is_some_and(|fee| fee < 0.0) {
+            return Err(Error::new("Monthly fee cannot be negative"));
+        }
+
+        let current = find_assignment(db, &input.student_id).await?;
+        let moving_routes = current
+            .as_ref()
+            .map(|a| a.route_id != input.route_id)
+            .unwrap_or(true);
+        if moving_routes {
+            if let Some(vehicle_id) = route.vehicle_id.as_deref() {
+                let vehicle = find_vehicle(db, vehicle_id).await?;
+                let riders = riders_on_route(db, &input.route_id).await?;
+                if riders >= vehicle.capacity as u64 {
+                    return Err(Error::new(format!(
+                        "{} is full ({} seats)",
+                        vehicle.name.unwrap_or(vehicle.plate_number),
+                        vehicle.capacity
+                    )));
+                }
+            }
+        }
+
+        let start_date = match input.start_date.as_deref() {
+            Some(date) => parse_date(date)?,
+            None => DateTime::now(),
+        };
+
+        let collection = db.collection::<TransportAssignment>("transport_assignments");
+        if let Some(current) = current.as_ref() {
+            collection
+                .update_one(
+                    doc! { "_id": current.id },
+                    doc! { "$set": { "is_active": false, "end_date": start_date } },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(format!("Failed to end current assignment: {}", e)))?;
+        }
+
+        let mut assignment = TransportAssignment {
+            id: None,
+            school_id: input.school_id,
+            student_id: input.student_id,
+            route_id: input.route_id,
+            pickup_stop_id: input.pickup_stop_id,
+            dropoff_stop_id,
+            monthly_fee_override: input.monthly_fee_override,
+            start_date,
+            end_date: None,
+            is_active: true,
+            // Months already billed on the previous assignment aren't billed again
+            invoiced_months: current
+                .as_ref()
+                .map(|a| a.invoiced_months.clone())
+                .unwrap_or_default(),
+            audit: AuditInfo::new(Some(member.user_id.clone())),
+        };
+        let result = collection
+            .insert_one(&assignment, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to assign student: {}", e)))?;
+        assignment.id = result.inserted_id.as_object_id();
+
+        record_change(
+            ctx,
+            "TransportAssignment",
+            assignment.id.map(|id| id.to_hex()),
+            Some(&assignment.school_id),
+            current.as_ref(),
+            Some(&assignment),
+        );
+
+        Ok(assignment)
+    }
+
+    /// Take a student off school transport (requires ManageTransport)
+    async fn end_student_transport(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+    ) -> Result<TransportAssignment> {
+        let db = ctx.data::<Database>()?;
+        let assignment = find_assignment(db, &student_id)
+            .await?
+            .ok_or_else(|| Error::new("This student is not assigned to a route"))?;
+        require_transport(ctx, db, &assignment.school_id, Permission::ManageTransport).await?;
+
+        let collection = db.collection::<TransportAssignment>("transport_assignments");
+        collection
+            .update_one(
+                doc! { "_id": assignment.id },
+                doc! { "$set": { "is_active": false, "end_date": DateTime::now() } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to end assignment: {}", e)))?;
+
+        let ended = collection
+            .find_one(doc! { "_id": assignment.id }, None)
+            .await
+            .map_err(|e| Error::new(e.to_string()))?
+            .ok_or_else(|| Error::new("Assignment not found"))?;
+        record_change(
+            ctx,
+            "TransportAssignment",
+            ended.id.map(|id| id.to_hex()),
+            Some(&ended.school_id),
+            Some(&assignment),
+            Some(&ended),
+        );
+
+        Ok(ended)
+    }
+
+    /// Log a student boarding or leaving the bus and text their guardians.
+    /// Open to ManageTransport and to the route's own driver.
+    async fn record_boarding(
+        &self,
+        ctx: &Context<'_>,
+        input: RecordBoardingInput,
+    ) -> Result<BoardingLog> {
+        let db = ctx.data::<Database>()?;
+        let member =
+            require_transport(ctx, db, &input.school_id, Permission::ViewTransport).await?;
+
+        let route = find_route(db, &input.route_id).await?;
+        if route.school_id != input.school_id {
+            return Err(Error::new("Route not found"));
+        }
+        if !member.has_permission(Permission::ManageTransport)
+            && !drives_route(db, &member, &route).await?
+        {
+            return Err(Error::new("Only the route's driver can log boardings"));
+        }
+
+        let assignment = find_assignment(db, &input.student_id)
+            .await?
+            .filter(|a| a.route_id == input.route_id)
+            .ok_or_else(|| Error::new("This student is not assigned to this route"))?;
+        let stop_id = input.stop_id.unwrap_or_else(|| match input.direction {
+            TripDirection::ToSchool => assignment.pickup_stop_id.clone(),
+            TripDirection::FromSchool => assignment.dropoff_stop_id.clone(),
+        });
+        let stop = route
+            .find_stop(&stop_id)
+            .ok_or_else(|| Error::new("Stop not found on this route"))?;
+
+        let mut log = BoardingLog {
+            id: None,
+            school_id: input.school_id,
+            route_id: input.route_id,
+            student_id: input.student_id,
+            stop_id: Some(stop_id.clone()),
+            direction: input.direction,
+            event: input.event,
+            location: input.location,
+            recorded_at: DateTime::now(),
+            recorded_by: member.user_id.clone(),
+            guardians_notified: 0,
+        };
+        if input.notify_guardians.unwrap_or(true) {
+            let student = find_student(db, &log.student_id).await?;
+            log.guardians_notified = notify_guardians(&student, &route, stop, log.event).await;
+        }
+
+        let result = db
+            .collection::<BoardingLog>("boarding_logs")
+            .insert_one(&log, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to record boarding: {}", e)))?;
+        log.id = result.inserted_id.as_object_id();
+
+        Ok(log)
+    }
+
+    /// Bill a month of transport fees to every student on a route, as
+    /// invoices on their finance account (requires ManageTransport and the
+    /// Finance module). Students already billed for the month are skipped.
+    async fn generate_transport_invoices(
+        &self,
+        ctx: &Context<'_>,
+        input: GenerateTransportInvoicesInput,
+    ) -> Result<TransportInvoiceRunType> {
+        let db = ctx.data::<Database>()?;
+        require_transport(ctx, db, &input.school_id, Permission::ManageTransport).await?;
+        ensure_feature(db, &input.school_id, SchoolFeature::Finance).await?;
+
+        let month_start = NaiveDate::parse_from_str(&format!("{}-01", input.month), "%Y-%m-%d")
+            .map_err(|_| Error::new("Invalid month. Use YYYY-MM"))?;
+        let next_month = match month_start.month() {
+            12 => NaiveDate::from_ymd_opt(month_start.year() + 1, 1, 1),
+            month => NaiveDate::from_ymd_opt(month_start.year(), month + 1, 1),
+        }
+        .ok_or_else(|| Error::new("Invalid month. Use YYYY-MM"))?;
+        let month_end = to_datetime(next_month);
+        let due_date = match input.due_date.as_deref() {
+            Some(date) => parse_date(date)?,
+            None => to_datetime(month_start.with_day(10).unwrap_or(month_start)),
+        };
+
+        let routes = collect(
+            &db.collection::<BusRoute>("bus_routes"),
+            doc! { "school_id": &input.school_id, "soft_delete.is_deleted": { "$ne": true } },
+            FindOptions::default(),
+        )
+        .await?;
+        let assignments = collect(
+            &db.collection::<TransportAssignment>("transport_assignments"),
+            doc! {
+                "school_id": &input.school_id,
+                "is_active": true,
+                "start_date": { "$lt": month_end }
+            },
+            FindOptions::default(),
+        )
+        .await?;
+
+        let mut run = TransportInvoiceRunType {
+            month: input.month.clone(),
+            invoices_created: 0,
+            total_amount: 0.0,
+            skipped: 0,
+        };
+        let now = DateTime::now();
+        for assignment in assignments {
+            let Some(route) = routes
+                .iter()
+                .find(|r| r.id.map(|id| id.to_hex()).as_deref() == Some(&assignment.route_id))
+            else {
+                run.skipped += 1;
+                continue;
+            };
+            let amount = assignment.monthly_fee_override.unwrap_or(route.monthly_fee);
+            if amount <= 0.0 || assignment.invoiced_months.contains(&input.month) {
+                run.skipped += 1;
+                continue;
+            }
+            let Ok(student_oid) = ObjectId::parse_str(&assignment.student_id) else {
+                run.skipped += 1;
+                continue;
+            };
+
+            let assignment_hex = assignment.id.map(|id| id.to_hex()).unwrap_or_default();
+            let invoice = Invoice {
+                id: None,
+                invoice_number: format!(
+                    "TRN-{}-{}",
+                    input.month.replace('-', ""),
+                    assignment_hex[assignment_hex.len().saturating_sub(6)..].to_uppercase()
+                ),
+                student_id: student_oid,
+                fee_ids: Vec::new(),
+                total_amount: amount,
+                amount_paid: 0.0,
+                balance: amount,
+                currency: route.currency.clone(),
+                issue_date: now,
+                due_date,
+                status: "unpaid".to_string(),
+                description: Some(format!("Transport fee: {} ({})", route.name, input.month)),
+                created_at: now,
+                updated_at: now,
+            };
+            db.collection::<Invoice>("invoices")
+                .insert_one(&invoice, None)
+                .await
+                .map_err(|e| Error::new(format!("Failed to create invoice: {}", e)))?;
+            db.collection::<TransportAssignment>("transport_assignments")
+                .update_one(
+                    doc! { "_id": assignment.id },
+                    doc! { "$addToSet": { "invoiced_months": &input.month } },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?;
+
+            run.invoices_created += 1;
+            run.total_amount += amount;
+        }
+
+        Ok(run)
+    }
+}
+
+/// Plate numbers are stored upper-case with single spaces
+fn normalize_plate(plate: &str) -> Result<String> {
+    let plate = plate
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase();
+    if plate.is_empty() {
+        return Err(Error::new("Plate number is required"));
+    }
+    Ok(plate)
+}
+
+/// Midnight UTC at the start of a date (YYYY-MM-DD)
+fn parse_date(date: &str) -> Result<DateTime> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(to_datetime)
+        .map_err(|_| Error::new("Invalid date. Use YYYY-MM-DD"))
+}
+
+fn to_datetime(date: NaiveDate) -> DateTime {
+    DateTime::from_millis(
+        Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+            .timestamp_millis(),
+    )
+}
+
+/// Number the stops in the order given, keeping IDs of existing stops
+fn build_stops(inputs: Vec<RouteStopInput>) -> Result<Vec<RouteStop>> {
+    let mut stops: Vec<RouteStop> = Vec::new();
+    for (index, input) in inputs.into_iter().enumerate() {
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            return Err(Error::new("Every stop needs a name"));
+        }
+        for time in [&input.pickup_time, &input.dropoff_time]
+            .into_iter()
+            .flatten()
+        {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| Error::new("Invalid stop time. Use HH:MM"))?;
+        }
+        let stop_id = input.stop_id.unwrap_or_else(|| ObjectId::new().to_hex());
+        if stops.iter().any(|s| s.stop_id == stop_id) {
+            return Err(Error::new("Each stop can appear only once"));
+        }
+        stops.push(RouteStop {
+            stop_id,
+            name,
+            location: input.location,
+            sequence: index as i32 + 1,
+            pickup_time: input.pickup_time,
+            dropoff_time: input.dropoff_time,
+        });
+    }
+    Ok(stops)
+}
+
+async fn find_vehicle(db: &Database, vehicle_id: &str) -> Result<Vehicle> {
+    let oid = ObjectId::parse_str(vehicle_id).map_err(|_| Error::new("Invalid vehicle ID"))?;
+    db.collection::<Vehicle>("vehicles")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Vehicle not found"))
+}
+
+async fn find_driver(db: &Database, driver_id: &str) -> Result<Driver> {
+    let oid = ObjectId::parse_str(driver_id).map_err(|_| Error::new("Invalid driver ID"))?;
+    db.collection::<Driver>("drivers")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Driver not found"))
+}
+
+async fn ensure_plate_unused(
+    db: &Database,
+    school_id: &str,
+    plate_number: &str,
+    except: Option<ObjectId>,
+) -> Result<()> {
+    let mut filter = doc! {
+        "school_id": school_id,
+        "plate_number": plate_number,
+        "soft_delete.is_deleted": { "$ne": true }
+    };
+    if let Some(id) = except {
+        filter.insert("_id", doc! { "$ne": id });
+    }
+    let existing = db
+        .collection::<Vehicle>("vehicles")
+        .find_one(filter, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    if existing.is_some() {
+        return Err(Error::new(
+            "A vehicle with this plate number already exists",
+        ));
+    }
+    Ok(())
+}
+
+/// Check a vehicle belongs to the school, is in service and seats `riders`
+async fn check_vehicle(
+    db: &Database,
+    school_id: &str,
+    vehicle_id: &str,
+    riders: usize,
+) -> Result<()> {
+    let vehicle = find_vehicle(db, vehicle_id).await?;
+    if vehicle.school_id != school_id {
+        return Err(Error::new("Vehicle not found"));
+    }
+    if vehicle.status != VehicleStatus::Active {
+        return Err(Error::new(format!("This vehicle is {:?}", vehicle.status)));
+    }
+    if riders > vehicle.capacity as usize {
+        return Err(Error::new(format!(
+            "This vehicle seats {} but {} students ride the route",
+            vehicle.capacity, riders
+        )));
+    }
+    Ok(())
+}
+
+async fn check_driver(db: &Database, school_id: &str, driver_id: &str) -> Result<()> {
+    let driver = find_driver(db, driver_id).await?;
+    if driver.school_id != school_id || !driver.is_active {
+        return Err(Error::new("Driver not found"));
+    }
+    Ok(())
+}
+
+/// Check a membership is active in the school
+async fn check_member(db: &Database, school_id: &str, member_id: &str) -> Result<()> {
+    let oid = ObjectId::parse_str(member_id).map_err(|_| Error::new("Invalid member ID"))?;
+    db.collection::<Member>("members")
+        .find_one(
+            doc! {
+                "_id": oid,
+                "school_id": school_id,
+                "status": "Active",
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Member not found"))?;
+    Ok(())
+}
+
+/// Refuse when an active route uses the vehicle or driver (`field` is
+/// "vehicle_id" or "driver_id")
+async fn ensure_not_on_route(db: &Database, field: &str, id: &str) -> Result<()> {
+    let route = db
+        .collection::<BusRoute>("bus_routes")
+        .find_one(
+            doc! {
+                field: id,
+                "is_active": true,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+    match route {
+        Some(route) => Err(Error::new(format!(
+            "Assigned to route {}; change the route first",
+            route.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+async fn riders_on_route(db: &Database, route_id: &str) -> Result<u64> {
+    db.collection::<TransportAssignment>("transport_assignments")
+        .count_documents(doc! { "route_id": route_id, "is_active": true }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))
+}
+
+/// Most students riding any single route served by the vehicle
+async fn riders_for_vehicle(db: &Database, vehicle_id: &str) -> Result<u64> {
+    let routes = collect(
+        &db.collection::<BusRoute>("bus_routes"),
+        doc! {
+            "vehicle_id": vehicle_id,
+            "is_active": true,
+            "soft_delete.is_deleted": { "$ne": true }
+        },
+        FindOptions::default(),
+    )
+    .await?;
+
+    let mut most = 0;
+    for route in routes {
+        let route_id = route.id.map(|id| id.to_hex()).unwrap_or_default();
+        most = most.max(riders_on_route(db, &route_id).await?);
+    }
+    Ok(most)
+}
+
+/// Whether the member is the driver assigned to the route
+async fn drives_route(db: &Database, member: &Member, route: &BusRoute) -> Result<bool> {
+    let Some(driver_id) = route.driver_id.as_deref() else {
+        return Ok(false);
+    };
+    let driver = find_driver(db, driver_id).await?;
+    Ok(driver.member_id.is_some() && driver.member_id == member.id.map(|id| id.to_hex()))
+}
+
+/// Text each guardian with a phone number; returns how many were sent
+async fn notify_guardians(
+    student: &Student,
+    route: &BusRoute,
+    stop: &RouteStop,
+    event: BoardingEvent,
+) -> i32 {
+    let name = match (&student.first_name_en, &student.last_name_en) {
+        (Some(first), Some(last)) => format!("{} {}", first, last),
+        _ => format!("{} {}", student.first_name_km, student.last_name_km),
+    };
+    let time = Utc::now().format("%H:%M UTC");
+    let message = match event {
+        BoardingEvent::Boarded => {
+            format!(
+                "{} boarded bus {} at {} ({}).",
+                name, route.name, stop.name, time
+            )
+        }
+        BoardingEvent::DroppedOff => format!(
+            "{} was dropped off by bus {} at {} ({}).",
+            name, route.name, stop.name, time
+        ),
+        BoardingEvent::NoShow => format!(
+            "{} was not at {} when bus {} arrived ({}).",
+            name, stop.name, route.name, time
+        ),
+    };
+
+    let mut sent = 0;
+    for guardian in student
+        .guardians
+        .iter()
+        .filter(|g| !g.phone.trim().is_empty())
+    {
+        match send_sms(&guardian.phone, &message).await {
+            Ok(()) => sent += 1,
+            Err(e) => eprintln!("Failed to notify guardian of {}: {}", name, e),
+        }
+    }
+    sent
+}
@@ -0,0 +1,288 @@
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::graphql::library::queries::collect;
+use crate::models::member::{Member, Permission};
+use crate::models::school::SchoolFeature;
+use crate::models::student::Student;
+use crate::models::transport::{BoardingLog, BusRoute, Driver, TransportAssignment, Vehicle};
+use crate::utils::features::FeatureGuard;
+use crate::utils::soft_delete::find_deleted;
+use crate::utils::subscription::ensure_feature;
+use async_graphql::*;
+use chrono::{NaiveDate, TimeZone, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::FindOptions,
+    Database,
+};
+
+#[derive(Default)]
+pub struct TransportQuery;
+
+#[Object(guard = "FeatureGuard::new(SchoolFeature::Transport)")]
+impl TransportQuery {
+    /// A school's vehicles (requires ViewTransport)
+    async fn vehicles(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Vehicle>> {
+        let db = ctx.data::<Database>()?;
+        require_transport(ctx, db, &school_id, Permission::ViewTransport).await?;
+
+        collect(
+            &db.collection::<Vehicle>("vehicles"),
+            doc! { "school_id": &school_id, "soft_delete.is_deleted": { "$ne": true } },
+            FindOptions::builder()
+                .sort(doc! { "plate_number": 1 })
+                .build(),
+        )
+        .await
+    }
+
+    /// A school's drivers (requires ViewTransport)
+    async fn drivers(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Driver>> {
+        let db = ctx.data::<Database>()?;
+        require_transport(ctx, db, &school_id, Permission::ViewTransport).await?;
+
+        collect(
+            &db.collection::<Driver>("drivers"),
+            doc! { "school_id": &school_id, "soft_delete.is_deleted": { "$ne": true } },
+            FindOptions::default(),
+        )
+        .await
+    }
+
+    /// A school's bus routes (requires ViewTransport)
+    async fn bus_routes(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        include_inactive: Option<bool>,
+    ) -> Result<Vec<BusRoute>> {
+        let db = ctx.data::<Database>()?;
+        require_transport(ctx, db, &school_id, Permission::ViewTransport).await?;
+
+        let mut filter = doc! {
+            "school_id": &school_id,
+            "soft_delete.is_deleted": { "$ne": true }
+        };
+        if !include_inactive.unwrap_or(false) {
+            filter.insert("is_active", true);
+        }
+
+        collect(
+            &db.collection::<BusRoute>("bus_routes"),
+            filter,
+            FindOptions::builder().sort(doc! { "name": 1 }).build(),
+        )
+        .await
+    }
+
+    /// A school's deleted vehicles (the trash), most recently deleted first
+    /// (requires ManageTransport)
+    async fn deleted_vehicles(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Vehicle>> {
+        let db = ctx.data::<Database>()?;
+        require_transport(ctx, db, &school_id, Permission::ManageTransport).await?;
+
+        find_deleted(
+            &db.collection::<Vehicle>("vehicles"),
+            doc! { "school_id": school_id },
+        )
+        .await
+    }
+
+    /// A school's deleted drivers (the trash), most recently deleted first
+    /// (requires ManageTransport)
+    async fn deleted_drivers(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<Driver>> {
+        let db = ctx.data::<Database>()?;
+        require_transport(ctx, db, &school_id, Permission::ManageTransport).await?;
+
+        find_deleted(
+            &db.collection::<Driver>("drivers"),
+            doc! { "school_id": school_id },
+        )
+        .await
+    }
+
+    /// A school's deleted bus routes (the trash), most recently deleted first
+    /// (requires ManageTransport)
+    async fn deleted_bus_routes(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+    ) -> Result<Vec<BusRoute>> {
+        let db = ctx.data::<Database>()?;
+        require_transport(ctx, db, &school_id, Permission::ManageTransport).await?;
+
+        find_deleted(
+            &db.collection::<BusRoute>("bus_routes"),
+            doc! { "school_id": school_id },
+        )
+        .await
+    }
+
+    /// A single bus route (requires ViewTransport)
+    async fn bus_route(&self, ctx: &Context<'_>, id: String) -> Result<BusRoute> {
+        let db = ctx.data::<Database>()?;
+        let route = find_route(db, &id).await?;
+        require_transport(ctx, db, &route.school_id, Permission::ViewTransport).await?;
+        Ok(route)
+    }
+
+    /// Routes driven by the caller in a school
+    async fn my_bus_routes(&self, ctx: &Context<'_>, school_id: String) -> Result<Vec<BusRoute>> {
+        let db = ctx.data::<Database>()?;
+        let member = require_transport(ctx, db, &school_id, Permission::ViewTransport).await?;
+        let member_id = member.id.map(|id| id.to_hex()).unwrap_or_default();
+
+        let drivers = collect(
+            &db.collection::<Driver>("drivers"),
+            doc! {
+                "school_id": &school_id,
+                "member_id": &member_id,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            FindOptions::default(),
+        )
+        .await?;
+        let driver_ids: Vec<String> = drivers
+            .iter()
+            .filter_map(|d| d.id.map(|id| id.to_hex()))
+            .collect();
+
+        collect(
+            &db.collection::<BusRoute>("bus_routes"),
+            doc! {
+                "school_id": &school_id,
+                "driver_id": { "$in": driver_ids },
+                "is_active": true,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            FindOptions::builder().sort(doc! { "name": 1 }).build(),
+        )
+        .await
+    }
+
+    /// Students riding a route (requires ViewTransport)
+    async fn route_assignments(
+        &self,
+        ctx: &Context<'_>,
+        route_id: String,
+    ) -> Result<Vec<TransportAssignment>> {
+        let db = ctx.data::<Database>()?;
+        let route = find_route(db, &route_id).await?;
+        require_transport(ctx, db, &route.school_id, Permission::ViewTransport).await?;
+
+        collect(
+            &db.collection::<TransportAssignment>("transport_assignments"),
+            doc! { "route_id": &route_id, "is_active": true },
+            FindOptions::default(),
+        )
+        .await
+    }
+
+    /// A student's current route assignment (requires ViewTransport)
+    async fn student_transport(
+        &self,
+        ctx: &Context<'_>,
+        student_id: String,
+    ) -> Result<Option<TransportAssignment>> {
+        let db = ctx.data::<Database>()?;
+        let student = find_student(db, &student_id).await?;
+        require_transport(ctx, db, &student.school_id, Permission::ViewTransport).await?;
+
+        find_assignment(db, &student_id).await
+    }
+
+    /// Boarding and drop-off logs, newest first, optionally for one route,
+    /// student or day (YYYY-MM-DD) (requires ViewTransport)
+    async fn boarding_logs(
+        &self,
+        ctx: &Context<'_>,
+        school_id: String,
+        route_id: Option<String>,
+        student_id: Option<String>,
+        date: Option<String>,
+    ) -> Result<Vec<BoardingLog>> {
+        let db = ctx.data::<Database>()?;
+        require_transport(ctx, db, &school_id, Permission::ViewTransport).await?;
+
+        let mut filter = doc! { "school_id": &school_id };
+        if let Some(route_id) = route_id {
+            filter.insert("route_id", route_id);
+        }
+        if let Some(student_id) = student_id {
+            filter.insert("student_id", student_id);
+        }
+        if let Some(date) = date {
+            let (start, end) = day_range(&date)?;
+            filter.insert("recorded_at", doc! { "$gte": start, "$lt": end });
+        }
+
+        collect(
+            &db.collection::<BoardingLog>("boarding_logs"),
+            filter,
+            FindOptions::builder()
+                .sort(doc! { "recorded_at": -1 })
+                .limit(200)
+                .build(),
+        )
+        .await
+    }
+}
+
+/// Require the Transport module for the school and a transport permission
+pub(crate) async fn require_transport(
+    ctx: &Context<'_>,
+    db: &Database,
+    school_id: &str,
+    permission: Permission,
+) -> Result<Member> {
+    ensure_feature(db, school_id, SchoolFeature::Transport).await?;
+    get_graphql_context(ctx)?
+        .require_member_permission(db, school_id, permission)
+        .await
+}
+
+/// Load a route that hasn't been deleted
+pub(crate) async fn find_route(db: &Database, route_id: &str) -> Result<BusRoute> {
+    let oid = ObjectId::parse_str(route_id).map_err(|_| Error::new("Invalid route ID"))?;
+    db.collection::<BusRoute>("bus_routes")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Route not found"))
+}
+
+pub(crate) async fn find_student(db: &Database, student_id: &str) -> Result<Student> {
+    let oid = ObjectId::parse_str(student_id).map_err(|_| Error::new("Invalid student ID"))?;
+    db.collection::<Student>("students")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new("Student not found"))
+}
+
+/// A student's active route assignment
+pub(crate) async fn find_assignment(
+    db: &Database,
+    student_id: &str,
+) -> Result<Option<TransportAssignment>> {
+    db.collection::<TransportAssignment>("transport_assignments")
+        .find_one(doc! { "student_id": student_id, "is_active": true }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))
+}
+
+/// Start and end of a day (YYYY-MM-DD, UTC)
+fn day_range(date: &str) -> Result<(DateTime, DateTime)> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| Error::new("Invalid date. Use YYYY-MM-DD"))?;
+    let start = Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap());
+    Ok((
+        DateTime::from_millis(start.timestamp_millis()),
+        DateTime::from_millis((start + chrono::Duration::days(1)).timestamp_millis()),
+    ))
+}
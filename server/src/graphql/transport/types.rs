@@ -0,0 +1,11 @@
+use async_graphql::SimpleObject;
+
+/// Result of billing transport fees for a month
+#[derive(SimpleObject)]
+pub struct TransportInvoiceRunType {
+    pub month: String,
+    pub invoices_created: i32,
+    pub total_amount: f64,
+    /// Students already billed for the month, or riding free
+    pub skipped: i32,
+}
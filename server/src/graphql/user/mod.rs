@@ -1,3 +1,5 @@
+pub mod mutations;
 pub mod queries;
 
+pub use mutations::*;
 pub use queries::*;
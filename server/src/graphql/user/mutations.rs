@@ -0,0 +1,156 @@
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::session::Session;
+use crate::models::user::{SystemRole, User, UserStatus};
+use async_graphql::*;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Database,
+};
+
+#[derive(Default)]
+pub struct UserMutation;
+
+#[Object]
+impl UserMutation {
+    /// Remove a linked sign-in method from the current user.
+    /// At least one sign-in method must remain.
+    async fn unlink_identity(
+        &self,
+        ctx: &Context<'_>,
+        provider: String,
+        subject: String,
+    ) -> Result<User> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+        let users_collection = db.collection::<User>("users");
+
+        let user_oid =
+            ObjectId::parse_str(&auth_user.id).map_err(|_| Error::new("Invalid user ID"))?;
+        let user = users_collection
+            .find_one(doc! { "_id": user_oid }, None)
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?
+            .ok_or_else(|| Error::new("User not found"))?;
+
+        if !user.has_identity(&provider, &subject) {
+            return Err(Error::new(
+                "This sign-in method is not linked to your account",
+            ));
+        }
+
+        let remaining = user
+            .identities
+            .iter()
+            .filter(|i| !(i.provider == provider && i.subject == subject))
+            .count();
+        if remaining == 0 {
+            return Err(Error::new("You cannot remove your only sign-in method"));
+        }
+
+        let mut set = doc! {
+            "audit.updated_at": DateTime::now(),
+            "audit.updated_by": &auth_user.id,
+        };
+        if provider == "koompi" && user.kid == subject {
+            set.insert("kid", "");
+        }
+
+        users_collection
+            .update_one(
+                doc! { "_id": user_oid },
+                doc! {
+                    "$pull": { "identities": { "provider": &provider, "subject": &subject } },
+                    "$set": set
+                },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to unlink identity: {}", e)))?;
+
+        users_collection
+            .find_one(doc! { "_id": user_oid }, None)
+            .await
+            .map_err(|e| Error::new(format!("Failed to retrieve user: {}", e)))?
+            .ok_or_else(|| Error::new("User not found"))
+    }
+
+    /// Admin only - suspend a user. Their sessions end immediately and existing
+    /// access tokens stop working.
+    async fn suspend_user(
+        &self,
+        ctx: &Context<'_>,
+        user_id: String,
+        reason: Option<String>,
+    ) -> Result<User> {
+        set_user_status(ctx, &user_id, UserStatus::Suspended, reason).await
+    }
+
+    /// Admin only - reactivate a suspended or inactive user
+    async fn reactivate_user(&self, ctx: &Context<'_>, user_id: String) -> Result<User> {
+        set_user_status(ctx, &user_id, UserStatus::Active, None).await
+    }
+}
+
+/// Change a user's status as a platform admin
+async fn set_user_status(
+    ctx: &Context<'_>,
+    user_id: &str,
+    status: UserStatus,
+    reason: Option<String>,
+) -> Result<User> {
+    let auth_user = get_graphql_context(ctx)?.require_role(SystemRole::Admin)?;
+    let db = ctx.data::<Database>()?;
+    let users_collection = db.collection::<User>("users");
+
+    if auth_user.id == user_id {
+        return Err(Error::new("You cannot change your own account status"));
+    }
+
+    let user_oid = ObjectId::parse_str(user_id).map_err(|_| Error::new("Invalid user ID"))?;
+    let user = users_collection
+        .find_one(doc! { "_id": user_oid }, None)
+        .await
+        .map_err(|e| Error::new(format!("Database error: {}", e)))?
+        .ok_or_else(|| Error::new("User not found"))?;
+
+    // Only super admins may act on other admins
+    if user.system_role != SystemRole::User && auth_user.role != SystemRole::SuperAdmin {
+        return Err(Error::new(
+            "Only super admins can change the status of administrators",
+        ));
+    }
+
+    let now = DateTime::now();
+    users_collection
+        .update_one(
+            doc! { "_id": user_oid },
+            doc! {
+                "$set": {
+                    "status": mongodb::bson::to_bson(&status)?,
+                    "status_reason": reason,
+                    "audit.updated_at": now,
+                    "audit.updated_by": &auth_user.id,
+                }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to update user status: {}", e)))?;
+
+    if status != UserStatus::Active {
+        db.collection::<Session>("sessions")
+            .update_many(
+                doc! { "user_id": user_id, "revoked_at": null },
+                doc! { "$set": { "revoked_at": now } },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to revoke sessions: {}", e)))?;
+    }
+
+    users_collection
+        .find_one(doc! { "_id": user_oid }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to retrieve user: {}", e)))?
+        .ok_or_else(|| Error::new("User not found"))
+}
@@ -1,6 +1,12 @@
-use crate::models::user::User;
+use crate::graphql::graphql_context::get_graphql_context;
+use crate::models::login_history::LoginHistory;
+use crate::models::user::{SystemRole, User};
 use async_graphql::*;
-use mongodb::{bson::doc, Database};
+use futures::stream::TryStreamExt;
+use mongodb::{bson::doc, options::FindOptions, Database};
+
+/// Default number of login history entries returned
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
 
 #[derive(Default)]
 pub struct UserQuery;
@@ -55,4 +61,58 @@ impl UserQuery {
 
         Ok(user)
     }
+
+    /// Get the current user's recent sign-in attempts, newest first
+    async fn my_login_history(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+    ) -> Result<Vec<LoginHistory>> {
+        let auth_user = get_graphql_context(ctx)?.require_auth()?;
+        let db = ctx.data::<Database>()?;
+
+        find_login_history(db, &auth_user.id, limit).await
+    }
+
+    /// Admin only - get a user's recent sign-in attempts, newest first
+    async fn login_history(
+        &self,
+        ctx: &Context<'_>,
+        user_id: String,
+        limit: Option<i64>,
+    ) -> Result<Vec<LoginHistory>> {
+        get_graphql_context(ctx)?.require_role(SystemRole::Admin)?;
+        let db = ctx.data::<Database>()?;
+
+        find_login_history(db, &user_id, limit).await
+    }
+}
+
+/// Load a user's login history, newest first
+async fn find_login_history(
+    db: &Database,
+    user_id: &str,
+    limit: Option<i64>,
+) -> Result<Vec<LoginHistory>> {
+    let options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .limit(limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, 500))
+        .build();
+
+    let mut cursor = db
+        .collection::<LoginHistory>("login_history")
+        .find(doc! { "user_id": user_id }, options)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut history = Vec::new();
+    while let Some(entry) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        history.push(entry);
+    }
+
+    Ok(history)
 }
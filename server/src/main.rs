@@ -1,6 +1,9 @@
+#![recursion_limit = "256"]
+
 use actix_cors::Cors;
-use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
-use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+use actix_web::{get, guard, web, App, HttpResponse, HttpServer, Responder};
+use async_graphql::http::MultipartOptions;
+use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
 use dotenv::dotenv;
 use mongodb::{options::ClientOptions, Client};
 use std::env;
@@ -13,6 +16,11 @@ mod utils;
 
 use graphql::schema::{create_schema, AppSchema};
 
+/// Largest file accepted in a GraphQL multipart request
+const MAX_UPLOAD_BYTES: usize = 20 * 1024 * 1024;
+/// Most files in one GraphQL multipart request
+const MAX_UPLOAD_FILES: usize = 5;
+
 #[get("/health")]
 async fn health_check() -> impl Responder {
     println!("health check 1");
@@ -25,23 +33,75 @@ async fn health_check() -> impl Responder {
 
 async fn graphql_handler(
     schema: web::Data<AppSchema>,
+    db: web::Data<mongodb::Database>,
     req: actix_web::HttpRequest,
     gql_req: GraphQLRequest,
 ) -> GraphQLResponse {
     use graphql::graphql_context::GraphQLContext;
+    use utils::audit::AuditTrail;
 
     // Create GraphQL context from HTTP request
-    let context = GraphQLContext::from_request(&req);
+    let context = GraphQLContext::from_request(&req, &db).await;
 
     schema
-        .execute(gql_req.into_inner().data(context))
+        .execute(
+            gql_req
+                .into_inner()
+                .data(context)
+                .data(AuditTrail::from_request(&req)),
+        )
         .await
         .into()
 }
 
+/// GraphQL subscriptions over WebSocket (graphql-ws and graphql-transport-ws).
+/// Browsers can't set headers on a WebSocket, so clients send the access token
+/// in the connection_init payload as `{"Authorization": "Bearer <token>"}`; an
+/// Authorization header on the upgrade request is used when there is none.
+/// The token is only read here; each subscription re-checks the session and
+/// membership before every event and ends when the token expires.
+async fn graphql_ws_handler(
+    schema: web::Data<AppSchema>,
+    db: web::Data<mongodb::Database>,
+    req: actix_web::HttpRequest,
+    payload: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    use graphql::graphql_context::{bearer_token, GraphQLContext};
+
+    let header = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let db = db.get_ref().clone();
+
+    GraphQLSubscription::new(schema.get_ref().clone())
+        .on_connection_init(move |init: serde_json::Value| async move {
+            let authorization = ["Authorization", "authorization"]
+                .iter()
+                .find_map(|key| init.get(*key).and_then(|value| value.as_str()))
+                .map(str::to_string)
+                .or(header);
+            let context =
+                GraphQLContext::from_token(authorization.as_deref().and_then(bearer_token), &db)
+                    .await;
+
+            let mut data = async_graphql::Data::default();
+            data.insert(context);
+            Ok(data)
+        })
+        .start(&req, payload)
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
+
+    // Refuse to start without a signing key for access tokens
+    if let Err(e) = utils::jwt_token::ensure_jwt_secret() {
+        return Err(std::io::Error::other(e));
+    }
+
     let port = env::var("PORT").unwrap_or_else(|_| "8081".to_string());
     let mongo_uri =
         env::var("MONGODB_URI").unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
@@ -59,9 +119,20 @@ async fn main() -> std::io::Result<()> {
     println!("✅ MongoDB connected successfully");
     println!("🌐 GraphQL endpoint: http://0.0.0.0:{}/graphql", port);
     println!("🌐 GraphQL Playground: http://0.0.0.0:{}/graphql", port);
+    println!("🌐 GraphQL subscriptions: ws://0.0.0.0:{}/graphql", port);
+
+    if let Err(e) = utils::indexes::ensure_indexes(&db).await {
+        eprintln!("Failed to create indexes: {}", e);
+    }
 
     let schema = create_schema(db.clone());
 
+    // Permanently remove soft-deleted records once their retention period ends
+    utils::soft_delete::spawn_purge_task(db.clone());
+
+    // Remind event audiences ahead of upcoming occurrences
+    utils::calendar::spawn_reminder_task(db.clone());
+
     let port_clone = port.clone();
 
     HttpServer::new(move || {
@@ -77,10 +148,34 @@ async fn main() -> std::io::Result<()> {
             .wrap(cors)
             .app_data(web::Data::new(db.clone()))
             .app_data(web::Data::new(schema.clone()))
+            // Uploads are checked per purpose by `uploadFile`; this caps what is
+            // accepted at all
+            .app_data(
+                MultipartOptions::default()
+                    .max_file_size(MAX_UPLOAD_BYTES)
+                    .max_num_files(MAX_UPLOAD_FILES),
+            )
             .service(health_check)
             .service(routes::auth::auth_callback)
+            .service(routes::auth::request_login_code)
+            .service(routes::auth::verify_login_code)
+            .service(routes::auth::provider_callback)
             .service(routes::auth::get_me)
-            .service(web::resource("/graphql").route(web::post().to(graphql_handler)))
+            .service(routes::auth::refresh)
+            .service(routes::auth::switch_school)
+            .service(routes::auth::logout)
+            .service(routes::auth::logout_all)
+            .service(routes::calendar::calendar_feed)
+            .service(routes::files::download_file)
+            .service(
+                web::resource("/graphql")
+                    .route(web::post().to(graphql_handler))
+                    .route(
+                        web::get()
+                            .guard(guard::Header("upgrade", "websocket"))
+                            .to(graphql_ws_handler),
+                    ),
+            )
     })
     .bind(("0.0.0.0", port.parse::<u16>().unwrap()))?
     .run()
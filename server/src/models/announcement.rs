@@ -0,0 +1,105 @@
+use async_graphql::{ComplexObject, SimpleObject};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::models::member::SchoolRole;
+use crate::utils::common_types::{Attachment, AudienceScope, AuditInfo, SoftDelete};
+
+// ============================================================================
+// ANNOUNCEMENT
+// ============================================================================
+
+/// Announcement - a notice for a school, branch, grade level or class,
+/// optionally narrowed to some roles
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Announcement {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub title: String,
+    pub body: String,
+
+    pub scope: AudienceScope,
+    /// Branch ID, grade level or class ID, depending on `scope`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_id: Option<String>,
+    /// Only members with these roles see it (empty = every role)
+    #[serde(default)]
+    pub roles: Vec<SchoolRole>,
+
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Shown above other announcements
+    pub pinned: bool,
+
+    /// User who wrote it
+    pub author_id: String,
+    /// Members it reached when published
+    pub recipient_count: i32,
+    #[graphql(skip)]
+    pub published_at: DateTime,
+    /// Hidden from readers after this time
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl Announcement {
+    /// Returns the announcement's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn published_at_str(&self) -> String {
+        self.published_at
+            .try_to_rfc3339_string()
+            .unwrap_or_default()
+    }
+
+    async fn expires_at_str(&self) -> Option<String> {
+        self.expires_at.and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+}
+
+impl Announcement {
+    /// Whether the role filter lets a member with this role see it
+    pub fn is_for_role(&self, role: SchoolRole) -> bool {
+        self.roles.is_empty() || self.roles.contains(&role)
+    }
+}
+
+// ============================================================================
+// READ RECEIPT
+// ============================================================================
+
+/// AnnouncementRead - when a user first opened an announcement
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct AnnouncementRead {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub announcement_id: String,
+    pub school_id: String,
+    pub user_id: String,
+    pub member_id: String,
+    #[graphql(skip)]
+    pub read_at: DateTime,
+}
+
+#[ComplexObject]
+impl AnnouncementRead {
+    async fn read_at_str(&self) -> String {
+        self.read_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::utils::common_types::SoftDelete;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Attendance {
@@ -11,6 +12,14 @@ pub struct Attendance {
     pub status: String, // "present" | "absent" | "late" | "excused"
     pub remarks: Option<String>,
     pub marked_by: ObjectId,
+    /// When an ID card scan checked the student in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked_in_at: Option<DateTime>,
+    /// Scanner that recorded the check-in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
 }
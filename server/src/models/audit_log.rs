@@ -0,0 +1,175 @@
+use async_graphql::{ComplexObject, SimpleObject};
+use mongodb::bson::{oid::ObjectId, Bson, DateTime, Document};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// AUDIT LOG MODEL
+// ============================================================================
+
+/// AuditLog - one append-only record per change made through a mutation.
+/// Entries are only ever inserted; there is no API to edit or delete them.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct AuditLog {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    // ========================
+    // Who
+    // ========================
+    /// User who made the change (none for anonymous mutations such as sign-up)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<String>,
+    /// School the change belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub school_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+
+    // ========================
+    // What
+    // ========================
+    /// Mutation that made the change (e.g., "updateGrade")
+    pub action: String,
+    /// Kind of record changed (e.g., "Grade", "Member")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_type: Option<String>,
+    /// ID of the record changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<String>,
+    /// Record before the change (none when created)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub before: Option<Document>,
+    /// Record after the change (none when deleted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub after: Option<Document>,
+    /// Mutation arguments, with secrets redacted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub arguments: Option<Document>,
+
+    // ========================
+    // Outcome
+    // ========================
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[graphql(skip)]
+    pub created_at: DateTime,
+}
+
+/// A single field that differs between the before and after records
+#[derive(Debug, Clone, SimpleObject)]
+pub struct AuditFieldChange {
+    /// Dotted field path (e.g., "scores.midterm")
+    pub field: String,
+    /// Previous value as JSON
+    pub before: Option<String>,
+    /// New value as JSON
+    pub after: Option<String>,
+}
+
+#[ComplexObject]
+impl AuditLog {
+    /// Returns the entry's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    /// Time of the change as ISO string
+    async fn created_at_str(&self) -> String {
+        self.created_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    /// Fields that changed, with their old and new values
+    async fn changes(&self) -> Vec<AuditFieldChange> {
+        self.diff()
+    }
+
+    /// Mutation arguments as JSON
+    async fn arguments_json(&self) -> Option<String> {
+        self.arguments
+            .as_ref()
+            .map(|d| to_json(&Bson::Document(d.clone())))
+    }
+}
+
+impl AuditLog {
+    /// Create a new entry for an action
+    pub fn new(action: String) -> Self {
+        Self {
+            id: None,
+            actor_id: None,
+            school_id: None,
+            ip: None,
+            action,
+            entity_type: None,
+            entity_id: None,
+            before: None,
+            after: None,
+            arguments: None,
+            success: true,
+            error: None,
+            created_at: DateTime::now(),
+        }
+    }
+
+    /// Field-level differences between `before` and `after`.
+    /// Bookkeeping fields (`_id`, audit timestamps) are left out.
+    pub fn diff(&self) -> Vec<AuditFieldChange> {
+        let empty = Document::new();
+        let mut changes = Vec::new();
+        diff_documents(
+            "",
+            self.before.as_ref().unwrap_or(&empty),
+            self.after.as_ref().unwrap_or(&empty),
+            &mut changes,
+        );
+        changes
+    }
+}
+
+fn diff_documents(
+    prefix: &str,
+    before: &Document,
+    after: &Document,
+    changes: &mut Vec<AuditFieldChange>,
+) {
+    let mut keys: Vec<&String> = before.keys().collect();
+    for key in after.keys() {
+        if !before.contains_key(key) {
+            keys.push(key);
+        }
+    }
+
+    for key in keys {
+        let field = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        if matches!(field.as_str(), "_id" | "audit" | "updated_at") {
+            continue;
+        }
+
+        match (before.get(key), after.get(key)) {
+            (Some(Bson::Document(b)), Some(Bson::Document(a))) => {
+                diff_documents(&field, b, a, changes)
+            }
+            (b, a) if b != a => changes.push(AuditFieldChange {
+                field,
+                before: b.map(to_json),
+                after: a.map(to_json),
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// Render a BSON value as relaxed extended JSON
+fn to_json(value: &Bson) -> String {
+    value.clone().into_relaxed_extjson().to_string()
+}
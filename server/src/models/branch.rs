@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use mongodb::bson::oid::ObjectId;
-use crate::utils::common_types::Address;
+use crate::utils::common_types::{Address, SoftDelete};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Branch {
@@ -13,6 +13,8 @@ pub struct Branch {
     pub contact_phone: String,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,6 +37,7 @@ impl Branch {
             contact_phone,
             created_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
+            soft_delete: SoftDelete::default(),
         }
     }
 }
\ No newline at end of file
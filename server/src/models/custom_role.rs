@@ -0,0 +1,60 @@
+use async_graphql::{ComplexObject, SimpleObject};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::models::member::Permission;
+use crate::utils::common_types::{AuditInfo, SoftDelete};
+
+// ============================================================================
+// CUSTOM ROLE MODEL
+// ============================================================================
+
+/// CustomRole - a school-defined role (e.g., "Exam Officer", "Registrar") with a
+/// chosen set of permissions. Members keep their built-in `SchoolRole` and can be
+/// assigned any number of custom roles on top of it.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct CustomRole {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    /// School that defined the role
+    pub school_id: String,
+    /// Display name, unique within the school
+    pub name: String,
+    /// What the role is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Permissions granted to members holding the role
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl CustomRole {
+    /// Returns the role's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+}
+
+impl CustomRole {
+    /// Create a new custom role
+    pub fn new(school_id: String, name: String, permissions: Vec<Permission>) -> Self {
+        Self {
+            id: None,
+            school_id,
+            name,
+            description: None,
+            permissions,
+            audit: AuditInfo::default(),
+            soft_delete: SoftDelete::default(),
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use async_graphql::{ComplexObject, Enum, SimpleObject};
+use chrono::{Datelike, Duration, Months, NaiveDate, TimeZone, Utc};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::common_types::{AudienceScope, AuditInfo, SoftDelete};
+
+/// Longest a single occurrence list may get, so an open-ended daily event
+/// can't blow up a query
+pub const MAX_OCCURRENCES: usize = 500;
+
+// ============================================================================
+// EVENT
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum, Default)]
+pub enum EventCategory {
+    Academic,
+    Exam,
+    Holiday,
+    Meeting,
+    Sports,
+    Trip,
+    Ceremony,
+    #[default]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum RecurrenceFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl RecurrenceFrequency {
+    fn as_rrule(self) -> &'static str {
+        match self {
+            RecurrenceFrequency::Daily => "DAILY",
+            RecurrenceFrequency::Weekly => "WEEKLY",
+            RecurrenceFrequency::Monthly => "MONTHLY",
+            RecurrenceFrequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// How an event repeats. Follows iCalendar RRULE semantics: monthly and yearly
+/// repeats skip months without the start's day (e.g., the 31st).
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct RecurrenceRule {
+    pub frequency: RecurrenceFrequency,
+    /// Repeat every N days/weeks/months/years
+    pub interval: i32,
+    /// Stop after this many occurrences
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<i32>,
+    /// Last moment an occurrence may start
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime>,
+}
+
+#[ComplexObject]
+impl RecurrenceRule {
+    async fn until_str(&self) -> Option<String> {
+        self.until.and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    /// The rule as an iCalendar RRULE value (e.g., "FREQ=WEEKLY;INTERVAL=1")
+    async fn rrule(&self) -> String {
+        self.to_rrule(false)
+    }
+}
+
+impl RecurrenceRule {
+    /// RRULE value; all-day events give UNTIL as a date to match their DTSTART
+    pub fn to_rrule(&self, date_only: bool) -> String {
+        let mut rule = format!(
+            "FREQ={};INTERVAL={}",
+            self.frequency.as_rrule(),
+            self.interval.max(1)
+        );
+        if let Some(count) = self.count {
+            rule.push_str(&format!(";COUNT={}", count));
+        }
+        if let Some(until) = self.until {
+            let until = if date_only {
+                ics_date(until)
+            } else {
+                ics_timestamp(until)
+            };
+            rule.push_str(&format!(";UNTIL={}", until));
+        }
+        rule
+    }
+
+    /// Start of the nth repeat (0 = the original start), or None when that
+    /// month has no such day
+    fn nth(&self, start: chrono::DateTime<Utc>, n: i64) -> Option<chrono::DateTime<Utc>> {
+        let step = n * i64::from(self.interval.max(1));
+        match self.frequency {
+            RecurrenceFrequency::Daily => Some(start + Duration::days(step)),
+            RecurrenceFrequency::Weekly => Some(start + Duration::weeks(step)),
+            RecurrenceFrequency::Monthly | RecurrenceFrequency::Yearly => {
+                let months = if self.frequency == RecurrenceFrequency::Yearly {
+                    step * 12
+                } else {
+                    step
+                };
+                let first = start.date_naive().with_day(1)?;
+                let month = first.checked_add_months(Months::new(u32::try_from(months).ok()?))?;
+                let day = NaiveDate::from_ymd_opt(month.year(), month.month(), start.day())?;
+                Some(Utc.from_utc_datetime(&day.and_time(start.time())))
+            }
+        }
+    }
+}
+
+/// SchoolEvent - a calendar entry for a school, branch, grade level or class
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct SchoolEvent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub category: EventCategory,
+
+    pub scope: AudienceScope,
+    /// Branch ID, grade level or class ID, depending on `scope`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_id: Option<String>,
+
+    /// Start of the first occurrence
+    #[graphql(skip)]
+    pub starts_at: DateTime,
+    /// End of the first occurrence
+    #[graphql(skip)]
+    pub ends_at: DateTime,
+    /// Shown as whole days; times are ignored
+    pub all_day: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<RecurrenceRule>,
+
+    /// Whether parents are asked to reply
+    pub rsvp_enabled: bool,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rsvp_deadline: Option<DateTime>,
+
+    /// Send reminders this many minutes before each occurrence
+    #[serde(default)]
+    pub reminder_minutes: Vec<i32>,
+    /// Reminders already sent, as "<occurrence start millis>:<minutes>"
+    #[graphql(skip)]
+    #[serde(default)]
+    pub reminders_sent: Vec<String>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl SchoolEvent {
+    /// Returns the event's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn starts_at_str(&self) -> String {
+        self.starts_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn ends_at_str(&self) -> String {
+        self.ends_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn rsvp_deadline_str(&self) -> Option<String> {
+        self.rsvp_deadline
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+}
+
+impl SchoolEvent {
+    /// Length of each occurrence
+    pub fn duration(&self) -> Duration {
+        Duration::milliseconds(self.ends_at.timestamp_millis() - self.starts_at.timestamp_millis())
+    }
+
+    /// Start times of the occurrences that overlap [from, to)
+    pub fn occurrences(&self, from: DateTime, to: DateTime) -> Vec<DateTime> {
+        let start = self.starts_at.to_chrono();
+        let duration = self.duration();
+        let (from, to) = (from.to_chrono(), to.to_chrono());
+        // Zero-length occurrences count when they start inside the range
+        let overlaps = |s: chrono::DateTime<Utc>| s < to && (s >= from || s + duration > from);
+
+        let Some(rule) = &self.recurrence else {
+            return if overlaps(start) {
+                vec![self.starts_at]
+            } else {
+                vec![]
+            };
+        };
+
+        let until = rule.until.map(|u| u.to_chrono());
+        let count = rule.count.map(i64::from);
+        // Daily and weekly repeats never skip, so jump straight to the range
+        let first = match rule.frequency {
+            RecurrenceFrequency::Daily | RecurrenceFrequency::Weekly => {
+                let step = rule.nth(start, 1).unwrap_or(start) - start;
+                let gap = from - duration - start;
+                if step > Duration::zero() && gap > Duration::zero() {
+                    gap.num_milliseconds() / step.num_milliseconds()
+                } else {
+                    0
+                }
+            }
+            _ => 0,
+        };
+
+        let mut produced = first;
+        let mut result = Vec::new();
+        // Bounded: monthly/yearly repeats walk from the start, at most ~1000 years
+        for n in first..first + 12_000 {
+            if count.is_some_and(|c| produced >= c) || result.len() >= MAX_OCCURRENCES {
+                break;
+            }
+            let Some(occurrence) = rule.nth(start, n) else {
+                continue;
+            };
+            produced += 1;
+            if occurrence >= to || until.is_some_and(|u| occurrence > u) {
+                break;
+            }
+            if overlaps(occurrence) {
+                result.push(DateTime::from_chrono(occurrence));
+            }
+        }
+        result
+    }
+}
+
+/// Format a timestamp as iCalendar UTC date-time (e.g., "20250301T083000Z")
+pub fn ics_timestamp(value: DateTime) -> String {
+    value.to_chrono().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Format the (UTC) day of a timestamp as an iCalendar date (e.g., "20250301")
+pub fn ics_date(value: DateTime) -> String {
+    value.to_chrono().format("%Y%m%d").to_string()
+}
+
+// ============================================================================
+// RSVP
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum RsvpResponse {
+    Going,
+    NotGoing,
+    Maybe,
+}
+
+/// EventRsvp - a parent's reply to an event, per child
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct EventRsvp {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub event_id: String,
+    pub school_id: String,
+    /// User who replied
+    pub user_id: String,
+    pub member_id: String,
+    /// Child the reply is for, when a parent replies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub student_id: Option<String>,
+    pub response: RsvpResponse,
+    /// Extra people coming along
+    pub guests: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[graphql(skip)]
+    pub responded_at: DateTime,
+}
+
+#[ComplexObject]
+impl EventRsvp {
+    /// Returns the RSVP's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn responded_at_str(&self) -> String {
+        self.responded_at
+            .try_to_rfc3339_string()
+            .unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// CALENDAR FEED
+// ============================================================================
+
+/// CalendarFeed - a secret iCalendar subscription URL for one member.
+/// Only the token's hash is stored; the token is shown once when created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeed {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    pub member_id: String,
+    pub user_id: String,
+    pub school_id: String,
+    pub token_hash: String,
+    pub created_at: DateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_fetched_at: Option<DateTime>,
+}
@@ -20,7 +20,14 @@ pub struct Payment {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub student_id: ObjectId,
-    pub fee_id: ObjectId,
+    /// School the student belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub school_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_id: Option<ObjectId>,
+    /// Invoice the payment settles, in part or in full
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invoice_id: Option<ObjectId>,
     pub amount_paid: f64,
     pub currency: String,
     pub payment_method: String, // "cash" | "bank_transfer" | "card"
@@ -47,6 +54,9 @@ pub struct Invoice {
     pub issue_date: DateTime,
     pub due_date: DateTime,
     pub status: String, // "unpaid" | "partial" | "paid" | "overdue"
+    /// What the invoice is for when it isn't built from fees (e.g., a library fine)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
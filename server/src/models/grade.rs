@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::utils::common_types::SoftDelete;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Grade {
@@ -20,4 +21,6 @@ pub struct Grade {
     pub graded_at: DateTime,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
 }
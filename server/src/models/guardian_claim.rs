@@ -0,0 +1,100 @@
+use async_graphql::{ComplexObject, SimpleObject};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::common_types::AuditInfo;
+
+/// Maximum wrong code entries before a claim is locked
+pub const MAX_CLAIM_ATTEMPTS: i32 = 5;
+
+// ============================================================================
+// GUARDIAN CLAIM MODEL
+// ============================================================================
+
+/// GuardianClaim - a user's request to be linked to a student's Guardian record.
+/// A verification code is sent to the guardian phone on file; entering it proves
+/// the user controls that phone.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct GuardianClaim {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    /// User making the claim
+    pub user_id: String,
+    /// School of the student
+    pub school_id: String,
+    /// Student MongoDB ID (hex string)
+    pub student_id: String,
+    /// Guardian phone number as stored on the student record
+    #[graphql(skip)]
+    pub guardian_phone: String,
+
+    /// SHA-256 hash of the verification code sent by SMS
+    #[graphql(skip)]
+    pub code_hash: String,
+    /// Number of wrong code entries
+    #[serde(default)]
+    pub attempts: i32,
+    /// Code expiry time
+    #[graphql(skip)]
+    pub expires_at: DateTime,
+    /// When the claim was verified
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<DateTime>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+}
+
+#[ComplexObject]
+impl GuardianClaim {
+    /// Returns the claim's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    /// Expiry time as ISO string
+    async fn expires_at_str(&self) -> String {
+        self.expires_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    /// Masked phone the code was sent to (e.g., "******678")
+    async fn masked_phone(&self) -> String {
+        crate::utils::sms::mask_phone(&self.guardian_phone)
+    }
+}
+
+impl GuardianClaim {
+    /// Create a new unverified claim
+    pub fn new(
+        user_id: String,
+        school_id: String,
+        student_id: String,
+        guardian_phone: String,
+        code_hash: String,
+        expires_at: DateTime,
+    ) -> Self {
+        Self {
+            id: None,
+            user_id: user_id.clone(),
+            school_id,
+            student_id,
+            guardian_phone,
+            code_hash,
+            attempts: 0,
+            expires_at,
+            verified_at: None,
+            audit: AuditInfo::new(Some(user_id)),
+        }
+    }
+
+    /// Check if the code can still be entered
+    pub fn is_open(&self) -> bool {
+        self.verified_at.is_none()
+            && self.attempts < MAX_CLAIM_ATTEMPTS
+            && self.expires_at > DateTime::now()
+    }
+}
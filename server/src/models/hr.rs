@@ -1,11 +1,15 @@
 use serde::{Deserialize, Serialize};
 use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::utils::common_types::SoftDelete;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Staff {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub staff_id: String,
+    /// School the staff member works at; older records have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub school_id: Option<String>,
     pub first_name: String,
     pub last_name: String,
     pub email: String,
@@ -23,6 +27,8 @@ pub struct Staff {
     pub profile_photo: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
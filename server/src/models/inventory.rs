@@ -0,0 +1,477 @@
+use async_graphql::{ComplexObject, Enum, SimpleObject};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::common_types::{AuditInfo, SoftDelete};
+
+const YEAR_MILLIS: f64 = 365.25 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+// ============================================================================
+// ASSET
+// ============================================================================
+
+/// Where an asset is in its life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum AssetStatus {
+    /// In its location, free to use or check out
+    Available,
+    /// Checked out to a staff member
+    CheckedOut,
+    /// Out of use while a maintenance request is in progress
+    InMaintenance,
+    /// Not found at a stock-take or reported missing
+    Lost,
+    /// Taken out of service (sold, scrapped, donated)
+    Retired,
+}
+
+/// Asset - a tracked piece of school equipment (projector, laptop, desk...)
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Asset {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_id: Option<String>,
+    /// Label printed on the asset (e.g., "AST-000042"), unique per school
+    pub asset_tag: String,
+    pub name: String,
+    /// Category (e.g., "IT", "Furniture", "Lab equipment")
+    pub category: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    /// Building or area (e.g., "Main building")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>,
+    /// HR staff record the asset is assigned to or checked out by
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_staff_id: Option<String>,
+    pub status: AssetStatus,
+
+    // ========================
+    // Value
+    // ========================
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_date: Option<DateTime>,
+    #[serde(default)]
+    pub purchase_cost: f64,
+    pub currency: String,
+    /// Years over which the cost is depreciated (straight line); none means
+    /// the asset isn't depreciated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub useful_life_years: Option<i32>,
+    /// Value left at the end of its useful life
+    #[serde(default)]
+    pub salvage_value: f64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl Asset {
+    /// Returns the asset's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn purchase_date_str(&self) -> Option<String> {
+        self.purchase_date
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    /// Depreciation charged so far
+    async fn accumulated_depreciation(&self) -> f64 {
+        self.depreciation_at(DateTime::now())
+    }
+
+    /// Purchase cost less depreciation so far
+    async fn book_value(&self) -> f64 {
+        self.book_value_at(DateTime::now())
+    }
+}
+
+impl Asset {
+    /// Straight-line depreciation from the purchase date to `at`
+    pub fn depreciation_at(&self, at: DateTime) -> f64 {
+        let (Some(purchased), Some(life)) = (self.purchase_date, self.useful_life_years) else {
+            return 0.0;
+        };
+        if life <= 0 {
+            return 0.0;
+        }
+        let depreciable = (self.purchase_cost - self.salvage_value).max(0.0);
+        let years =
+            (at.timestamp_millis() - purchased.timestamp_millis()).max(0) as f64 / YEAR_MILLIS;
+        let depreciation = (depreciable * years / life as f64).min(depreciable);
+        (depreciation * 100.0).round() / 100.0
+    }
+
+    pub fn book_value_at(&self, at: DateTime) -> f64 {
+        self.purchase_cost - self.depreciation_at(at)
+    }
+}
+
+// ============================================================================
+// ASSET MOVEMENT
+// ============================================================================
+
+/// What happened to an asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum AssetAction {
+    CheckedOut,
+    CheckedIn,
+    /// Location, room or assignee changed
+    Moved,
+    /// Status changed (maintenance, lost, retired...)
+    StatusChanged,
+}
+
+/// AssetMovement - one entry in an asset's check-in/check-out history
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct AssetMovement {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub asset_id: String,
+    pub action: AssetAction,
+    /// Staff record holding the asset after the movement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staff_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>,
+    pub status: AssetStatus,
+    /// When a checked-out asset should come back
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_return: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// User who recorded the movement
+    pub recorded_by: String,
+    #[graphql(skip)]
+    pub recorded_at: DateTime,
+}
+
+#[ComplexObject]
+impl AssetMovement {
+    /// Returns the movement's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn expected_return_str(&self) -> Option<String> {
+        self.expected_return
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    async fn recorded_at_str(&self) -> String {
+        self.recorded_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// CONSUMABLE STOCK
+// ============================================================================
+
+/// StockItem - a consumable kept in stock (paper, chalk, cleaning supplies...)
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct StockItem {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_id: Option<String>,
+    pub name: String,
+    /// Stock-keeping code, unique per school
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    pub category: String,
+    /// Unit counted (e.g., "box", "ream", "bottle")
+    pub unit: String,
+    pub quantity: i32,
+    /// Quantity at or below which the item is low on stock
+    pub reorder_level: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_cost: Option<f64>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl StockItem {
+    /// Returns the item's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    /// At or below the reorder level
+    async fn is_low_stock(&self) -> bool {
+        self.is_low()
+    }
+}
+
+impl StockItem {
+    pub fn is_low(&self) -> bool {
+        self.quantity <= self.reorder_level
+    }
+}
+
+/// Why a stock level changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum StockReason {
+    /// Delivery added to stock
+    Received,
+    /// Handed out for use
+    Issued,
+    /// Manual correction (breakage, expiry, miscount)
+    Adjusted,
+    /// Correction from a stock-take count
+    StockTake,
+}
+
+/// StockMovement - a change in a consumable's stock level
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct StockMovement {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub item_id: String,
+    /// Units added (positive) or removed (negative)
+    pub change: i32,
+    pub quantity_after: i32,
+    pub reason: StockReason,
+    /// Who the stock was issued to, for issues
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    pub recorded_by: String,
+    #[graphql(skip)]
+    pub recorded_at: DateTime,
+}
+
+#[ComplexObject]
+impl StockMovement {
+    /// Returns the movement's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn recorded_at_str(&self) -> String {
+        self.recorded_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// MAINTENANCE
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum MaintenancePriority {
+    Low,
+    Medium,
+    High,
+    /// Safety issue or blocking teaching
+    Urgent,
+}
+
+/// Maintenance request workflow: Open -> InProgress -> Completed, with
+/// Cancelled possible until work is completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum MaintenanceStatus {
+    /// Reported, waiting to be picked up
+    Open,
+    /// Being repaired; the asset is out of use
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+impl MaintenanceStatus {
+    /// Whether a request can move from this status to `next`
+    pub fn can_move_to(self, next: MaintenanceStatus) -> bool {
+        use MaintenanceStatus::*;
+        matches!(
+            (self, next),
+            (Open, InProgress)
+                | (Open, Cancelled)
+                | (InProgress, Completed)
+                | (InProgress, Cancelled)
+        )
+    }
+}
+
+/// MaintenanceRequest - a repair or service job for an asset
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct MaintenanceRequest {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub asset_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub priority: MaintenancePriority,
+    pub status: MaintenanceStatus,
+    /// Technician or vendor doing the work
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<String>,
+    /// Cost of the repair
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    /// What was done, or why it was cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+    /// Asset status before it went into maintenance, restored afterwards
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_asset_status: Option<AssetStatus>,
+    /// User who reported the problem
+    pub reported_by: String,
+    #[graphql(skip)]
+    pub created_at: DateTime,
+    #[graphql(skip)]
+    pub updated_at: DateTime,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime>,
+}
+
+#[ComplexObject]
+impl MaintenanceRequest {
+    /// Returns the request's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn created_at_str(&self) -> String {
+        self.created_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn updated_at_str(&self) -> String {
+        self.updated_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn completed_at_str(&self) -> Option<String> {
+        self.completed_at
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+}
+
+// ============================================================================
+// STOCK-TAKE
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum StockTakeStatus {
+    /// Counting in progress
+    InProgress,
+    /// Counts reconciled against the records
+    Completed,
+}
+
+/// An asset seen during a stock-take
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct AssetCount {
+    pub asset_id: String,
+    /// Where it was found, when different from the record
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location_found: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room_found: Option<String>,
+    pub counted_by: String,
+}
+
+/// A consumable counted during a stock-take
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct ItemCount {
+    pub item_id: String,
+    /// Quantity on record when counted
+    pub expected: i32,
+    pub counted: i32,
+    pub counted_by: String,
+}
+
+/// StockTake - an annual (or ad hoc) physical count of assets and stock
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct StockTake {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    /// Limit the count to one branch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_id: Option<String>,
+    /// Year the count is for (e.g., 2026)
+    pub year: i32,
+    pub status: StockTakeStatus,
+    /// Assets on record when the count started
+    #[graphql(skip)]
+    #[serde(default)]
+    pub expected_asset_ids: Vec<String>,
+    #[serde(default)]
+    pub asset_counts: Vec<AssetCount>,
+    #[serde(default)]
+    pub item_counts: Vec<ItemCount>,
+    pub started_by: String,
+    #[graphql(skip)]
+    pub started_at: DateTime,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime>,
+}
+
+#[ComplexObject]
+impl StockTake {
+    /// Returns the stock-take's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn started_at_str(&self) -> String {
+        self.started_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn completed_at_str(&self) -> Option<String> {
+        self.completed_at
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    /// Assets expected to be counted
+    async fn assets_expected(&self) -> i32 {
+        self.expected_asset_ids.len() as i32
+    }
+}
@@ -0,0 +1,165 @@
+use async_graphql::{ComplexObject, Enum, SimpleObject};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::models::member::SchoolRole;
+use crate::utils::common_types::AuditInfo;
+
+// ============================================================================
+// INVITATION STATUS
+// ============================================================================
+
+/// Invitation lifecycle status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum, Default)]
+pub enum InvitationStatus {
+    /// Waiting to be accepted
+    #[default]
+    Pending,
+    /// Accepted (single-use) or used up (shareable code)
+    Accepted,
+    /// Revoked by an admin
+    Revoked,
+}
+
+// ============================================================================
+// INVITATION MODEL
+// ============================================================================
+
+/// Invitation - an offer to join a school with a preassigned role
+/// Sent to an email or phone number, or shared as an open join code
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Invitation {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    // ========================
+    // Target School & Role
+    // ========================
+    /// School ID
+    pub school_id: String,
+    /// Branch ID (optional, for branch-level assignment)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_id: Option<String>,
+    /// Role the invitee will receive
+    pub role: SchoolRole,
+    /// Custom title for the member (e.g., "Head of Math Dept")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    // ========================
+    // Invitee
+    // ========================
+    /// Invitee email (email invitations)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Invitee phone number (SMS invitations)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+
+    // ========================
+    // Code & Usage
+    // ========================
+    /// Join code entered or linked by the invitee
+    pub code: String,
+    /// Maximum number of acceptances (shareable codes only, None = unlimited)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<i32>,
+    /// Number of times the invitation has been accepted
+    #[serde(default)]
+    pub use_count: i32,
+
+    // ========================
+    // Status & Tracking
+    // ========================
+    #[serde(default)]
+    pub status: InvitationStatus,
+    /// Who created the invitation
+    pub invited_by: String,
+    /// Expiry time
+    #[graphql(skip)]
+    pub expires_at: DateTime,
+    /// Number of times the invitation has been sent
+    #[serde(default)]
+    pub send_count: i32,
+    /// Last time the invitation was sent
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_sent_at: Option<DateTime>,
+    /// User IDs that accepted this invitation
+    #[serde(default)]
+    pub accepted_by: Vec<String>,
+
+    // ========================
+    // Audit
+    // ========================
+    #[serde(default)]
+    pub audit: AuditInfo,
+}
+
+#[ComplexObject]
+impl Invitation {
+    /// Returns the invitation's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    /// Expiry time as ISO string
+    async fn expires_at_str(&self) -> String {
+        self.expires_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    /// Whether the invitation can still be accepted
+    async fn is_open(&self) -> bool {
+        self.can_accept()
+    }
+}
+
+impl Invitation {
+    /// Create a new pending invitation
+    pub fn new(
+        school_id: String,
+        role: SchoolRole,
+        code: String,
+        invited_by: String,
+        expires_at: DateTime,
+    ) -> Self {
+        Self {
+            id: None,
+            school_id,
+            branch_id: None,
+            role,
+            title: None,
+            email: None,
+            phone: None,
+            code,
+            max_uses: None,
+            use_count: 0,
+            status: InvitationStatus::Pending,
+            invited_by: invited_by.clone(),
+            expires_at,
+            send_count: 0,
+            last_sent_at: None,
+            accepted_by: Vec::new(),
+            audit: AuditInfo::new(Some(invited_by)),
+        }
+    }
+
+    /// Is this an open join code rather than a personal invitation?
+    pub fn is_shareable(&self) -> bool {
+        self.email.is_none() && self.phone.is_none()
+    }
+
+    /// Check if the invitation has passed its expiry time
+    pub fn is_expired(&self) -> bool {
+        self.expires_at < DateTime::now()
+    }
+
+    /// Check if the invitation can still be accepted
+    pub fn can_accept(&self) -> bool {
+        matches!(self.status, InvitationStatus::Pending)
+            && !self.is_expired()
+            && self.max_uses.is_none_or(|max| self.use_count < max)
+    }
+}
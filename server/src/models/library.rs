@@ -0,0 +1,355 @@
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use mongodb::Database;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::common_types::{AuditInfo, LocalizedText, SoftDelete};
+
+/// Default loan period in days
+pub const LOAN_DAYS: i64 = 14;
+/// Fine charged per day a loan is overdue (USD)
+pub const FINE_PER_DAY: f64 = 0.10;
+/// Times a loan can be renewed
+pub const MAX_RENEWALS: i32 = 2;
+/// Days a returned copy is held for the next reservation
+pub const HOLD_DAYS: i64 = 3;
+/// Books a student can have on loan at once (staff are not limited)
+pub const MAX_STUDENT_LOANS: u64 = 3;
+
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+// ============================================================================
+// BOOK
+// ============================================================================
+
+/// Book - a title in the school library's catalog. Physical copies are
+/// tracked separately as `BookCopy`.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Book {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    /// Title in English, with optional Khmer title
+    pub title: LocalizedText,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// ISBN-10 or ISBN-13, digits only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_year: Option<i32>,
+    /// Subject or genre (e.g., "Mathematics", "Fiction")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Language of the book ("en", "km", ...)
+    #[serde(default = "default_language")]
+    pub language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Charged when a copy is lost (in addition to any overdue fine)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement_cost: Option<f64>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+#[ComplexObject]
+impl Book {
+    /// Returns the book's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    /// Copies in circulation (not lost, damaged or withdrawn)
+    async fn total_copies(&self, ctx: &Context<'_>) -> Result<i64> {
+        self.count_copies(
+            ctx,
+            doc! { "status": { "$in": ["Available", "OnLoan", "OnHold"] } },
+        )
+        .await
+    }
+
+    /// Copies on the shelf and free to borrow
+    async fn available_copies(&self, ctx: &Context<'_>) -> Result<i64> {
+        self.count_copies(ctx, doc! { "status": "Available" }).await
+    }
+}
+
+impl Book {
+    async fn count_copies(&self, ctx: &Context<'_>, mut filter: Document) -> Result<i64> {
+        let db = ctx.data::<Database>()?;
+        filter.insert("book_id", self.id.map(|id| id.to_hex()).unwrap_or_default());
+        filter.insert("soft_delete.is_deleted", doc! { "$ne": true });
+        db.collection::<BookCopy>("book_copies")
+            .count_documents(filter, None)
+            .await
+            .map(|n| n as i64)
+            .map_err(|e| async_graphql::Error::new(e.to_string()))
+    }
+
+    /// Create a new catalog entry
+    pub fn new(school_id: String, title: LocalizedText) -> Self {
+        Self {
+            id: None,
+            school_id,
+            title,
+            authors: Vec::new(),
+            isbn: None,
+            publisher: None,
+            published_year: None,
+            category: None,
+            language: default_language(),
+            description: None,
+            replacement_cost: None,
+            audit: AuditInfo::default(),
+            soft_delete: SoftDelete::default(),
+        }
+    }
+}
+
+// ============================================================================
+// BOOK COPY
+// ============================================================================
+
+/// Circulation state of a physical copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum CopyStatus {
+    /// On the shelf
+    Available,
+    /// Checked out
+    OnLoan,
+    /// Held for a reservation
+    OnHold,
+    Lost,
+    Damaged,
+    /// Removed from circulation
+    Withdrawn,
+}
+
+/// BookCopy - one physical copy of a book, identified by its barcode label
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct BookCopy {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub book_id: String,
+    /// Barcode / QR value printed on the copy (e.g., "LIB-000123"), unique per school
+    pub barcode: String,
+    pub status: CopyStatus,
+    /// Shelf or room location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shelf: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl BookCopy {
+    /// Returns the copy's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+}
+
+// ============================================================================
+// BORROWER
+// ============================================================================
+
+/// Who can borrow from the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum BorrowerType {
+    /// `borrower_id` is a student record ID
+    Student,
+    /// `borrower_id` is a staff member's membership ID
+    Staff,
+}
+
+// ============================================================================
+// LOAN
+// ============================================================================
+
+/// State of a loan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum LoanStatus {
+    Active,
+    Returned,
+    Lost,
+}
+
+/// Loan - a copy checked out to a borrower
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Loan {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub book_id: String,
+    pub copy_id: String,
+    /// Copy barcode at checkout, for display
+    pub barcode: String,
+    pub borrower_type: BorrowerType,
+    pub borrower_id: String,
+    pub status: LoanStatus,
+    #[graphql(skip)]
+    pub borrowed_at: DateTime,
+    #[graphql(skip)]
+    pub due_at: DateTime,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub returned_at: Option<DateTime>,
+    #[serde(default)]
+    pub renewals: i32,
+    /// Fine charged when the loan was closed
+    #[serde(default)]
+    pub fine_amount: f64,
+    /// Student invoice the fine was posted to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fine_invoice_id: Option<String>,
+    /// Member who issued the loan
+    pub issued_by: String,
+    /// Member who checked the copy back in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub returned_to: Option<String>,
+}
+
+#[ComplexObject]
+impl Loan {
+    /// Returns the loan's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn borrowed_at_str(&self) -> String {
+        self.borrowed_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn due_at_str(&self) -> String {
+        self.due_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn returned_at_str(&self) -> Option<String> {
+        self.returned_at
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    /// Whether the loan is still out past its due date
+    async fn is_overdue(&self) -> bool {
+        self.status == LoanStatus::Active && self.days_overdue_at(DateTime::now()) > 0
+    }
+
+    /// Whole days past the due date (until returned, or today)
+    async fn days_overdue(&self) -> i64 {
+        self.days_overdue_at(self.returned_at.unwrap_or_else(DateTime::now))
+    }
+
+    /// Fine owed so far for an active loan, or the fine charged for a closed one
+    async fn current_fine(&self) -> f64 {
+        match self.status {
+            LoanStatus::Active => self.overdue_fine_at(DateTime::now()),
+            _ => self.fine_amount,
+        }
+    }
+}
+
+impl Loan {
+    /// Whole days the loan is past due at `at`
+    pub fn days_overdue_at(&self, at: DateTime) -> i64 {
+        let late = at.timestamp_millis() - self.due_at.timestamp_millis();
+        if late <= 0 {
+            0
+        } else {
+            (late + DAY_MILLIS - 1) / DAY_MILLIS
+        }
+    }
+
+    /// Overdue fine for returning the loan at `at`
+    pub fn overdue_fine_at(&self, at: DateTime) -> f64 {
+        self.days_overdue_at(at) as f64 * FINE_PER_DAY
+    }
+}
+
+/// Date `days` days after `from`
+pub fn days_after(from: DateTime, days: i64) -> DateTime {
+    DateTime::from_millis(from.timestamp_millis() + days * DAY_MILLIS)
+}
+
+// ============================================================================
+// RESERVATION
+// ============================================================================
+
+/// State of a reservation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum ReservationStatus {
+    /// Waiting for a copy to come back
+    Waiting,
+    /// A copy is on hold for the borrower until `hold_until`
+    Ready,
+    /// The borrower checked out the held copy
+    Fulfilled,
+    Cancelled,
+    /// The hold ran out before the borrower collected the copy
+    Expired,
+}
+
+/// Reservation - a borrower's place in the queue for a book
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Reservation {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub book_id: String,
+    pub borrower_type: BorrowerType,
+    pub borrower_id: String,
+    pub status: ReservationStatus,
+    /// Copy held for the borrower once the reservation is ready
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy_id: Option<String>,
+    #[graphql(skip)]
+    pub reserved_at: DateTime,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold_until: Option<DateTime>,
+    /// User who placed the reservation
+    pub created_by: String,
+}
+
+#[ComplexObject]
+impl Reservation {
+    /// Returns the reservation's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn reserved_at_str(&self) -> String {
+        self.reserved_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn hold_until_str(&self) -> Option<String> {
+        self.hold_until.and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+}
@@ -0,0 +1,54 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::common_types::AuditInfo;
+
+/// Maximum wrong code entries before a login code is locked
+pub const MAX_LOGIN_CODE_ATTEMPTS: i32 = 5;
+
+// ============================================================================
+// LOGIN CODE MODEL
+// ============================================================================
+
+/// LoginCode - a one-time code sent by SMS for phone-number login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginCode {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// Normalized phone number the code was sent to
+    pub phone: String,
+    /// SHA-256 hash of the code
+    pub code_hash: String,
+    /// Number of wrong code entries
+    #[serde(default)]
+    pub attempts: i32,
+    pub expires_at: DateTime,
+    /// When the code was used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumed_at: Option<DateTime>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+}
+
+impl LoginCode {
+    pub fn new(phone: String, code_hash: String, expires_at: DateTime) -> Self {
+        Self {
+            id: None,
+            phone,
+            code_hash,
+            attempts: 0,
+            expires_at,
+            consumed_at: None,
+            audit: AuditInfo::default(),
+        }
+    }
+
+    /// Check if the code can still be entered
+    pub fn is_open(&self) -> bool {
+        self.consumed_at.is_none()
+            && self.attempts < MAX_LOGIN_CODE_ATTEMPTS
+            && self.expires_at > DateTime::now()
+    }
+}
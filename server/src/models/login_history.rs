@@ -0,0 +1,68 @@
+use async_graphql::{ComplexObject, SimpleObject};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// LOGIN HISTORY MODEL
+// ============================================================================
+
+/// LoginHistory - one sign-in attempt (append-only)
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct LoginHistory {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    /// User ID (hex string)
+    pub user_id: String,
+    /// How the user signed in ("koompi", "oidc", "phone", "refresh")
+    pub provider: String,
+    /// Client IP address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    /// User agent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Whether tokens were issued
+    pub success: bool,
+    /// Why the attempt was refused (e.g., "Suspended")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    #[graphql(skip)]
+    pub created_at: DateTime,
+}
+
+#[ComplexObject]
+impl LoginHistory {
+    /// Returns the record's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    /// Attempt time as ISO string
+    async fn created_at_str(&self) -> String {
+        self.created_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+}
+
+impl LoginHistory {
+    pub fn new(
+        user_id: String,
+        provider: String,
+        ip: Option<String>,
+        device: Option<String>,
+        failure_reason: Option<String>,
+    ) -> Self {
+        Self {
+            id: None,
+            user_id,
+            provider,
+            ip,
+            device,
+            success: failure_reason.is_none(),
+            failure_reason,
+            created_at: DateTime::now(),
+        }
+    }
+}
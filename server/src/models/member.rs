@@ -127,11 +127,16 @@ pub enum Permission {
     ViewTransport,
     ManageTransport,
 
+    // Inventory
+    ViewInventory,
+    ManageInventory,
+
     // Settings
     ViewSettings,
     ManageSettings,
     ManageUsers,
     ManageRoles,
+    ViewAuditLog,
 }
 
 // ============================================================================
@@ -166,6 +171,16 @@ pub struct Member {
     /// Additional permissions beyond role defaults
     #[serde(default)]
     pub permissions: Vec<Permission>,
+    /// Permissions explicitly denied; a denial overrides every other source
+    #[serde(default)]
+    pub denied_permissions: Vec<Permission>,
+    /// School-defined custom roles held by the member
+    #[serde(default)]
+    pub custom_role_ids: Vec<String>,
+    /// Permissions from the member's custom roles, loaded alongside the member
+    #[serde(skip)]
+    #[graphql(skip)]
+    pub custom_role_permissions: Vec<Permission>,
     /// Custom title (e.g., "Principal", "Head of Math Dept")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -218,6 +233,12 @@ impl Member {
         self.id.as_ref().map(|id| id.to_hex())
     }
 
+    /// Role defaults plus explicitly assigned permissions
+    #[graphql(name = "effectivePermissions")]
+    async fn effective_permissions_field(&self) -> Vec<Permission> {
+        self.effective_permissions()
+    }
+
     /// Returns the associated user's information
     async fn user(
         &self,
@@ -252,6 +273,9 @@ impl Member {
             branch_id: None,
             role,
             permissions: Vec::new(),
+            denied_permissions: Vec::new(),
+            custom_role_ids: Vec::new(),
+            custom_role_permissions: Vec::new(),
             title: None,
             student_id: None,
             staff_id: None,
@@ -308,10 +332,17 @@ impl Member {
                 Permission::ViewMessages,
                 Permission::ViewEvents,
                 Permission::ManageEvents,
+                Permission::ViewLibrary,
+                Permission::ManageLibrary,
+                Permission::ViewTransport,
+                Permission::ManageTransport,
+                Permission::ViewInventory,
+                Permission::ManageInventory,
                 Permission::ViewSettings,
                 Permission::ManageSettings,
                 Permission::ManageUsers,
                 Permission::ManageRoles,
+                Permission::ViewAuditLog,
             ],
             // Director and Deputy Director have same access as Owner (except billing)
             SchoolRole::Director | SchoolRole::DeputyDirector => vec![
@@ -345,10 +376,17 @@ impl Member {
                 Permission::ViewMessages,
                 Permission::ViewEvents,
                 Permission::ManageEvents,
+                Permission::ViewLibrary,
+                Permission::ManageLibrary,
+                Permission::ViewTransport,
+                Permission::ManageTransport,
+                Permission::ViewInventory,
+                Permission::ManageInventory,
                 Permission::ViewSettings,
                 Permission::ManageSettings,
                 Permission::ManageUsers,
                 Permission::ManageRoles,
+                Permission::ViewAuditLog,
             ],
             // HeadTeacher has admin-like access
             SchoolRole::HeadTeacher => vec![
@@ -372,6 +410,8 @@ impl Member {
                 Permission::ViewMessages,
                 Permission::ViewEvents,
                 Permission::ManageEvents,
+                Permission::ViewLibrary,
+                Permission::ViewInventory,
             ],
             SchoolRole::Admin => vec![
                 Permission::ViewDashboard,
@@ -398,6 +438,12 @@ impl Member {
                 Permission::ViewMessages,
                 Permission::ViewEvents,
                 Permission::ManageEvents,
+                Permission::ViewLibrary,
+                Permission::ManageLibrary,
+                Permission::ViewTransport,
+                Permission::ManageTransport,
+                Permission::ViewInventory,
+                Permission::ManageInventory,
                 Permission::ViewSettings,
                 Permission::ManageUsers,
             ],
@@ -414,6 +460,8 @@ impl Member {
                 Permission::SendMessages,
                 Permission::ViewMessages,
                 Permission::ViewEvents,
+                Permission::ViewLibrary,
+                Permission::ViewInventory,
             ],
             SchoolRole::Student => vec![
                 Permission::ViewDashboard,
@@ -421,28 +469,69 @@ impl Member {
                 Permission::ViewGrades,
                 Permission::ViewMessages,
                 Permission::ViewEvents,
+                Permission::ViewLibrary,
             ],
             SchoolRole::Parent => vec![
                 Permission::ViewDashboard,
                 Permission::ViewAttendance,
                 Permission::ViewGrades,
                 Permission::ViewFinance,
+                Permission::ViewTransport,
                 Permission::SendMessages,
                 Permission::ViewMessages,
                 Permission::ViewEvents,
             ],
+            SchoolRole::Librarian => vec![
+                Permission::ViewDashboard,
+                Permission::ViewStudents,
+                Permission::ViewMessages,
+                Permission::ViewEvents,
+                Permission::ViewLibrary,
+                Permission::ManageLibrary,
+            ],
+            // Non-teaching staff includes drivers, who log boardings on their routes,
+            // and caretakers, who report broken equipment
+            SchoolRole::Staff => vec![
+                Permission::ViewDashboard,
+                Permission::ViewMessages,
+                Permission::ViewEvents,
+                Permission::ViewTransport,
+                Permission::ViewInventory,
+            ],
             _ => vec![Permission::ViewDashboard],
         }
     }
 
+    /// All permissions the member holds: role defaults, custom roles and explicit
+    /// grants, minus explicit denials
+    pub fn effective_permissions(&self) -> Vec<Permission> {
+        let mut permissions = Vec::new();
+        for permission in Self::default_permissions(self.role)
+            .into_iter()
+            .chain(self.custom_role_permissions.iter().copied())
+            .chain(self.permissions.iter().copied())
+        {
+            if !permissions.contains(&permission) && !self.denied_permissions.contains(&permission)
+            {
+                permissions.push(permission);
+            }
+        }
+        permissions
+    }
+
     /// Check if member has a specific permission
     pub fn has_permission(&self, permission: Permission) -> bool {
+        // Explicit denials win over every grant
+        if self.denied_permissions.contains(&permission) {
+            return false;
+        }
         // Check explicitly assigned permissions first
         if self.permissions.contains(&permission) {
             return true;
         }
-        // Check default permissions for role
-        Self::default_permissions(self.role).contains(&permission)
+        // Then permissions from custom roles and the role defaults
+        self.custom_role_permissions.contains(&permission)
+            || Self::default_permissions(self.role).contains(&permission)
     }
 
     /// Check if member is active
@@ -0,0 +1,153 @@
+use async_graphql::{ComplexObject, Enum, SimpleObject};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::models::member::SchoolRole;
+use crate::utils::common_types::{Attachment, AuditInfo, SoftDelete};
+
+// ============================================================================
+// THREAD
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum ThreadKind {
+    /// Two people
+    Direct,
+    /// Several people, started by staff (e.g., a class's parents)
+    Group,
+}
+
+/// A member taking part in a thread
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct ThreadParticipant {
+    pub user_id: String,
+    pub member_id: String,
+    pub role: SchoolRole,
+    /// Messages after this are unread for the participant
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_read_at: Option<DateTime>,
+}
+
+#[ComplexObject]
+impl ThreadParticipant {
+    async fn last_read_at_str(&self) -> Option<String> {
+        self.last_read_at
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+}
+
+/// MessageThread - a conversation between teachers and parents
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct MessageThread {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub kind: ThreadKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Student the conversation is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub student_id: Option<String>,
+    pub participants: Vec<ThreadParticipant>,
+
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_message_at: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_message_preview: Option<String>,
+
+    /// Locked by a school leader; nobody can write until unlocked
+    pub locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked_by: Option<String>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl MessageThread {
+    /// Returns the thread's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn last_message_at_str(&self) -> Option<String> {
+        self.last_message_at
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+}
+
+impl MessageThread {
+    pub fn participant(&self, user_id: &str) -> Option<&ThreadParticipant> {
+        self.participants.iter().find(|p| p.user_id == user_id)
+    }
+}
+
+// ============================================================================
+// MESSAGE
+// ============================================================================
+
+/// Message - one post in a thread
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Message {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub thread_id: String,
+    pub school_id: String,
+    /// User who wrote it
+    pub sender_id: String,
+    pub body: String,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    #[graphql(skip)]
+    pub sent_at: DateTime,
+
+    /// Hidden by a school leader; only leaders still see the content
+    pub hidden: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden_reason: Option<String>,
+    /// Users who reported the message to school leaders
+    #[serde(default)]
+    pub reported_by: Vec<String>,
+
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl Message {
+    /// Returns the message's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn sent_at_str(&self) -> String {
+        self.sent_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+}
+
+impl Message {
+    /// Blank out a hidden message for readers who aren't moderating
+    pub fn redacted(mut self) -> Self {
+        if self.hidden {
+            self.body = String::new();
+            self.attachments.clear();
+            self.hidden_reason = None;
+        }
+        self.reported_by.clear();
+        self
+    }
+}
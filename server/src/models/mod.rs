@@ -1,12 +1,28 @@
+pub mod announcement;
 pub mod attendance;
+pub mod audit_log;
 pub mod branch;
 pub mod class;
+pub mod custom_role;
+pub mod event;
 pub mod finance;
 pub mod grade;
 pub mod grade_level;
+pub mod guardian_claim;
 pub mod hr;
+pub mod inventory;
+pub mod invitation;
+pub mod library;
+pub mod login_code;
+pub mod login_history;
 pub mod member;
+pub mod message;
+pub mod notification;
 pub mod school;
+pub mod session;
+pub mod stored_file;
 pub mod student;
 pub mod subject;
+pub mod subscription_invoice;
+pub mod transport;
 pub mod user;
@@ -0,0 +1,71 @@
+use async_graphql::{ComplexObject, Enum, SimpleObject};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// NOTIFICATION KIND
+// ============================================================================
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum NotificationKind {
+    /// An event the user can see is coming up
+    EventReminder,
+    /// An event the user can see was changed or cancelled
+    EventUpdate,
+    /// A new announcement reached the user
+    Announcement,
+    /// Someone wrote in one of the user's message threads
+    Message,
+    General,
+}
+
+// ============================================================================
+// NOTIFICATION MODEL
+// ============================================================================
+
+/// Notification - an in-app message for one user
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Notification {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    /// Recipient
+    pub user_id: String,
+    /// School the notification comes from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub school_id: Option<String>,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    /// What to open, as "<type>:<id>" (e.g., "event:65f0...")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<DateTime>,
+    #[graphql(skip)]
+    pub created_at: DateTime,
+}
+
+#[ComplexObject]
+impl Notification {
+    /// Returns the notification's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+
+    async fn read_at_str(&self) -> Option<String> {
+        self.read_at.and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    async fn created_at_str(&self) -> String {
+        self.created_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+}
@@ -2,6 +2,8 @@ use async_graphql::{ComplexObject, Enum, InputObject, SimpleObject};
 use mongodb::bson::{oid::ObjectId, DateTime};
 use serde::{Deserialize, Serialize};
 
+use crate::models::member::SchoolRole;
+use crate::models::student::DocumentType;
 use crate::utils::common_types::{
     Address, Attachment, AuditInfo, ContactInfo, GpsCoordinates, LocalizedText, SoftDelete,
 };
@@ -111,6 +113,20 @@ pub enum SchoolFeature {
     StudentPortal,
 }
 
+impl SchoolFeature {
+    /// Modules that must be enabled before this one can be
+    pub fn dependencies(&self) -> &'static [SchoolFeature] {
+        match self {
+            SchoolFeature::Reports | SchoolFeature::ParentPortal | SchoolFeature::StudentPortal => {
+                &[SchoolFeature::Attendance, SchoolFeature::Grading]
+            }
+            // Payroll is paid out through the finance module
+            SchoolFeature::HR => &[SchoolFeature::Finance],
+            _ => &[],
+        }
+    }
+}
+
 // ============================================================================
 // SCHOOL STATISTICS
 // ============================================================================
@@ -155,12 +171,24 @@ pub struct SchoolSettings {
     /// Minimum attendance percentage required
     #[serde(default = "default_attendance_percent")]
     pub attendance_required_percent: f64,
+    /// Local time (HH:MM) after which an ID card check-in counts as late
+    #[serde(default = "default_late_after")]
+    pub attendance_late_after: String,
     /// Number of terms/semesters per year
     #[serde(default = "default_terms")]
     pub terms_per_year: i32,
     /// Working days
     #[serde(default = "default_working_days")]
     pub working_days: Vec<String>,
+    /// Require multi-factor authentication for members in `mfa_required_roles`
+    #[serde(default)]
+    pub require_mfa: bool,
+    /// Roles that must use MFA when `require_mfa` is on
+    #[serde(default = "default_mfa_required_roles")]
+    pub mfa_required_roles: Vec<SchoolRole>,
+    /// Documents every student must have on file
+    #[serde(default = "default_required_documents")]
+    pub required_documents: Vec<DocumentType>,
 }
 
 fn default_academic_start_month() -> i32 {
@@ -178,6 +206,9 @@ fn default_timezone() -> String {
 fn default_attendance_percent() -> f64 {
     80.0
 }
+fn default_late_after() -> String {
+    "07:30".to_string()
+}
 fn default_terms() -> i32 {
     2
 }
@@ -191,6 +222,19 @@ fn default_working_days() -> Vec<String> {
     ]
 }
 
+fn default_required_documents() -> Vec<DocumentType> {
+    vec![DocumentType::BirthCertificate]
+}
+
+fn default_mfa_required_roles() -> Vec<SchoolRole> {
+    vec![
+        SchoolRole::Owner,
+        SchoolRole::Director,
+        SchoolRole::DeputyDirector,
+        SchoolRole::Accountant,
+    ]
+}
+
 impl Default for SchoolSettings {
     fn default() -> Self {
         Self {
@@ -200,8 +244,12 @@ impl Default for SchoolSettings {
             language: default_language(),
             timezone: default_timezone(),
             attendance_required_percent: default_attendance_percent(),
+            attendance_late_after: default_late_after(),
             terms_per_year: default_terms(),
             working_days: default_working_days(),
+            require_mfa: false,
+            mfa_required_roles: default_mfa_required_roles(),
+            required_documents: default_required_documents(),
         }
     }
 }
@@ -210,6 +258,9 @@ impl Default for SchoolSettings {
 // SUBSCRIPTION (for SaaS model)
 // ============================================================================
 
+/// Days a school keeps full access after its subscription expires
+pub const SUBSCRIPTION_GRACE_DAYS: i64 = 14;
+
 /// Subscription plan for school
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
 pub enum SubscriptionPlan {
@@ -220,6 +271,85 @@ pub enum SubscriptionPlan {
     Enterprise,
 }
 
+impl SubscriptionPlan {
+    /// Student limit included in the plan (None = unlimited)
+    pub fn max_students(&self) -> Option<i32> {
+        match self {
+            SubscriptionPlan::Free => Some(50),
+            SubscriptionPlan::Basic => Some(200),
+            SubscriptionPlan::Standard => Some(500),
+            SubscriptionPlan::Premium => Some(2000),
+            SubscriptionPlan::Enterprise => None,
+        }
+    }
+
+    /// Staff (non-student, non-parent member) limit included in the plan (None = unlimited)
+    pub fn max_staff(&self) -> Option<i32> {
+        match self {
+            SubscriptionPlan::Free => Some(5),
+            SubscriptionPlan::Basic => Some(20),
+            SubscriptionPlan::Standard => Some(50),
+            SubscriptionPlan::Premium => Some(200),
+            SubscriptionPlan::Enterprise => None,
+        }
+    }
+
+    /// Monthly price in USD
+    pub fn monthly_price(&self) -> f64 {
+        match self {
+            SubscriptionPlan::Free => 0.0,
+            SubscriptionPlan::Basic => 29.0,
+            SubscriptionPlan::Standard => 79.0,
+            SubscriptionPlan::Premium => 199.0,
+            SubscriptionPlan::Enterprise => 499.0,
+        }
+    }
+
+    /// Modules the plan includes; each plan includes everything in the plans below it
+    pub fn features(&self) -> Vec<SchoolFeature> {
+        let mut features = vec![SchoolFeature::Attendance, SchoolFeature::Grading];
+        if *self == SubscriptionPlan::Free {
+            return features;
+        }
+        features.extend([SchoolFeature::Reports, SchoolFeature::ParentPortal]);
+        if *self == SubscriptionPlan::Basic {
+            return features;
+        }
+        features.extend([
+            SchoolFeature::Finance,
+            SchoolFeature::HR,
+            SchoolFeature::Messaging,
+            SchoolFeature::Events,
+            SchoolFeature::StudentPortal,
+        ]);
+        if *self == SubscriptionPlan::Standard {
+            return features;
+        }
+        features.extend([
+            SchoolFeature::Library,
+            SchoolFeature::Transport,
+            SchoolFeature::Inventory,
+        ]);
+        features
+    }
+
+    /// Check if the plan includes a module
+    pub fn includes(&self, feature: SchoolFeature) -> bool {
+        self.features().contains(&feature)
+    }
+}
+
+/// Where a subscription stands relative to its expiry date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum SubscriptionState {
+    /// Paid up (or a plan that never expires)
+    Active,
+    /// Expired, but still fully usable until the grace period ends
+    GracePeriod,
+    /// Expired past the grace period (or deactivated); data can be viewed but not changed
+    ReadOnly,
+}
+
 /// Subscription information
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject, InputObject)]
 #[graphql(input_name = "SubscriptionInput")]
@@ -239,6 +369,62 @@ pub struct Subscription {
     pub is_active: bool,
 }
 
+impl Subscription {
+    /// Free plan that never expires; used for schools without a subscription record
+    pub fn free() -> Self {
+        Self {
+            plan: SubscriptionPlan::Free,
+            started_at: None,
+            expires_at: None,
+            max_students: SubscriptionPlan::Free.max_students().unwrap_or(0),
+            max_staff: SubscriptionPlan::Free.max_staff().unwrap_or(0),
+            is_active: true,
+        }
+    }
+
+    /// Student limit; a stored limit of 0 falls back to the plan's limit
+    pub fn student_limit(&self) -> Option<i32> {
+        if self.max_students > 0 {
+            Some(self.max_students)
+        } else {
+            self.plan.max_students()
+        }
+    }
+
+    /// Staff limit; a stored limit of 0 falls back to the plan's limit
+    pub fn staff_limit(&self) -> Option<i32> {
+        if self.max_staff > 0 {
+            Some(self.max_staff)
+        } else {
+            self.plan.max_staff()
+        }
+    }
+
+    /// When the grace period after expiry ends
+    pub fn grace_ends_at(&self) -> Option<DateTime> {
+        self.expires_at.map(|expires| {
+            DateTime::from_millis(
+                expires.timestamp_millis() + SUBSCRIPTION_GRACE_DAYS * 24 * 60 * 60 * 1000,
+            )
+        })
+    }
+
+    /// Current state of the subscription
+    pub fn state(&self) -> SubscriptionState {
+        if !self.is_active {
+            return SubscriptionState::ReadOnly;
+        }
+
+        let now = DateTime::now();
+        match (self.expires_at, self.grace_ends_at()) {
+            (Some(expires), _) if now <= expires => SubscriptionState::Active,
+            (Some(_), Some(grace_end)) if now <= grace_end => SubscriptionState::GracePeriod,
+            (Some(_), _) => SubscriptionState::ReadOnly,
+            (None, _) => SubscriptionState::Active,
+        }
+    }
+}
+
 // ============================================================================
 // SCHOOL MODEL
 // ============================================================================
@@ -439,9 +625,14 @@ impl School {
         self.stats.last_updated = Some(DateTime::now());
     }
 
-    /// Check if a feature is enabled
+    /// The school's subscription, or the Free plan when it has none
+    pub fn current_subscription(&self) -> Subscription {
+        self.subscription.clone().unwrap_or_else(Subscription::free)
+    }
+
+    /// Check if a feature is enabled and included in the school's plan
     pub fn has_feature(&self, feature: SchoolFeature) -> bool {
-        self.features.contains(&feature)
+        self.features.contains(&feature) && self.current_subscription().plan.includes(feature)
     }
 
     /// Enable a feature
@@ -450,4 +641,9 @@ impl School {
             self.features.push(feature);
         }
     }
+
+    /// Disable a feature
+    pub fn disable_feature(&mut self, feature: SchoolFeature) {
+        self.features.retain(|f| *f != feature);
+    }
 }
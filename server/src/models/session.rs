@@ -0,0 +1,99 @@
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::common_types::AuditInfo;
+
+/// Refresh token lifetime in days
+pub const REFRESH_TOKEN_DAYS: i64 = 30;
+
+// ============================================================================
+// SESSION MODEL
+// ============================================================================
+
+/// Session - one signed-in device. Holds the current refresh token (hashed),
+/// which is rotated on every refresh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// User ID
+    pub user_id: String,
+
+    // ========================
+    // Refresh Token
+    // ========================
+    /// SHA-256 hash of the current refresh token
+    pub refresh_token_hash: String,
+    /// Hash of the refresh token it replaced (used to detect token reuse)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_token_hash: Option<String>,
+    /// Refresh token issued by the identity provider (e.g., KOOMPI)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_refresh_token: Option<String>,
+
+    // ========================
+    // Device
+    // ========================
+    /// User agent of the signed-in device
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Last seen IP address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+
+    // ========================
+    // Lifetime
+    // ========================
+    pub last_used_at: DateTime,
+    pub expires_at: DateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime>,
+    /// School selected by the client; carried in access tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_school_id: Option<String>,
+    /// Last successful MFA check (step-up) in this session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfa_verified_at: Option<DateTime>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+}
+
+impl Session {
+    /// Create a new session for a freshly issued refresh token
+    pub fn new(
+        user_id: String,
+        refresh_token_hash: String,
+        device: Option<String>,
+        ip: Option<String>,
+    ) -> Self {
+        Self {
+            id: None,
+            user_id: user_id.clone(),
+            refresh_token_hash,
+            previous_token_hash: None,
+            provider_refresh_token: None,
+            device,
+            ip,
+            last_used_at: DateTime::now(),
+            expires_at: Self::expiry_from_now(),
+            revoked_at: None,
+            active_school_id: None,
+            mfa_verified_at: None,
+            audit: AuditInfo::new(Some(user_id)),
+        }
+    }
+
+    /// Expiry for a refresh token issued now
+    pub fn expiry_from_now() -> DateTime {
+        DateTime::from_millis(
+            DateTime::now().timestamp_millis() + REFRESH_TOKEN_DAYS * 24 * 60 * 60 * 1000,
+        )
+    }
+
+    /// Check if the session can still be used
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > DateTime::now()
+    }
+}
@@ -0,0 +1,130 @@
+use async_graphql::{ComplexObject, Enum, SimpleObject};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::common_types::{AuditInfo, SoftDelete};
+
+// ============================================================================
+// FILE PURPOSE
+// ============================================================================
+
+/// What an uploaded file is for; decides who may upload and download it and
+/// which types and sizes are accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum FilePurpose {
+    SchoolLogo,
+    SchoolBanner,
+    StudentPhoto,
+    /// Birth certificate, national ID, transcript, etc.
+    StudentDocument,
+    /// Files attached to messages, announcements and events
+    Attachment,
+    /// Generated sheets of student ID cards
+    IdCards,
+}
+
+impl FilePurpose {
+    /// MIME types accepted for the purpose
+    pub fn allowed_types(&self) -> &'static [&'static str] {
+        const IMAGES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+        const DOCUMENTS: &[&str] = &[
+            "application/pdf",
+            "image/png",
+            "image/jpeg",
+            "image/gif",
+            "image/webp",
+        ];
+        const ATTACHMENTS: &[&str] = &[
+            "application/pdf",
+            "image/png",
+            "image/jpeg",
+            "image/gif",
+            "image/webp",
+            "text/plain",
+            "text/csv",
+            "application/zip",
+            "application/msword",
+            "application/vnd.ms-excel",
+            "application/vnd.ms-powerpoint",
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        ];
+        match self {
+            FilePurpose::SchoolLogo | FilePurpose::SchoolBanner | FilePurpose::StudentPhoto => {
+                IMAGES
+            }
+            FilePurpose::StudentDocument => DOCUMENTS,
+            FilePurpose::Attachment => ATTACHMENTS,
+            FilePurpose::IdCards => &["application/pdf"],
+        }
+    }
+
+    /// Largest accepted file, in bytes
+    pub fn max_size(&self) -> usize {
+        match self {
+            FilePurpose::SchoolLogo | FilePurpose::StudentPhoto => 5 * 1024 * 1024,
+            FilePurpose::SchoolBanner | FilePurpose::StudentDocument => 10 * 1024 * 1024,
+            FilePurpose::Attachment => 20 * 1024 * 1024,
+            FilePurpose::IdCards => 200 * 1024 * 1024,
+        }
+    }
+}
+
+// ============================================================================
+// STORED FILE
+// ============================================================================
+
+/// StoredFile - an uploaded file kept in the configured storage backend.
+/// Attachments pointing at it use its `/files/{id}` URL; downloads need a
+/// signed link from `fileDownloadUrl`.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct StoredFile {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub purpose: FilePurpose,
+    /// Student the file belongs to (photos and documents)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub student_id: Option<String>,
+
+    pub file_name: String,
+    /// MIME type, detected from the content
+    pub file_type: String,
+    /// Size in bytes
+    pub file_size: i64,
+    /// SHA-256 of the content, hex encoded
+    pub checksum: String,
+
+    /// Object key in the storage backend
+    #[graphql(skip)]
+    pub storage_key: String,
+    /// Object key of the thumbnail, for images that could be thumbnailed
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_key: Option<String>,
+    /// Storage backend holding the file (e.g., "local", "s3")
+    pub backend: String,
+
+    pub uploaded_by: String,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl StoredFile {
+    /// Returns the file's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn has_thumbnail(&self) -> bool {
+        self.thumbnail_key.is_some()
+    }
+}
@@ -74,6 +74,25 @@ pub enum DocumentType {
     Other,
 }
 
+impl DocumentType {
+    /// Whether a student may hold several documents of this type; for the
+    /// others a new upload replaces the previous one
+    pub fn allows_multiple(&self) -> bool {
+        matches!(
+            self,
+            DocumentType::PreviousTranscript | DocumentType::MedicalRecord | DocumentType::Other
+        )
+    }
+}
+
+/// Where a student document is in the verification workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum DocumentStatus {
+    Pending,  // Waiting for an admin to check it
+    Verified, // Checked and accepted
+    Rejected, // Checked and turned down; a new one is needed
+}
+
 // ============================================================================
 // EMBEDDED STRUCTURES
 // ============================================================================
@@ -165,11 +184,18 @@ pub struct HealthInfo {
 
 /// Student document with verification
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
 pub struct StudentDocument {
+    /// Identifies the document within the student's record
+    #[serde(default)]
+    pub document_id: String,
     /// Document type
     pub doc_type: DocumentType,
     /// File attachment
     pub attachment: Attachment,
+    /// Uploaded file behind the attachment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
     /// Is verified?
     #[serde(default)]
     pub verified: bool,
@@ -180,6 +206,47 @@ pub struct StudentDocument {
     #[graphql(skip)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub verified_at: Option<DateTime>,
+    /// Why the document was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+    /// Rejected by (user ID)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_by: Option<String>,
+    /// Rejection date
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_at: Option<DateTime>,
+}
+
+impl StudentDocument {
+    /// Verified, rejected, or still waiting for a check
+    pub fn verification_status(&self) -> DocumentStatus {
+        if self.verified {
+            DocumentStatus::Verified
+        } else if self.rejection_reason.is_some() {
+            DocumentStatus::Rejected
+        } else {
+            DocumentStatus::Pending
+        }
+    }
+}
+
+#[async_graphql::ComplexObject]
+impl StudentDocument {
+    /// Verification status
+    async fn status(&self) -> DocumentStatus {
+        self.verification_status()
+    }
+
+    async fn verified_at_str(&self) -> Option<String> {
+        self.verified_at
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    async fn rejected_at_str(&self) -> Option<String> {
+        self.rejected_at
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
 }
 
 // ============================================================================
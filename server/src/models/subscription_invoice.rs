@@ -0,0 +1,98 @@
+use async_graphql::{ComplexObject, Enum, SimpleObject};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::models::school::SubscriptionPlan;
+
+// ============================================================================
+// INVOICE STATUS
+// ============================================================================
+
+/// Payment state of a subscription invoice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum SubscriptionInvoiceStatus {
+    /// Issued, waiting for payment
+    Pending,
+    /// Paid; the billed period has been applied to the subscription
+    Paid,
+    /// The payment provider declined the charge
+    Failed,
+}
+
+// ============================================================================
+// SUBSCRIPTION INVOICE MODEL
+// ============================================================================
+
+/// SubscriptionInvoice - what the platform bills a school for its plan
+/// (not to be confused with the school's own student invoices)
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct SubscriptionInvoice {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    /// Human-readable number (e.g., "SUB-202610-0001")
+    pub invoice_number: String,
+
+    // ========================
+    // What is billed
+    // ========================
+    pub plan: SubscriptionPlan,
+    /// Number of months billed
+    pub months: i32,
+    pub amount: f64,
+    pub currency: String,
+    #[graphql(skip)]
+    pub period_start: DateTime,
+    #[graphql(skip)]
+    pub period_end: DateTime,
+
+    // ========================
+    // Payment
+    // ========================
+    pub status: SubscriptionInvoiceStatus,
+    /// Payment provider that handled the charge (e.g., "local")
+    pub provider: String,
+    /// Provider's reference for the charge
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_reference: Option<String>,
+    /// Reason the charge failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_at: Option<DateTime>,
+
+    /// Member who placed the order
+    pub created_by: String,
+    #[graphql(skip)]
+    pub created_at: DateTime,
+}
+
+#[ComplexObject]
+impl SubscriptionInvoice {
+    /// Returns the invoice's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn period_start_str(&self) -> String {
+        self.period_start
+            .try_to_rfc3339_string()
+            .unwrap_or_default()
+    }
+
+    async fn period_end_str(&self) -> String {
+        self.period_end.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn paid_at_str(&self) -> Option<String> {
+        self.paid_at.and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    async fn created_at_str(&self) -> String {
+        self.created_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+}
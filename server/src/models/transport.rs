@@ -0,0 +1,319 @@
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use mongodb::Database;
+use serde::{Deserialize, Serialize};
+
+use crate::models::hr::Staff;
+use crate::utils::common_types::{AuditInfo, GpsCoordinates, SoftDelete};
+
+// ============================================================================
+// VEHICLE
+// ============================================================================
+
+/// Whether a vehicle can be put on a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum, Default)]
+pub enum VehicleStatus {
+    #[default]
+    Active,
+    /// Temporarily off the road
+    Maintenance,
+    /// No longer in service
+    Retired,
+}
+
+/// Vehicle - a school bus or van
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Vehicle {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    /// License plate (e.g., "PP 2A-1234"), unique per school
+    pub plate_number: String,
+    /// Display name (e.g., "Bus 3")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Seats available for students
+    pub capacity: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub make_model: Option<String>,
+    #[serde(default)]
+    pub status: VehicleStatus,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl Vehicle {
+    /// Returns the vehicle's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+}
+
+// ============================================================================
+// DRIVER
+// ============================================================================
+
+/// Driver - a staff record cleared to drive school vehicles
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct Driver {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    /// HR staff record of the driver
+    pub staff_id: String,
+    /// School membership of the driver, so they can log boardings from the app
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member_id: Option<String>,
+    pub license_number: String,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_expiry: Option<DateTime>,
+    /// Phone guardians can call during trips
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[ComplexObject]
+impl Driver {
+    /// Returns the driver's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn license_expiry_str(&self) -> Option<String> {
+        self.license_expiry
+            .and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+
+    /// Driver's name from their staff record
+    async fn name(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let db = ctx.data::<Database>()?;
+        let Ok(oid) = ObjectId::parse_str(&self.staff_id) else {
+            return Ok(None);
+        };
+        let staff = db
+            .collection::<Staff>("staff")
+            .find_one(doc! { "_id": oid }, None)
+            .await
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        Ok(staff.map(|s| format!("{} {}", s.first_name, s.last_name)))
+    }
+}
+
+// ============================================================================
+// ROUTE
+// ============================================================================
+
+/// A stop on a bus route
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct RouteStop {
+    /// Stable ID of the stop within its route
+    pub stop_id: String,
+    pub name: String,
+    pub location: GpsCoordinates,
+    /// Position along the route, starting at 1 for the first morning pickup
+    pub sequence: i32,
+    /// Scheduled morning pickup (HH:MM)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pickup_time: Option<String>,
+    /// Scheduled afternoon drop-off (HH:MM)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dropoff_time: Option<String>,
+}
+
+/// BusRoute - a named route with ordered stops, served by one vehicle and driver
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct BusRoute {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub name: String,
+    /// Short code shown on the bus (e.g., "R3")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vehicle_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver_id: Option<String>,
+    /// Stops ordered by `sequence`
+    #[serde(default)]
+    pub stops: Vec<RouteStop>,
+    /// Monthly transport fee for students on this route
+    #[serde(default)]
+    pub monthly_fee: f64,
+    pub currency: String,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+    #[serde(default)]
+    pub soft_delete: SoftDelete,
+}
+
+#[ComplexObject]
+impl BusRoute {
+    /// Returns the route's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    /// Students currently assigned to the route
+    async fn student_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let db = ctx.data::<Database>()?;
+        db.collection::<TransportAssignment>("transport_assignments")
+            .count_documents(
+                doc! {
+                    "route_id": self.id.map(|id| id.to_hex()).unwrap_or_default(),
+                    "is_active": true
+                },
+                None,
+            )
+            .await
+            .map(|n| n as i64)
+            .map_err(|e| async_graphql::Error::new(e.to_string()))
+    }
+}
+
+impl BusRoute {
+    pub fn find_stop(&self, stop_id: &str) -> Option<&RouteStop> {
+        self.stops.iter().find(|s| s.stop_id == stop_id)
+    }
+}
+
+// ============================================================================
+// STUDENT ASSIGNMENT
+// ============================================================================
+
+/// TransportAssignment - a student riding a route between their stops
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct TransportAssignment {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub student_id: String,
+    pub route_id: String,
+    /// Stop where the student boards in the morning
+    pub pickup_stop_id: String,
+    /// Stop where the student is dropped off in the afternoon
+    pub dropoff_stop_id: String,
+    /// Fee charged instead of the route's monthly fee (e.g., sibling discount)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_fee_override: Option<f64>,
+    #[graphql(skip)]
+    pub start_date: DateTime,
+    #[graphql(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<DateTime>,
+    pub is_active: bool,
+    /// Months already invoiced ("YYYY-MM")
+    #[serde(default)]
+    pub invoiced_months: Vec<String>,
+
+    #[serde(default)]
+    pub audit: AuditInfo,
+}
+
+#[ComplexObject]
+impl TransportAssignment {
+    /// Returns the assignment's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn start_date_str(&self) -> String {
+        self.start_date.try_to_rfc3339_string().unwrap_or_default()
+    }
+
+    async fn end_date_str(&self) -> Option<String> {
+        self.end_date.and_then(|d| d.try_to_rfc3339_string().ok())
+    }
+}
+
+// ============================================================================
+// BOARDING LOG
+// ============================================================================
+
+/// Which leg of the day a trip is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum TripDirection {
+    /// Morning run, home to school
+    ToSchool,
+    /// Afternoon run, school to home
+    FromSchool,
+}
+
+/// What happened to a student at a stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum BoardingEvent {
+    Boarded,
+    DroppedOff,
+    /// Expected at the stop but didn't board
+    NoShow,
+}
+
+/// BoardingLog - a student boarding or leaving a bus
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
+pub struct BoardingLog {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[graphql(skip)]
+    pub id: Option<ObjectId>,
+
+    pub school_id: String,
+    pub route_id: String,
+    pub student_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_id: Option<String>,
+    pub direction: TripDirection,
+    pub event: BoardingEvent,
+    /// Where the bus was when the event was logged
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<GpsCoordinates>,
+    #[graphql(skip)]
+    pub recorded_at: DateTime,
+    /// User who logged the event
+    pub recorded_by: String,
+    /// Number of guardians sent an SMS about the event
+    #[serde(default)]
+    pub guardians_notified: i32,
+}
+
+#[ComplexObject]
+impl BoardingLog {
+    /// Returns the log's MongoDB ObjectId as a hex string
+    async fn id_str(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.to_hex())
+    }
+
+    async fn recorded_at_str(&self) -> String {
+        self.recorded_at.try_to_rfc3339_string().unwrap_or_default()
+    }
+}
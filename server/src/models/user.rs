@@ -3,6 +3,8 @@ use mongodb::bson::{oid::ObjectId, DateTime};
 use serde::{Deserialize, Serialize};
 
 use crate::utils::common_types::{AuditInfo, Gender, SoftDelete};
+use crate::utils::identity::PHONE_PROVIDER;
+use crate::utils::sms::normalize_phone;
 
 // ============================================================================
 // SYSTEM ROLE (Platform-level)
@@ -53,6 +55,73 @@ impl Default for UserStatus {
     }
 }
 
+// ============================================================================
+// LINKED IDENTITY
+// ============================================================================
+
+/// A sign-in method linked to a user (KOOMPI, OIDC, phone OTP)
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct LinkedIdentity {
+    /// Provider name ("koompi", "oidc", "phone")
+    pub provider: String,
+    /// User ID at the provider (the phone number for phone login)
+    pub subject: String,
+    /// Email reported by the provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// When the identity was linked
+    #[graphql(skip)]
+    pub linked_at: DateTime,
+}
+
+impl LinkedIdentity {
+    pub fn new(provider: String, subject: String, email: Option<String>) -> Self {
+        Self {
+            provider,
+            subject,
+            email,
+            linked_at: DateTime::now(),
+        }
+    }
+}
+
+// ============================================================================
+// MULTI-FACTOR AUTHENTICATION
+// ============================================================================
+
+/// Wrong MFA codes in a row before MFA checks are locked
+pub const MAX_MFA_ATTEMPTS: i32 = 5;
+/// How long MFA checks stay locked after too many wrong codes
+pub const MFA_LOCKOUT_MINUTES: i64 = 15;
+
+/// TOTP multi-factor authentication settings (never exposed over GraphQL)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MfaInfo {
+    /// MFA is active and required at step-up checks
+    #[serde(default)]
+    pub enabled: bool,
+    /// Base32 TOTP secret
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Secret awaiting confirmation during enrollment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_secret: Option<String>,
+    /// SHA-256 hashes of unused recovery codes
+    #[serde(default)]
+    pub recovery_code_hashes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enrolled_at: Option<DateTime>,
+    /// Last accepted TOTP time step (prevents code replay)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_step: Option<i64>,
+    /// Wrong codes entered since the last accepted one
+    #[serde(default)]
+    pub failed_attempts: i32,
+    /// MFA checks are refused until then after too many wrong codes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked_until: Option<DateTime>,
+}
+
 // ============================================================================
 // USER MODEL
 // ============================================================================
@@ -69,8 +138,12 @@ pub struct User {
     // ========================
     // Authentication
     // ========================
-    /// KOOMPI OAuth ID (unique identifier from KOOMPI)
+    /// KOOMPI OAuth ID (unique identifier from KOOMPI, empty for users without KOOMPI)
+    #[serde(default)]
     pub kid: String,
+    /// Sign-in methods linked to this user
+    #[serde(default)]
+    pub identities: Vec<LinkedIdentity>,
     /// Username (unique)
     pub username: String,
     /// Email address
@@ -108,6 +181,9 @@ pub struct User {
     /// Account status
     #[serde(default)]
     pub status: UserStatus,
+    /// Reason given when the account was suspended or banned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_reason: Option<String>,
 
     // ========================
     // Verification
@@ -120,6 +196,13 @@ pub struct User {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub verification_token: Option<String>,
 
+    // ========================
+    // Multi-Factor Authentication
+    // ========================
+    #[graphql(skip)]
+    #[serde(default)]
+    pub mfa: MfaInfo,
+
     // ========================
     // Login Tracking
     // ========================
@@ -190,6 +273,16 @@ impl User {
         }
     }
 
+    /// Whether the user has enrolled in multi-factor authentication
+    async fn mfa_enabled(&self) -> bool {
+        self.mfa.enabled
+    }
+
+    /// Number of unused MFA recovery codes
+    async fn mfa_recovery_codes_left(&self) -> i32 {
+        self.mfa.recovery_code_hashes.len() as i32
+    }
+
     /// Returns the user's full name in Khmer
     async fn full_name_km(&self) -> Option<String> {
         match (&self.first_name_km, &self.last_name_km) {
@@ -202,11 +295,12 @@ impl User {
 }
 
 impl User {
-    /// Create a new user from KOOMPI OAuth
+    /// Create a new user (`kid` is empty unless created from KOOMPI OAuth)
     pub fn new(kid: String, username: String, email: Option<String>) -> Self {
         Self {
             id: None,
             kid,
+            identities: Vec::new(),
             username,
             email,
             first_name: None,
@@ -220,9 +314,11 @@ impl User {
             gender: None,
             system_role: SystemRole::User,
             status: UserStatus::Active,
+            status_reason: None,
             is_verified: false,
             verified_at: None,
             verification_token: None,
+            mfa: MfaInfo::default(),
             last_login: None,
             login_count: 0,
             last_ip: None,
@@ -235,6 +331,32 @@ impl User {
         }
     }
 
+    /// Check if a provider identity is linked to this user
+    pub fn has_identity(&self, provider: &str, subject: &str) -> bool {
+        self.identities
+            .iter()
+            .any(|i| i.provider == provider && i.subject == subject)
+            || (provider == "koompi" && !self.kid.is_empty() && self.kid == subject)
+    }
+
+    /// Whether a linked sign-in provider verified this email address
+    pub fn has_verified_email(&self, email: &str) -> bool {
+        let email = email.trim();
+        self.identities.iter().any(|i| {
+            i.email
+                .as_deref()
+                .is_some_and(|e| e.trim().eq_ignore_ascii_case(email))
+        })
+    }
+
+    /// Whether this phone number was proven with a login code
+    pub fn has_verified_phone(&self, phone: &str) -> bool {
+        let phone = normalize_phone(phone);
+        self.identities
+            .iter()
+            .any(|i| i.provider == PHONE_PROVIDER && normalize_phone(&i.subject) == phone)
+    }
+
     /// Record a login
     pub fn record_login(&mut self, ip: Option<String>) {
         self.last_login = Some(DateTime::now());
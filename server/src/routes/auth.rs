@@ -1,6 +1,5 @@
 use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
-use std::env;
 
 #[derive(Deserialize, Debug, Serialize)]
 pub struct CallbackInput {
@@ -8,15 +7,6 @@ pub struct CallbackInput {
     pub state: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct TokenResponse {
-    access_token: String,
-    refresh_token: Option<String>,
-    token_type: String,
-    expires_in: Option<i64>,
-    scope: Option<String>,
-}
-
 #[derive(Serialize)]
 struct UserProfile {
     id: String,
@@ -29,229 +19,290 @@ struct UserProfile {
 // AuthResponse is now handled inline with serde_json::json!
 
 use crate::{
+    graphql::graphql_context::load_custom_role_permissions,
+    models::login_code::{LoginCode, MAX_LOGIN_CODE_ATTEMPTS},
+    models::login_history::LoginHistory,
     models::member::Member,
-    models::user::{SystemRole, User},
-    utils::jwt_token::sign_token,
+    models::session::Session,
+    models::user::{LinkedIdentity, SystemRole, User, UserStatus},
+    utils::codes::{hash_token, random_digits, random_token},
+    utils::identity::{provider_by_name, ExternalIdentity, IdentityProvider, PHONE_PROVIDER},
+    utils::jwt_token::{sign_token, verify_token, ACCESS_TOKEN_MINUTES},
+    utils::sms::{normalize_phone, send_sms},
+};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    Collection, Database,
 };
-use mongodb::{bson::doc, Collection, Database};
 
-#[derive(Serialize, Deserialize, Debug)]
-struct KoompiUser {
-    #[serde(rename = "_id")]
-    id: String,
-    fullname: String,
-    first_name: Option<String>,
-    last_name: Option<String>,
-    username: String,
-    #[serde(rename = "profile")]
-    picture: Option<String>,
-    email: Option<String>,
-    wallet_address: Option<String>,
-    telegram_id: Option<i64>,
+#[derive(Deserialize, Debug)]
+pub struct RefreshInput {
+    pub refresh_token: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct UserInfoResponse {
-    user: KoompiUser,
-    status: String,
-    scopes: Option<Vec<String>>,
+#[derive(Deserialize, Debug)]
+pub struct SwitchSchoolInput {
+    /// School to make active, or none to clear the active school
+    pub school_id: Option<String>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct OtpRequestInput {
+    pub phone: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OtpVerifyInput {
+    pub phone: String,
+    pub code: String,
+}
+
+/// Login code lifetime in minutes
+const LOGIN_CODE_MINUTES: i64 = 5;
+
+/// KOOMPI OAuth callback (kept for existing clients; same as `/auth/koompi/callback`)
 #[post("/auth/callback")]
 pub async fn auth_callback(
+    req: HttpRequest,
     input: web::Json<CallbackInput>,
     db: web::Data<Database>,
 ) -> impl Responder {
-    let client_id = env::var("KOOMPI_CLIENT_ID").unwrap_or_default();
-    let client_secret = env::var("KOOMPI_CLIENT_SECRET").unwrap_or_default();
-    let redirect_uri = env::var("KOOMPI_REDIRECT_URI").unwrap_or_default();
+    login_with_provider(&req, &db, "koompi", &input).await
+}
 
-    if client_id.is_empty() || client_secret.is_empty() {
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Server misconfiguration: Client ID or Secret missing"
-        }));
+/// OAuth callback for any configured identity provider ("koompi", "oidc").
+/// With a valid Bearer token the identity is linked to the signed-in user instead.
+#[post("/auth/{provider}/callback")]
+pub async fn provider_callback(
+    req: HttpRequest,
+    path: web::Path<String>,
+    input: web::Json<CallbackInput>,
+    db: web::Data<Database>,
+) -> impl Responder {
+    login_with_provider(&req, &db, &path.into_inner(), &input).await
+}
+
+/// Exchange an authorization code with the named provider and sign in
+async fn login_with_provider(
+    req: &HttpRequest,
+    db: &Database,
+    provider_name: &str,
+    input: &CallbackInput,
+) -> HttpResponse {
+    match provider_by_name(provider_name) {
+        Ok(provider) => login_with(req, db, provider.as_ref(), input).await,
+        Err(err) => HttpResponse::BadRequest().json(serde_json::json!({ "error": err })),
+    }
+}
+
+/// Exchange an authorization code with a provider and sign in
+async fn login_with(
+    req: &HttpRequest,
+    db: &Database,
+    provider: &dyn IdentityProvider,
+    input: &CallbackInput,
+) -> HttpResponse {
+    match provider
+        .exchange_code(&input.code, input.state.as_deref())
+        .await
+    {
+        Ok(identity) => complete_login(req, db, identity).await,
+        Err(err) => HttpResponse::BadRequest().json(serde_json::json!({ "error": err })),
     }
+}
 
-    let client = reqwest::Client::new();
+/// Send a one-time login code to a phone number
+#[post("/auth/otp/request")]
+pub async fn request_login_code(
+    input: web::Json<OtpRequestInput>,
+    db: web::Data<Database>,
+) -> impl Responder {
+    let phone = normalize_phone(&input.phone);
+    if phone.len() < 8 {
+        return HttpResponse::BadRequest()
+            .json(serde_json::json!({ "error": "Invalid phone number" }));
+    }
 
-    // 1. Exchange code for token
-    let token_params = serde_json::json!({
-        "grant_type": "authorization_code",
-        "code": input.code,
-        "client_id": client_id,
-        "client_secret": client_secret,
-        "redirect_uri": redirect_uri,
-        "state": input.state,
-    });
+    let codes_collection: Collection<LoginCode> = db.collection("login_codes");
 
-    let token_res = match client
-        .post("https://oauth.koompi.org/v1/oauth/token")
-        .json(&token_params)
-        .send()
+    // One code per minute per phone number
+    let cooldown_start = DateTime::from_millis(DateTime::now().timestamp_millis() - 60 * 1000);
+    match codes_collection
+        .count_documents(
+            doc! { "phone": &phone, "audit.created_at": { "$gt": cooldown_start } },
+            None,
+        )
         .await
     {
-        Ok(res) => res,
-        Err(err) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to connect to auth server: {}", err)
-            }));
+        Ok(0) => {}
+        Ok(_) => {
+            return HttpResponse::TooManyRequests().json(serde_json::json!({
+                "error": "A code was sent recently. Please wait a minute before requesting another."
+            }))
         }
-    };
-
-    // Check response status and get raw body for debugging
-    let status = token_res.status();
-    let raw_body = match token_res.text().await {
-        Ok(body) => body,
-        Err(err) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to read token response body: {}", err)
-            }));
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": "Database error" }))
         }
-    };
+    }
 
-    // If not successful, return the error from OAuth server
-    if !status.is_success() {
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": format!("OAuth token exchange failed: {}", raw_body)
-        }));
+    let code = random_digits(6);
+    let expires_at =
+        DateTime::from_millis(DateTime::now().timestamp_millis() + LOGIN_CODE_MINUTES * 60 * 1000);
+    let login_code = LoginCode::new(phone.clone(), hash_token(&code), expires_at);
+    if codes_collection
+        .insert_one(&login_code, None)
+        .await
+        .is_err()
+    {
+        return HttpResponse::InternalServerError()
+            .json(serde_json::json!({ "error": "Database error" }));
     }
 
-    let token_data: TokenResponse = match serde_json::from_str(&raw_body) {
-        Ok(data) => data,
-        Err(err) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to parse token response: {}. Raw: {}", err, raw_body)
-            }));
-        }
-    };
+    let message = format!(
+        "Your login code is {}. It expires in {} minutes.",
+        code, LOGIN_CODE_MINUTES
+    );
+    match send_sms(&phone, &message).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "expires_in": LOGIN_CODE_MINUTES * 60
+        })),
+        Err(err) => HttpResponse::BadGateway().json(serde_json::json!({ "error": err })),
+    }
+}
 
-    // Reuse existing client
-    let mut headers = reqwest::header::HeaderMap::new();
-    let auth_header_val = match format!("Bearer {}", token_data.access_token).parse() {
-        Ok(v) => v,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Invalid token format"
-            }))
-        }
-    };
-    headers.insert("Authorization", auth_header_val);
+/// Sign in (or link the phone to the signed-in user) with a code from `/auth/otp/request`
+#[post("/auth/otp/verify")]
+pub async fn verify_login_code(
+    req: HttpRequest,
+    input: web::Json<OtpVerifyInput>,
+    db: web::Data<Database>,
+) -> impl Responder {
+    let phone = normalize_phone(&input.phone);
+    let codes_collection: Collection<LoginCode> = db.collection("login_codes");
 
-    let response = match client
-        .get("https://oauth.koompi.org/v1/oauth/userinfo")
-        .headers(headers)
-        .send()
+    let options = mongodb::options::FindOneOptions::builder()
+        .sort(doc! { "audit.created_at": -1 })
+        .build();
+    let login_code = match codes_collection
+        .find_one(doc! { "phone": &phone }, options)
         .await
     {
-        Ok(res) => res,
-        Err(err) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to fetch user info: {}", err)
+        Ok(Some(c)) if c.is_open() => c,
+        Ok(_) => {
+            return HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "This login code is no longer valid. Please request a new one."
             }))
         }
-    };
-
-    let user_info_res: UserInfoResponse = match response.json().await {
-        Ok(data) => data,
-        Err(err) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to parse user info: {}", err)
-            }))
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": "Database error" }))
         }
     };
 
-    let koompi_user = user_info_res.user;
+    if hash_token(input.code.trim()) != login_code.code_hash {
+        let _ = codes_collection
+            .update_one(
+                doc! { "_id": login_code.id },
+                doc! { "$inc": { "attempts": 1 } },
+                None,
+            )
+            .await;
+        let remaining = MAX_LOGIN_CODE_ATTEMPTS - login_code.attempts - 1;
+        return HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": format!("Incorrect code. {} attempt(s) remaining.", remaining.max(0))
+        }));
+    }
 
-    // Fallback email since Koompi might not provide it with basic scope
-    let user_email = koompi_user
-        .email
-        .clone()
-        .unwrap_or_else(|| format!("{}@koompi.org", koompi_user.username));
+    // Consume the code; a concurrent verify with the same code loses here
+    match codes_collection
+        .update_one(
+            doc! { "_id": login_code.id, "consumed_at": null },
+            doc! { "$set": { "consumed_at": DateTime::now() } },
+            None,
+        )
+        .await
+    {
+        Ok(result) if result.modified_count == 1 => {}
+        _ => {
+            return HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "This login code is no longer valid. Please request a new one."
+            }))
+        }
+    }
 
-    // 3. Find or Create User in MongoDB
-    let users_collection: Collection<User> = db.collection("users");
+    let identity = ExternalIdentity {
+        provider: PHONE_PROVIDER.to_string(),
+        subject: phone.clone(),
+        phone: Some(phone),
+        ..Default::default()
+    };
+    complete_login(&req, &db, identity).await
+}
 
-    // Check if this is the first user in the system
-    let user_count = users_collection
-        .count_documents(None, None)
-        .await
-        .unwrap_or(0);
+/// Sign in with a provider identity: find (or create) its user, start a session
+/// and return tokens. With a valid Bearer token, the identity is linked to that user.
+async fn complete_login(
+    req: &HttpRequest,
+    db: &Database,
+    identity: ExternalIdentity,
+) -> HttpResponse {
+    let linking_user_id = bearer_token(req)
+        .and_then(|token| verify_token(token).ok())
+        .map(|claims| claims.sub);
 
-    let is_first_user = user_count == 0;
-    println!(
-        "User count: {}, is_first_user: {}",
-        user_count, is_first_user
-    );
+    let user_record = match linking_user_id {
+        Some(user_id) => link_identity(db, &user_id, &identity).await,
+        None => find_or_create_user(db, &identity).await,
+    };
+    let mut user_record = match user_record {
+        Ok(user) => user,
+        Err(err) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": err })),
+    };
 
-    // Check if user exists by Koompi ID
-    println!("Looking for user with kid: {}", &koompi_user.id);
-    let existing_user = users_collection
-        .find_one(doc! { "kid": &koompi_user.id }, None)
-        .await;
+    let user_id_str = user_record.id.map(|oid| oid.to_hex()).unwrap_or_default();
+    let (device, ip) = client_info(req);
 
-    let user_record =
-        match existing_user {
-            Ok(Some(user)) => {
-                println!("Found existing user: {:?}", user.id);
-                user
-            }
-            Ok(None) => {
-                println!("User not found, creating new user...");
-                // Create new user - first user gets SuperAdmin, others get User role
-                let mut new_user = User::new(
-                    koompi_user.id.clone(),
-                    koompi_user.username.clone(),
-                    Some(user_email.clone()),
-                );
-
-                // Assign SuperAdmin to first user
-                if is_first_user {
-                    new_user.system_role = SystemRole::SuperAdmin;
-                    println!("Assigning SuperAdmin role to first user");
-                }
+    // Refuse suspended, banned or inactive accounts
+    if let Some(refusal) = login_refusal(&user_record) {
+        let history = LoginHistory::new(
+            user_id_str,
+            identity.provider.clone(),
+            ip,
+            device,
+            Some(format!("{:?}", user_record.status)),
+        );
+        record_login_history(db, &history).await;
+        return refusal;
+    }
 
-                // Set profile info from Koompi
-                new_user.first_name = koompi_user.first_name.clone();
-                new_user.last_name = koompi_user.last_name.clone();
-                new_user.avatar_url = koompi_user.picture.clone();
-
-                println!("Inserting new user: {:?}", new_user.username);
-                match users_collection.insert_one(&new_user, None).await {
-                    Ok(insert_result) => {
-                        println!("User inserted with id: {:?}", insert_result.inserted_id);
-                        // Fetch the created user to get the ID
-                        match users_collection
-                            .find_one(doc! { "_id": insert_result.inserted_id }, None)
-                            .await
-                        {
-                            Ok(Some(u)) => {
-                                println!("Successfully retrieved created user");
-                                u
-                            }
-                            _ => return HttpResponse::InternalServerError().json(
-                                serde_json::json!({ "error": "Failed to retrieve created user" }),
-                            ),
-                        }
-                    }
-                    Err(err) => {
-                        println!("Failed to insert user: {}", err);
-                        return HttpResponse::InternalServerError().json(serde_json::json!({
-                            "error": format!("Failed to create user: {}", err)
-                        }));
-                    }
+    // Record the login on the user and in the login history
+    user_record.record_login(ip.clone());
+    let _ = db
+        .collection::<User>("users")
+        .update_one(
+            doc! { "_id": user_record.id },
+            doc! {
+                "$set": {
+                    "last_login": user_record.last_login,
+                    "login_count": user_record.login_count,
+                    "last_ip": user_record.last_ip.as_deref(),
                 }
-            }
-            Err(err) => {
-                println!("Database error when finding user: {}", err);
-                return HttpResponse::InternalServerError().json(serde_json::json!({
-                    "error": format!("Database error: {}", err)
-                }));
-            }
-        };
-
-    let user_id_str = user_record.id.map(|oid| oid.to_hex()).unwrap_or_default();
+            },
+            None,
+        )
+        .await;
+    let history = LoginHistory::new(
+        user_id_str.clone(),
+        identity.provider.clone(),
+        ip,
+        device,
+        None,
+    );
+    record_login_history(db, &history).await;
 
-    // 4. Check if user has any memberships (for frontend routing)
+    // Check if user has any memberships (for frontend routing)
     let members_collection: Collection<Member> = db.collection("members");
     let user_memberships = members_collection
         .count_documents(
@@ -276,21 +327,572 @@ pub async fn auth_callback(
         id: user_id_str.clone(),
         email: user_record.email.clone(),
         name: user_record.username.clone(),
-        picture: koompi_user.picture,
+        picture: identity.picture.or(user_record.avatar_url.clone()),
         role: format!("{:?}", user_record.system_role),
     };
 
-    // Use the actual user role from the database
-    let token = sign_token(user_id_str, user_record.system_role).unwrap();
+    // Start a session and issue tokens using the actual user role from the database
+    let mut session = Session::new(user_id_str, String::new(), None, None);
+    session.provider_refresh_token = identity.refresh_token;
+    let (access_token, refresh_token) =
+        match start_session(db, req, session, user_record.system_role).await {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                return HttpResponse::InternalServerError()
+                    .json(serde_json::json!({ "error": err }))
+            }
+        };
 
     HttpResponse::Ok().json(serde_json::json!({
-        "access_token": token,
+        "access_token": access_token,
+        "refresh_token": refresh_token,
         "token_type": "Bearer",
+        "expires_in": ACCESS_TOKEN_MINUTES * 60,
         "user": user_profile,
         "has_membership": user_memberships > 0
     }))
 }
 
+/// Filter matching the user that owns a provider identity.
+/// KOOMPI users created before linked identities existed are matched by `kid`.
+fn identity_filter(identity: &ExternalIdentity) -> mongodb::bson::Document {
+    let mut conditions = vec![doc! {
+        "identities": {
+            "$elemMatch": { "provider": &identity.provider, "subject": &identity.subject }
+        }
+    }];
+    if identity.provider == "koompi" {
+        conditions.push(doc! { "kid": &identity.subject });
+    }
+    doc! { "$or": conditions }
+}
+
+/// Find the user owning an identity, creating a new user on first sign-in
+async fn find_or_create_user(db: &Database, identity: &ExternalIdentity) -> Result<User, String> {
+    let users_collection: Collection<User> = db.collection("users");
+
+    let existing = users_collection
+        .find_one(identity_filter(identity), None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    if let Some(user) = existing {
+        // Backfill the linked identity for users matched by `kid`
+        if !user
+            .identities
+            .iter()
+            .any(|i| i.provider == identity.provider && i.subject == identity.subject)
+        {
+            push_identity(db, &user, identity).await?;
+        }
+        return Ok(user);
+    }
+
+    // Check if this is the first user in the system
+    let is_first_user = users_collection
+        .count_documents(None, None)
+        .await
+        .unwrap_or(0)
+        == 0;
+
+    let mut new_user = new_user(identity, is_first_user);
+    let insert_result = users_collection
+        .insert_one(&new_user, None)
+        .await
+        .map_err(|e| format!("Failed to create user: {}", e))?;
+    new_user.id = insert_result.inserted_id.as_object_id();
+    println!("User created with id: {:?}", new_user.id);
+
+    Ok(new_user)
+}
+
+/// A new user for an identity signing in for the first time
+fn new_user(identity: &ExternalIdentity, is_first_user: bool) -> User {
+    let username = identity
+        .username
+        .clone()
+        .or_else(|| {
+            identity
+                .email
+                .as_ref()
+                .and_then(|e| e.split('@').next().map(|s| s.to_string()))
+        })
+        .or_else(|| identity.phone.clone())
+        .unwrap_or_else(|| format!("user-{}", &random_token()[..8]));
+    let kid = if identity.provider == "koompi" {
+        identity.subject.clone()
+    } else {
+        String::new()
+    };
+
+    // Create new user - first user gets SuperAdmin, others get User role
+    let mut new_user = User::new(kid, username, identity.email.clone());
+    if is_first_user {
+        new_user.system_role = SystemRole::SuperAdmin;
+        println!("Assigning SuperAdmin role to first user");
+    }
+
+    // Set profile info from the provider
+    new_user.first_name = identity.first_name.clone();
+    new_user.last_name = identity.last_name.clone();
+    new_user.avatar_url = identity.picture.clone();
+    new_user.phone = identity.phone.clone();
+    if identity.provider == PHONE_PROVIDER {
+        // Phone ownership was just proven with the code
+        new_user.is_verified = true;
+        new_user.verified_at = Some(DateTime::now());
+    }
+    new_user.identities.push(LinkedIdentity::new(
+        identity.provider.clone(),
+        identity.subject.clone(),
+        identity.email.clone(),
+    ));
+
+    new_user
+}
+
+/// Link an identity to the signed-in user, unless another user already owns it
+async fn link_identity(
+    db: &Database,
+    user_id: &str,
+    identity: &ExternalIdentity,
+) -> Result<User, String> {
+    let users_collection: Collection<User> = db.collection("users");
+    let user_oid = ObjectId::parse_str(user_id).map_err(|_| "Invalid user ID in token")?;
+
+    let user = users_collection
+        .find_one(doc! { "_id": user_oid }, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or("User not found")?;
+
+    if user.has_identity(&identity.provider, &identity.subject) {
+        return Ok(user);
+    }
+
+    let owner = users_collection
+        .find_one(identity_filter(identity), None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    if owner.is_some() {
+        return Err("This sign-in method is already linked to another account".to_string());
+    }
+
+    push_identity(db, &user, identity).await?;
+
+    users_collection
+        .find_one(doc! { "_id": user_oid }, None)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "User not found".to_string())
+}
+
+/// Append a linked identity to a user
+async fn push_identity(
+    db: &Database,
+    user: &User,
+    identity: &ExternalIdentity,
+) -> Result<(), String> {
+    let linked = LinkedIdentity::new(
+        identity.provider.clone(),
+        identity.subject.clone(),
+        identity.email.clone(),
+    );
+    let linked = mongodb::bson::to_bson(&linked).map_err(|e| e.to_string())?;
+
+    let mut set = doc! { "audit.updated_at": DateTime::now() };
+    // Keep `kid` populated for KOOMPI so existing lookups keep working
+    if identity.provider == "koompi" && user.kid.is_empty() {
+        set.insert("kid", &identity.subject);
+    }
+
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$push": { "identities": linked }, "$set": set },
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to link identity: {}", e))?;
+
+    Ok(())
+}
+
+/// Exchange a refresh token for a new access token and a new refresh token.
+/// The old refresh token stops working; presenting it again revokes the session.
+#[post("/auth/refresh")]
+pub async fn refresh(
+    req: HttpRequest,
+    input: web::Json<RefreshInput>,
+    db: web::Data<Database>,
+) -> impl Responder {
+    let sessions_collection: Collection<Session> = db.collection("sessions");
+    let token_hash = hash_token(&input.refresh_token);
+
+    let session = match sessions_collection
+        .find_one(doc! { "refresh_token_hash": &token_hash }, None)
+        .await
+    {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            // A rotated-out token being replayed means it leaked: end that session
+            let _ = sessions_collection
+                .update_one(
+                    doc! { "previous_token_hash": &token_hash, "revoked_at": null },
+                    doc! { "$set": { "revoked_at": DateTime::now() } },
+                    None,
+                )
+                .await;
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "error": "Invalid refresh token" }));
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": "Database error" }))
+        }
+    };
+
+    if !session.is_active() {
+        return HttpResponse::Unauthorized()
+            .json(serde_json::json!({ "error": "Session expired or revoked" }));
+    }
+
+    let users_collection: Collection<User> = db.collection("users");
+    let user_oid = match ObjectId::parse_str(&session.user_id) {
+        Ok(oid) => oid,
+        Err(_) => {
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "error": "Invalid user ID in session" }))
+        }
+    };
+    let user_record = match users_collection
+        .find_one(doc! { "_id": user_oid }, None)
+        .await
+    {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "error": "User not found" }))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": "Database error" }))
+        }
+    };
+
+    // A blocked account loses its sessions
+    if let Some(refusal) = login_refusal(&user_record) {
+        let _ = sessions_collection
+            .update_one(
+                doc! { "_id": session.id },
+                doc! { "$set": { "revoked_at": DateTime::now() } },
+                None,
+            )
+            .await;
+        let (device, ip) = client_info(&req);
+        let history = LoginHistory::new(
+            session.user_id.clone(),
+            "refresh".to_string(),
+            ip,
+            device,
+            Some(format!("{:?}", user_record.status)),
+        );
+        record_login_history(&db, &history).await;
+        return refusal;
+    }
+
+    // Rotate the refresh token
+    let new_refresh_token = random_token();
+    let (device, ip) = client_info(&req);
+    let now = DateTime::now();
+    let update = sessions_collection
+        .update_one(
+            doc! { "_id": session.id, "refresh_token_hash": &token_hash },
+            doc! {
+                "$set": {
+                    "refresh_token_hash": hash_token(&new_refresh_token),
+                    "previous_token_hash": &token_hash,
+                    "device": device,
+                    "ip": ip,
+                    "last_used_at": now,
+                    "expires_at": Session::expiry_from_now(),
+                    "audit.updated_at": now,
+                }
+            },
+            None,
+        )
+        .await;
+    match update {
+        // Lost a race with a concurrent refresh using the same token
+        Ok(result) if result.modified_count == 0 => {
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "error": "Invalid refresh token" }))
+        }
+        Ok(_) => {}
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": "Failed to rotate refresh token" }))
+        }
+    }
+
+    let session_id = session.id.map(|oid| oid.to_hex());
+    let access_token = match sign_token(
+        session.user_id,
+        user_record.system_role,
+        session_id,
+        session.active_school_id,
+    ) {
+        Ok(token) => token,
+        Err(err) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to sign token: {}", err)
+            }))
+        }
+    };
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "access_token": access_token,
+        "refresh_token": new_refresh_token,
+        "token_type": "Bearer",
+        "expires_in": ACCESS_TOKEN_MINUTES * 60
+    }))
+}
+
+/// Select the active school for the current session and issue an access token
+/// scoped to it. Later refreshes keep the selection.
+#[post("/auth/switch-school")]
+pub async fn switch_school(
+    req: HttpRequest,
+    input: web::Json<SwitchSchoolInput>,
+    db: web::Data<Database>,
+) -> impl Responder {
+    let claims = match bearer_token(&req).map(verify_token) {
+        Some(Ok(c)) => c,
+        _ => {
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "error": "Invalid or expired token" }))
+        }
+    };
+    let Some(session_oid) = claims
+        .sid
+        .as_deref()
+        .and_then(|id| ObjectId::parse_str(id).ok())
+    else {
+        return HttpResponse::Unauthorized()
+            .json(serde_json::json!({ "error": "Please sign in again to continue" }));
+    };
+
+    // The user must be an active member of the school being selected
+    let mut member = None;
+    if let Some(ref school_id) = input.school_id {
+        let members_collection: Collection<Member> = db.collection("members");
+        member = match members_collection
+            .find_one(
+                doc! {
+                    "user_id": &claims.sub,
+                    "school_id": school_id,
+                    "status": "Active",
+                    "soft_delete.is_deleted": false
+                },
+                None,
+            )
+            .await
+        {
+            Ok(Some(mut m)) => {
+                if load_custom_role_permissions(&db, &mut m).await.is_err() {
+                    return HttpResponse::InternalServerError()
+                        .json(serde_json::json!({ "error": "Database error" }));
+                }
+                Some(m)
+            }
+            Ok(None) => {
+                return HttpResponse::Forbidden()
+                    .json(serde_json::json!({ "error": "You are not a member of this school" }))
+            }
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .json(serde_json::json!({ "error": "Database error" }))
+            }
+        };
+    }
+
+    let sessions_collection: Collection<Session> = db.collection("sessions");
+    match sessions_collection
+        .update_one(
+            doc! {
+                "_id": session_oid,
+                "user_id": &claims.sub,
+                "revoked_at": null,
+                "expires_at": { "$gt": DateTime::now() }
+            },
+            doc! { "$set": { "active_school_id": input.school_id.as_deref() } },
+            None,
+        )
+        .await
+    {
+        Ok(result) if result.matched_count == 1 => {}
+        Ok(_) => {
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "error": "Session expired or revoked" }))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": "Database error" }))
+        }
+    }
+
+    let access_token =
+        match sign_token(claims.sub, claims.role, claims.sid, input.school_id.clone()) {
+            Ok(token) => token,
+            Err(err) => {
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": format!("Failed to sign token: {}", err)
+                }))
+            }
+        };
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "access_token": access_token,
+        "token_type": "Bearer",
+        "expires_in": ACCESS_TOKEN_MINUTES * 60,
+        "school_id": input.school_id,
+        "role": member.as_ref().map(|m| format!("{:?}", m.role)),
+        "permissions": member
+            .as_ref()
+            .map(|m| m.effective_permissions())
+            .unwrap_or_default()
+    }))
+}
+
+/// End the session that owns the given refresh token
+#[post("/auth/logout")]
+pub async fn logout(input: web::Json<RefreshInput>, db: web::Data<Database>) -> impl Responder {
+    let sessions_collection: Collection<Session> = db.collection("sessions");
+
+    match sessions_collection
+        .update_one(
+            doc! { "refresh_token_hash": hash_token(&input.refresh_token), "revoked_at": null },
+            doc! { "$set": { "revoked_at": DateTime::now() } },
+            None,
+        )
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "success": true })),
+        Err(_) => HttpResponse::InternalServerError()
+            .json(serde_json::json!({ "error": "Database error" })),
+    }
+}
+
+/// End every session of the signed-in user
+#[post("/auth/logout-all")]
+pub async fn logout_all(req: HttpRequest, db: web::Data<Database>) -> impl Responder {
+    let claims = match bearer_token(&req).map(verify_token) {
+        Some(Ok(c)) => c,
+        _ => {
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "error": "Invalid or expired token" }))
+        }
+    };
+
+    let sessions_collection: Collection<Session> = db.collection("sessions");
+    match sessions_collection
+        .update_many(
+            doc! { "user_id": &claims.sub, "revoked_at": null },
+            doc! { "$set": { "revoked_at": DateTime::now() } },
+            None,
+        )
+        .await
+    {
+        Ok(result) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "revoked": result.modified_count
+        })),
+        Err(_) => HttpResponse::InternalServerError()
+            .json(serde_json::json!({ "error": "Database error" })),
+    }
+}
+
+/// Error response for a user who may not sign in, if any
+fn login_refusal(user: &User) -> Option<HttpResponse> {
+    if user.can_login() {
+        return None;
+    }
+
+    let message = match user.status {
+        _ if user.soft_delete.is_deleted => "This account has been deleted",
+        UserStatus::Suspended => "Your account has been suspended",
+        UserStatus::Banned => "Your account has been banned",
+        UserStatus::Inactive => "Your account is inactive",
+        UserStatus::PendingVerification => "Your account is awaiting verification",
+        UserStatus::Active => "You cannot sign in at this time",
+    };
+    Some(HttpResponse::Forbidden().json(serde_json::json!({
+        "error": message,
+        "status": format!("{:?}", user.status),
+        "reason": user.status_reason
+    })))
+}
+
+/// Append a sign-in attempt to the login history (best effort)
+async fn record_login_history(db: &Database, history: &LoginHistory) {
+    if let Err(err) = db
+        .collection::<LoginHistory>("login_history")
+        .insert_one(history, None)
+        .await
+    {
+        println!("Failed to record login history: {}", err);
+    }
+}
+
+/// Save a new session and issue its access and refresh tokens
+async fn start_session(
+    db: &Database,
+    req: &HttpRequest,
+    mut session: Session,
+    role: SystemRole,
+) -> Result<(String, String), String> {
+    let refresh_token = random_token();
+    let (device, ip) = client_info(req);
+    session.refresh_token_hash = hash_token(&refresh_token);
+    session.device = device;
+    session.ip = ip;
+
+    let result = db
+        .collection::<Session>("sessions")
+        .insert_one(&session, None)
+        .await
+        .map_err(|e| format!("Failed to create session: {}", e))?;
+    let session_id = result.inserted_id.as_object_id().map(|oid| oid.to_hex());
+
+    let access_token = sign_token(session.user_id, role, session_id, None)
+        .map_err(|e| format!("Failed to sign token: {}", e))?;
+
+    Ok((access_token, refresh_token))
+}
+
+/// Device (user agent) and IP address of the caller
+fn client_info(req: &HttpRequest) -> (Option<String>, Option<String>) {
+    let device = req
+        .headers()
+        .get("User-Agent")
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string());
+    let ip = req
+        .connection_info()
+        .realip_remote_addr()
+        .map(|s| s.to_string());
+    (device, ip)
+}
+
+/// Bearer token from the Authorization header
+fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get("Authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
 #[get("/auth/me")]
 pub async fn get_me(req: HttpRequest, db: web::Data<Database>) -> impl Responder {
     let auth_header = match req.headers().get("Authorization") {
@@ -308,8 +910,6 @@ pub async fn get_me(req: HttpRequest, db: web::Data<Database>) -> impl Responder
             .json(serde_json::json!({ "error": "Invalid token type" }));
     };
 
-    use crate::utils::jwt_token::verify_token;
-
     let claims = match verify_token(token) {
         Ok(c) => c,
         Err(_) => {
@@ -348,6 +948,10 @@ pub async fn get_me(req: HttpRequest, db: web::Data<Database>) -> impl Responder
         }
     };
 
+    if let Some(refusal) = login_refusal(&user_record) {
+        return refusal;
+    }
+
     let user_profile = UserProfile {
         id: user_record.id.map(|oid| oid.to_hex()).unwrap_or_default(),
         email: user_record.email,
@@ -358,3 +962,253 @@ pub async fn get_me(req: HttpRequest, db: web::Data<Database>) -> impl Responder
 
     HttpResponse::Ok().json(user_profile)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::to_bytes, http::StatusCode, test::TestRequest};
+    use async_trait::async_trait;
+    use mongodb::{options::ClientOptions, options::ServerAddress, Client};
+
+    /// Authorization code the stub provider accepts
+    const GOOD_CODE: &str = "good-code";
+
+    /// Identity provider that hands out a fixed identity for `GOOD_CODE`
+    struct StubProvider {
+        identity: ExternalIdentity,
+    }
+
+    #[async_trait]
+    impl IdentityProvider for StubProvider {
+        async fn exchange_code(
+            &self,
+            code: &str,
+            _state: Option<&str>,
+        ) -> Result<ExternalIdentity, String> {
+            if code == GOOD_CODE {
+                Ok(self.identity.clone())
+            } else {
+                Err("Invalid authorization code".to_string())
+            }
+        }
+    }
+
+    fn identity(provider: &str, subject: &str) -> ExternalIdentity {
+        ExternalIdentity {
+            provider: provider.to_string(),
+            subject: subject.to_string(),
+            email: Some(format!("{}@example.com", subject)),
+            ..Default::default()
+        }
+    }
+
+    fn stub(provider: &str, subject: &str) -> StubProvider {
+        StubProvider {
+            identity: identity(provider, subject),
+        }
+    }
+
+    fn callback(code: &str) -> CallbackInput {
+        CallbackInput {
+            code: code.to_string(),
+            state: None,
+        }
+    }
+
+    async fn body_json(response: HttpResponse) -> serde_json::Value {
+        let bytes = to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    /// Handle to a database that is never reached
+    fn offline_db() -> Database {
+        let options = ClientOptions::builder()
+            .hosts(vec![ServerAddress::parse("localhost:27017").unwrap()])
+            .build();
+        Client::with_options(options).unwrap().database("sms_test")
+    }
+
+    /// A fresh database on the server at `MONGODB_TEST_URI`
+    async fn test_db() -> Database {
+        std::env::set_var("JWT_SECRET", "test-secret");
+        let uri = std::env::var("MONGODB_TEST_URI").expect("MONGODB_TEST_URI must be set");
+        let client = Client::with_uri_str(&uri).await.unwrap();
+        client.database(&format!("sms_test_{}", ObjectId::new().to_hex()))
+    }
+
+    /// Run the callback with a stub provider, optionally as a signed-in user
+    async fn sign_in(
+        db: &Database,
+        provider: &StubProvider,
+        access_token: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = TestRequest::post();
+        if let Some(token) = access_token {
+            request = request.insert_header(("Authorization", format!("Bearer {}", token)));
+        }
+        let response = login_with(
+            &request.to_http_request(),
+            db,
+            provider,
+            &callback(GOOD_CODE),
+        )
+        .await;
+        let status = response.status();
+        (status, body_json(response).await)
+    }
+
+    async fn find_user(db: &Database, user_id: &str) -> User {
+        db.collection::<User>("users")
+            .find_one(doc! { "_id": ObjectId::parse_str(user_id).unwrap() }, None)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn callback_rejects_a_failed_code_exchange() {
+        let request = TestRequest::post().to_http_request();
+        let provider = stub("oidc", "alice");
+
+        let response = login_with(&request, &offline_db(), &provider, &callback("stolen")).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body_json(response).await["error"],
+            "Invalid authorization code"
+        );
+    }
+
+    #[actix_web::test]
+    async fn callback_rejects_an_unknown_provider() {
+        let request = TestRequest::post().to_http_request();
+
+        let response =
+            login_with_provider(&request, &offline_db(), "myspace", &callback(GOOD_CODE)).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body_json(response).await["error"],
+            "Unknown identity provider: myspace"
+        );
+    }
+
+    #[test]
+    fn first_koompi_user_keeps_kid_and_becomes_super_admin() {
+        let user = new_user(&identity("koompi", "k-1"), true);
+
+        assert_eq!(user.kid, "k-1");
+        assert_eq!(user.username, "k-1");
+        assert_eq!(user.system_role, SystemRole::SuperAdmin);
+        assert!(user.has_identity("koompi", "k-1"));
+        assert!(!user.is_verified);
+    }
+
+    #[test]
+    fn later_users_get_the_default_role_and_no_kid() {
+        let user = new_user(&identity("oidc", "o-1"), false);
+
+        assert_eq!(user.kid, "");
+        assert_ne!(user.system_role, SystemRole::SuperAdmin);
+        assert!(user.has_identity("oidc", "o-1"));
+        assert_eq!(user.identities[0].email.as_deref(), Some("o-1@example.com"));
+    }
+
+    #[test]
+    fn phone_users_are_verified_and_named_after_their_number() {
+        let identity = ExternalIdentity {
+            provider: PHONE_PROVIDER.to_string(),
+            subject: "+85512345678".to_string(),
+            phone: Some("+85512345678".to_string()),
+            ..Default::default()
+        };
+
+        let user = new_user(&identity, false);
+
+        assert_eq!(user.username, "+85512345678");
+        assert!(user.is_verified);
+        assert!(user.has_identity(PHONE_PROVIDER, "+85512345678"));
+    }
+
+    #[test]
+    fn identity_filter_matches_legacy_koompi_users_by_kid() {
+        let koompi = identity_filter(&identity("koompi", "k-1"));
+        let oidc = identity_filter(&identity("oidc", "k-1"));
+
+        assert_eq!(koompi.get_array("$or").unwrap().len(), 2);
+        assert_eq!(oidc.get_array("$or").unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    #[ignore = "needs MongoDB at MONGODB_TEST_URI"]
+    async fn callback_creates_a_user_once_and_signs_them_in_again() {
+        let db = test_db().await;
+        let provider = stub("oidc", "alice");
+
+        let (status, first) = sign_in(&db, &provider, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(first["has_membership"], false);
+        assert_eq!(first["user"]["role"], "SuperAdmin");
+
+        let (status, second) = sign_in(&db, &provider, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(second["user"]["id"], first["user"]["id"]);
+        assert_eq!(
+            db.collection::<User>("users")
+                .count_documents(None, None)
+                .await
+                .unwrap(),
+            1
+        );
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[actix_web::test]
+    #[ignore = "needs MongoDB at MONGODB_TEST_URI"]
+    async fn callback_with_a_bearer_token_links_the_identity() {
+        let db = test_db().await;
+
+        let (_, signed_in) = sign_in(&db, &stub("koompi", "k-1"), None).await;
+        let token = signed_in["access_token"].as_str().unwrap();
+        let user_id = signed_in["user"]["id"].as_str().unwrap();
+
+        let (status, linked) = sign_in(&db, &stub("oidc", "o-1"), Some(token)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(linked["user"]["id"], user_id);
+
+        let user = find_user(&db, user_id).await;
+        assert!(user.has_identity("koompi", "k-1"));
+        assert!(user.has_identity("oidc", "o-1"));
+
+        // The linked identity now signs in to the same account
+        let (_, again) = sign_in(&db, &stub("oidc", "o-1"), None).await;
+        assert_eq!(again["user"]["id"], user_id);
+
+        db.drop(None).await.unwrap();
+    }
+
+    #[actix_web::test]
+    #[ignore = "needs MongoDB at MONGODB_TEST_URI"]
+    async fn linking_an_identity_owned_by_another_user_is_refused() {
+        let db = test_db().await;
+
+        let (_, alice) = sign_in(&db, &stub("koompi", "alice"), None).await;
+        let (_, bob) = sign_in(&db, &stub("oidc", "bob"), None).await;
+        let bob_token = bob["access_token"].as_str().unwrap();
+
+        let (status, body) = sign_in(&db, &stub("koompi", "alice"), Some(bob_token)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["error"],
+            "This sign-in method is already linked to another account"
+        );
+
+        let bob_user = find_user(&db, bob["user"]["id"].as_str().unwrap()).await;
+        assert!(!bob_user.has_identity("koompi", "alice"));
+        let alice_user = find_user(&db, alice["user"]["id"].as_str().unwrap()).await;
+        assert!(alice_user.has_identity("koompi", "alice"));
+
+        db.drop(None).await.unwrap();
+    }
+}
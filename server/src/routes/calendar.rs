@@ -0,0 +1,116 @@
+use actix_web::{get, web, HttpResponse, Responder};
+
+use crate::{
+    graphql::graphql_context::load_custom_role_permissions,
+    graphql::library::queries::collect,
+    models::event::{CalendarFeed, SchoolEvent},
+    models::member::{Member, Permission},
+    models::school::SchoolFeature,
+    utils::calendar::{render_ics, visible_events_filter},
+    utils::codes::hash_token,
+    utils::subscription::{ensure_feature, find_school},
+};
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::FindOptions,
+    Database,
+};
+
+/// How far back past events stay in the feed
+const FEED_HISTORY_DAYS: i64 = 90;
+
+/// iCalendar feed of the events a member can see, for subscribing from
+/// phone and desktop calendars. The secret token comes from `createCalendarFeed`.
+#[get("/calendar/{token}.ics")]
+pub async fn calendar_feed(db: web::Data<Database>, path: web::Path<String>) -> impl Responder {
+    let not_found =
+        || HttpResponse::NotFound().json(serde_json::json!({ "error": "Calendar feed not found" }));
+    let db_error = || {
+        HttpResponse::InternalServerError().json(serde_json::json!({ "error": "Database error" }))
+    };
+
+    let feeds = db.collection::<CalendarFeed>("calendar_feeds");
+    let feed = match feeds
+        .find_one(doc! { "token_hash": hash_token(&path.into_inner()) }, None)
+        .await
+    {
+        Ok(Some(feed)) => feed,
+        Ok(None) => return not_found(),
+        Err(_) => return db_error(),
+    };
+
+    // The feed stops working once the membership ends or loses access to events
+    let Ok(member_oid) = ObjectId::parse_str(&feed.member_id) else {
+        return not_found();
+    };
+    let mut member = match db
+        .collection::<Member>("members")
+        .find_one(
+            doc! {
+                "_id": member_oid,
+                "status": "Active",
+                "soft_delete.is_deleted": false
+            },
+            None,
+        )
+        .await
+    {
+        Ok(Some(member)) => member,
+        Ok(None) => return not_found(),
+        Err(_) => return db_error(),
+    };
+    if load_custom_role_permissions(&db, &mut member)
+        .await
+        .is_err()
+    {
+        return db_error();
+    }
+    if !member.has_permission(Permission::ViewEvents)
+        || ensure_feature(&db, &member.school_id, SchoolFeature::Events)
+            .await
+            .is_err()
+    {
+        return not_found();
+    }
+
+    let Ok(mut filter) = visible_events_filter(&db, &member).await else {
+        return db_error();
+    };
+    let since =
+        DateTime::from_millis(DateTime::now().timestamp_millis() - FEED_HISTORY_DAYS * 86_400_000);
+    filter.insert(
+        "$and",
+        vec![doc! { "$or": [
+            { "ends_at": { "$gte": since } },
+            { "recurrence": { "$ne": null }, "recurrence.until": null },
+            { "recurrence.until": { "$gte": since } }
+        ] }],
+    );
+    let events = match collect(
+        &db.collection::<SchoolEvent>("events"),
+        filter,
+        FindOptions::builder().sort(doc! { "starts_at": 1 }).build(),
+    )
+    .await
+    {
+        Ok(events) => events,
+        Err(_) => return db_error(),
+    };
+
+    let calendar_name = match find_school(&db, &member.school_id).await {
+        Ok(school) => school.name.get("en").to_string(),
+        Err(_) => "School calendar".to_string(),
+    };
+
+    let _ = feeds
+        .update_one(
+            doc! { "_id": feed.id },
+            doc! { "$set": { "last_fetched_at": DateTime::now() } },
+            None,
+        )
+        .await;
+
+    HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(render_ics(&calendar_name, &events))
+}
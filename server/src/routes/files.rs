@@ -0,0 +1,90 @@
+use actix_web::{get, http::header, web, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::{
+    models::stored_file::StoredFile,
+    utils::storage::{file_storage, verify_download_signature, DOWNLOAD_URL_MINUTES},
+};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    Database,
+};
+
+#[derive(Deserialize)]
+pub struct DownloadQuery {
+    expires: i64,
+    signature: String,
+    #[serde(default)]
+    thumbnail: bool,
+}
+
+/// Serve an uploaded file. Links are signed and short-lived; they come from
+/// the `fileDownloadUrl` query, which checks the caller may see the file.
+#[get("/files/{id}")]
+pub async fn download_file(
+    db: web::Data<Database>,
+    path: web::Path<String>,
+    query: web::Query<DownloadQuery>,
+) -> impl Responder {
+    let file_id = path.into_inner();
+    if !verify_download_signature(&file_id, query.thumbnail, query.expires, &query.signature) {
+        return HttpResponse::Forbidden()
+            .json(serde_json::json!({ "error": "This link is invalid or has expired" }));
+    }
+    let not_found =
+        || HttpResponse::NotFound().json(serde_json::json!({ "error": "File not found" }));
+
+    let Ok(oid) = ObjectId::parse_str(&file_id) else {
+        return not_found();
+    };
+    let file = match db
+        .collection::<StoredFile>("files")
+        .find_one(
+            doc! { "_id": oid, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+    {
+        Ok(Some(file)) => file,
+        Ok(None) => return not_found(),
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": "Database error" }))
+        }
+    };
+
+    let (key, content_type) = match (query.thumbnail, file.thumbnail_key.as_deref()) {
+        (true, Some(key)) => (key, "image/png"),
+        _ => (file.storage_key.as_str(), file.file_type.as_str()),
+    };
+    let bytes = match file_storage() {
+        Ok(storage) => storage.get(key).await,
+        Err(e) => Err(e),
+    };
+    let bytes = match bytes {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Failed to load file {}: {}", file_id, e);
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "error": "Storage error" }));
+        }
+    };
+
+    let file_name: String = file
+        .file_name
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"' && *c != '\\')
+        .collect();
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{}\"", file_name),
+        ))
+        .insert_header((
+            header::CACHE_CONTROL,
+            format!("private, max-age={}", DOWNLOAD_URL_MINUTES * 60),
+        ))
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .body(bytes)
+}
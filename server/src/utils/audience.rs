@@ -0,0 +1,211 @@
+// Targeting records (events, announcements) at parts of a school
+use crate::graphql::library::queries::collect;
+use crate::graphql::teacher::queries::{find_teacher_classes, teacher_identifiers};
+use crate::models::branch::Branch;
+use crate::models::class::Class;
+use crate::models::member::Member;
+use crate::models::student::Student;
+use crate::utils::common_types::AudienceScope;
+use async_graphql::{Error, Result};
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::FindOptions,
+    Database,
+};
+
+/// Clause matching records whose `scope`/`scope_id` reach a member: school-wide
+/// ones plus those for the branches, grade levels and classes of the member,
+/// their children and the classes they teach
+pub async fn audience_clause(db: &Database, member: &Member) -> Result<Document> {
+    let mut branch_ids: Vec<String> = member.branch_id.iter().cloned().collect();
+    let mut grade_levels = Vec::new();
+    let mut class_ids = Vec::new();
+
+    let student_oids: Vec<ObjectId> = member
+        .student_id
+        .iter()
+        .chain(member.parent_of.iter())
+        .filter_map(|id| ObjectId::parse_str(id).ok())
+        .collect();
+    if !student_oids.is_empty() {
+        let students = collect(
+            &db.collection::<Student>("students"),
+            doc! {
+                "_id": { "$in": student_oids },
+                "school_id": &member.school_id,
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            FindOptions::default(),
+        )
+        .await?;
+        for student in students {
+            branch_ids.extend(student.branch_id);
+            grade_levels.push(student.grade_level);
+            class_ids.extend(student.current_class_id);
+        }
+    }
+
+    let classes = find_teacher_classes(db, &member.school_id, &teacher_identifiers(member)).await?;
+    for class in classes {
+        branch_ids.extend(class.branch_id);
+        grade_levels.push(class.grade_level);
+        class_ids.extend(class.id.map(|id| id.to_hex()));
+    }
+
+    Ok(doc! {
+        "$or": [
+            { "scope": "School" },
+            { "scope": "Branch", "scope_id": { "$in": branch_ids } },
+            { "scope": "GradeLevel", "scope_id": { "$in": grade_levels } },
+            { "scope": "Class", "scope_id": { "$in": class_ids } },
+        ]
+    })
+}
+
+/// Active members a scope reaches: students in it, their parents and their
+/// teachers (plus staff assigned to a targeted branch). School scope reaches
+/// every active member.
+pub async fn audience_members(
+    db: &Database,
+    school_id: &str,
+    scope: AudienceScope,
+    scope_id: Option<&str>,
+) -> Result<Vec<Member>> {
+    let mut member_filter = doc! {
+        "school_id": school_id,
+        "status": "Active",
+        "soft_delete.is_deleted": false
+    };
+
+    if scope != AudienceScope::School {
+        let scope_id = scope_id.unwrap_or_default();
+        let mut student_filter = doc! {
+            "school_id": school_id,
+            "soft_delete.is_deleted": { "$ne": true }
+        };
+        let mut class_filter = doc! {
+            "school_id": school_id,
+            "soft_delete.is_deleted": { "$ne": true }
+        };
+        match scope {
+            AudienceScope::Branch => {
+                student_filter.insert("branch_id", scope_id);
+                class_filter.insert("branch_id", scope_id);
+            }
+            AudienceScope::GradeLevel => {
+                student_filter.insert("grade_level", scope_id);
+                class_filter.insert("grade_level", scope_id);
+            }
+            AudienceScope::Class => {
+                student_filter.insert("current_class_id", scope_id);
+                let oid =
+                    ObjectId::parse_str(scope_id).map_err(|_| Error::new("Invalid class ID"))?;
+                class_filter.insert("_id", oid);
+            }
+            AudienceScope::School => {}
+        }
+
+        let student_ids: Vec<String> = collect(
+            &db.collection::<Student>("students"),
+            student_filter,
+            FindOptions::default(),
+        )
+        .await?
+        .into_iter()
+        .filter_map(|s| s.id.map(|id| id.to_hex()))
+        .collect();
+
+        let mut teacher_ids = Vec::new();
+        for class in collect(
+            &db.collection::<Class>("classes"),
+            class_filter,
+            FindOptions::default(),
+        )
+        .await?
+        {
+            teacher_ids.extend(class.homeroom_teacher_id);
+            for day in class.schedule {
+                teacher_ids.extend(day.periods.into_iter().map(|p| p.teacher_id));
+            }
+        }
+        teacher_ids.sort();
+        teacher_ids.dedup();
+        let teacher_oids: Vec<ObjectId> = teacher_ids
+            .iter()
+            .filter_map(|id| ObjectId::parse_str(id).ok())
+            .collect();
+
+        let mut audience = vec![
+            doc! { "student_id": { "$in": &student_ids } },
+            doc! { "parent_of": { "$in": &student_ids } },
+            doc! { "user_id": { "$in": &teacher_ids } },
+            doc! { "staff_id": { "$in": &teacher_ids } },
+            doc! { "_id": { "$in": teacher_oids } },
+        ];
+        if scope == AudienceScope::Branch {
+            audience.push(doc! { "branch_id": scope_id });
+        }
+        member_filter.insert("$or", audience);
+    }
+
+    collect(
+        &db.collection::<Member>("members"),
+        member_filter,
+        FindOptions::default(),
+    )
+    .await
+}
+
+/// Check the target of a scope exists in the school and normalise `scope_id`
+/// (school-wide records have none)
+pub async fn check_scope(
+    db: &Database,
+    school_id: &str,
+    scope: AudienceScope,
+    scope_id: &mut Option<String>,
+) -> Result<()> {
+    if scope == AudienceScope::School {
+        *scope_id = None;
+        return Ok(());
+    }
+    let Some(id) = scope_id.as_deref() else {
+        return Err(Error::new("Choose the branch, grade level or class"));
+    };
+    match scope {
+        AudienceScope::Branch => {
+            let branch_oid =
+                ObjectId::parse_str(id).map_err(|_| Error::new("Invalid branch ID"))?;
+            let school_oid =
+                ObjectId::parse_str(school_id).map_err(|_| Error::new("Invalid school ID"))?;
+            db.collection::<Branch>("branches")
+                .find_one(
+                    doc! {
+                        "_id": branch_oid,
+                        "school_id": school_oid,
+                        "soft_delete.is_deleted": { "$ne": true }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?
+                .ok_or_else(|| Error::new("Branch not found"))?;
+        }
+        AudienceScope::Class => {
+            let oid = ObjectId::parse_str(id).map_err(|_| Error::new("Invalid class ID"))?;
+            db.collection::<Class>("classes")
+                .find_one(
+                    doc! {
+                        "_id": oid,
+                        "school_id": school_id,
+                        "soft_delete.is_deleted": { "$ne": true }
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(e.to_string()))?
+                .ok_or_else(|| Error::new("Class not found"))?;
+        }
+        AudienceScope::GradeLevel | AudienceScope::School => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,255 @@
+// Audit trail for GraphQL mutations
+//
+// `AuditLogger` is a schema extension that writes one `AuditLog` entry for every
+// top-level mutation field. Resolvers describe what they changed with
+// `record_change`; the changes are collected in the request's `AuditTrail` and
+// stored with the mutation's actor, school and (redacted) arguments. Mutations
+// that record nothing still get an entry with their arguments and outcome.
+use crate::graphql::graphql_context::GraphQLContext;
+use crate::models::audit_log::AuditLog;
+use actix_web::HttpRequest;
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextParseQuery, NextResolve, ResolveInfo,
+};
+use async_graphql::parser::types::ExecutableDocument;
+use async_graphql::{Context, ServerResult, Value, Variables};
+use mongodb::bson::{self, Bson, Document};
+use mongodb::Database;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// Argument names whose values are never written to the audit log
+const REDACTED_ARGUMENTS: [&str; 5] = ["password", "code", "token", "secret", "otp"];
+
+/// A change a resolver made to one record
+#[derive(Debug, Clone)]
+pub struct AuditChange {
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub school_id: Option<String>,
+    pub before: Option<Document>,
+    pub after: Option<Document>,
+}
+
+/// Request-scoped collector for the changes made while executing mutations
+#[derive(Debug, Default)]
+pub struct AuditTrail {
+    ip: Option<String>,
+    changes: Mutex<Vec<AuditChange>>,
+}
+
+impl AuditTrail {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        Self {
+            ip: req
+                .connection_info()
+                .realip_remote_addr()
+                .map(|s| s.to_string()),
+            changes: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn take_changes(&self) -> Vec<AuditChange> {
+        self.changes
+            .lock()
+            .map(|mut changes| std::mem::take(&mut *changes))
+            .unwrap_or_default()
+    }
+}
+
+/// Record a change to a record for the audit log of the running mutation.
+/// Pass `None` for `before` when creating and for `after` when deleting.
+pub fn record_change<T: Serialize>(
+    ctx: &Context<'_>,
+    entity_type: &str,
+    entity_id: Option<String>,
+    school_id: Option<&str>,
+    before: Option<&T>,
+    after: Option<&T>,
+) {
+    let Ok(trail) = ctx.data::<AuditTrail>() else {
+        return;
+    };
+    let change = AuditChange {
+        entity_type: entity_type.to_string(),
+        entity_id,
+        school_id: school_id.map(|s| s.to_string()),
+        before: before.and_then(|b| bson::to_document(b).ok()),
+        after: after.and_then(|a| bson::to_document(a).ok()),
+    };
+    if let Ok(mut changes) = trail.changes.lock() {
+        changes.push(change);
+    }
+}
+
+/// Schema extension writing the audit log (collection "audit_logs")
+pub struct AuditLogger {
+    db: Database,
+}
+
+impl AuditLogger {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+impl ExtensionFactory for AuditLogger {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(AuditLoggerExtension {
+            db: self.db.clone(),
+            variables: Mutex::new(Variables::default()),
+        })
+    }
+}
+
+struct AuditLoggerExtension {
+    db: Database,
+    variables: Mutex<Variables>,
+}
+
+#[async_trait::async_trait]
+impl Extension for AuditLoggerExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        if let Ok(mut stored) = self.variables.lock() {
+            *stored = variables.clone();
+        }
+        next.run(ctx, query, variables).await
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let is_mutation = info.path_node.parent.is_none()
+            && ctx.schema_env.registry.mutation_type.as_deref() == Some(info.parent_type);
+        if !is_mutation {
+            return next.run(ctx, info).await;
+        }
+
+        let action = info.name.to_string();
+        let arguments = self.arguments(&info);
+        let result = next.run(ctx, info).await;
+
+        let graphql_ctx = ctx.data_opt::<GraphQLContext>();
+        let trail = ctx.data_opt::<AuditTrail>();
+        let changes = trail.map(|t| t.take_changes()).unwrap_or_default();
+
+        let mut entry = AuditLog::new(action);
+        entry.actor_id = graphql_ctx
+            .and_then(|c| c.auth_user.as_ref())
+            .map(|u| u.id.clone());
+        entry.school_id = arguments.as_ref().and_then(school_id_argument).or_else(|| {
+            graphql_ctx
+                .and_then(|c| c.auth_user.as_ref())
+                .and_then(|u| u.school_id.clone())
+        });
+        entry.ip = trail.and_then(|t| t.ip.clone());
+        entry.arguments = arguments;
+        if let Err(ref e) = result {
+            entry.success = false;
+            entry.error = Some(e.message.clone());
+        }
+
+        let entries: Vec<AuditLog> = if changes.is_empty() {
+            vec![entry]
+        } else {
+            changes
+                .into_iter()
+                .map(|change| {
+                    let mut log = entry.clone();
+                    if change.school_id.is_some() {
+                        log.school_id = change.school_id;
+                    }
+                    log.entity_type = Some(change.entity_type);
+                    log.entity_id = change.entity_id;
+                    log.before = change.before;
+                    log.after = change.after;
+                    log
+                })
+                .collect()
+        };
+
+        // The change is already committed, so a failed write can't undo it;
+        // report it instead of failing the mutation
+        if let Err(e) = self
+            .db
+            .collection::<AuditLog>("audit_logs")
+            .insert_many(entries, None)
+            .await
+        {
+            eprintln!("Failed to write audit log: {}", e);
+        }
+
+        result
+    }
+}
+
+impl AuditLoggerExtension {
+    /// Field arguments with variables substituted and secrets redacted
+    fn arguments(&self, info: &ResolveInfo<'_>) -> Option<Document> {
+        let variables = self.variables.lock().ok()?;
+        field_arguments(info, &variables).map(|arguments| match redact(arguments.into()) {
+            Bson::Document(doc) => doc,
+            _ => Document::new(),
+        })
+    }
+}
+
+/// Arguments of the field being resolved, with variables substituted
+pub(crate) fn field_arguments(info: &ResolveInfo<'_>, variables: &Variables) -> Option<Document> {
+    let mut arguments = Document::new();
+    for (name, value) in &info.field.arguments {
+        let value = value
+            .node
+            .clone()
+            .into_const_with(|var| variables.get(&var).cloned().ok_or(()))
+            .ok()?;
+        let value = serde_json::to_value(&value)
+            .ok()
+            .and_then(|v| bson::to_bson(&v).ok())?;
+        arguments.insert(name.node.to_string(), value);
+    }
+    Some(arguments)
+}
+
+/// Replace secret values (passwords, codes, tokens) with a placeholder
+fn redact(value: Bson) -> Bson {
+    match value {
+        Bson::Document(doc) => Bson::Document(
+            doc.into_iter()
+                .map(|(key, value)| {
+                    let lower = key.to_lowercase();
+                    if REDACTED_ARGUMENTS.iter().any(|r| lower.contains(r)) {
+                        (key, Bson::String("[REDACTED]".to_string()))
+                    } else {
+                        (key, redact(value))
+                    }
+                })
+                .collect(),
+        ),
+        Bson::Array(items) => Bson::Array(items.into_iter().map(redact).collect()),
+        other => other,
+    }
+}
+
+/// `schoolId` passed directly or inside an `input` object
+pub(crate) fn school_id_argument(arguments: &Document) -> Option<String> {
+    arguments
+        .get_str("schoolId")
+        .ok()
+        .or_else(|| {
+            arguments
+                .get_document("input")
+                .ok()
+                .and_then(|input| input.get_str("schoolId").ok())
+        })
+        .map(|s| s.to_string())
+}
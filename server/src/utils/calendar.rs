@@ -0,0 +1,276 @@
+// School calendar: who sees an event, iCalendar rendering and reminders
+use crate::graphql::library::queries::collect;
+use crate::models::event::{ics_date, ics_timestamp, SchoolEvent};
+use crate::models::member::{Member, Permission};
+use crate::models::notification::NotificationKind;
+use crate::utils::audience::{audience_clause, audience_members};
+use crate::utils::notifications::{notify, NotificationMessage};
+use async_graphql::{Error, Result};
+use chrono::Duration as ChronoDuration;
+use mongodb::{
+    bson::{doc, DateTime, Document},
+    options::FindOptions,
+    Database,
+};
+use std::env;
+use std::time::Duration;
+
+/// Default minutes between reminder runs
+const DEFAULT_REMINDER_INTERVAL_MINUTES: u64 = 5;
+
+/// Longest reminder lead time (one week)
+pub const MAX_REMINDER_MINUTES: i32 = 7 * 24 * 60;
+
+// ============================================================================
+// AUDIENCE
+// ============================================================================
+
+/// Filter on `events` for the ones a member can see. ManageEvents holders see
+/// everything in the school.
+pub async fn visible_events_filter(db: &Database, member: &Member) -> Result<Document> {
+    let mut filter = doc! {
+        "school_id": &member.school_id,
+        "soft_delete.is_deleted": { "$ne": true }
+    };
+    if !member.has_permission(Permission::ManageEvents) {
+        filter.extend(audience_clause(db, member).await?);
+    }
+    Ok(filter)
+}
+
+/// Users an event is for
+pub async fn audience_user_ids(db: &Database, event: &SchoolEvent) -> Result<Vec<String>> {
+    let mut user_ids: Vec<String> =
+        audience_members(db, &event.school_id, event.scope, event.scope_id.as_deref())
+            .await?
+            .into_iter()
+            .map(|m| m.user_id)
+            .collect();
+    user_ids.sort();
+    user_ids.dedup();
+    Ok(user_ids)
+}
+
+// ============================================================================
+// ICALENDAR
+// ============================================================================
+
+/// Render events as an iCalendar (RFC 5545) document
+pub fn render_ics(calendar_name: &str, events: &[SchoolEvent]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//SMS//School Calendar//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(calendar_name)),
+    ];
+
+    for event in events {
+        let Some(id) = event.id else {
+            continue;
+        };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@sms", id.to_hex()));
+        lines.push(format!(
+            "DTSTAMP:{}",
+            ics_timestamp(event.audit.updated_at.unwrap_or(event.starts_at))
+        ));
+        if event.all_day {
+            // All-day DTEND is exclusive: the day after the last day
+            let last_day = event.ends_at.max(event.starts_at);
+            let end = DateTime::from_chrono(last_day.to_chrono() + ChronoDuration::days(1));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", ics_date(event.starts_at)));
+            lines.push(format!("DTEND;VALUE=DATE:{}", ics_date(end)));
+        } else {
+            lines.push(format!("DTSTART:{}", ics_timestamp(event.starts_at)));
+            lines.push(format!("DTEND:{}", ics_timestamp(event.ends_at)));
+        }
+        if let Some(rule) = &event.recurrence {
+            lines.push(format!("RRULE:{}", rule.to_rrule(event.all_day)));
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&event.title)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(location) = &event.location {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        lines.push(format!(
+            "CATEGORIES:{}",
+            escape_text(&format!("{:?}", event.category))
+        ));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in lines {
+        fold_line(&line, &mut ics);
+    }
+    ics
+}
+
+/// Escape a TEXT value (backslash, comma, semicolon and newlines)
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append a content line, folded at 75 octets without splitting characters
+fn fold_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+// ============================================================================
+// REMINDERS
+// ============================================================================
+
+/// Notify each event's audience `reminder_minutes` before its occurrences.
+/// Returns how many reminders went out.
+pub async fn send_due_reminders(db: &Database) -> Result<usize> {
+    let collection = db.collection::<SchoolEvent>("events");
+    let now = DateTime::now();
+    let horizon = DateTime::from_chrono(
+        now.to_chrono() + ChronoDuration::minutes(i64::from(MAX_REMINDER_MINUTES)),
+    );
+
+    let events = collect(
+        &collection,
+        doc! {
+            "soft_delete.is_deleted": { "$ne": true },
+            "reminder_minutes.0": { "$exists": true },
+            "$or": [
+                { "recurrence": null, "starts_at": { "$gte": now, "$lte": horizon } },
+                {
+                    "recurrence": { "$ne": null },
+                    "starts_at": { "$lte": horizon },
+                    "$or": [
+                        { "recurrence.until": null },
+                        { "recurrence.until": { "$gte": now } }
+                    ]
+                }
+            ]
+        },
+        FindOptions::default(),
+    )
+    .await?;
+
+    let mut sent = 0;
+    for event in events {
+        let upcoming: Vec<DateTime> = event
+            .occurrences(now, horizon)
+            .into_iter()
+            .filter(|start| *start >= now)
+            .collect();
+
+        // Only keep keys for occurrences that haven't started yet
+        let mut reminders_sent: Vec<String> = event
+            .reminders_sent
+            .iter()
+            .filter(|key| {
+                key.split(':')
+                    .next()
+                    .and_then(|ms| ms.parse::<i64>().ok())
+                    .is_some_and(|ms| ms >= now.timestamp_millis())
+            })
+            .cloned()
+            .collect();
+
+        let mut due = Vec::new();
+        for start in &upcoming {
+            for minutes in &event.reminder_minutes {
+                let key = format!("{}:{}", start.timestamp_millis(), minutes);
+                let fire_at = start.timestamp_millis() - i64::from(*minutes) * 60_000;
+                if fire_at <= now.timestamp_millis() && !reminders_sent.contains(&key) {
+                    due.push(*start);
+                    reminders_sent.push(key);
+                }
+            }
+        }
+        due.dedup();
+
+        if !due.is_empty() {
+            let user_ids = audience_user_ids(db, &event).await?;
+            let event_id = event.id.map(|id| id.to_hex()).unwrap_or_default();
+            for start in due {
+                let message = NotificationMessage {
+                    school_id: Some(&event.school_id),
+                    kind: NotificationKind::EventReminder,
+                    title: format!("Reminder: {}", event.title),
+                    body: occurrence_summary(&event, start),
+                    link: Some(format!("event:{}", event_id)),
+                };
+                sent += notify(db, &user_ids, &message)
+                    .await
+                    .map_err(|e| Error::new(format!("Failed to send reminders: {}", e)))?;
+            }
+        }
+
+        if reminders_sent != event.reminders_sent {
+            collection
+                .update_one(
+                    doc! { "_id": event.id },
+                    doc! { "$set": { "reminders_sent": reminders_sent } },
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new(format!("Failed to update event: {}", e)))?;
+        }
+    }
+
+    Ok(sent)
+}
+
+/// When and where an occurrence takes place, for notifications
+pub fn occurrence_summary(event: &SchoolEvent, start: DateTime) -> String {
+    let format = if event.all_day {
+        "%Y-%m-%d"
+    } else {
+        "%Y-%m-%d %H:%M UTC"
+    };
+    let when = start.to_chrono().format(format).to_string();
+    match &event.location {
+        Some(location) => format!("{} at {}", when, location),
+        None => when,
+    }
+}
+
+/// Spawn a background task that sends event reminders
+/// every EVENT_REMINDER_INTERVAL_MINUTES (default 5)
+pub fn spawn_reminder_task(db: Database) {
+    let minutes = env::var("EVENT_REMINDER_INTERVAL_MINUTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|m| *m > 0)
+        .unwrap_or(DEFAULT_REMINDER_INTERVAL_MINUTES);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(minutes * 60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = send_due_reminders(&db).await {
+                eprintln!("Failed to send event reminders: {}", e.message);
+            }
+        }
+    });
+}
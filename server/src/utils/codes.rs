@@ -0,0 +1,33 @@
+// Random code generation for invitations and verification
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+/// Characters used for human-entered codes (no 0/O or 1/I to avoid confusion)
+const CODE_CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Generate a human-friendly uppercase code (e.g., for invitations)
+pub fn random_code(len: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| CODE_CHARSET[rng.gen_range(0..CODE_CHARSET.len())] as char)
+        .collect()
+}
+
+/// Generate a numeric code (e.g., for SMS verification)
+pub fn random_digits(len: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect()
+}
+
+/// Generate an opaque random token (64 hex characters), e.g. for refresh tokens
+pub fn random_token() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    hex::encode(bytes)
+}
+
+/// SHA-256 hash of a token, hex encoded. Only hashes are stored server-side.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
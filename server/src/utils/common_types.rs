@@ -1,4 +1,4 @@
-use async_graphql::{Enum, InputObject, SimpleObject};
+use async_graphql::{ComplexObject, Enum, InputObject, SimpleObject};
 use mongodb::bson::DateTime;
 use serde::{Deserialize, Serialize};
 
@@ -88,7 +88,7 @@ impl AuditInfo {
 
 /// Soft delete support for all models
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject, InputObject)]
-#[graphql(input_name = "SoftDeleteInput")]
+#[graphql(input_name = "SoftDeleteInput", complex)]
 pub struct SoftDelete {
     #[serde(default)]
     pub is_deleted: bool,
@@ -109,6 +109,15 @@ impl Default for SoftDelete {
     }
 }
 
+#[ComplexObject]
+impl SoftDelete {
+    /// When the record was moved to the trash, as ISO string
+    async fn deleted_at_str(&self) -> Option<String> {
+        self.deleted_at
+            .map(|dt| dt.try_to_rfc3339_string().unwrap_or_default())
+    }
+}
+
 impl SoftDelete {
     pub fn mark_deleted(&mut self, user_id: Option<String>) {
         self.is_deleted = true;
@@ -242,12 +251,30 @@ impl Default for ContactInfo {
     }
 }
 
+// ============================================================================
+// AUDIENCE
+// ============================================================================
+
+/// Part of a school a record (event, announcement) is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum AudienceScope {
+    /// Everyone in the school
+    School,
+    /// One branch (`scope_id` is the branch ID)
+    Branch,
+    /// One grade level (`scope_id` is the grade level, e.g., "Grade 5")
+    GradeLevel,
+    /// One class (`scope_id` is the class ID)
+    Class,
+}
+
 // ============================================================================
 // FILE ATTACHMENTS
 // ============================================================================
 
 /// Photo/Document attachment
-#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject, InputObject)]
+#[graphql(input_name = "AttachmentInput")]
 pub struct Attachment {
     pub url: String,
     pub file_name: String,
@@ -346,6 +373,19 @@ impl DayOfWeek {
         ]
     }
 
+    /// Convert from a chrono weekday
+    pub fn from_weekday(weekday: chrono::Weekday) -> Self {
+        match weekday {
+            chrono::Weekday::Mon => DayOfWeek::Monday,
+            chrono::Weekday::Tue => DayOfWeek::Tuesday,
+            chrono::Weekday::Wed => DayOfWeek::Wednesday,
+            chrono::Weekday::Thu => DayOfWeek::Thursday,
+            chrono::Weekday::Fri => DayOfWeek::Friday,
+            chrono::Weekday::Sat => DayOfWeek::Saturday,
+            chrono::Weekday::Sun => DayOfWeek::Sunday,
+        }
+    }
+
     pub fn weekdays() -> Vec<DayOfWeek> {
         vec![
             DayOfWeek::Monday,
@@ -0,0 +1,64 @@
+// Per-school feature flags for GraphQL modules
+//
+// Each module's query and mutation objects carry a `FeatureGuard` for their
+// `SchoolFeature`, e.g. `#[Object(guard = "FeatureGuard::new(SchoolFeature::HR)")]`,
+// so every resolver in the module is refused for schools that haven't enabled it
+// (or whose plan doesn't include it).
+use crate::graphql::graphql_context::GraphQLContext;
+use crate::models::member::Permission;
+use crate::models::school::SchoolFeature;
+use crate::utils::school_scope::{arguments_document, request_school_id};
+use crate::utils::subscription::ensure_feature;
+use async_graphql::{Context, Error, Guard, Result};
+use mongodb::Database;
+
+/// Field guard requiring a feature for the school a request is about: its
+/// `schoolId` argument, the school of the record it names, or the caller's
+/// active school (see `utils::school_scope`). Requests that don't lead to a
+/// school are refused.
+pub struct FeatureGuard {
+    feature: SchoolFeature,
+}
+
+impl FeatureGuard {
+    pub fn new(feature: SchoolFeature) -> Self {
+        Self { feature }
+    }
+}
+
+impl Guard for FeatureGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        let db = ctx.data::<Database>()?;
+        let field = ctx.field();
+        let arguments = arguments_document(field.arguments()?);
+        let active_school = ctx
+            .data_opt::<GraphQLContext>()
+            .and_then(|c| c.auth_user.as_ref())
+            .and_then(|u| u.school_id.as_deref());
+        let school_id = request_school_id(db, field.name(), &arguments, active_school)
+            .await
+            .ok_or_else(|| Error::new("Select a school to use this feature"))?;
+
+        ensure_feature(db, &school_id, self.feature).await
+    }
+}
+
+/// Enabled features that depend on `feature` (and so block disabling it)
+pub fn dependents(enabled: &[SchoolFeature], feature: SchoolFeature) -> Vec<SchoolFeature> {
+    enabled
+        .iter()
+        .copied()
+        .filter(|f| f.dependencies().contains(&feature))
+        .collect()
+}
+
+/// The module whose records a portal read permission exposes, if any
+pub fn permission_feature(permission: Permission) -> Option<SchoolFeature> {
+    match permission {
+        Permission::ViewAttendance => Some(SchoolFeature::Attendance),
+        Permission::ViewGrades => Some(SchoolFeature::Grading),
+        Permission::ViewFinance => Some(SchoolFeature::Finance),
+        Permission::ViewTransport => Some(SchoolFeature::Transport),
+        _ => None,
+    }
+}
@@ -0,0 +1,742 @@
+// Printable student ID cards
+//
+// Cards are ID-1 size (85.6 x 54 mm, like a bank card), ten to an A4 sheet in
+// two columns with cut guides. Each carries the student's Khmer and English
+// names and a QR code of their school ID number, which the attendance and
+// library scanners accept.
+//
+// Latin text uses the standard Helvetica fonts. Khmer names need a Khmer
+// TrueType font, named by `ID_CARD_KHMER_FONT` and embedded in each PDF; its
+// glyphs are placed one after another without complex-script shaping.
+use crate::utils::thumbnail::png_to_rgb;
+use printpdf::lopdf::{content::Operation, Object};
+use printpdf::path::PaintMode;
+use printpdf::{
+    BuiltinFont, ColorBits, ColorSpace, Image, ImageFilter, ImageTransform, ImageXObject,
+    IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Pt, Px, Rect, Rgb,
+};
+use qrcode::QrCode;
+use std::env;
+use std::io::Cursor;
+use ttf_parser::Face;
+
+/// A4 page size in points
+const A4_WIDTH: f64 = 595.28;
+const A4_HEIGHT: f64 = 841.89;
+
+/// Card size in points
+const CARD_WIDTH: f64 = 242.65;
+const CARD_HEIGHT: f64 = 153.07;
+
+const COLUMNS: usize = 2;
+const ROWS: usize = 5;
+/// Space between cards, in points (3 mm)
+const GAP: f64 = 8.5;
+
+pub const CARDS_PER_PAGE: usize = COLUMNS * ROWS;
+
+/// Longest side of photos and logos embedded from PNGs, in pixels
+const IMAGE_MAX_SIDE: u32 = 400;
+
+const HEADER_HEIGHT: f64 = 34.0;
+const MARGIN: f64 = 8.0;
+const QR_SIDE: f64 = 64.0;
+const PHOTO_WIDTH: f64 = 58.0;
+const PHOTO_HEIGHT: f64 = 72.0;
+
+const DEFAULT_COLOR: CardColor = CardColor::rgb(30, 64, 175);
+const GUIDE_COLOR: CardColor = CardColor::rgb(200, 200, 200);
+const PLACEHOLDER_COLOR: CardColor = CardColor::rgb(235, 235, 235);
+const MUTED_COLOR: CardColor = CardColor::rgb(90, 90, 90);
+
+/// Helvetica glyph widths for ' ' to '~', in 1/1000 of the font size
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// School details shared by every card
+pub struct CardBranding {
+    pub school_name: String,
+    pub logo: Option<CardImage>,
+    /// Brand colour ("#RRGGBB"); a dark blue when unset or invalid
+    pub primary_color: Option<String>,
+    /// Printed as the card's validity, e.g. "2025-2026"
+    pub academic_year: String,
+}
+
+/// One student's card
+pub struct IdCard {
+    /// Printed when a Khmer font is configured
+    pub name_km: Option<String>,
+    pub name_en: Option<String>,
+    /// School ID number, printed and encoded in the QR code
+    pub code: String,
+    pub grade_level: String,
+    pub class_name: Option<String>,
+    pub photo: Option<CardImage>,
+}
+
+/// A photo or logo ready to embed
+#[derive(Clone)]
+pub struct CardImage(ImageXObject);
+
+impl CardImage {
+    fn size(&self) -> (f64, f64) {
+        (self.0.width.0 as f64, self.0.height.0 as f64)
+    }
+}
+
+/// Turn a stored JPEG or PNG into an embeddable image. JPEGs are embedded
+/// as is; PNGs are flattened onto white and scaled down.
+pub fn card_image(bytes: Vec<u8>, file_type: &str) -> Option<CardImage> {
+    let image = match file_type {
+        "image/jpeg" => {
+            let info = {
+                let mut decoder = jpeg_decoder::Decoder::new(&bytes[..]);
+                decoder.read_info().ok()?;
+                decoder.info()?
+            };
+            let color_space = match info.pixel_format {
+                jpeg_decoder::PixelFormat::L8 => ColorSpace::Greyscale,
+                jpeg_decoder::PixelFormat::RGB24 => ColorSpace::Rgb,
+                jpeg_decoder::PixelFormat::CMYK32 => ColorSpace::Cmyk,
+                jpeg_decoder::PixelFormat::L16 => return None,
+            };
+            ImageXObject {
+                width: Px(info.width as usize),
+                height: Px(info.height as usize),
+                color_space,
+                bits_per_component: ColorBits::Bit8,
+                interpolate: true,
+                image_data: bytes,
+                image_filter: Some(ImageFilter::DCT),
+                smask: None,
+                clipping_bbox: None,
+            }
+        }
+        "image/png" => {
+            let (width, height, pixels) = png_to_rgb(&bytes, IMAGE_MAX_SIDE)?;
+            ImageXObject {
+                width: Px(width as usize),
+                height: Px(height as usize),
+                color_space: ColorSpace::Rgb,
+                bits_per_component: ColorBits::Bit8,
+                interpolate: true,
+                image_data: pixels,
+                image_filter: None,
+                smask: None,
+                clipping_bbox: None,
+            }
+        }
+        _ => return None,
+    };
+    if image.width.0 == 0 || image.height.0 == 0 {
+        return None;
+    }
+    Some(CardImage(image))
+}
+
+/// The Khmer font named by `ID_CARD_KHMER_FONT`, or `None` when it isn't set
+pub async fn load_khmer_font() -> Result<Option<Vec<u8>>, String> {
+    let path = match env::var("ID_CARD_KHMER_FONT") {
+        Ok(path) if !path.trim().is_empty() => path.trim().to_string(),
+        _ => return Ok(None),
+    };
+    let data = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("the Khmer font {} couldn't be read: {}", path, e))?;
+    Face::parse(&data, 0).map_err(|e| format!("{} isn't a TrueType font: {}", path, e))?;
+    Ok(Some(data))
+}
+
+/// Lay the cards out on A4 sheets, printing Khmer names in `khmer_font`
+/// when given. Returns the PDF and its page count.
+pub fn render_id_cards(
+    branding: CardBranding,
+    cards: Vec<IdCard>,
+    khmer_font: Option<&[u8]>,
+) -> Result<(Vec<u8>, usize), String> {
+    let failed = |e: printpdf::Error| format!("Failed to create ID cards: {}", e);
+    let (document, first_page, first_layer) =
+        PdfDocument::new("Student ID cards", mm(A4_WIDTH), mm(A4_HEIGHT), "Cards");
+    let khmer = match khmer_font {
+        Some(data) => Some((
+            document
+                .add_external_font(Cursor::new(data))
+                .map_err(failed)?,
+            Face::parse(data, 0).map_err(|e| format!("Invalid Khmer font: {}", e))?,
+        )),
+        None => None,
+    };
+    let fonts = Fonts {
+        regular: document
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(failed)?,
+        bold: document
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(failed)?,
+        khmer,
+    };
+
+    let color = branding
+        .primary_color
+        .as_deref()
+        .and_then(CardColor::from_hex)
+        .unwrap_or(DEFAULT_COLOR);
+    let header_text = if color.luminance() > 0.6 {
+        CardColor::BLACK
+    } else {
+        CardColor::WHITE
+    };
+    let style = CardStyle {
+        school_name: &branding.school_name,
+        academic_year: &branding.academic_year,
+        color,
+        header_text,
+        logo: branding.logo.as_ref(),
+    };
+
+    let left = (A4_WIDTH - COLUMNS as f64 * CARD_WIDTH - (COLUMNS - 1) as f64 * GAP) / 2.0;
+    let top = A4_HEIGHT - (A4_HEIGHT - ROWS as f64 * CARD_HEIGHT - (ROWS - 1) as f64 * GAP) / 2.0;
+    let mut pages = 0;
+    let mut cards = cards.into_iter().peekable();
+    while cards.peek().is_some() {
+        let layer = if pages == 0 {
+            document.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (page, layer) = document.add_page(mm(A4_WIDTH), mm(A4_HEIGHT), "Cards");
+            document.get_page(page).get_layer(layer)
+        };
+        pages += 1;
+        let canvas = Canvas {
+            layer,
+            fonts: &fonts,
+        };
+        for (slot, card) in cards.by_ref().take(CARDS_PER_PAGE).enumerate() {
+            let (column, row) = (slot % COLUMNS, slot / COLUMNS);
+            let x = left + column as f64 * (CARD_WIDTH + GAP);
+            let y = top - (row + 1) as f64 * CARD_HEIGHT - row as f64 * GAP;
+            draw_card(&canvas, &style, x, y, card);
+        }
+    }
+
+    let pdf = document.save_to_bytes().map_err(failed)?;
+    Ok((pdf, pages.max(1)))
+}
+
+struct CardStyle<'a> {
+    school_name: &'a str,
+    academic_year: &'a str,
+    color: CardColor,
+    header_text: CardColor,
+    logo: Option<&'a CardImage>,
+}
+
+/// Draw one card with its bottom-left corner at (x, y)
+fn draw_card(canvas: &Canvas, style: &CardStyle, x: f64, y: f64, card: IdCard) {
+    let top = y + CARD_HEIGHT;
+    canvas.stroke_rect(x, y, CARD_WIDTH, CARD_HEIGHT, 0.5, GUIDE_COLOR);
+
+    // Header band with logo and school name
+    canvas.fill_rect(
+        x,
+        top - HEADER_HEIGHT,
+        CARD_WIDTH,
+        HEADER_HEIGHT,
+        style.color,
+    );
+    let mut text_x = x + MARGIN;
+    if let Some(logo) = style.logo {
+        canvas.image(logo.clone(), x + 6.0, top - 30.0, 26.0, 26.0, Fit::Contain);
+        text_x = x + 38.0;
+    }
+    let header_width = x + CARD_WIDTH - MARGIN - text_x;
+    canvas.fitted_text(
+        Font::HelveticaBold,
+        style.school_name,
+        (9.0, 6.0),
+        header_width,
+        (text_x, top - 16.0),
+        style.header_text,
+    );
+    canvas.text(
+        Font::Helvetica,
+        6.0,
+        text_x,
+        top - 26.0,
+        style.header_text,
+        "STUDENT ID CARD",
+    );
+    canvas.fill_rect(x, y, CARD_WIDTH, 4.0, style.color);
+
+    // Photo, or a placeholder box
+    let (photo_x, photo_y) = (x + MARGIN, y + 14.0);
+    match card.photo {
+        Some(photo) => canvas.image(
+            photo,
+            photo_x,
+            photo_y,
+            PHOTO_WIDTH,
+            PHOTO_HEIGHT,
+            Fit::Cover,
+        ),
+        None => {
+            canvas.fill_rect(
+                photo_x,
+                photo_y,
+                PHOTO_WIDTH,
+                PHOTO_HEIGHT,
+                PLACEHOLDER_COLOR,
+            );
+            let width = helvetica_width("PHOTO", 6.0, false);
+            canvas.text(
+                Font::Helvetica,
+                6.0,
+                photo_x + (PHOTO_WIDTH - width) / 2.0,
+                photo_y + PHOTO_HEIGHT / 2.0 - 2.0,
+                MUTED_COLOR,
+                "PHOTO",
+            );
+        }
+    }
+
+    // QR code of the ID number, with its quiet zone, and the number below it
+    let qr_x = x + CARD_WIDTH - MARGIN - QR_SIDE;
+    let qr_y = y + 20.0;
+    if let Ok(qr) = QrCode::new(card.code.as_bytes()) {
+        draw_qr(canvas, &qr, qr_x, qr_y, QR_SIDE);
+    }
+    let (code, size) = fit_text(&card.code, (6.5, 5.0), QR_SIDE, |text, size| {
+        helvetica_width(text, size, false)
+    });
+    let width = helvetica_width(&code, size, false);
+    canvas.text(
+        Font::Helvetica,
+        size,
+        qr_x + (QR_SIDE - width) / 2.0,
+        y + 10.0,
+        CardColor::BLACK,
+        &code,
+    );
+
+    // Names, Khmer above English, then the student's details
+    let details_x = photo_x + PHOTO_WIDTH + 8.0;
+    let details_width = qr_x - 4.0 - details_x;
+    let mut line_y = top - HEADER_HEIGHT - 16.0;
+    if let Some(name) = card
+        .name_km
+        .as_deref()
+        .filter(|_| canvas.fonts.khmer.is_some())
+    {
+        canvas.fitted_text(
+            Font::Khmer,
+            name,
+            (10.0, 6.5),
+            details_width,
+            (details_x, line_y),
+            CardColor::BLACK,
+        );
+        line_y -= 14.0;
+    }
+    if let Some(name) = card.name_en.as_deref() {
+        canvas.fitted_text(
+            Font::HelveticaBold,
+            name,
+            (9.0, 6.5),
+            details_width,
+            (details_x, line_y),
+            CardColor::BLACK,
+        );
+    }
+    line_y -= 15.0;
+    let mut details = vec![
+        format!("ID: {}", card.code),
+        format!("Grade: {}", card.grade_level),
+    ];
+    if let Some(class_name) = card.class_name.as_deref() {
+        details.push(format!("Class: {}", class_name));
+    }
+    details.push(format!("Year: {}", style.academic_year));
+    for detail in details {
+        canvas.fitted_text(
+            Font::Helvetica,
+            &detail,
+            (7.5, 6.0),
+            details_width,
+            (details_x, line_y),
+            MUTED_COLOR,
+        );
+        line_y -= 11.0;
+    }
+}
+
+/// Dark modules as filled squares. `side` includes a four-module quiet zone.
+fn draw_qr(canvas: &Canvas, qr: &QrCode, x: f64, y: f64, side: f64) {
+    let size = qr.width();
+    let module = side / (size + 8) as f64;
+    let origin_x = x + 4.0 * module;
+    let origin_top = y + side - 4.0 * module;
+    canvas.fill_rect(x, y, side, side, CardColor::WHITE);
+    for (i, color) in qr.to_colors().into_iter().enumerate() {
+        if color == qrcode::Color::Dark {
+            let (column, row) = (i % size, i / size);
+            canvas.fill_rect(
+                origin_x + column as f64 * module,
+                origin_top - (row + 1) as f64 * module,
+                module,
+                module,
+                CardColor::BLACK,
+            );
+        }
+    }
+}
+
+/// Shrink text to fit the width, down to the smaller of `sizes`, then cut it
+/// short. `measure` gives the width of text at a font size.
+fn fit_text(
+    text: &str,
+    (max_size, min_size): (f64, f64),
+    width: f64,
+    measure: impl Fn(&str, f64) -> f64,
+) -> (String, f64) {
+    let text = text.trim();
+    let natural = measure(text, max_size);
+    if natural <= width {
+        return (text.to_string(), max_size);
+    }
+    let size = (max_size * width / natural).max(min_size);
+    if measure(text, size) <= width {
+        return (text.to_string(), size);
+    }
+    let mut shortened: String = text.to_string();
+    while !shortened.is_empty() && measure(&format!("{}...", shortened), size) > width {
+        shortened.pop();
+    }
+    (format!("{}...", shortened.trim_end()), size)
+}
+
+/// Width of text in Helvetica at the font size, in points
+fn helvetica_width(text: &str, size: f64, bold: bool) -> f64 {
+    let widths = if bold {
+        &HELVETICA_BOLD_WIDTHS
+    } else {
+        &HELVETICA_WIDTHS
+    };
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => widths[c as usize - 32] as u32,
+            _ => 556,
+        })
+        .sum();
+    units as f64 * size / 1000.0
+}
+
+// ============================================================================
+// DRAWING
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Helvetica,
+    HelveticaBold,
+    Khmer,
+}
+
+struct Fonts<'a> {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    khmer: Option<(IndirectFontRef, Face<'a>)>,
+}
+
+impl Fonts<'_> {
+    /// Width of the text at the font size, in points
+    fn text_width(&self, font: Font, text: &str, size: f64) -> f64 {
+        match (font, self.khmer.as_ref()) {
+            (Font::Khmer, Some((_, face))) => {
+                let units: u32 = text
+                    .chars()
+                    .filter_map(|c| face.glyph_index(c))
+                    .filter_map(|glyph| face.glyph_hor_advance(glyph))
+                    .map(u32::from)
+                    .sum();
+                units as f64 * size / face.units_per_em() as f64
+            }
+            (font, _) => helvetica_width(text, size, font == Font::HelveticaBold),
+        }
+    }
+}
+
+/// How an image fills its box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fit {
+    /// Scaled to fit inside, centred
+    Contain,
+    /// Scaled to cover it, cropping what overflows
+    Cover,
+}
+
+/// Drawing on one page. Coordinates are in points from the bottom-left corner.
+struct Canvas<'a> {
+    layer: PdfLayerReference,
+    fonts: &'a Fonts<'a>,
+}
+
+impl Canvas<'_> {
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64, color: CardColor) {
+        self.layer.set_fill_color(color.into());
+        self.layer.add_rect(
+            Rect::new(mm(x), mm(y), mm(x + width), mm(y + height)).with_mode(PaintMode::Fill),
+        );
+    }
+
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64, line: f64, color: CardColor) {
+        self.layer.set_outline_color(color.into());
+        self.layer.set_outline_thickness(line as f32);
+        self.layer.add_rect(
+            Rect::new(mm(x), mm(y), mm(x + width), mm(y + height)).with_mode(PaintMode::Stroke),
+        );
+    }
+
+    /// Text with its baseline starting at (x, y)
+    fn text(&self, font: Font, size: f64, x: f64, y: f64, color: CardColor, text: &str) {
+        let font = match (font, self.fonts.khmer.as_ref()) {
+            (Font::Khmer, Some((khmer, _))) => khmer,
+            (Font::Khmer, None) => return,
+            (Font::Helvetica, _) => &self.fonts.regular,
+            (Font::HelveticaBold, _) => &self.fonts.bold,
+        };
+        self.layer.set_fill_color(color.into());
+        self.layer.use_text(text, size as f32, mm(x), mm(y), font);
+    }
+
+    /// Text shrunk or shortened (see `fit_text`) to fit the width
+    fn fitted_text(
+        &self,
+        font: Font,
+        text: &str,
+        sizes: (f64, f64),
+        width: f64,
+        (x, y): (f64, f64),
+        color: CardColor,
+    ) {
+        let (text, size) = fit_text(text, sizes, width, |text, size| {
+            self.fonts.text_width(font, text, size)
+        });
+        self.text(font, size, x, y, color, &text);
+    }
+
+    fn image(&self, image: CardImage, x: f64, y: f64, width: f64, height: f64, fit: Fit) {
+        let (image_width, image_height) = image.size();
+        let scale = match fit {
+            Fit::Contain => (width / image_width).min(height / image_height),
+            Fit::Cover => (width / image_width).max(height / image_height),
+        };
+        let (drawn_width, drawn_height) = (image_width * scale, image_height * scale);
+
+        self.layer.save_graphics_state();
+        if fit == Fit::Cover {
+            let clip = [x, y, width, height].map(|v| Object::Real(v as f32));
+            self.layer
+                .add_operation(Operation::new("re", clip.to_vec()));
+            self.layer.add_operation(Operation::new("W", vec![]));
+            self.layer.add_operation(Operation::new("n", vec![]));
+        }
+        // At 72 dpi one pixel is one point
+        Image::from(image.0).add_to_layer(
+            self.layer.clone(),
+            ImageTransform {
+                translate_x: Some(mm(x + (width - drawn_width) / 2.0)),
+                translate_y: Some(mm(y + (height - drawn_height) / 2.0)),
+                scale_x: Some(scale as f32),
+                scale_y: Some(scale as f32),
+                dpi: Some(72.0),
+                ..Default::default()
+            },
+        );
+        self.layer.restore_graphics_state();
+    }
+}
+
+fn mm(points: f64) -> Mm {
+    Pt(points as f32).into()
+}
+
+/// An RGB colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CardColor {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl CardColor {
+    const BLACK: CardColor = CardColor::rgb(0, 0, 0);
+    const WHITE: CardColor = CardColor::rgb(255, 255, 255);
+
+    const fn rgb(r: u8, g: u8, b: u8) -> CardColor {
+        CardColor { r, g, b }
+    }
+
+    /// Parse "#RRGGBB" or "#RGB" (the leading # is optional)
+    fn from_hex(hex: &str) -> Option<CardColor> {
+        let hex = hex.trim().trim_start_matches('#');
+        let digits: Vec<u8> = match hex.len() {
+            3 => hex
+                .chars()
+                .map(|c| c.to_digit(16).map(|d| (d * 17) as u8))
+                .collect::<Option<_>>()?,
+            6 => (0..6)
+                .step_by(2)
+                .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        Some(CardColor::rgb(digits[0], digits[1], digits[2]))
+    }
+
+    /// Perceived brightness from 0 (black) to 1 (white)
+    fn luminance(&self) -> f64 {
+        (0.299 * self.r as f64 + 0.587 * self.g as f64 + 0.114 * self.b as f64) / 255.0
+    }
+}
+
+impl From<CardColor> for printpdf::Color {
+    fn from(color: CardColor) -> Self {
+        let channel = |value: u8| value as f32 / 255.0;
+        printpdf::Color::Rgb(Rgb::new(
+            channel(color.r),
+            channel(color.g),
+            channel(color.b),
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(code: &str) -> IdCard {
+        IdCard {
+            name_km: Some("សុខ ដារា".to_string()),
+            name_en: Some("Sok Dara".to_string()),
+            code: code.to_string(),
+            grade_level: "Grade 7".to_string(),
+            class_name: Some("7A".to_string()),
+            photo: None,
+        }
+    }
+
+    fn branding() -> CardBranding {
+        CardBranding {
+            school_name: "Hope International School".to_string(),
+            logo: None,
+            primary_color: Some("#1e40af".to_string()),
+            academic_year: "2025-2026".to_string(),
+        }
+    }
+
+    #[test]
+    fn lays_out_ten_cards_per_page() {
+        let cards = (0..CARDS_PER_PAGE + 1)
+            .map(|i| card(&format!("STU-{:04}", i)))
+            .collect();
+
+        let (pdf, pages) = render_id_cards(branding(), cards, None).unwrap();
+
+        assert_eq!(pages, 2);
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(String::from_utf8_lossy(&pdf).contains("/Count 2"));
+    }
+
+    #[test]
+    fn cards_fit_on_the_page() {
+        let width = COLUMNS as f64 * CARD_WIDTH + (COLUMNS - 1) as f64 * GAP;
+        let height = ROWS as f64 * CARD_HEIGHT + (ROWS - 1) as f64 * GAP;
+        assert!(width < A4_WIDTH);
+        assert!(height < A4_HEIGHT);
+    }
+
+    #[test]
+    fn every_card_code_gets_a_qr_code() {
+        // Codes print at up to 64 points, so they should stay in small versions
+        let qr = QrCode::new(b"STU-2025-000123").unwrap();
+        assert!(qr.width() <= 25);
+    }
+
+    #[test]
+    fn fit_text_keeps_shrinks_or_shortens() {
+        let measure = |text: &str, size: f64| helvetica_width(text, size, false);
+        assert_eq!(
+            fit_text(" Dara ", (10.0, 6.0), 100.0, measure),
+            ("Dara".to_string(), 10.0)
+        );
+
+        let text = "Hope International School";
+        let natural = measure(text, 10.0);
+        let (shrunk, size) = fit_text(text, (10.0, 6.0), natural * 0.8, measure);
+        assert_eq!(shrunk, text);
+        assert!((6.0..10.0).contains(&size));
+
+        let (shortened, size) = fit_text(text, (10.0, 6.0), 40.0, measure);
+        assert_eq!(size, 6.0);
+        assert!(shortened.ends_with("..."));
+        assert!(measure(&shortened, size) <= 40.0);
+    }
+
+    #[test]
+    fn measures_helvetica_text() {
+        // "Hi" is 722 + 222 units
+        assert!((helvetica_width("Hi", 10.0, false) - 9.44).abs() < 1e-9);
+        assert!(helvetica_width("Hi", 10.0, true) > helvetica_width("Hi", 10.0, false));
+    }
+
+    #[test]
+    fn parses_hex_colours() {
+        assert_eq!(CardColor::from_hex("#ffffff"), Some(CardColor::WHITE));
+        assert_eq!(CardColor::from_hex("000"), Some(CardColor::BLACK));
+        assert_eq!(
+            CardColor::from_hex("#1e40af"),
+            Some(CardColor::rgb(30, 64, 175))
+        );
+        assert_eq!(CardColor::from_hex("#12345"), None);
+        assert_eq!(CardColor::from_hex("#gggggg"), None);
+    }
+
+    #[test]
+    fn only_jpeg_and_png_images_are_embedded() {
+        assert!(card_image(b"GIF89a".to_vec(), "image/gif").is_none());
+        assert!(card_image(b"not a png".to_vec(), "image/png").is_none());
+        assert!(card_image(b"not a jpeg".to_vec(), "image/jpeg").is_none());
+    }
+
+    #[test]
+    fn jpeg_photos_are_embedded_as_is() {
+        let mut jpeg = Vec::new();
+        jpeg_encoder::Encoder::new(&mut jpeg, 90)
+            .encode(&[200u8; 40 * 50 * 3], 40, 50, jpeg_encoder::ColorType::Rgb)
+            .unwrap();
+        let photo = card_image(jpeg, "image/jpeg").unwrap();
+        assert_eq!(photo.size(), (40.0, 50.0));
+
+        let mut with_photo = card("STU-0001");
+        with_photo.photo = Some(photo);
+        let (pdf, pages) = render_id_cards(branding(), vec![with_photo], None).unwrap();
+
+        assert_eq!(pages, 1);
+        assert!(String::from_utf8_lossy(&pdf).contains("/DCTDecode"));
+    }
+}
@@ -0,0 +1,269 @@
+use super::{required_env, ExternalIdentity, IdentityProvider};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::env;
+
+pub const PROVIDER_NAME: &str = "koompi";
+
+/// Default KOOMPI OAuth server
+const DEFAULT_BASE_URL: &str = "https://oauth.koompi.org";
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct KoompiUser {
+    #[serde(rename = "_id")]
+    id: String,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    username: String,
+    #[serde(rename = "profile")]
+    picture: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct UserInfoResponse {
+    user: KoompiUser,
+}
+
+/// KOOMPI OAuth
+pub struct KoompiProvider {
+    base_url: String,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+impl KoompiProvider {
+    /// Configure from `KOOMPI_CLIENT_ID`, `KOOMPI_CLIENT_SECRET`, `KOOMPI_REDIRECT_URI`
+    /// and optionally `KOOMPI_OAUTH_URL`
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self {
+            base_url: env::var("KOOMPI_OAUTH_URL")
+                .unwrap_or_else(|_| DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            client_id: required_env("KOOMPI_CLIENT_ID")?,
+            client_secret: required_env("KOOMPI_CLIENT_SECRET")?,
+            redirect_uri: env::var("KOOMPI_REDIRECT_URI").unwrap_or_default(),
+        })
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for KoompiProvider {
+    async fn exchange_code(
+        &self,
+        code: &str,
+        state: Option<&str>,
+    ) -> Result<ExternalIdentity, String> {
+        let client = reqwest::Client::new();
+
+        // 1. Exchange code for token
+        let token_params = serde_json::json!({
+            "grant_type": "authorization_code",
+            "code": code,
+            "client_id": self.client_id,
+            "client_secret": self.client_secret,
+            "redirect_uri": self.redirect_uri,
+            "state": state,
+        });
+
+        let token_res = client
+            .post(format!("{}/v1/oauth/token", self.base_url))
+            .json(&token_params)
+            .send()
+            .await
+            .map_err(|err| format!("Failed to connect to auth server: {}", err))?;
+
+        let status = token_res.status();
+        let raw_body = token_res
+            .text()
+            .await
+            .map_err(|err| format!("Failed to read token response body: {}", err))?;
+        if !status.is_success() {
+            return Err(format!("OAuth token exchange failed: {}", raw_body));
+        }
+
+        let token_data: TokenResponse = serde_json::from_str(&raw_body)
+            .map_err(|err| format!("Failed to parse token response: {}. Raw: {}", err, raw_body))?;
+
+        // 2. Fetch the user profile
+        let user_info: UserInfoResponse = client
+            .get(format!("{}/v1/oauth/userinfo", self.base_url))
+            .bearer_auth(&token_data.access_token)
+            .send()
+            .await
+            .map_err(|err| format!("Failed to fetch user info: {}", err))?
+            .json()
+            .await
+            .map_err(|err| format!("Failed to parse user info: {}", err))?;
+
+        let user = user_info.user;
+        Ok(ExternalIdentity {
+            provider: PROVIDER_NAME.to_string(),
+            subject: user.id,
+            // Fallback email since Koompi might not provide it with basic scope
+            email: user
+                .email
+                .or_else(|| Some(format!("{}@koompi.org", user.username))),
+            username: Some(user.username),
+            phone: None,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            picture: user.picture,
+            refresh_token: token_data.refresh_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn provider(server: &MockServer) -> KoompiProvider {
+        KoompiProvider {
+            base_url: server.base_url(),
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "https://school.example/callback".to_string(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn exchanges_the_code_and_reads_the_profile() {
+        let server = MockServer::start_async().await;
+        let token = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v1/oauth/token").json_body(json!({
+                    "grant_type": "authorization_code",
+                    "code": "the-code",
+                    "client_id": "client",
+                    "client_secret": "secret",
+                    "redirect_uri": "https://school.example/callback",
+                    "state": "the-state",
+                }));
+                then.status(200)
+                    .json_body(json!({ "access_token": "access", "refresh_token": "refresh" }));
+            })
+            .await;
+        let userinfo = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v1/oauth/userinfo")
+                    .header("authorization", "Bearer access");
+                then.status(200).json_body(json!({
+                    "user": {
+                        "_id": "u-1",
+                        "first_name": "Dara",
+                        "last_name": "Sok",
+                        "username": "dara",
+                        "profile": "https://koompi.example/dara.png",
+                        "email": "dara@example.com",
+                    }
+                }));
+            })
+            .await;
+
+        let identity = provider(&server)
+            .exchange_code("the-code", Some("the-state"))
+            .await
+            .unwrap();
+
+        token.assert_async().await;
+        userinfo.assert_async().await;
+        assert_eq!(identity.provider, PROVIDER_NAME);
+        assert_eq!(identity.subject, "u-1");
+        assert_eq!(identity.username.as_deref(), Some("dara"));
+        assert_eq!(identity.email.as_deref(), Some("dara@example.com"));
+        assert_eq!(identity.first_name.as_deref(), Some("Dara"));
+        assert_eq!(identity.last_name.as_deref(), Some("Sok"));
+        assert_eq!(
+            identity.picture.as_deref(),
+            Some("https://koompi.example/dara.png")
+        );
+        assert_eq!(identity.refresh_token.as_deref(), Some("refresh"));
+    }
+
+    #[actix_web::test]
+    async fn falls_back_to_a_koompi_address_without_an_email() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v1/oauth/token");
+                then.status(200)
+                    .json_body(json!({ "access_token": "access" }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v1/oauth/userinfo");
+                then.status(200)
+                    .json_body(json!({ "user": { "_id": "u-2", "username": "vanna" } }));
+            })
+            .await;
+
+        let identity = provider(&server).exchange_code("code", None).await.unwrap();
+
+        assert_eq!(identity.email.as_deref(), Some("vanna@koompi.org"));
+        assert_eq!(identity.refresh_token, None);
+    }
+
+    #[actix_web::test]
+    async fn reports_a_rejected_code() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v1/oauth/token");
+                then.status(400).body("invalid_grant");
+            })
+            .await;
+        let userinfo = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v1/oauth/userinfo");
+                then.status(200);
+            })
+            .await;
+
+        let err = provider(&server)
+            .exchange_code("stale", None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err, "OAuth token exchange failed: invalid_grant");
+        userinfo.assert_hits_async(0).await;
+    }
+
+    #[actix_web::test]
+    async fn reports_a_rejected_access_token() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v1/oauth/token");
+                then.status(200)
+                    .json_body(json!({ "access_token": "access" }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v1/oauth/userinfo");
+                then.status(401)
+                    .json_body(json!({ "error": "invalid_token" }));
+            })
+            .await;
+
+        let err = provider(&server)
+            .exchange_code("code", None)
+            .await
+            .unwrap_err();
+
+        assert!(err.starts_with("Failed to parse user info"), "{}", err);
+    }
+}
@@ -0,0 +1,56 @@
+// Identity providers used to sign users in
+pub mod koompi;
+pub mod oidc;
+
+use async_trait::async_trait;
+
+pub use koompi::KoompiProvider;
+pub use oidc::OidcProvider;
+
+/// Provider name for local phone-number OTP login
+pub const PHONE_PROVIDER: &str = "phone";
+
+/// A user's identity as reported by an identity provider
+#[derive(Debug, Clone, Default)]
+pub struct ExternalIdentity {
+    /// Provider name (e.g., "koompi", "oidc", "phone")
+    pub provider: String,
+    /// Stable user ID at the provider
+    pub subject: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub picture: Option<String>,
+    /// Refresh token issued by the provider, if any
+    pub refresh_token: Option<String>,
+}
+
+/// An OAuth-style provider that turns an authorization code into an identity
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Exchange an authorization code for the user's identity
+    async fn exchange_code(
+        &self,
+        code: &str,
+        state: Option<&str>,
+    ) -> Result<ExternalIdentity, String>;
+}
+
+/// Look up a configured provider by name
+pub fn provider_by_name(name: &str) -> Result<Box<dyn IdentityProvider>, String> {
+    match name {
+        koompi::PROVIDER_NAME => Ok(Box::new(KoompiProvider::from_env()?)),
+        oidc::PROVIDER_NAME => Ok(Box::new(OidcProvider::from_env()?)),
+        _ => Err(format!("Unknown identity provider: {}", name)),
+    }
+}
+
+/// Read a required environment variable
+fn required_env(key: &str) -> Result<String, String> {
+    std::env::var(key)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| format!("Server misconfiguration: {} missing", key))
+}
@@ -0,0 +1,329 @@
+use super::{required_env, ExternalIdentity, IdentityProvider};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+pub const PROVIDER_NAME: &str = "oidc";
+
+#[derive(Deserialize, Debug)]
+struct Discovery {
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+/// Standard OIDC claims returned by the userinfo endpoint
+#[derive(Deserialize, Debug)]
+struct UserInfo {
+    sub: String,
+    preferred_username: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    phone_number: Option<String>,
+    given_name: Option<String>,
+    family_name: Option<String>,
+    picture: Option<String>,
+}
+
+/// Generic OpenID Connect provider (e.g., Google, Microsoft, Keycloak),
+/// configured through the issuer's discovery document
+pub struct OidcProvider {
+    issuer: String,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+impl OidcProvider {
+    /// Configure from `OIDC_ISSUER`, `OIDC_CLIENT_ID`, `OIDC_CLIENT_SECRET`
+    /// and `OIDC_REDIRECT_URI`
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self {
+            issuer: required_env("OIDC_ISSUER")?
+                .trim_end_matches('/')
+                .to_string(),
+            client_id: required_env("OIDC_CLIENT_ID")?,
+            client_secret: required_env("OIDC_CLIENT_SECRET")?,
+            redirect_uri: required_env("OIDC_REDIRECT_URI")?,
+        })
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcProvider {
+    async fn exchange_code(
+        &self,
+        code: &str,
+        _state: Option<&str>,
+    ) -> Result<ExternalIdentity, String> {
+        let client = reqwest::Client::new();
+
+        let discovery: Discovery = client
+            .get(format!("{}/.well-known/openid-configuration", self.issuer))
+            .send()
+            .await
+            .map_err(|err| format!("Failed to fetch OIDC discovery document: {}", err))?
+            .json()
+            .await
+            .map_err(|err| format!("Failed to parse OIDC discovery document: {}", err))?;
+
+        let token_res = client
+            .post(&discovery.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("redirect_uri", &self.redirect_uri),
+            ])
+            .send()
+            .await
+            .map_err(|err| format!("Failed to connect to auth server: {}", err))?;
+
+        let status = token_res.status();
+        let raw_body = token_res
+            .text()
+            .await
+            .map_err(|err| format!("Failed to read token response body: {}", err))?;
+        if !status.is_success() {
+            return Err(format!("OAuth token exchange failed: {}", raw_body));
+        }
+
+        let token_data: TokenResponse = serde_json::from_str(&raw_body)
+            .map_err(|err| format!("Failed to parse token response: {}", err))?;
+
+        let user: UserInfo = client
+            .get(&discovery.userinfo_endpoint)
+            .bearer_auth(&token_data.access_token)
+            .send()
+            .await
+            .map_err(|err| format!("Failed to fetch user info: {}", err))?
+            .json()
+            .await
+            .map_err(|err| format!("Failed to parse user info: {}", err))?;
+
+        Ok(ExternalIdentity {
+            provider: PROVIDER_NAME.to_string(),
+            subject: user.sub,
+            username: user.preferred_username,
+            // Invitations trust a linked identity's email, so only keep one
+            // the provider has verified
+            email: user.email.filter(|_| user.email_verified),
+            phone: user.phone_number,
+            first_name: user.given_name,
+            last_name: user.family_name,
+            picture: user.picture,
+            refresh_token: token_data.refresh_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn provider(server: &MockServer) -> OidcProvider {
+        OidcProvider {
+            issuer: server.base_url(),
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "https://school.example/callback".to_string(),
+        }
+    }
+
+    /// Serve a discovery document pointing at the mock's token and userinfo endpoints
+    async fn discovery(server: &MockServer) {
+        let body = json!({
+            "token_endpoint": server.url("/token"),
+            "userinfo_endpoint": server.url("/userinfo"),
+        });
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/.well-known/openid-configuration");
+                then.status(200).json_body(body);
+            })
+            .await;
+    }
+
+    async fn token(server: &MockServer) {
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/token");
+                then.status(200)
+                    .json_body(json!({ "access_token": "access" }));
+            })
+            .await;
+    }
+
+    #[actix_web::test]
+    async fn exchanges_the_code_and_reads_the_claims() {
+        let server = MockServer::start_async().await;
+        discovery(&server).await;
+        let token = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/token")
+                    .x_www_form_urlencoded_tuple("grant_type", "authorization_code")
+                    .x_www_form_urlencoded_tuple("code", "the-code")
+                    .x_www_form_urlencoded_tuple("client_id", "client")
+                    .x_www_form_urlencoded_tuple("client_secret", "secret")
+                    .x_www_form_urlencoded_tuple("redirect_uri", "https://school.example/callback");
+                then.status(200)
+                    .json_body(json!({ "access_token": "access", "refresh_token": "refresh" }));
+            })
+            .await;
+        let userinfo = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/userinfo")
+                    .header("authorization", "Bearer access");
+                then.status(200).json_body(json!({
+                    "sub": "sub-1",
+                    "preferred_username": "dara",
+                    "email": "dara@example.com",
+                    "email_verified": true,
+                    "phone_number": "+85512345678",
+                    "given_name": "Dara",
+                    "family_name": "Sok",
+                    "picture": "https://idp.example/dara.png",
+                }));
+            })
+            .await;
+
+        let identity = provider(&server)
+            .exchange_code("the-code", None)
+            .await
+            .unwrap();
+
+        token.assert_async().await;
+        userinfo.assert_async().await;
+        assert_eq!(identity.provider, PROVIDER_NAME);
+        assert_eq!(identity.subject, "sub-1");
+        assert_eq!(identity.username.as_deref(), Some("dara"));
+        assert_eq!(identity.email.as_deref(), Some("dara@example.com"));
+        assert_eq!(identity.phone.as_deref(), Some("+85512345678"));
+        assert_eq!(identity.first_name.as_deref(), Some("Dara"));
+        assert_eq!(identity.last_name.as_deref(), Some("Sok"));
+        assert_eq!(
+            identity.picture.as_deref(),
+            Some("https://idp.example/dara.png")
+        );
+        assert_eq!(identity.refresh_token.as_deref(), Some("refresh"));
+    }
+
+    #[actix_web::test]
+    async fn drops_an_unverified_email() {
+        let server = MockServer::start_async().await;
+        discovery(&server).await;
+        token(&server).await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/userinfo");
+                then.status(200).json_body(json!({
+                    "sub": "sub-2",
+                    "email": "someone@example.com",
+                    "email_verified": false,
+                }));
+            })
+            .await;
+
+        let identity = provider(&server).exchange_code("code", None).await.unwrap();
+
+        assert_eq!(identity.subject, "sub-2");
+        assert_eq!(identity.email, None);
+    }
+
+    #[actix_web::test]
+    async fn drops_an_email_without_a_verified_claim() {
+        let server = MockServer::start_async().await;
+        discovery(&server).await;
+        token(&server).await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/userinfo");
+                then.status(200)
+                    .json_body(json!({ "sub": "sub-3", "email": "someone@example.com" }));
+            })
+            .await;
+
+        let identity = provider(&server).exchange_code("code", None).await.unwrap();
+
+        assert_eq!(identity.email, None);
+    }
+
+    #[actix_web::test]
+    async fn reports_a_rejected_code() {
+        let server = MockServer::start_async().await;
+        discovery(&server).await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/token");
+                then.status(400).body("invalid_grant");
+            })
+            .await;
+        let userinfo = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/userinfo");
+                then.status(200);
+            })
+            .await;
+
+        let err = provider(&server)
+            .exchange_code("stale", None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err, "OAuth token exchange failed: invalid_grant");
+        userinfo.assert_hits_async(0).await;
+    }
+
+    #[actix_web::test]
+    async fn reports_a_missing_discovery_document() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/.well-known/openid-configuration");
+                then.status(404).body("not found");
+            })
+            .await;
+
+        let err = provider(&server)
+            .exchange_code("code", None)
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.starts_with("Failed to parse OIDC discovery document"),
+            "{}",
+            err
+        );
+    }
+
+    #[actix_web::test]
+    async fn reports_a_rejected_access_token() {
+        let server = MockServer::start_async().await;
+        discovery(&server).await;
+        token(&server).await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/userinfo");
+                then.status(401)
+                    .json_body(json!({ "error": "invalid_token" }));
+            })
+            .await;
+
+        let err = provider(&server)
+            .exchange_code("code", None)
+            .await
+            .unwrap_err();
+
+        assert!(err.starts_with("Failed to parse user info"), "{}", err);
+    }
+}
@@ -0,0 +1,27 @@
+// Indexes the server relies on for correctness, created at startup
+use mongodb::{bson::doc, options::IndexOptions, Database, IndexModel};
+
+use crate::models::attendance::Attendance;
+
+/// Create the unique indexes that back upserts. Creating an index that already
+/// exists is a no-op.
+pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error> {
+    // One attendance row per student, class and day, so concurrent marks and
+    // gate scans upsert the same record instead of inserting duplicates
+    db.collection::<Attendance>("attendances")
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "student_id": 1, "class_id": 1, "date": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("student_class_date".to_string())
+                        .unique(true)
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+    Ok(())
+}
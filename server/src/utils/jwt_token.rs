@@ -1,27 +1,59 @@
 use crate::models::user::UserRole;
 use chrono::{Duration, Utc};
+use jsonwebtoken::errors::ErrorKind;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use std::env;
 
+/// Access token lifetime; clients renew with a refresh token
+pub const ACCESS_TOKEN_MINUTES: i64 = 15;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub role: UserRole,
+    /// Session the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Active school selected by the client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub school_id: Option<String>,
     pub exp: usize,
     pub iat: usize,
 }
 
-pub fn sign_token(id: String, role: UserRole) -> Result<String, jsonwebtoken::errors::Error> {
-    let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "secret".to_string());
+/// Check that `JWT_SECRET` is configured. Called at startup so the server
+/// never signs tokens with a guessable key.
+pub fn ensure_jwt_secret() -> Result<(), String> {
+    jwt_secret()
+        .map(|_| ())
+        .map_err(|_| "JWT_SECRET must be set to a non-empty value".to_string())
+}
+
+fn jwt_secret() -> Result<String, jsonwebtoken::errors::Error> {
+    env::var("JWT_SECRET")
+        .ok()
+        .filter(|secret| !secret.trim().is_empty())
+        .ok_or_else(|| ErrorKind::InvalidKeyFormat.into())
+}
+
+pub fn sign_token(
+    id: String,
+    role: UserRole,
+    session_id: Option<String>,
+    school_id: Option<String>,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let secret = jwt_secret()?;
     let expiration = Utc::now()
-        .checked_add_signed(Duration::hours(24))
+        .checked_add_signed(Duration::minutes(ACCESS_TOKEN_MINUTES))
         .expect("valid timestamp")
         .timestamp();
 
     let claims = Claims {
         sub: id,
         role,
+        sid: session_id,
+        school_id,
         iat: Utc::now().timestamp() as usize,
         exp: expiration as usize,
     };
@@ -34,7 +66,7 @@ pub fn sign_token(id: String, role: UserRole) -> Result<String, jsonwebtoken::er
 }
 
 pub fn verify_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "secret".to_string());
+    let secret = jwt_secret()?;
     let token_data = decode::<Claims>(
         token,
         &DecodingKey::from_secret(secret.as_ref()),
@@ -0,0 +1,42 @@
+// Outbound email
+use std::env;
+
+/// Send a plain-text email through the HTTP mail API configured in
+/// `MAIL_API_URL` (sender from `MAIL_FROM`, bearer token from `MAIL_API_TOKEN`).
+/// Without an API sending fails, unless `MAIL_DEV_MODE=true` (development),
+/// where the message is dropped. Bodies carry invitation codes, so they are
+/// never logged.
+pub async fn send_email(to: &str, subject: &str, body: &str) -> Result<(), String> {
+    let Ok(api_url) = env::var("MAIL_API_URL") else {
+        if env::var("MAIL_DEV_MODE").is_ok_and(|v| v == "true") {
+            println!(
+                "📧 Email \"{}\" not sent: no mail API (MAIL_DEV_MODE)",
+                subject
+            );
+            return Ok(());
+        }
+        return Err("Email is not configured. Set MAIL_API_URL.".to_string());
+    };
+
+    let mut request = reqwest::Client::new()
+        .post(&api_url)
+        .json(&serde_json::json!({
+            "from": env::var("MAIL_FROM").ok(),
+            "to": to,
+            "subject": subject,
+            "text": body,
+        }));
+    if let Ok(token) = env::var("MAIL_API_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send email: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Mail API returned {}", response.status()));
+    }
+
+    Ok(())
+}
@@ -1,3 +1,21 @@
+pub mod audience;
+pub mod audit;
+pub mod calendar;
+pub mod codes;
 pub mod common_types;
+pub mod features;
+pub mod id_card;
+pub mod indexes;
+pub mod identity;
 pub mod jwt_token;
+pub mod mail;
+pub mod notifications;
 pub mod permissions;
+pub mod realtime;
+pub mod school_scope;
+pub mod sms;
+pub mod soft_delete;
+pub mod storage;
+pub mod subscription;
+pub mod thumbnail;
+pub mod totp;
@@ -0,0 +1,50 @@
+// In-app notifications
+use crate::models::notification::{Notification, NotificationKind};
+use mongodb::bson::DateTime;
+use mongodb::Database;
+
+/// Something to tell a set of users
+pub struct NotificationMessage<'a> {
+    pub school_id: Option<&'a str>,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub link: Option<String>,
+}
+
+/// Store a notification for each user (duplicates in `user_ids` are skipped).
+/// Returns how many were created.
+pub async fn notify(
+    db: &Database,
+    user_ids: &[String],
+    message: &NotificationMessage<'_>,
+) -> Result<usize, mongodb::error::Error> {
+    let mut recipients: Vec<&String> = user_ids.iter().collect();
+    recipients.sort();
+    recipients.dedup();
+    if recipients.is_empty() {
+        return Ok(0);
+    }
+
+    let now = DateTime::now();
+    let notifications: Vec<Notification> = recipients
+        .into_iter()
+        .map(|user_id| Notification {
+            id: None,
+            user_id: user_id.clone(),
+            school_id: message.school_id.map(str::to_string),
+            kind: message.kind,
+            title: message.title.clone(),
+            body: message.body.clone(),
+            link: message.link.clone(),
+            read_at: None,
+            created_at: now,
+        })
+        .collect();
+
+    let result = db
+        .collection::<Notification>("notifications")
+        .insert_many(notifications, None)
+        .await?;
+    Ok(result.inserted_ids.len())
+}
@@ -0,0 +1,119 @@
+// In-process event bus behind the GraphQL subscriptions
+//
+// Mutations publish a `RealtimeEvent` after their write succeeds; each
+// subscription resolver checks the subscriber's membership, then filters the
+// shared stream down to the events that subscriber may see. The membership and
+// session are checked again before every event, and the stream ends when the
+// access token expires, so signing out or losing access stops delivery. Events
+// only reach subscribers connected to the same server process.
+use crate::graphql::attendance::AttendanceType;
+use crate::graphql::finance::PaymentType;
+use crate::graphql::graphql_context::{is_still_member, AuthUser};
+use crate::models::announcement::Announcement;
+use crate::models::member::{Member, Permission};
+use crate::models::message::Message;
+use crate::models::school::School;
+use futures::stream::{self, Stream, StreamExt};
+use mongodb::Database;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Events buffered per subscriber before the slowest ones start skipping
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Something that happened which subscribers may want to hear about
+#[derive(Debug, Clone)]
+pub enum RealtimeEvent {
+    /// Attendance was marked or corrected for a student in a class
+    AttendanceMarked { attendance: AttendanceType },
+    /// A message was posted to a thread
+    MessagePosted {
+        /// Users taking part in the thread
+        participant_ids: Vec<String>,
+        message: Message,
+    },
+    /// An announcement was published
+    AnnouncementPublished {
+        /// Users the announcement reaches
+        recipient_ids: Vec<String>,
+        announcement: Announcement,
+    },
+    /// A fee payment was recorded and a receipt issued
+    PaymentReceived { payment: PaymentType },
+    /// A school was approved or rejected
+    SchoolStatusChanged { school: Box<School> },
+}
+
+fn sender() -> &'static broadcast::Sender<RealtimeEvent> {
+    static SENDER: OnceLock<broadcast::Sender<RealtimeEvent>> = OnceLock::new();
+    SENDER.get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+}
+
+/// Send an event to every open subscription. Nobody listening is not an error.
+pub fn publish(event: RealtimeEvent) {
+    let _ = sender().send(event);
+}
+
+/// Stream of events from now on, mapped and filtered by `select`. A subscriber
+/// that falls too far behind skips the events it missed rather than closing.
+pub fn events<T, F>(mut select: F) -> impl Stream<Item = T>
+where
+    T: Send + 'static,
+    F: FnMut(RealtimeEvent) -> Option<T> + Send + 'static,
+{
+    stream::unfold(sender().subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .filter_map(move |event| futures::future::ready(select(event)))
+}
+
+/// `events` for a subscriber in a school. Each event is only delivered while
+/// `is_still_member` holds for the subscriber, and the stream ends at the first
+/// one that fails or when their access token expires.
+pub fn member_events<T, F>(
+    db: Database,
+    user: AuthUser,
+    school_id: String,
+    permission: Option<Permission>,
+    select: F,
+) -> impl Stream<Item = T>
+where
+    T: Send + 'static,
+    F: FnMut(RealtimeEvent) -> Option<T> + Send + 'static,
+{
+    let remaining = (user.expires_at - chrono::Utc::now().timestamp()).max(0) as u64;
+    let expired = tokio::time::sleep(Duration::from_secs(remaining));
+
+    events(select)
+        .take_while(move |_| {
+            let (db, user, school_id) = (db.clone(), user.clone(), school_id.clone());
+            async move { is_still_member(&db, &user, &school_id, permission).await }
+        })
+        .take_until(expired)
+}
+
+/// Students whose events a member may follow: `None` (every student) when the
+/// member holds `school_wide`, otherwise only their children (parents) or
+/// themselves (students)
+pub fn student_scope(member: &Member, school_wide: Permission) -> Option<Vec<String>> {
+    if member.has_permission(school_wide) {
+        return None;
+    }
+    let mut student_ids = member.parent_of.clone();
+    student_ids.extend(member.student_id.clone());
+    Some(student_ids)
+}
+
+/// Whether a student is within a scope from `student_scope`
+pub fn in_scope(scope: &Option<Vec<String>>, student_id: &str) -> bool {
+    scope
+        .as_ref()
+        .is_none_or(|ids| ids.iter().any(|id| id == student_id))
+}
@@ -0,0 +1,152 @@
+// The school a GraphQL request is about
+//
+// Guards that apply per-school rules (`FeatureGuard`, `SubscriptionGuard`) look
+// for a `schoolId` argument first, then for a school record named by an ID
+// argument, directly or inside `input`: `id` is the kind of record the field is
+// named after (`deleteGrade(id)`, `thread(id)`), `<kind>Id` a record of that
+// kind (`threadId`, `input.classId`). Failing both, the caller's active school.
+use crate::utils::audit::school_id_argument;
+use async_graphql::{Name, Value};
+use mongodb::bson::{self, doc, oid::ObjectId, Bson, Document};
+use mongodb::options::FindOneOptions;
+use mongodb::Database;
+
+/// Kinds of school records and their collections. Records without a
+/// `school_id` of their own (attendance, grades) belong to their class's school.
+const SCHOOL_RECORDS: &[(&str, &str)] = &[
+    ("Announcement", "announcements"),
+    ("Asset", "assets"),
+    ("Attendance", "attendances"),
+    ("Book", "books"),
+    ("BookCopy", "book_copies"),
+    ("Branch", "branches"),
+    ("BusRoute", "bus_routes"),
+    ("Claim", "guardian_claims"),
+    ("Class", "classes"),
+    ("Copy", "book_copies"),
+    ("CustomRole", "custom_roles"),
+    ("Driver", "drivers"),
+    ("Event", "events"),
+    ("File", "files"),
+    ("Grade", "grades"),
+    ("GradeLevel", "grade_levels"),
+    ("Invitation", "invitations"),
+    ("Invoice", "invoices"),
+    ("Item", "stock_items"),
+    ("Loan", "loans"),
+    ("MaintenanceRequest", "maintenance_requests"),
+    ("Member", "members"),
+    ("Message", "messages"),
+    ("Request", "maintenance_requests"),
+    ("Reservation", "reservations"),
+    ("Role", "custom_roles"),
+    ("Route", "bus_routes"),
+    ("Staff", "staff"),
+    ("StockItem", "stock_items"),
+    ("StockTake", "stock_takes"),
+    ("Student", "students"),
+    ("Subject", "subjects"),
+    ("Thread", "message_threads"),
+    ("Vehicle", "vehicles"),
+];
+
+/// The school a field is about (see the module comment), or `None` when the
+/// request names no school and the caller has no active school
+pub async fn request_school_id(
+    db: &Database,
+    field_name: &str,
+    arguments: &Document,
+    active_school: Option<&str>,
+) -> Option<String> {
+    if let Some(school_id) = school_id_argument(arguments) {
+        return Some(school_id);
+    }
+
+    for (collection, id) in record_arguments(field_name, arguments) {
+        if let Some(school_id) = record_school_id(db, collection, id).await {
+            return Some(school_id);
+        }
+    }
+
+    active_school.map(|s| s.to_string())
+}
+
+/// Resolved field arguments as a document, for `request_school_id`
+pub fn arguments_document(arguments: Vec<(Name, Value)>) -> Document {
+    arguments
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let value = serde_json::to_value(&value)
+                .ok()
+                .and_then(|v| bson::to_bson(&v).ok())?;
+            Some((name.to_string(), value))
+        })
+        .collect()
+}
+
+/// Records named by the field's ID arguments, as (collection, ID)
+fn record_arguments(field_name: &str, arguments: &Document) -> Vec<(&'static str, ObjectId)> {
+    let input = arguments.get_document("input").ok();
+    let mut records = Vec::new();
+    for (name, value) in input.into_iter().flatten().chain(arguments) {
+        let Some(id) = value.as_str().and_then(|v| ObjectId::parse_str(v).ok()) else {
+            continue;
+        };
+        let collection = if name == "id" {
+            // The longest match, so `deleteGradeLevel` is a grade level
+            let field_name = capitalized(field_name);
+            SCHOOL_RECORDS
+                .iter()
+                .filter(|(kind, _)| field_name.contains(kind))
+                .max_by_key(|(kind, _)| kind.len())
+                .map(|(_, collection)| *collection)
+        } else {
+            let kind = name.strip_suffix("Id").map(capitalized);
+            SCHOOL_RECORDS
+                .iter()
+                .find(|(record, _)| kind.as_deref() == Some(*record))
+                .map(|(_, collection)| *collection)
+        };
+        if let Some(collection) = collection {
+            records.push((collection, id));
+        }
+    }
+    records
+}
+
+fn capitalized(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// The school a record belongs to, directly or through its class
+async fn record_school_id(db: &Database, collection: &str, id: ObjectId) -> Option<String> {
+    let record = find_record(db, collection, id).await?;
+    if let Some(school_id) = school_id_field(&record) {
+        return Some(school_id);
+    }
+    let class_id = record.get_object_id("class_id").ok()?;
+    school_id_field(&find_record(db, "classes", class_id).await?)
+}
+
+async fn find_record(db: &Database, collection: &str, id: ObjectId) -> Option<Document> {
+    let options = FindOneOptions::builder()
+        .projection(doc! { "school_id": 1, "class_id": 1 })
+        .build();
+    db.collection::<Document>(collection)
+        .find_one(doc! { "_id": id }, options)
+        .await
+        .ok()?
+}
+
+/// `school_id`, stored as a string or (branches) an ObjectId
+fn school_id_field(record: &Document) -> Option<String> {
+    match record.get("school_id") {
+        Some(Bson::String(school_id)) => Some(school_id.clone()),
+        Some(Bson::ObjectId(school_id)) => Some(school_id.to_hex()),
+        _ => None,
+    }
+}
@@ -0,0 +1,67 @@
+// Outbound SMS and phone number helpers
+use std::env;
+
+/// Normalize a Cambodian phone number to its local form (e.g., "+855 12 345 678" -> "012345678")
+pub fn normalize_phone(phone: &str) -> String {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    match digits.strip_prefix("855") {
+        Some(rest) if !rest.is_empty() => format!("0{}", rest.trim_start_matches('0')),
+        _ => digits,
+    }
+}
+
+/// Common spellings of a phone number, for matching stored values
+pub fn phone_variants(phone: &str) -> Vec<String> {
+    let local = normalize_phone(phone);
+    let national = local.trim_start_matches('0');
+
+    let mut variants = vec![
+        phone.trim().to_string(),
+        local.clone(),
+        format!("+855{}", national),
+        format!("855{}", national),
+    ];
+    variants.dedup();
+    variants
+}
+
+/// Mask a phone number for display, keeping the last 3 digits (e.g., "******678")
+pub fn mask_phone(phone: &str) -> String {
+    let digits = normalize_phone(phone);
+    let visible = digits.len().saturating_sub(3);
+    format!("{}{}", "*".repeat(visible), &digits[visible..])
+}
+
+/// Send an SMS through the gateway configured in `SMS_GATEWAY_URL`.
+/// Without a gateway sending fails, unless `SMS_DEV_MODE=true` (development),
+/// where the message is dropped. Messages carry login and claim codes, so they
+/// are never logged.
+pub async fn send_sms(phone: &str, message: &str) -> Result<(), String> {
+    let Ok(gateway_url) = env::var("SMS_GATEWAY_URL") else {
+        if env::var("SMS_DEV_MODE").is_ok_and(|v| v == "true") {
+            println!(
+                "📱 SMS to {} not sent: no SMS gateway (SMS_DEV_MODE)",
+                mask_phone(phone)
+            );
+            return Ok(());
+        }
+        return Err("SMS is not configured. Set SMS_GATEWAY_URL.".to_string());
+    };
+
+    let mut request = reqwest::Client::new()
+        .post(&gateway_url)
+        .json(&serde_json::json!({ "to": phone, "message": message }));
+    if let Ok(token) = env::var("SMS_GATEWAY_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send SMS: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("SMS gateway returned {}", response.status()));
+    }
+
+    Ok(())
+}
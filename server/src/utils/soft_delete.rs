@@ -0,0 +1,183 @@
+// Soft delete helpers: move records to the trash, restore them, list the trash,
+// and purge trashed records once the retention period has passed.
+use async_graphql::{Error, Result};
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, Bson, DateTime, Document},
+    options::FindOptions,
+    Collection, Database,
+};
+use serde::de::DeserializeOwned;
+use std::env;
+use std::time::Duration;
+
+/// Collections whose deletes are soft deletes and which are purged on schedule
+pub const SOFT_DELETE_COLLECTIONS: &[&str] = &[
+    "students",
+    "grades",
+    "attendances",
+    "staff",
+    "classes",
+    "subjects",
+    "grade_levels",
+    "branches",
+    "custom_roles",
+    "books",
+    "book_copies",
+    "vehicles",
+    "drivers",
+    "bus_routes",
+    "assets",
+    "stock_items",
+    "events",
+    "announcements",
+    "messages",
+    // Deleting a file removes its stored contents straight away, so file
+    // records are purged but have no trash or restore
+    "files",
+];
+
+/// Days a trashed record is kept before purging (SOFT_DELETE_RETENTION_DAYS)
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+/// Hours between purge runs (SOFT_DELETE_PURGE_INTERVAL_HOURS)
+const DEFAULT_PURGE_INTERVAL_HOURS: u64 = 24;
+
+/// Mark a record as deleted. Returns false if it doesn't exist or is already
+/// in the trash.
+pub async fn soft_delete_by_id<T: Send + Sync>(
+    collection: &Collection<T>,
+    id: ObjectId,
+    deleted_by: &str,
+) -> Result<bool> {
+    let result = collection
+        .update_one(
+            doc! { "_id": id, "soft_delete.is_deleted": { "$ne": true } },
+            doc! {
+                "$set": {
+                    "soft_delete.is_deleted": true,
+                    "soft_delete.deleted_at": DateTime::now(),
+                    "soft_delete.deleted_by": deleted_by,
+                }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to delete: {}", e)))?;
+
+    Ok(result.modified_count > 0)
+}
+
+/// Take a record out of the trash. Returns false if it isn't in the trash.
+pub async fn restore_by_id<T: Send + Sync>(
+    collection: &Collection<T>,
+    id: ObjectId,
+) -> Result<bool> {
+    let result = collection
+        .update_one(
+            doc! { "_id": id, "soft_delete.is_deleted": true },
+            doc! {
+                "$set": {
+                    "soft_delete.is_deleted": false,
+                    "soft_delete.deleted_at": Bson::Null,
+                    "soft_delete.deleted_by": Bson::Null,
+                }
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new(format!("Failed to restore: {}", e)))?;
+
+    Ok(result.modified_count > 0)
+}
+
+/// Load a trashed record by ID; `kind` names it in errors (e.g., "vehicle")
+pub async fn find_deleted_by_id<T>(collection: &Collection<T>, id: &str, kind: &str) -> Result<T>
+where
+    T: DeserializeOwned + Unpin + Send + Sync,
+{
+    let oid = ObjectId::parse_str(id).map_err(|_| Error::new(format!("Invalid {} ID", kind)))?;
+    collection
+        .find_one(doc! { "_id": oid, "soft_delete.is_deleted": true }, None)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+        .ok_or_else(|| Error::new(format!("Deleted {} not found", kind)))
+}
+
+/// List trashed records matching a filter, most recently deleted first
+pub async fn find_deleted<T>(collection: &Collection<T>, mut filter: Document) -> Result<Vec<T>>
+where
+    T: DeserializeOwned + Unpin + Send + Sync,
+{
+    filter.insert("soft_delete.is_deleted", true);
+    let options = FindOptions::builder()
+        .sort(doc! { "soft_delete.deleted_at": -1 })
+        .build();
+
+    let mut cursor = collection
+        .find(filter, options)
+        .await
+        .map_err(|e| Error::new(e.to_string()))?;
+
+    let mut records = Vec::new();
+    while let Some(record) = cursor
+        .try_next()
+        .await
+        .map_err(|e| Error::new(e.to_string()))?
+    {
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn retention_days() -> i64 {
+    env::var("SOFT_DELETE_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+/// Permanently remove records that have been in the trash longer than the
+/// retention period. Returns the number of records removed.
+pub async fn purge_expired(db: &Database) -> std::result::Result<u64, mongodb::error::Error> {
+    let cutoff =
+        DateTime::from_millis(DateTime::now().timestamp_millis() - retention_days() * 86_400_000);
+
+    let mut purged = 0;
+    for name in SOFT_DELETE_COLLECTIONS {
+        let result = db
+            .collection::<Document>(name)
+            .delete_many(
+                doc! {
+                    "soft_delete.is_deleted": true,
+                    "soft_delete.deleted_at": { "$lt": cutoff }
+                },
+                None,
+            )
+            .await?;
+        purged += result.deleted_count;
+    }
+
+    Ok(purged)
+}
+
+/// Run `purge_expired` in the background on a fixed interval
+pub fn spawn_purge_task(db: Database) {
+    let hours = env::var("SOFT_DELETE_PURGE_INTERVAL_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|h| *h > 0)
+        .unwrap_or(DEFAULT_PURGE_INTERVAL_HOURS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(hours * 3600));
+        loop {
+            interval.tick().await;
+            match purge_expired(&db).await {
+                Ok(0) => {}
+                Ok(count) => println!("Purged {} deleted record(s) past retention", count),
+                Err(e) => eprintln!("Failed to purge deleted records: {}", e),
+            }
+        }
+    });
+}
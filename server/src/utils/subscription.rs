@@ -0,0 +1,274 @@
+// Subscription plan enforcement and the payment provider for SaaS billing
+//
+// Plan limits are checked where students and staff members are created, plan
+// modules through `ensure_feature`, and expiry through `SubscriptionGuard`: once a
+// school's subscription is past its grace period, every mutation in that school
+// is rejected until the owner renews.
+use crate::graphql::graphql_context::GraphQLContext;
+use crate::models::member::SchoolRole;
+use crate::models::school::{School, SchoolFeature, SubscriptionState};
+use crate::models::subscription_invoice::SubscriptionInvoice;
+use crate::utils::audit::field_arguments;
+use crate::utils::school_scope::request_school_id;
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextParseQuery, NextResolve, ResolveInfo,
+};
+use async_graphql::parser::types::ExecutableDocument;
+use async_graphql::{Error, Result, ServerError, ServerResult, Value, Variables};
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::Database;
+use std::sync::{Arc, Mutex};
+
+/// Mutations that stay available while a school is read-only: account and
+/// platform-level actions, and the billing actions needed to renew
+const READ_ONLY_ALLOWED_MUTATIONS: &[&str] = &[
+    "changeSubscriptionPlan",
+    "renewSubscription",
+    "registerSchool",
+    "approveSchool",
+    "rejectSchool",
+    "beginMfaEnrollment",
+    "confirmMfaEnrollment",
+    "verifyMfa",
+    "regenerateMfaRecoveryCodes",
+    "disableMfa",
+    "revokeSession",
+    "revokeAllSessions",
+    "unlinkIdentity",
+    "suspendUser",
+    "reactivateUser",
+];
+
+// ============================================================================
+// LIMITS & GATING
+// ============================================================================
+
+/// Load a school by its ID
+pub async fn find_school(db: &Database, school_id: &str) -> Result<School> {
+    let oid = ObjectId::parse_str(school_id).map_err(|_| Error::new("Invalid school ID"))?;
+    db.collection::<School>("schools")
+        .find_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|e| Error::new(format!("Failed to load school: {}", e)))?
+        .ok_or_else(|| Error::new("School not found"))
+}
+
+/// Roles that count against the plan's staff limit
+pub fn is_staff_role(role: SchoolRole) -> bool {
+    !matches!(role, SchoolRole::Student | SchoolRole::Parent)
+}
+
+/// Number of students the school has (excluding the trash)
+pub async fn count_students(db: &Database, school_id: &str) -> Result<i64> {
+    db.collection::<mongodb::bson::Document>("students")
+        .count_documents(
+            doc! { "school_id": school_id, "soft_delete.is_deleted": { "$ne": true } },
+            None,
+        )
+        .await
+        .map(|n| n as i64)
+        .map_err(|e| Error::new(format!("Failed to count students: {}", e)))
+}
+
+/// Number of active or invited staff members the school has
+pub async fn count_staff(db: &Database, school_id: &str) -> Result<i64> {
+    db.collection::<mongodb::bson::Document>("members")
+        .count_documents(
+            doc! {
+                "school_id": school_id,
+                "role": { "$nin": ["Student", "Parent"] },
+                "status": { "$in": ["Active", "Pending"] },
+                "soft_delete.is_deleted": { "$ne": true }
+            },
+            None,
+        )
+        .await
+        .map(|n| n as i64)
+        .map_err(|e| Error::new(format!("Failed to count staff: {}", e)))
+}
+
+/// Reject changes to a school whose subscription has run out
+pub fn ensure_writable(school: &School) -> Result<()> {
+    if school.current_subscription().state() == SubscriptionState::ReadOnly {
+        return Err(Error::new(
+            "This school's subscription has expired and the school is read-only. The owner can renew it to make changes again.",
+        ));
+    }
+    Ok(())
+}
+
+/// Check there is room in the school's plan for one more student
+pub async fn ensure_student_capacity(db: &Database, school_id: &str) -> Result<()> {
+    let school = find_school(db, school_id).await?;
+    ensure_writable(&school)?;
+
+    if let Some(limit) = school.current_subscription().student_limit() {
+        if count_students(db, school_id).await? >= limit as i64 {
+            return Err(Error::new(format!(
+                "The school has reached its plan's limit of {} students. Upgrade the plan to add more.",
+                limit
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check there is room in the school's plan for one more member with the given role.
+/// Students and parents don't count against the staff limit.
+pub async fn ensure_staff_capacity(db: &Database, school_id: &str, role: SchoolRole) -> Result<()> {
+    let school = find_school(db, school_id).await?;
+    ensure_writable(&school)?;
+
+    if !is_staff_role(role) {
+        return Ok(());
+    }
+    if let Some(limit) = school.current_subscription().staff_limit() {
+        if count_staff(db, school_id).await? >= limit as i64 {
+            return Err(Error::new(format!(
+                "The school has reached its plan's limit of {} staff members. Upgrade the plan to add more.",
+                limit
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Require a module to be enabled for the school and included in its plan
+pub async fn ensure_feature(db: &Database, school_id: &str, feature: SchoolFeature) -> Result<()> {
+    let school = find_school(db, school_id).await?;
+    if !school.current_subscription().plan.includes(feature) {
+        return Err(Error::new(format!(
+            "The {:?} module is not included in this school's plan",
+            feature
+        )));
+    }
+    if !school.has_feature(feature) {
+        return Err(Error::new(format!(
+            "The {:?} module is not enabled for this school",
+            feature
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// PAYMENT PROVIDER
+// ============================================================================
+
+/// Charges schools for their subscription invoices
+#[async_trait::async_trait]
+pub trait PaymentProvider: Send + Sync {
+    /// Name stored on invoices (e.g., "local")
+    fn name(&self) -> &'static str;
+
+    /// Charge an invoice, returning the provider's reference for the payment
+    async fn charge(&self, invoice: &SubscriptionInvoice) -> Result<String, String>;
+}
+
+/// Stand-in provider for development and self-hosted installs: every charge
+/// succeeds immediately without contacting a payment processor
+pub struct LocalPaymentProvider;
+
+#[async_trait::async_trait]
+impl PaymentProvider for LocalPaymentProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn charge(&self, invoice: &SubscriptionInvoice) -> Result<String, String> {
+        println!(
+            "💳 Local payment for {}: {:.2} {}",
+            invoice.invoice_number, invoice.amount, invoice.currency
+        );
+        Ok(format!("local_{}", ObjectId::new().to_hex()))
+    }
+}
+
+/// The payment provider used for subscription invoices
+pub fn payment_provider() -> Box<dyn PaymentProvider> {
+    Box::new(LocalPaymentProvider)
+}
+
+// ============================================================================
+// READ-ONLY GUARD
+// ============================================================================
+
+/// Schema extension rejecting mutations in schools whose subscription is read-only.
+/// The school is the mutation's `schoolId` argument, the school of the record it
+/// names, or the caller's active school (see `utils::school_scope`).
+pub struct SubscriptionGuard {
+    db: Database,
+}
+
+impl SubscriptionGuard {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+impl ExtensionFactory for SubscriptionGuard {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(SubscriptionGuardExtension {
+            db: self.db.clone(),
+            variables: Mutex::new(Variables::default()),
+        })
+    }
+}
+
+struct SubscriptionGuardExtension {
+    db: Database,
+    variables: Mutex<Variables>,
+}
+
+#[async_trait::async_trait]
+impl Extension for SubscriptionGuardExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        if let Ok(mut stored) = self.variables.lock() {
+            *stored = variables.clone();
+        }
+        next.run(ctx, query, variables).await
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let is_mutation = info.path_node.parent.is_none()
+            && ctx.schema_env.registry.mutation_type.as_deref() == Some(info.parent_type);
+        if !is_mutation || READ_ONLY_ALLOWED_MUTATIONS.contains(&info.name) {
+            return next.run(ctx, info).await;
+        }
+
+        let arguments = self
+            .variables
+            .lock()
+            .ok()
+            .and_then(|variables| field_arguments(&info, &variables))
+            .unwrap_or_default();
+        let active_school = ctx
+            .data_opt::<GraphQLContext>()
+            .and_then(|c| c.auth_user.as_ref())
+            .and_then(|u| u.school_id.as_deref());
+        let school_id = request_school_id(&self.db, info.name, &arguments, active_school).await;
+
+        // Account and platform mutations name no school
+        if let Some(school_id) = school_id {
+            // Unknown schools are left to the resolver to report
+            if let Ok(school) = find_school(&self.db, &school_id).await {
+                if let Err(e) = ensure_writable(&school) {
+                    return Err(ServerError::new(e.message, None));
+                }
+            }
+        }
+
+        next.run(ctx, info).await
+    }
+}
@@ -0,0 +1,375 @@
+// Thumbnails for uploaded images
+//
+// PNG and JPEG images are decoded and scaled down here, and thumbnails are
+// saved as PNGs; other image formats are kept without a thumbnail and served
+// at full size.
+use png::{BitDepth, ColorType, Decoder, Encoder, Transformations};
+
+/// Longest side of a thumbnail, in pixels
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+/// Largest image decoded for a thumbnail, in pixels (64 MB as RGBA). Larger
+/// images are refused from their header, before any pixels are decoded.
+const MAX_PIXELS: u64 = 16_000_000;
+
+/// A PNG thumbnail of a PNG or JPEG image whose longest side is `max_side`, or
+/// `None` when the image is already that small or can't be decoded (another
+/// format, too large, CMYK JPEG, corrupt)
+pub fn thumbnail(bytes: &[u8], file_type: &str, max_side: u32) -> Option<Vec<u8>> {
+    let image = match file_type {
+        "image/png" => decode_png(bytes)?,
+        "image/jpeg" => decode_jpeg(bytes)?,
+        _ => return None,
+    };
+    if image.width <= max_side && image.height <= max_side {
+        return None;
+    }
+    encode_png(&image.resize_to_fit(max_side))
+}
+
+/// A PNG as 8-bit RGB pixels over a white background, scaled down so its
+/// longest side is at most `max_side`. Returns width, height and pixels.
+pub fn png_to_rgb(bytes: &[u8], max_side: u32) -> Option<(u32, u32, Vec<u8>)> {
+    let mut image = decode_png(bytes)?;
+    if image.width > max_side || image.height > max_side {
+        image = image.resize_to_fit(max_side);
+    }
+    let pixels = image
+        .pixels
+        .chunks_exact(4)
+        .flat_map(|pixel| {
+            let alpha = pixel[3] as u16;
+            [0, 1, 2].map(|c| ((pixel[c] as u16 * alpha + 255 * (255 - alpha)) / 255) as u8)
+        })
+        .collect();
+    Some((image.width, image.height, pixels))
+}
+
+/// 8-bit RGBA pixels, row by row
+struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Scale down so the longest side is `max_side`, averaging the source
+    /// pixels each target pixel covers
+    fn resize_to_fit(&self, max_side: u32) -> RgbaImage {
+        let scale = max_side as f64 / self.width.max(self.height) as f64;
+        let width = ((self.width as f64 * scale).round() as u32).max(1);
+        let height = ((self.height as f64 * scale).round() as u32).max(1);
+        let span = |target: u32, target_len: u32, source_len: u32| {
+            let start = target as u64 * source_len as u64 / target_len as u64;
+            let end = ((target as u64 + 1) * source_len as u64 / target_len as u64).max(start + 1);
+            start..end
+        };
+
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for ty in 0..height {
+            let rows = span(ty, height, self.height);
+            for tx in 0..width {
+                let columns = span(tx, width, self.width);
+                let mut sum = [0u64; 4];
+                let mut count = 0u64;
+                for y in rows.clone() {
+                    for x in columns.clone() {
+                        let i = ((y * self.width as u64 + x) * 4) as usize;
+                        for (c, total) in sum.iter_mut().enumerate() {
+                            *total += self.pixels[i + c] as u64;
+                        }
+                        count += 1;
+                    }
+                }
+                pixels.extend(sum.iter().map(|total| (total / count) as u8));
+            }
+        }
+
+        RgbaImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+// ============================================================================
+// PNG DECODING
+// ============================================================================
+
+fn decode_png(bytes: &[u8]) -> Option<RgbaImage> {
+    let mut decoder = Decoder::new(bytes);
+    // Palettes and transparency expanded, 16-bit samples cut to 8 bits
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let (width, height) = reader.info().size();
+    if width == 0 || height == 0 || width as u64 * height as u64 > MAX_PIXELS {
+        return None;
+    }
+
+    let mut data = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut data).ok()?;
+    data.truncate(frame.buffer_size());
+    let pixels = match frame.color_type {
+        ColorType::Rgba => data,
+        ColorType::Rgb => data
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        ColorType::Grayscale => data.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        ColorType::Indexed => return None,
+    };
+
+    Some(RgbaImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+// ============================================================================
+// JPEG DECODING
+// ============================================================================
+
+fn decode_jpeg(bytes: &[u8]) -> Option<RgbaImage> {
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    decoder.read_info().ok()?;
+    let info = decoder.info()?;
+    let (width, height) = (info.width as u32, info.height as u32);
+    if width == 0 || height == 0 || width as u64 * height as u64 > MAX_PIXELS {
+        return None;
+    }
+
+    let data = decoder.decode().ok()?;
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => data
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::L8 => data.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        // Big-endian samples; keep the high byte
+        jpeg_decoder::PixelFormat::L16 => data
+            .chunks_exact(2)
+            .flat_map(|g| [g[0], g[0], g[0], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => return None,
+    };
+
+    Some(RgbaImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+// ============================================================================
+// PNG ENCODING
+// ============================================================================
+
+fn encode_png(image: &RgbaImage) -> Option<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = Encoder::new(&mut png, image.width, image.height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    let mut writer = encoder.write_header().ok()?;
+    writer.write_image_data(&image.pixels).ok()?;
+    writer.finish().ok()?;
+    Some(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An RGBA image: left half red, right half blue, fully opaque
+    fn two_tone(width: u32, height: u32) -> RgbaImage {
+        let pixels = (0..height)
+            .flat_map(|_| {
+                (0..width).flat_map(move |x| {
+                    if x < width / 2 {
+                        [255, 0, 0, 255]
+                    } else {
+                        [0, 0, 255, 255]
+                    }
+                })
+            })
+            .collect();
+        RgbaImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// A PNG of raw (unfiltered) scanlines with the given format
+    fn png(
+        width: u32,
+        height: u32,
+        color: ColorType,
+        depth: BitDepth,
+        configure: impl FnOnce(&mut Encoder<&mut Vec<u8>>),
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = Encoder::new(&mut png, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        configure(&mut encoder);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+        writer.finish().unwrap();
+        png
+    }
+
+    #[test]
+    fn encoded_pngs_decode_to_the_same_pixels() {
+        let image = two_tone(7, 3);
+        let decoded = decode_png(&encode_png(&image).unwrap()).unwrap();
+
+        assert_eq!((decoded.width, decoded.height), (7, 3));
+        assert_eq!(decoded.pixels, image.pixels);
+    }
+
+    #[test]
+    fn thumbnails_round_trip_at_the_requested_size() {
+        let original = encode_png(&two_tone(600, 300)).unwrap();
+
+        let thumbnail = thumbnail(&original, "image/png", THUMBNAIL_SIZE).unwrap();
+        let decoded = decode_png(&thumbnail).unwrap();
+
+        assert_eq!((decoded.width, decoded.height), (256, 128));
+        let pixel = |x: usize, y: usize| {
+            let i = (y * decoded.width as usize + x) * 4;
+            &decoded.pixels[i..i + 4]
+        };
+        assert_eq!(pixel(10, 64), [255, 0, 0, 255]);
+        assert_eq!(pixel(245, 64), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn small_or_unreadable_images_get_no_thumbnail() {
+        let small = encode_png(&two_tone(100, 50)).unwrap();
+        assert!(thumbnail(&small, "image/png", THUMBNAIL_SIZE).is_none());
+        assert!(thumbnail(b"not an image", "image/png", THUMBNAIL_SIZE).is_none());
+        assert!(thumbnail(b"not an image", "image/jpeg", THUMBNAIL_SIZE).is_none());
+        assert!(thumbnail(&small[..small.len() / 2], "image/png", 10).is_none());
+        // Only the type detected from the content counts
+        assert!(thumbnail(&encode_png(&two_tone(600, 300)).unwrap(), "image/gif", 10).is_none());
+    }
+
+    #[test]
+    fn decodes_palette_and_grayscale_images() {
+        // 4x2 palette image, 2 bits per pixel, second entry transparent
+        let palette_png = png(
+            4,
+            2,
+            ColorType::Indexed,
+            BitDepth::Two,
+            |encoder| {
+                encoder.set_palette(vec![255, 0, 0, 0, 255, 0, 0, 0, 255]);
+                encoder.set_trns(vec![255, 0]);
+            },
+            &[0b00_01_10_00, 0b01_00_00_00],
+        );
+        let image = decode_png(&palette_png).unwrap();
+        assert_eq!(&image.pixels[0..4], [255, 0, 0, 255]);
+        assert_eq!(&image.pixels[4..8], [0, 255, 0, 0]);
+        assert_eq!(&image.pixels[8..12], [0, 0, 255, 255]);
+        assert_eq!(&image.pixels[16..20], [0, 255, 0, 0]);
+
+        // 2x2 16-bit grayscale
+        let gray_png = png(
+            2,
+            2,
+            ColorType::Grayscale,
+            BitDepth::Sixteen,
+            |_| {},
+            &[0x80, 0x00, 0xFF, 0xFF, 0x90, 0x00, 0xFF, 0xFF],
+        );
+        let image = decode_png(&gray_png).unwrap();
+        let grays: Vec<u8> = image.pixels.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(grays, [0x80, 0xFF, 0x90, 0xFF]);
+    }
+
+    #[test]
+    fn flattens_transparency_onto_white() {
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            pixels: vec![0, 0, 0, 0, 0, 0, 0, 255],
+        };
+        let (width, height, rgb) = png_to_rgb(&encode_png(&image).unwrap(), 10).unwrap();
+
+        assert_eq!((width, height), (2, 1));
+        assert_eq!(rgb, [255, 255, 255, 0, 0, 0]);
+    }
+
+    /// A JPEG of `two_tone` at high quality
+    fn two_tone_jpeg(width: u16, height: u16) -> Vec<u8> {
+        let image = two_tone(width as u32, height as u32);
+        let rgb: Vec<u8> = image
+            .pixels
+            .chunks_exact(4)
+            .flat_map(|p| [p[0], p[1], p[2]])
+            .collect();
+        let mut jpeg = Vec::new();
+        jpeg_encoder::Encoder::new(&mut jpeg, 95)
+            .encode(&rgb, width, height, jpeg_encoder::ColorType::Rgb)
+            .unwrap();
+        jpeg
+    }
+
+    #[test]
+    fn jpegs_get_png_thumbnails() {
+        let jpeg = two_tone_jpeg(640, 480);
+
+        let png = thumbnail(&jpeg, "image/jpeg", THUMBNAIL_SIZE).unwrap();
+        let decoded = decode_png(&png).unwrap();
+
+        assert_eq!((decoded.width, decoded.height), (256, 192));
+        // Lossy, so only roughly the same colours
+        let pixel = |x: usize, y: usize| {
+            let i = (y * decoded.width as usize + x) * 4;
+            decoded.pixels[i..i + 4].to_vec()
+        };
+        let (red, blue) = (pixel(20, 96), pixel(236, 96));
+        assert!(red[0] > 230 && red[2] < 25 && red[3] == 255, "{:?}", red);
+        assert!(blue[2] > 230 && blue[0] < 25, "{:?}", blue);
+    }
+
+    #[test]
+    fn decodes_grayscale_jpegs() {
+        let gray = vec![128u8; 300 * 300];
+        let mut jpeg = Vec::new();
+        jpeg_encoder::Encoder::new(&mut jpeg, 90)
+            .encode(&gray, 300, 300, jpeg_encoder::ColorType::Luma)
+            .unwrap();
+
+        let image = decode_jpeg(&jpeg).unwrap();
+
+        assert_eq!((image.width, image.height), (300, 300));
+        assert!(image.pixels[..4]
+            .iter()
+            .take(3)
+            .all(|&v| v.abs_diff(128) <= 2));
+        assert_eq!(image.pixels[3], 255);
+    }
+
+    #[test]
+    fn refuses_oversized_images_from_their_header() {
+        // A blank 1-bit 5000x5000 image: small to store, 25 MP to decode
+        let huge = png(
+            5000,
+            5000,
+            ColorType::Grayscale,
+            BitDepth::One,
+            |_| {},
+            &vec![0; 625 * 5000],
+        );
+        assert!(decode_png(&huge).is_none());
+    }
+}